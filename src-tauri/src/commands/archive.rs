@@ -3,12 +3,14 @@
 //! This module provides commands for creating, listing, and managing
 //! archived sessions stored in ~/.claude-history-viewer/archives/
 
-use crate::models::ClaudeSession;
+use super::archive_store;
+use crate::models::{ClaudeSession, RedactionReport, SessionMetadata};
 use crate::utils::find_subagent_files;
 use chrono::Utc;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
@@ -77,6 +79,9 @@ pub struct ArchiveSessionInfo {
     pub subagent_count: u32,
     pub subagent_size_bytes: u64,
     pub subagents: Vec<SubagentFileInfo>,
+    /// Tags, notes and other user metadata carried in by an imported bundle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<SessionMetadata>,
 }
 
 /// Metadata for a single subagent JSONL file
@@ -92,7 +97,10 @@ pub struct SubagentFileInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ArchiveDiskUsage {
+    /// Bytes on disk, counting objects shared between archives once.
     pub total_bytes: u64,
+    /// Uncompressed size of everything archived.
+    pub logical_bytes: u64,
     pub archive_count: usize,
     pub session_count: usize,
     pub per_archive: Vec<ArchiveDiskEntry>,
@@ -122,9 +130,13 @@ pub struct ExpiringSession {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportResult {
+    /// The export; empty for a redaction dry run.
     pub content: String,
     pub format: String,
     pub session_id: String,
+    /// What was (or, for a dry run, would be) redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionReport>,
 }
 
 // ---------------------------------------------------------------------------
@@ -132,13 +144,13 @@ pub struct ExportResult {
 // ---------------------------------------------------------------------------
 
 /// Returns the archives base directory path: `~/.claude-history-viewer/archives/`
-fn get_archives_dir() -> Result<PathBuf, String> {
+pub(crate) fn get_archives_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     Ok(home.join(".claude-history-viewer").join("archives"))
 }
 
 /// Ensures the archives base directory exists.
-fn ensure_archives_dir() -> Result<PathBuf, String> {
+pub(crate) fn ensure_archives_dir() -> Result<PathBuf, String> {
     let dir = get_archives_dir()?;
     if dir.exists() {
         if !dir.is_dir() {
@@ -163,7 +175,7 @@ fn get_manifest_path() -> Result<PathBuf, String> {
 ///
 /// Accepts both legacy UUID format (`3f8a1b2c-...`) and new name-based format
 /// (`my-project_3f8a1b2c`). Rejects path traversal, separators, and null bytes.
-fn validate_archive_id(id: &str) -> Result<(), String> {
+pub(crate) fn validate_archive_id(id: &str) -> Result<(), String> {
     if id.is_empty() {
         return Err("Archive ID must not be empty".to_string());
     }
@@ -239,14 +251,30 @@ fn sanitize_for_dirname(name: &str) -> String {
     truncated.trim_end_matches('-').to_string()
 }
 
+/// Generates a fresh archive ID (`<sanitized-name>_<short-uuid>`) for `name`,
+/// falling back to a bare UUID when the name yields no usable characters.
+pub(crate) fn new_archive_id(name: &str) -> String {
+    let uuid = Uuid::new_v4().to_string();
+    let sanitized_name = sanitize_for_dirname(name);
+    let archive_id = if sanitized_name.is_empty() {
+        uuid.clone()
+    } else {
+        format!("{sanitized_name}_{}", &uuid[..8])
+    };
+    if validate_archive_id(&archive_id).is_err() {
+        return uuid;
+    }
+    archive_id
+}
+
 /// Returns the directory path for an individual archive.
-fn get_archive_dir(archive_id: &str) -> Result<PathBuf, String> {
+pub(crate) fn get_archive_dir(archive_id: &str) -> Result<PathBuf, String> {
     validate_archive_id(archive_id)?;
     Ok(get_archives_dir()?.join(archive_id))
 }
 
 /// Ensures a directory exists and is not a symlink.
-fn ensure_real_directory(path: &Path, label: &str) -> Result<bool, String> {
+pub(crate) fn ensure_real_directory(path: &Path, label: &str) -> Result<bool, String> {
     match fs::symlink_metadata(path) {
         Ok(meta) if meta.file_type().is_symlink() => {
             Err(format!("{label} must not be a symlink: {}", path.display()))
@@ -259,7 +287,7 @@ fn ensure_real_directory(path: &Path, label: &str) -> Result<bool, String> {
 }
 
 /// Ensures a file exists and is not a symlink.
-fn ensure_real_file(path: &Path, label: &str) -> Result<(), String> {
+pub(crate) fn ensure_real_file(path: &Path, label: &str) -> Result<(), String> {
     match fs::symlink_metadata(path) {
         Ok(meta) if meta.file_type().is_symlink() => {
             Err(format!("{label} must not be a symlink: {}", path.display()))
@@ -274,7 +302,7 @@ fn ensure_real_file(path: &Path, label: &str) -> Result<(), String> {
 }
 
 /// Loads the global archive manifest from disk (returns default if missing).
-fn load_manifest() -> Result<ArchiveManifest, String> {
+pub(crate) fn load_manifest() -> Result<ArchiveManifest, String> {
     let path = get_manifest_path()?;
     if !path.exists() {
         return Ok(ArchiveManifest::default());
@@ -287,7 +315,7 @@ fn load_manifest() -> Result<ArchiveManifest, String> {
 }
 
 /// Atomically writes the global archive manifest to disk.
-fn save_manifest(manifest: &ArchiveManifest) -> Result<(), String> {
+pub(crate) fn save_manifest(manifest: &ArchiveManifest) -> Result<(), String> {
     ensure_archives_dir()?;
     let path = get_manifest_path()?;
     let content = serde_json::to_string_pretty(manifest)
@@ -307,7 +335,7 @@ fn save_manifest(manifest: &ArchiveManifest) -> Result<(), String> {
 }
 
 /// Atomically writes string content to a file.
-fn atomic_write_string(path: &Path, content: &str) -> Result<(), String> {
+pub(crate) fn atomic_write_string(path: &Path, content: &str) -> Result<(), String> {
     let tmp_path = path.with_extension(format!("json.{}.tmp", Uuid::new_v4()));
     let mut file = fs::File::create(&tmp_path)
         .map_err(|e| format!("Failed to create temp file '{}': {e}", tmp_path.display()))?;
@@ -355,7 +383,7 @@ fn update_per_archive_manifest(
 }
 
 /// Counts the number of non-sidechain messages in a JSONL file.
-fn count_messages(path: &Path) -> usize {
+pub(crate) fn count_messages(path: &Path) -> usize {
     let file = match fs::File::open(path) {
         Ok(f) => f,
         Err(_) => return 0,
@@ -509,6 +537,88 @@ fn dir_size(path: &Path) -> u64 {
     total
 }
 
+/// `storage` marker of per-archive manifests whose files live in the object store.
+pub(crate) const COMPRESSED_STORAGE: &str = "zstd";
+
+/// `format` of a manifest session entry holding serialized `ClaudeMessage`s
+/// rather than a Claude Code transcript (see [`snapshot_provider_session`]).
+pub(crate) const SNAPSHOT_FORMAT: &str = "messages";
+
+/// Reads a per-archive manifest.json, if present and parseable.
+pub(crate) fn read_per_archive_manifest(archive_dir: &Path) -> Option<serde_json::Value> {
+    fs::read_to_string(archive_dir.join("manifest.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+/// Object hashes a per-archive manifest refers to, for sessions and subagents.
+fn manifest_object_refs(per_manifest: &serde_json::Value) -> Vec<String> {
+    let object_of = |v: &serde_json::Value| {
+        v.get("object")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+    };
+    let mut refs = Vec::new();
+    for session in per_manifest
+        .get("sessions")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        refs.extend(object_of(session));
+        for subagent in session
+            .get("subagents")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            refs.extend(object_of(subagent));
+        }
+    }
+    refs
+}
+
+/// Every object referenced by an archive directory on disk. Fails when a
+/// manifest can't be read, so garbage collection never removes objects that
+/// belong to an archive it could not inspect.
+fn referenced_objects(archives_dir: &Path) -> Result<HashSet<String>, String> {
+    let mut referenced = HashSet::new();
+    let Ok(rd) = fs::read_dir(archives_dir) else {
+        return Ok(referenced);
+    };
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if entry.file_name() == archive_store::OBJECTS_DIR || !path.is_dir() {
+            continue;
+        }
+        let manifest_path = path.join("manifest.json");
+        if !manifest_path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&manifest_path).map_err(|e| {
+            format!(
+                "Failed to read per-archive manifest '{}': {e}",
+                manifest_path.display()
+            )
+        })?;
+        let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "Failed to parse per-archive manifest '{}': {e}",
+                manifest_path.display()
+            )
+        })?;
+        referenced.extend(manifest_object_refs(&value));
+    }
+    Ok(referenced)
+}
+
+/// Removes objects no archive refers to. Callers must hold
+/// [`archive_store::lock_store`].
+pub(crate) fn collect_unreferenced_objects(archives_dir: &Path) -> Result<u64, String> {
+    let referenced = referenced_objects(archives_dir)?;
+    Ok(archive_store::collect_garbage(archives_dir, &referenced))
+}
+
 /// Converts a JSONL file to a pretty-printed JSON array string.
 fn jsonl_to_json_array(path: &Path) -> Result<String, String> {
     let file = fs::File::open(path).map_err(|e| format!("Failed to read session file: {e}"))?;
@@ -616,16 +726,18 @@ pub async fn list_archives() -> Result<ArchiveManifest, String> {
     .map_err(|e| format!("Task join error: {e}"))?
 }
 
-/// Creates a new archive by copying the given JSONL session files.
+/// Creates a new archive by copying the given JSONL session files. Sessions of
+/// other providers are archived as a snapshot of their loaded messages.
 ///
 /// # Arguments
 /// * `name` - Human-readable name for the archive
 /// * `description` - Optional description
-/// * `session_file_paths` - Absolute paths to the JSONL session files to archive
+/// * `session_file_paths` - Absolute paths to the JSONL session files to archive,
+///   or the provider's session paths for non-Claude providers
 /// * `source_provider` - Provider identifier (e.g. "claude", "codex", "opencode", "codebuddy")
 /// * `source_project_path` - Filesystem path of the originating project
 /// * `source_project_name` - Display name of the originating project
-/// * `include_subagents` - Whether to also copy subagent JSONL files
+/// * `include_subagents` - Whether to also copy subagent JSONL files (Claude only)
 #[tauri::command]
 pub async fn create_archive(
     name: String,
@@ -637,225 +749,318 @@ pub async fn create_archive(
     include_subagents: bool,
 ) -> Result<ArchiveEntry, String> {
     tauri::async_runtime::spawn_blocking(move || {
-        if name.trim().is_empty() {
-            return Err("Archive name is required".to_string());
-        }
+        create_archive_blocking(
+            name,
+            description,
+            session_file_paths,
+            source_provider,
+            source_project_path,
+            source_project_name,
+            include_subagents,
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
 
-        ensure_archives_dir()?;
+/// Reserves a unique `.jsonl` file name within an archive, preferring
+/// `preferred` and falling back to `<stem>_<n>.jsonl`.
+fn claim_file_name(used: &mut HashSet<String>, preferred: &str, stem: &str) -> String {
+    let mut file_name = preferred.to_string();
+    let mut suffix = 1u32;
+    while used.contains(&file_name) {
+        file_name = format!("{stem}_{suffix}.jsonl");
+        suffix += 1;
+    }
+    used.insert(file_name.clone());
+    file_name
+}
 
-        let uuid = Uuid::new_v4().to_string();
-        let short_uuid = &uuid[..8];
-        let sanitized_name = sanitize_for_dirname(&name);
-        let mut archive_id = if sanitized_name.is_empty() {
-            uuid.clone()
-        } else {
-            format!("{sanitized_name}_{short_uuid}")
-        };
-        if validate_archive_id(&archive_id).is_err() {
-            archive_id.clone_from(&uuid);
+/// Snapshots one non-Claude session into the object store as JSONL of the
+/// viewer's own messages (one serialized `ClaudeMessage` per line, marked
+/// `"format": "messages"` in the manifest), so it renders without the provider's
+/// original storage. Returns the snapshot size and its manifest entry.
+fn snapshot_provider_session(
+    archives_dir: &Path,
+    provider: &str,
+    session_path: &str,
+    used_file_names: &mut HashSet<String>,
+) -> Result<(u64, serde_json::Value), String> {
+    let mut messages = super::multi_provider::load_non_claude_session(provider, session_path)
+        .map_err(|e| format!("Failed to load {provider} session '{session_path}': {e}"))?;
+    for message in &mut messages {
+        if message.provider.is_none() {
+            message.provider = Some(provider.to_string());
         }
-        let archive_dir = get_archives_dir()?.join(&archive_id);
-        let sessions_dir = archive_dir.join("sessions");
-        let subagents_dir = archive_dir.join("subagents");
-
-        fs::create_dir_all(&sessions_dir)
-            .map_err(|e| format!("Failed to create sessions directory: {e}"))?;
-
-        // Wrap inner logic so we can clean up on failure
-        let result: Result<ArchiveEntry, String> = (|| {
-            let mut total_size: u64 = 0;
-            let mut session_count: u32 = 0;
-            let mut per_session_info: Vec<serde_json::Value> = Vec::new();
-
-            for session_path_str in &session_file_paths {
-                let session_path = Path::new(session_path_str);
-
-                // Security: reject path traversal
-                if session_path
-                    .components()
-                    .any(|c| matches!(c, std::path::Component::ParentDir))
-                {
+    }
+
+    let mut content = Vec::new();
+    for message in &messages {
+        serde_json::to_writer(&mut content, message)
+            .map_err(|e| format!("Failed to serialize session snapshot: {e}"))?;
+        content.push(b'\n');
+    }
+    let stored = archive_store::store_reader(archives_dir, content.as_slice(), session_path)?;
+
+    // Provider session paths are often URIs (`crush:///proj#id`); name the
+    // snapshot after their last segment.
+    let tail = session_path
+        .rsplit(['/', '\\', '#', ':'])
+        .find(|part| !part.is_empty())
+        .unwrap_or_default();
+    let stem = sanitize_for_dirname(
+        Path::new(tail)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default(),
+    );
+    let stem = if stem.is_empty() { "session" } else { &stem };
+    let file_name = claim_file_name(used_file_names, &format!("{stem}.jsonl"), stem);
+
+    let info = serde_json::json!({
+        "sessionId": Path::new(&file_name).file_stem().and_then(|s| s.to_str()),
+        "fileName": file_name,
+        "originalFilePath": session_path,
+        "format": SNAPSHOT_FORMAT,
+        "messageCount": messages.len(),
+        "firstMessageTime": messages.first().map(|m| m.timestamp.as_str()).unwrap_or_default(),
+        "lastMessageTime": messages.last().map(|m| m.timestamp.as_str()).unwrap_or_default(),
+        "summary": serde_json::Value::Null,
+        "sizeBytes": stored.size_bytes,
+        "object": stored.hash,
+        "storedBytes": stored.stored_bytes,
+        "subagentCount": 0,
+        "subagentSizeBytes": 0,
+        "subagents": [],
+    });
+    Ok((stored.size_bytes, info))
+}
+
+/// Blocking body of [`create_archive`], shared with the background archiver.
+pub(crate) fn create_archive_blocking(
+    name: String,
+    description: Option<String>,
+    session_file_paths: Vec<String>,
+    source_provider: String,
+    source_project_path: String,
+    source_project_name: String,
+    include_subagents: bool,
+) -> Result<ArchiveEntry, String> {
+    if name.trim().is_empty() {
+        return Err("Archive name is required".to_string());
+    }
+
+    // Other providers' sessions may live in databases or formats the Claude
+    // loader can't read, so they are archived as a snapshot of their messages.
+    let snapshot = source_provider != "claude";
+    if snapshot && !super::multi_provider::is_known_provider(&source_provider) {
+        return Err(format!("Unknown provider: {source_provider}"));
+    }
+
+    ensure_archives_dir()?;
+
+    let archive_id = new_archive_id(&name);
+    let archives_dir = get_archives_dir()?;
+    let archive_dir = archives_dir.join(&archive_id);
+
+    fs::create_dir_all(&archive_dir)
+        .map_err(|e| format!("Failed to create archive directory: {e}"))?;
+
+    // Objects stored below stay unreferenced until the global manifest lists
+    // this archive; hold the store lock so garbage collection can't race us.
+    let _store = archive_store::lock_store();
+
+    // Wrap inner logic so we can clean up on failure
+    let result: Result<ArchiveEntry, String> = (|| {
+        let mut total_size: u64 = 0;
+        let mut session_count: u32 = 0;
+        let mut per_session_info: Vec<serde_json::Value> = Vec::new();
+        let mut used_file_names: HashSet<String> = HashSet::new();
+
+        for session_path_str in &session_file_paths {
+            if snapshot {
+                let (size, info) = snapshot_provider_session(
+                    &archives_dir,
+                    &source_provider,
+                    session_path_str,
+                    &mut used_file_names,
+                )?;
+                total_size += size;
+                session_count += 1;
+                per_session_info.push(info);
+                continue;
+            }
+
+            let session_path = Path::new(session_path_str);
+
+            // Security: reject path traversal
+            if session_path
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(format!(
+                    "Session path contains '..' components: {session_path_str}"
+                ));
+            }
+
+            // Security: reject symlinks and verify file exists
+            match fs::symlink_metadata(session_path) {
+                Ok(meta) if meta.file_type().is_symlink() => {
                     return Err(format!(
-                        "Session path contains '..' components: {session_path_str}"
+                        "Session path is a symlink (rejected for security): {session_path_str}"
                     ));
                 }
+                Ok(_) => {}
+                Err(_) => {
+                    return Err(format!("Session file not found: {session_path_str}"));
+                }
+            }
 
-                // Security: reject symlinks and verify file exists
-                match fs::symlink_metadata(session_path) {
-                    Ok(meta) if meta.file_type().is_symlink() => {
+            let file_name = session_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| format!("Invalid session file name: {session_path_str}"))?;
+
+            let stem = session_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("session");
+            let dest_file_name = claim_file_name(&mut used_file_names, file_name, stem);
+            let dest_stem = Path::new(&dest_file_name)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("session")
+                .to_string();
+
+            let stored = archive_store::store_file(&archives_dir, session_path)
+                .map_err(|e| format!("Failed to archive session file '{session_path_str}': {e}"))?;
+            total_size += stored.size_bytes;
+            session_count += 1;
+
+            // Store subagent files if requested
+            let mut subagent_size: u64 = 0;
+            let mut subagents: Vec<serde_json::Value> = Vec::new();
+
+            if include_subagents {
+                let mut used_subagent_names: HashSet<String> = HashSet::new();
+                for subagent_file in &find_subagent_files(session_path) {
+                    let subagent_meta = fs::symlink_metadata(subagent_file).map_err(|e| {
+                        format!(
+                            "Failed to inspect subagent file '{}': {e}",
+                            subagent_file.display()
+                        )
+                    })?;
+                    if !subagent_meta.is_file() {
                         return Err(format!(
-                            "Session path is a symlink (rejected for security): {session_path_str}"
+                            "Subagent path is not a file: {}",
+                            subagent_file.display()
                         ));
                     }
-                    Ok(_) => {}
-                    Err(_) => {
-                        return Err(format!("Session file not found: {session_path_str}"));
-                    }
-                }
 
-                let file_name = session_path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .ok_or_else(|| format!("Invalid session file name: {session_path_str}"))?;
-
-                let mut dest = sessions_dir.join(file_name);
-                // Handle filename collision: append numeric suffix
-                if dest.exists() {
-                    let stem = session_path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("session");
-                    let mut suffix = 1u32;
-                    loop {
-                        dest = sessions_dir.join(format!("{stem}_{suffix}.jsonl"));
-                        if !dest.exists() {
-                            break;
-                        }
-                        suffix += 1;
-                    }
-                }
-                fs::copy(session_path, &dest).map_err(|e| {
-                    format!("Failed to copy session file '{session_path_str}': {e}")
-                })?;
-
-                let dest_file_name = dest
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or(file_name)
-                    .to_string();
-                let dest_stem = dest
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("session")
-                    .to_string();
-                let file_size = dest.metadata().map(|m| m.len()).unwrap_or(0);
-                total_size += file_size;
-                session_count += 1;
-
-                // Copy subagent files if requested
-                let mut subagent_count: u32 = 0;
-                let mut subagent_size: u64 = 0;
-
-                if include_subagents {
-                    let subagent_files = find_subagent_files(session_path);
-                    if !subagent_files.is_empty() {
-                        let dest_subagent_dir = subagents_dir.join(&dest_stem);
-                        fs::create_dir_all(&dest_subagent_dir)
-                            .map_err(|e| format!("Failed to create subagents directory: {e}"))?;
-
-                        for subagent_file in &subagent_files {
-                            let subagent_meta =
-                                fs::symlink_metadata(subagent_file).map_err(|e| {
-                                    format!(
-                                        "Failed to inspect subagent file '{}': {e}",
-                                        subagent_file.display()
-                                    )
-                                })?;
-                            if !subagent_meta.is_file() {
-                                return Err(format!(
-                                    "Subagent path is not a file: {}",
-                                    subagent_file.display()
-                                ));
-                            }
-
-                            let sa_name = subagent_file
-                                .file_name()
-                                .and_then(|n| n.to_str())
-                                .unwrap_or("agent.jsonl");
-                            let sa_dest = dest_subagent_dir.join(sa_name);
-                            fs::copy(subagent_file, &sa_dest).map_err(|e| {
-                                format!(
-                                    "Failed to copy subagent file '{}': {e}",
-                                    subagent_file.display()
-                                )
-                            })?;
-                            let copied_meta = sa_dest.metadata().map_err(|e| {
-                                format!(
-                                    "Failed to read copied subagent file '{}': {e}",
-                                    sa_dest.display()
-                                )
-                            })?;
-                            subagent_size += copied_meta.len();
-                            subagent_count += 1;
-                        }
-                        total_size += subagent_size;
+                    let sa_name = subagent_file
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("agent.jsonl")
+                        .to_string();
+                    if !used_subagent_names.insert(sa_name.clone()) {
+                        continue;
                     }
+                    let sa_stored = archive_store::store_file(&archives_dir, subagent_file)
+                        .map_err(|e| {
+                            format!(
+                                "Failed to archive subagent file '{}': {e}",
+                                subagent_file.display()
+                            )
+                        })?;
+                    subagent_size += sa_stored.size_bytes;
+                    subagents.push(serde_json::json!({
+                        "fileName": sa_name,
+                        "object": sa_stored.hash,
+                        "sizeBytes": sa_stored.size_bytes,
+                        "storedBytes": sa_stored.stored_bytes,
+                        "messageCount": count_messages(subagent_file),
+                    }));
                 }
-
-                // Extract per-session metadata for the archive manifest
-                let (msg_count, first_ts, last_ts, summary) = extract_session_metadata(&dest);
-                per_session_info.push(serde_json::json!({
-                    "sessionId": dest_stem,
-                    "fileName": dest_file_name,
-                    "originalFilePath": session_path_str,
-                    "messageCount": msg_count,
-                    "firstMessageTime": first_ts,
-                    "lastMessageTime": last_ts,
-                    "summary": summary,
-                    "sizeBytes": file_size,
-                    "subagentCount": subagent_count,
-                    "subagentSizeBytes": subagent_size,
-                }));
+                total_size += subagent_size;
             }
 
-            // Write per-archive manifest
-            let created_at = Utc::now().to_rfc3339();
-            let archive_manifest_path = archive_dir.join("manifest.json");
-            let archive_manifest = serde_json::json!({
-                "version": 1,
-                "archiveId": archive_id,
-                "name": name,
-                "description": description,
-                "createdAt": &created_at,
-                "sourceProvider": source_provider,
-                "sourceProjectPath": source_project_path,
-                "sourceProjectName": source_project_name,
-                "includeSubagents": include_subagents,
-                "sessions": per_session_info,
-            });
-            let manifest_content = serde_json::to_string_pretty(&archive_manifest)
-                .map_err(|e| format!("Failed to serialize per-archive manifest: {e}"))?;
-
-            let tmp_path =
-                archive_manifest_path.with_extension(format!("json.{}.tmp", Uuid::new_v4()));
-            let mut f = fs::File::create(&tmp_path)
-                .map_err(|e| format!("Failed to create manifest temp file: {e}"))?;
-            f.write_all(manifest_content.as_bytes())
-                .map_err(|e| format!("Failed to write manifest temp file: {e}"))?;
-            f.sync_all()
-                .map_err(|e| format!("Failed to sync manifest temp file: {e}"))?;
-            drop(f);
-            super::fs_utils::atomic_rename(&tmp_path, &archive_manifest_path)?;
-
-            // Update global manifest
-            let entry = ArchiveEntry {
-                id: archive_id.clone(),
-                name: name.clone(),
-                description: description.clone(),
-                created_at: created_at.clone(),
-                source_provider,
-                source_project_path,
-                source_project_name,
-                session_count,
-                total_size_bytes: total_size,
-                include_subagents,
-            };
+            // Extract per-session metadata for the archive manifest
+            let (msg_count, first_ts, last_ts, summary) = extract_session_metadata(session_path);
+            per_session_info.push(serde_json::json!({
+                "sessionId": dest_stem,
+                "fileName": dest_file_name,
+                "originalFilePath": session_path_str,
+                "messageCount": msg_count,
+                "firstMessageTime": first_ts,
+                "lastMessageTime": last_ts,
+                "summary": summary,
+                "sizeBytes": stored.size_bytes,
+                "object": stored.hash,
+                "storedBytes": stored.stored_bytes,
+                "subagentCount": subagents.len(),
+                "subagentSizeBytes": subagent_size,
+                "subagents": subagents,
+            }));
+        }
 
-            let mut global = load_manifest()?;
-            global.archives.push(entry.clone());
-            save_manifest(&global)?;
+        // Write per-archive manifest
+        let created_at = Utc::now().to_rfc3339();
+        let archive_manifest_path = archive_dir.join("manifest.json");
+        let archive_manifest = serde_json::json!({
+            "version": 2,
+            "storage": COMPRESSED_STORAGE,
+            "archiveId": archive_id,
+            "name": name,
+            "description": description,
+            "createdAt": &created_at,
+            "sourceProvider": source_provider,
+            "sourceProjectPath": source_project_path,
+            "sourceProjectName": source_project_name,
+            "includeSubagents": include_subagents,
+            "sessions": per_session_info,
+        });
+        let manifest_content = serde_json::to_string_pretty(&archive_manifest)
+            .map_err(|e| format!("Failed to serialize per-archive manifest: {e}"))?;
+
+        let tmp_path = archive_manifest_path.with_extension(format!("json.{}.tmp", Uuid::new_v4()));
+        let mut f = fs::File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create manifest temp file: {e}"))?;
+        f.write_all(manifest_content.as_bytes())
+            .map_err(|e| format!("Failed to write manifest temp file: {e}"))?;
+        f.sync_all()
+            .map_err(|e| format!("Failed to sync manifest temp file: {e}"))?;
+        drop(f);
+        super::fs_utils::atomic_rename(&tmp_path, &archive_manifest_path)?;
+
+        // Update global manifest
+        let entry = ArchiveEntry {
+            id: archive_id.clone(),
+            name: name.clone(),
+            description: description.clone(),
+            created_at: created_at.clone(),
+            source_provider,
+            source_project_path,
+            source_project_name,
+            session_count,
+            total_size_bytes: total_size,
+            include_subagents,
+        };
+
+        let mut global = load_manifest()?;
+        global.archives.push(entry.clone());
+        save_manifest(&global)?;
 
-            Ok(entry)
-        })(); // end inner closure
+        Ok(entry)
+    })(); // end inner closure
 
-        if result.is_err() {
-            let _ = fs::remove_dir_all(&archive_dir);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&archive_dir);
+        if let Err(e) = collect_unreferenced_objects(&archives_dir) {
+            log::warn!("Archive object cleanup skipped: {e}");
         }
+    }
 
-        result
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?
+    result
 }
 
 /// Deletes an archive directory and removes it from the global manifest.
@@ -864,31 +1069,44 @@ pub async fn create_archive(
 /// * `archive_id` - UUID of the archive to delete
 #[tauri::command]
 pub async fn delete_archive(archive_id: String) -> Result<(), String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        validate_archive_id(&archive_id)?;
+    tauri::async_runtime::spawn_blocking(move || delete_archive_blocking(&archive_id))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
 
-        // Update manifest first, then delete directory.
-        // An orphan directory is recoverable; a dangling manifest entry is not.
-        let mut manifest = load_manifest()?;
-        let initial_len = manifest.archives.len();
-        manifest.archives.retain(|a| a.id != archive_id);
+/// Blocking body of [`delete_archive`], shared with the background archiver.
+pub(crate) fn delete_archive_blocking(archive_id: &str) -> Result<(), String> {
+    validate_archive_id(archive_id)?;
 
-        if manifest.archives.len() == initial_len {
-            return Err(format!("Archive not found in manifest: {archive_id}"));
-        }
+    // Held across the manifest update too: `create_archive_blocking` saves
+    // the manifest under this lock, and the two must not overwrite each other.
+    let _store = archive_store::lock_store();
 
-        save_manifest(&manifest)?;
+    // Update manifest first, then delete directory.
+    // An orphan directory is recoverable; a dangling manifest entry is not.
+    let mut manifest = load_manifest()?;
+    let initial_len = manifest.archives.len();
+    manifest.archives.retain(|a| a.id != archive_id);
 
-        let archive_dir = get_archive_dir(&archive_id)?;
-        if archive_dir.exists() {
-            fs::remove_dir_all(&archive_dir)
-                .map_err(|e| format!("Failed to delete archive directory: {e}"))?;
-        }
+    if manifest.archives.len() == initial_len {
+        return Err(format!("Archive not found in manifest: {archive_id}"));
+    }
 
-        Ok(())
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?
+    save_manifest(&manifest)?;
+
+    let archive_dir = get_archive_dir(archive_id)?;
+    if archive_dir.exists() {
+        fs::remove_dir_all(&archive_dir)
+            .map_err(|e| format!("Failed to delete archive directory: {e}"))?;
+    }
+
+    // Objects shared with other archives stay; the archive is already gone,
+    // so a failed cleanup only leaves reclaimable space behind.
+    if let Err(e) = collect_unreferenced_objects(&get_archives_dir()?) {
+        log::warn!("Archive object cleanup skipped: {e}");
+    }
+
+    Ok(())
 }
 
 /// Renames an archive (updates name in manifest and renames the directory).
@@ -992,201 +1210,280 @@ pub async fn rename_archive(archive_id: String, new_name: String) -> Result<Stri
     .map_err(|e| format!("Task join error: {e}"))?
 }
 
+/// User metadata stored with a session in a per-archive manifest.
+fn session_user_metadata(meta: &serde_json::Value) -> Option<SessionMetadata> {
+    meta.get("userMetadata")
+        .and_then(|v| serde_json::from_value::<SessionMetadata>(v.clone()).ok())
+        .filter(|m| !m.is_empty())
+}
+
+/// Builds session info for a compressed archive's manifest entry; `None` for
+/// entries of legacy archives, whose files are read from disk instead.
+fn stored_session_info(meta: &serde_json::Value) -> Option<ArchiveSessionInfo> {
+    meta.get("object")?;
+    let str_field = |v: &serde_json::Value, key: &str| {
+        v.get(key)
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("")
+            .to_string()
+    };
+    let u64_field = |v: &serde_json::Value, key: &str| {
+        v.get(key).and_then(serde_json::Value::as_u64).unwrap_or(0)
+    };
+
+    let file_name = str_field(meta, "fileName");
+    let session_id = Path::new(&file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let mut subagents: Vec<SubagentFileInfo> = meta
+        .get("subagents")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|sa| SubagentFileInfo {
+            file_name: str_field(sa, "fileName"),
+            size_bytes: u64_field(sa, "sizeBytes"),
+            message_count: u64_field(sa, "messageCount") as usize,
+        })
+        .collect();
+    subagents.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+    Some(ArchiveSessionInfo {
+        session_id,
+        file_name,
+        original_file_path: str_field(meta, "originalFilePath"),
+        message_count: u64_field(meta, "messageCount") as usize,
+        first_message_time: str_field(meta, "firstMessageTime"),
+        last_message_time: str_field(meta, "lastMessageTime"),
+        summary: meta
+            .get("summary")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string),
+        size_bytes: u64_field(meta, "sizeBytes"),
+        subagent_count: subagents.len() as u32,
+        subagent_size_bytes: subagents.iter().map(|sa| sa.size_bytes).sum(),
+        subagents,
+        user_metadata: session_user_metadata(meta),
+    })
+}
+
 /// Lists all sessions stored within a specific archive.
 ///
 /// # Arguments
 /// * `archive_id` - UUID of the archive
 #[tauri::command]
 pub async fn get_archive_sessions(archive_id: String) -> Result<Vec<ArchiveSessionInfo>, String> {
-    tauri::async_runtime::spawn_blocking(move || {
-        validate_archive_id(&archive_id)?;
-
-        let archive_dir = get_archive_dir(&archive_id)?;
-        if !ensure_real_directory(&archive_dir, "Archive directory")? {
-            return Err(format!("Archive not found: {archive_id}"));
-        }
+    tauri::async_runtime::spawn_blocking(move || list_archive_sessions(&archive_id))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
 
-        let sessions_dir = archive_dir.join("sessions");
-        let subagents_dir = archive_dir.join("subagents");
+/// Blocking body of [`get_archive_sessions`], shared with bundle export.
+pub(crate) fn list_archive_sessions(archive_id: &str) -> Result<Vec<ArchiveSessionInfo>, String> {
+    validate_archive_id(archive_id)?;
 
-        if !ensure_real_directory(&sessions_dir, "Archive sessions directory")? {
-            return Ok(Vec::new());
-        }
+    let archive_dir = get_archive_dir(archive_id)?;
+    if !ensure_real_directory(&archive_dir, "Archive directory")? {
+        return Err(format!("Archive not found: {archive_id}"));
+    }
 
-        // Try to load metadata from the per-archive manifest for richer information
-        let per_manifest: Option<serde_json::Value> = {
-            let path = archive_dir.join("manifest.json");
-            fs::read_to_string(&path)
-                .ok()
-                .and_then(|c| serde_json::from_str(&c).ok())
+    let sessions_dir = archive_dir.join("sessions");
+    let subagents_dir = archive_dir.join("subagents");
+
+    // Try to load metadata from the per-archive manifest for richer information
+    let per_manifest = read_per_archive_manifest(&archive_dir);
+
+    let session_meta_map: std::collections::HashMap<String, serde_json::Value> =
+        if let Some(ref pm) = per_manifest {
+            pm.get("sessions")
+                .and_then(serde_json::Value::as_array)
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|s| {
+                            s.get("fileName")
+                                .and_then(serde_json::Value::as_str)
+                                .map(|k| (k.to_string(), s.clone()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            std::collections::HashMap::new()
         };
 
-        let session_meta_map: std::collections::HashMap<String, serde_json::Value> =
-            if let Some(ref pm) = per_manifest {
-                pm.get("sessions")
-                    .and_then(serde_json::Value::as_array)
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|s| {
-                                s.get("fileName")
-                                    .and_then(serde_json::Value::as_str)
-                                    .map(|k| (k.to_string(), s.clone()))
-                            })
-                            .collect()
-                    })
-                    .unwrap_or_default()
-            } else {
-                std::collections::HashMap::new()
-            };
+    // Compressed archives list their sessions in the manifest only; legacy
+    // archives keep plain copies under sessions/.
+    let mut sessions: Vec<ArchiveSessionInfo> = session_meta_map
+        .values()
+        .filter_map(stored_session_info)
+        .collect();
 
-        let mut sessions = Vec::new();
+    let legacy_entries: Vec<fs::DirEntry> =
+        if ensure_real_directory(&sessions_dir, "Archive sessions directory")? {
+            fs::read_dir(&sessions_dir)
+                .map_err(|e| format!("Failed to read sessions directory: {e}"))?
+                .flatten()
+                .collect()
+        } else {
+            Vec::new()
+        };
 
-        let rd = fs::read_dir(&sessions_dir)
-            .map_err(|e| format!("Failed to read sessions directory: {e}"))?;
+    for entry in legacy_entries {
+        let path = entry.path();
+        let Ok(path_meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if path_meta.file_type().is_symlink() || !path_meta.is_file() {
+            continue;
+        }
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
 
-        for entry in rd.flatten() {
-            let path = entry.path();
-            let Ok(path_meta) = fs::symlink_metadata(&path) else {
-                continue;
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_string();
+        let size_bytes = path_meta.len();
+
+        // Prefer cached metadata from manifest, fall back to live parsing
+        let (message_count, first_message_time, last_message_time, summary) =
+            if let Some(meta) = session_meta_map.get(&file_name) {
+                (
+                    meta.get("messageCount")
+                        .and_then(serde_json::Value::as_u64)
+                        .map(|n| n as usize)
+                        .unwrap_or_else(|| count_messages(&path)),
+                    meta.get("firstMessageTime")
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                    meta.get("lastMessageTime")
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or("")
+                        .to_string(),
+                    meta.get("summary")
+                        .and_then(serde_json::Value::as_str)
+                        .map(str::to_string),
+                )
+            } else {
+                let (mc, first, last, sum) = extract_session_metadata(&path);
+                (mc, first, last, sum)
             };
-            if path_meta.file_type().is_symlink() || !path_meta.is_file() {
-                continue;
-            }
-            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-                continue;
-            }
-
-            let file_name = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("")
-                .to_string();
-            let stem = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_string();
-            let size_bytes = path_meta.len();
-
-            // Prefer cached metadata from manifest, fall back to live parsing
-            let (message_count, first_message_time, last_message_time, summary) =
-                if let Some(meta) = session_meta_map.get(&file_name) {
-                    (
-                        meta.get("messageCount")
-                            .and_then(serde_json::Value::as_u64)
-                            .map(|n| n as usize)
-                            .unwrap_or_else(|| count_messages(&path)),
-                        meta.get("firstMessageTime")
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("")
-                            .to_string(),
-                        meta.get("lastMessageTime")
-                            .and_then(serde_json::Value::as_str)
-                            .unwrap_or("")
-                            .to_string(),
-                        meta.get("summary")
-                            .and_then(serde_json::Value::as_str)
-                            .map(str::to_string),
-                    )
-                } else {
-                    let (mc, first, last, sum) = extract_session_metadata(&path);
-                    (mc, first, last, sum)
-                };
 
-            // Collect subagent file info for this session
-            let (subagent_count, subagent_size_bytes, subagents) = {
-                let sa_dir = subagents_dir.join(&stem);
-                let mut sa_list: Vec<SubagentFileInfo> = Vec::new();
-                let mut total_sa_size: u64 = 0;
-
-                if sa_dir.exists() {
-                    if let Ok(dir_meta) = fs::symlink_metadata(&sa_dir) {
-                        if !dir_meta.file_type().is_symlink() {
-                            if let Ok(rd) = fs::read_dir(&sa_dir) {
-                                for sa_entry in rd.flatten() {
-                                    let sa_path = sa_entry.path();
-                                    let Ok(sa_meta) = fs::symlink_metadata(&sa_path) else {
-                                        continue;
-                                    };
-                                    if sa_meta.file_type().is_symlink() {
-                                        continue;
-                                    }
-                                    if !sa_meta.is_file() {
-                                        continue;
-                                    }
-                                    if sa_path.extension().and_then(|e| e.to_str()) != Some("jsonl")
-                                    {
-                                        continue;
-                                    }
-                                    let sa_file_name = sa_path
-                                        .file_name()
-                                        .and_then(|n| n.to_str())
-                                        .unwrap_or("")
-                                        .to_string();
-                                    let sa_size = sa_meta.len();
-                                    let sa_msg_count = count_messages(&sa_path);
-                                    total_sa_size += sa_size;
-                                    sa_list.push(SubagentFileInfo {
-                                        file_name: sa_file_name,
-                                        size_bytes: sa_size,
-                                        message_count: sa_msg_count,
-                                    });
+        // Collect subagent file info for this session
+        let (subagent_count, subagent_size_bytes, subagents) = {
+            let sa_dir = subagents_dir.join(&stem);
+            let mut sa_list: Vec<SubagentFileInfo> = Vec::new();
+            let mut total_sa_size: u64 = 0;
+
+            if sa_dir.exists() {
+                if let Ok(dir_meta) = fs::symlink_metadata(&sa_dir) {
+                    if !dir_meta.file_type().is_symlink() {
+                        if let Ok(rd) = fs::read_dir(&sa_dir) {
+                            for sa_entry in rd.flatten() {
+                                let sa_path = sa_entry.path();
+                                let Ok(sa_meta) = fs::symlink_metadata(&sa_path) else {
+                                    continue;
+                                };
+                                if sa_meta.file_type().is_symlink() {
+                                    continue;
                                 }
+                                if !sa_meta.is_file() {
+                                    continue;
+                                }
+                                if sa_path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                                    continue;
+                                }
+                                let sa_file_name = sa_path
+                                    .file_name()
+                                    .and_then(|n| n.to_str())
+                                    .unwrap_or("")
+                                    .to_string();
+                                let sa_size = sa_meta.len();
+                                let sa_msg_count = count_messages(&sa_path);
+                                total_sa_size += sa_size;
+                                sa_list.push(SubagentFileInfo {
+                                    file_name: sa_file_name,
+                                    size_bytes: sa_size,
+                                    message_count: sa_msg_count,
+                                });
                             }
                         }
                     }
                 }
-                sa_list.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+            }
+            sa_list.sort_by(|a, b| a.file_name.cmp(&b.file_name));
 
-                (sa_list.len() as u32, total_sa_size, sa_list)
-            };
+            (sa_list.len() as u32, total_sa_size, sa_list)
+        };
 
-            // Try to get the original file path from metadata
-            let original_file_path = if let Some(meta) = session_meta_map.get(&file_name) {
-                meta.get("originalFilePath")
-                    .and_then(serde_json::Value::as_str)
-                    .unwrap_or("")
-                    .to_string()
-            } else {
-                String::new()
-            };
+        // Try to get the original file path from metadata
+        let original_file_path = if let Some(meta) = session_meta_map.get(&file_name) {
+            meta.get("originalFilePath")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("")
+                .to_string()
+        } else {
+            String::new()
+        };
+        let user_metadata = session_meta_map
+            .get(&file_name)
+            .and_then(session_user_metadata);
+
+        sessions.push(ArchiveSessionInfo {
+            session_id: stem,
+            file_name,
+            original_file_path,
+            message_count,
+            first_message_time,
+            last_message_time,
+            summary,
+            size_bytes,
+            subagent_count,
+            subagent_size_bytes,
+            subagents,
+            user_metadata,
+        });
+    }
 
-            sessions.push(ArchiveSessionInfo {
-                session_id: stem,
-                file_name,
-                original_file_path,
-                message_count,
-                first_message_time,
-                last_message_time,
-                summary,
-                size_bytes,
-                subagent_count,
-                subagent_size_bytes,
-                subagents,
-            });
-        }
+    // Sort by first message time descending (newest first), falling back to file name
+    sessions.sort_by(|a, b| {
+        b.first_message_time
+            .cmp(&a.first_message_time)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
 
-        // Sort by first message time descending (newest first), falling back to file name
-        sessions.sort_by(|a, b| {
-            b.first_message_time
-                .cmp(&a.first_message_time)
-                .then_with(|| b.file_name.cmp(&a.file_name))
-        });
+    Ok(sessions)
+}
 
-        Ok(sessions)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?
+/// Parses a provider session snapshot written by [`snapshot_provider_session`].
+fn parse_snapshot(data: &[u8]) -> Result<Vec<crate::models::ClaudeMessage>, String> {
+    data.split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|line| {
+            serde_json::from_slice(line)
+                .map_err(|e| format!("Failed to parse archived session snapshot: {e}"))
+        })
+        .collect()
 }
 
 /// Loads all messages from a specific session file within an archive.
 ///
-/// Constructs the full path from `archive_id` and `session_file_name`, then
-/// delegates to the existing session loader.
+/// Compressed archives decompress the session's object and parse it in memory;
+/// legacy archives delegate to the existing session loader on the plain copy.
 ///
 /// # Arguments
 /// * `archive_id` - UUID of the archive
-/// * `session_file_name` - File name (e.g. `"abc123.jsonl"`) within the archive's sessions/ dir
+/// * `session_file_name` - File name (e.g. `"abc123.jsonl"`) as listed by `get_archive_sessions`
 #[tauri::command]
 pub async fn load_archive_session_messages(
     archive_id: String,
@@ -1217,6 +1514,39 @@ pub async fn load_archive_session_messages(
     if !ensure_real_directory(&archive_dir, "Archive directory")? {
         return Err(format!("Archive not found: {archive_id}"));
     }
+
+    // Compressed archives: decompress the session's object and parse it in memory.
+    let stored_entry = read_per_archive_manifest(&archive_dir).and_then(|pm| {
+        pm.get("sessions")?
+            .as_array()?
+            .iter()
+            .find(|s| {
+                s.get("fileName").and_then(serde_json::Value::as_str) == Some(&session_file_name)
+            })
+            .cloned()
+    });
+    if let Some(hash) = stored_entry
+        .as_ref()
+        .and_then(|e| e.get("object")?.as_str())
+        .map(str::to_string)
+    {
+        let is_snapshot = stored_entry
+            .as_ref()
+            .and_then(|e| e.get("format")?.as_str())
+            == Some(SNAPSHOT_FORMAT);
+        let archives_dir = get_archives_dir()?;
+        return tauri::async_runtime::spawn_blocking(move || {
+            let data = archive_store::read_object(&archives_dir, &hash)?;
+            if is_snapshot {
+                parse_snapshot(&data)
+            } else {
+                Ok(crate::commands::session::parse_session_bytes(&data))
+            }
+        })
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?;
+    }
+
     let sessions_dir = archive_dir.join("sessions");
     if !ensure_real_directory(&sessions_dir, "Archive sessions directory")? {
         return Err(format!(
@@ -1243,6 +1573,7 @@ pub async fn get_archive_disk_usage() -> Result<ArchiveDiskUsage, String> {
         if !archives_dir.exists() {
             return Ok(ArchiveDiskUsage {
                 total_bytes: 0,
+                logical_bytes: 0,
                 archive_count: 0,
                 session_count: 0,
                 per_archive: Vec::new(),
@@ -1251,15 +1582,31 @@ pub async fn get_archive_disk_usage() -> Result<ArchiveDiskUsage, String> {
 
         let manifest = load_manifest()?;
         let mut total_bytes: u64 = 0;
+        let mut logical_bytes: u64 = 0;
         let mut total_sessions: usize = 0;
         let mut per_archive: Vec<ArchiveDiskEntry> = Vec::new();
+        let mut counted_objects: HashSet<String> = HashSet::new();
 
         for entry in &manifest.archives {
             let archive_dir = archives_dir.join(&entry.id);
-            let size = dir_size(&archive_dir);
+            // An archive's size includes every object it refers to, but an
+            // object shared between archives counts once toward the total.
+            let own_size = dir_size(&archive_dir);
+            let mut size = own_size;
+            total_bytes += own_size;
+            if let Some(pm) = read_per_archive_manifest(&archive_dir) {
+                let refs: HashSet<String> = manifest_object_refs(&pm).into_iter().collect();
+                for hash in refs {
+                    let stored = archive_store::stored_size(&archives_dir, &hash);
+                    size += stored;
+                    if counted_objects.insert(hash) {
+                        total_bytes += stored;
+                    }
+                }
+            }
             let session_count = entry.session_count;
 
-            total_bytes += size;
+            logical_bytes += entry.total_size_bytes;
             total_sessions += session_count as usize;
 
             per_archive.push(ArchiveDiskEntry {
@@ -1272,6 +1619,7 @@ pub async fn get_archive_disk_usage() -> Result<ArchiveDiskUsage, String> {
 
         Ok(ArchiveDiskUsage {
             total_bytes,
+            logical_bytes,
             archive_count: manifest.archives.len(),
             session_count: total_sessions,
             per_archive,
@@ -1296,139 +1644,146 @@ pub async fn get_expiring_sessions(
     threshold_days: i64,
 ) -> Result<Vec<ExpiringSession>, String> {
     tauri::async_runtime::spawn_blocking(move || {
-        // Security: reject path traversal
-        let project_pb = PathBuf::from(&project_path);
-        if !project_pb.is_absolute() {
-            return Err("project_path must be absolute".to_string());
-        }
-        if project_pb
-            .components()
-            .any(|c| matches!(c, std::path::Component::ParentDir))
-        {
-            return Err("project_path cannot contain '..' components".to_string());
+        find_expiring_sessions(&project_path, threshold_days)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Blocking body of [`get_expiring_sessions`], shared with the background archiver.
+pub(crate) fn find_expiring_sessions(
+    project_path: &str,
+    threshold_days: i64,
+) -> Result<Vec<ExpiringSession>, String> {
+    // Security: reject path traversal
+    let project_pb = PathBuf::from(project_path);
+    if !project_pb.is_absolute() {
+        return Err("project_path must be absolute".to_string());
+    }
+    if project_pb
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return Err("project_path cannot contain '..' components".to_string());
+    }
+    if !project_pb.exists() {
+        return Err(format!("project_path does not exist: {project_path}"));
+    }
+
+    // Read cleanupPeriodDays from ~/.claude/settings.json
+    let cleanup_period_days: i64 = {
+        let home = dirs::home_dir().ok_or("Could not find home directory")?;
+        let settings_path = home.join(".claude").join("settings.json");
+        if settings_path.exists() {
+            fs::read_to_string(&settings_path)
+                .ok()
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+                .and_then(|v| {
+                    v.get("cleanupPeriodDays")
+                        .and_then(serde_json::Value::as_i64)
+                })
+                .unwrap_or(30)
+        } else {
+            30
         }
-        if !project_pb.exists() {
-            return Err(format!("project_path does not exist: {project_path}"));
+    };
+
+    // Expiry boundary: files older than cleanup_period_days are expired.
+    // "expiring" means they are within threshold_days of that boundary.
+    // i.e. file mtime is older than (cleanup_period_days - threshold_days) days.
+    let expiry_cutoff_days = (cleanup_period_days - threshold_days).max(0);
+
+    let now = SystemTime::now();
+
+    let rd =
+        fs::read_dir(&project_pb).map_err(|e| format!("Failed to read project directory: {e}"))?;
+
+    let mut expiring = Vec::new();
+
+    for entry in rd.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
         }
 
-        // Read cleanupPeriodDays from ~/.claude/settings.json
-        let cleanup_period_days: i64 = {
-            let home = dirs::home_dir().ok_or("Could not find home directory")?;
-            let settings_path = home.join(".claude").join("settings.json");
-            if settings_path.exists() {
-                fs::read_to_string(&settings_path)
-                    .ok()
-                    .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
-                    .and_then(|v| {
-                        v.get("cleanupPeriodDays")
-                            .and_then(serde_json::Value::as_i64)
-                    })
-                    .unwrap_or(30)
-            } else {
-                30
-            }
+        let metadata = match path.metadata() {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        let file_size = metadata.len();
+        let mtime = match metadata.modified() {
+            Ok(t) => t,
+            Err(_) => continue,
         };
 
-        // Expiry boundary: files older than cleanup_period_days are expired.
-        // "expiring" means they are within threshold_days of that boundary.
-        // i.e. file mtime is older than (cleanup_period_days - threshold_days) days.
-        let expiry_cutoff_days = (cleanup_period_days - threshold_days).max(0);
+        let age_secs = now.duration_since(mtime).map(|d| d.as_secs()).unwrap_or(0);
+        #[allow(clippy::cast_possible_wrap)]
+        let age_days = (age_secs / 86400) as i64;
 
-        let now = SystemTime::now();
+        // If the file is already past the expiry_cutoff_days, it qualifies
+        if age_days >= expiry_cutoff_days {
+            let days_remaining = (cleanup_period_days - age_days).max(0);
 
-        let rd = fs::read_dir(&project_pb)
-            .map_err(|e| format!("Failed to read project directory: {e}"))?;
+            // Load the session info for this file
+            let file_path_str = path.to_string_lossy().to_string();
 
-        let mut expiring = Vec::new();
+            // Build a minimal ClaudeSession from file metadata + light parsing
+            let (msg_count, first_ts, last_ts, summary) = extract_session_metadata(&path);
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_string();
 
-        for entry in rd.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-                continue;
-            }
+            // Extract project name from path
+            let project_name = project_pb
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string();
 
-            let metadata = match path.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-            let file_size = metadata.len();
-            let mtime = match metadata.modified() {
-                Ok(t) => t,
-                Err(_) => continue,
+            let session = ClaudeSession {
+                session_id: file_path_str.clone(),
+                actual_session_id: stem,
+                file_path: file_path_str,
+                project_name,
+                message_count: msg_count,
+                first_message_time: first_ts,
+                last_message_time: last_ts,
+                last_modified: mtime
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .ok()
+                    .map(|d| {
+                        chrono::DateTime::<Utc>::from(
+                            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(d.as_secs()),
+                        )
+                        .to_rfc3339()
+                    })
+                    .unwrap_or_default(),
+                has_tool_use: false,
+                has_errors: false,
+                summary,
+                is_renamed: false,
+                provider: None,
+                storage_type: None,
+                entrypoint: None,
             };
 
-            let age_secs = now.duration_since(mtime).map(|d| d.as_secs()).unwrap_or(0);
-            #[allow(clippy::cast_possible_wrap)]
-            let age_days = (age_secs / 86400) as i64;
-
-            // If the file is already past the expiry_cutoff_days, it qualifies
-            if age_days >= expiry_cutoff_days {
-                let days_remaining = (cleanup_period_days - age_days).max(0);
-
-                // Load the session info for this file
-                let file_path_str = path.to_string_lossy().to_string();
-
-                // Build a minimal ClaudeSession from file metadata + light parsing
-                let (msg_count, first_ts, last_ts, summary) = extract_session_metadata(&path);
-                let stem = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                // Extract project name from path
-                let project_name = project_pb
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                let session = ClaudeSession {
-                    session_id: file_path_str.clone(),
-                    actual_session_id: stem,
-                    file_path: file_path_str,
-                    project_name,
-                    message_count: msg_count,
-                    first_message_time: first_ts,
-                    last_message_time: last_ts,
-                    last_modified: mtime
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .ok()
-                        .map(|d| {
-                            chrono::DateTime::<Utc>::from(
-                                SystemTime::UNIX_EPOCH
-                                    + std::time::Duration::from_secs(d.as_secs()),
-                            )
-                            .to_rfc3339()
-                        })
-                        .unwrap_or_default(),
-                    has_tool_use: false,
-                    has_errors: false,
-                    summary,
-                    is_renamed: false,
-                    provider: None,
-                    storage_type: None,
-                    entrypoint: None,
-                };
-
-                let subagent_count = find_subagent_files(&path).len() as u32;
+            let subagent_count = find_subagent_files(&path).len() as u32;
 
-                expiring.push(ExpiringSession {
-                    session,
-                    days_remaining,
-                    file_size_bytes: file_size,
-                    subagent_count,
-                });
-            }
+            expiring.push(ExpiringSession {
+                session,
+                days_remaining,
+                file_size_bytes: file_size,
+                subagent_count,
+            });
         }
+    }
 
-        // Sort by days_remaining ascending (most urgent first)
-        expiring.sort_by_key(|e| e.days_remaining);
+    // Sort by days_remaining ascending (most urgent first)
+    expiring.sort_by_key(|e| e.days_remaining);
 
-        Ok(expiring)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?
+    Ok(expiring)
 }
 
 /// Exports a session file to either Markdown or JSON format.
@@ -1436,10 +1791,16 @@ pub async fn get_expiring_sessions(
 /// # Arguments
 /// * `session_file_path` - Absolute path to the JSONL session file
 /// * `format` - Either `"markdown"` or `"json"`
+/// * `redact` - Scrub secrets, hostnames and home paths using the redaction
+///   config; the result then carries a report of what was replaced
+/// * `dry_run` - Only report what `redact` would replace, leaving `content`
+///   empty; implies `redact`
 #[tauri::command]
 pub async fn export_session(
     session_file_path: String,
     format: String,
+    redact: Option<bool>,
+    dry_run: Option<bool>,
 ) -> Result<ExportResult, String> {
     tauri::async_runtime::spawn_blocking(move || {
         let path = PathBuf::from(&session_file_path);
@@ -1464,11 +1825,33 @@ pub async fn export_session(
             .unwrap_or("unknown")
             .to_string();
 
-        let content = match format.as_str() {
-            "json" => jsonl_to_json_array(&path)?,
-            other => {
+        let dry_run = dry_run.unwrap_or(false);
+        let redactor = if dry_run || redact.unwrap_or(false) {
+            let config = crate::export::load_redaction_config(None)?;
+            Some(crate::export::Redactor::new(&config)?)
+        } else {
+            None
+        };
+
+        let (content, redaction) = match (format.as_str(), redactor) {
+            ("json", None) => (jsonl_to_json_array(&path)?, None),
+            ("json" | "markdown", redactor) => {
+                let mut messages = crate::export::load_jsonl_messages(&path)?;
+                let mut title = crate::export::session_title(&messages, &path);
+                let report = redactor.map(|r| r.redact_session(&mut messages, &mut title));
+                let content = if dry_run {
+                    String::new()
+                } else if format == "json" {
+                    serde_json::to_string_pretty(&messages)
+                        .map_err(|e| format!("Failed to serialize JSON array: {e}"))?
+                } else {
+                    crate::export::render_session_markdown(&messages, &title)
+                };
+                (content, report)
+            }
+            (other, _) => {
                 return Err(format!(
-                    "Unsupported export format '{other}': must be 'json'"
+                    "Unsupported export format '{other}': must be 'json' or 'markdown'"
                 ))
             }
         };
@@ -1477,6 +1860,7 @@ pub async fn export_session(
             content,
             format,
             session_id,
+            redaction,
         })
     })
     .await
@@ -2005,6 +2389,134 @@ mod tests {
         assert!(manifest.archives.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_compressed_archives_share_objects_until_deleted() {
+        let _temp = setup_test_env();
+
+        let session_dir = tempfile::tempdir().unwrap();
+        let session_path = session_dir.path().join("shared.jsonl");
+        let line = r#"{"type":"user","uuid":"u1","sessionId":"shared","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"hello"}}"#;
+        fs::write(&session_path, format!("{line}\n")).unwrap();
+        let paths = vec![session_path.to_string_lossy().to_string()];
+
+        let first = create_archive_blocking(
+            "First".to_string(),
+            None,
+            paths.clone(),
+            "claude".to_string(),
+            "/p".to_string(),
+            "p".to_string(),
+            false,
+        )
+        .unwrap();
+        let second = create_archive_blocking(
+            "Second".to_string(),
+            None,
+            paths,
+            "claude".to_string(),
+            "/p".to_string(),
+            "p".to_string(),
+            false,
+        )
+        .unwrap();
+
+        let archives_dir = get_archives_dir().unwrap();
+        assert!(!archives_dir.join(&first.id).join("sessions").exists());
+        let referenced = referenced_objects(&archives_dir).unwrap();
+        assert_eq!(referenced.len(), 1);
+
+        let messages = load_archive_session_messages(first.id.clone(), "shared.jsonl".to_string())
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+
+        let sessions = get_archive_sessions(second.id.clone()).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].size_bytes, line.len() as u64 + 1);
+
+        let usage = get_archive_disk_usage().await.unwrap();
+        let per_archive_sum: u64 = usage.per_archive.iter().map(|a| a.size_bytes).sum();
+        assert!(usage.total_bytes < per_archive_sum);
+        assert_eq!(usage.logical_bytes, 2 * (line.len() as u64 + 1));
+
+        delete_archive_blocking(&first.id).unwrap();
+        let messages = load_archive_session_messages(second.id.clone(), "shared.jsonl".to_string())
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+
+        delete_archive_blocking(&second.id).unwrap();
+        let remaining = fs::read_dir(archives_dir.join(archive_store::OBJECTS_DIR))
+            .map(Iterator::count)
+            .unwrap_or(0);
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_provider_sessions_are_archived_as_message_snapshots() {
+        let temp = setup_test_env();
+        let codex_home = temp.path().join(".codex");
+        let sessions_dir = codex_home.join("sessions");
+        fs::create_dir_all(&sessions_dir).unwrap();
+        env::set_var("CODEX_HOME", &codex_home);
+        let rollout = sessions_dir.join("rollout-2026-02-19T12-00-00-sess.jsonl");
+        fs::write(
+            &rollout,
+            concat!(
+                r#"{"timestamp":"2026-02-19T12:00:00Z","type":"session_meta","payload":{"id":"sess-1"}}"#,
+                "\n",
+                r#"{"timestamp":"2026-02-19T12:00:04Z","type":"response_item","payload":{"id":"item-1","type":"message","role":"assistant","content":[{"type":"output_text","text":"done"}]}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let entry = create_archive_blocking(
+            "Codex Snapshot".to_string(),
+            None,
+            vec![rollout.to_string_lossy().to_string()],
+            "codex".to_string(),
+            "/p".to_string(),
+            "p".to_string(),
+            true,
+        )
+        .unwrap();
+
+        // The snapshot no longer depends on the provider's own storage.
+        fs::remove_file(&rollout).unwrap();
+
+        let sessions = get_archive_sessions(entry.id.clone()).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(
+            sessions[0].file_name,
+            "rollout-2026-02-19T12-00-00-sess.jsonl"
+        );
+        assert_eq!(sessions[0].message_count, 1);
+
+        let messages = load_archive_session_messages(entry.id, sessions[0].file_name.clone())
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_type, "assistant");
+        assert_eq!(messages[0].provider.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn test_create_archive_rejects_unknown_provider() {
+        let _temp = setup_test_env();
+        let err = create_archive_blocking(
+            "Unknown".to_string(),
+            None,
+            vec!["x://session".to_string()],
+            "not-a-provider".to_string(),
+            "/p".to_string(),
+            "p".to_string(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("Unknown provider"));
+    }
+
     #[cfg(unix)]
     #[tokio::test]
     async fn test_get_archive_sessions_skips_symlinked_session_files() {
@@ -2031,6 +2543,7 @@ mod tests {
         fs::write(&outside_file, r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"outside"}}"#).unwrap();
 
         let archive_sessions_dir = get_archives_dir().unwrap().join(&entry.id).join("sessions");
+        fs::create_dir_all(&archive_sessions_dir).unwrap();
         unix_fs::symlink(&outside_file, archive_sessions_dir.join("linked.jsonl")).unwrap();
 
         let sessions = get_archive_sessions(entry.id).await.unwrap();
@@ -2064,6 +2577,7 @@ mod tests {
         fs::write(&outside_file, r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"outside"}}"#).unwrap();
 
         let archive_sessions_dir = get_archives_dir().unwrap().join(&entry.id).join("sessions");
+        fs::create_dir_all(&archive_sessions_dir).unwrap();
         unix_fs::symlink(&outside_file, archive_sessions_dir.join("linked.jsonl")).unwrap();
 
         let err = load_archive_session_messages(entry.id, "linked.jsonl".to_string())
@@ -2080,9 +2594,14 @@ mod tests {
 "#;
         fs::write(&path, content).unwrap();
 
-        let result = export_session(path.to_string_lossy().to_string(), "json".to_string())
-            .await
-            .unwrap();
+        let result = export_session(
+            path.to_string_lossy().to_string(),
+            "json".to_string(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(result.format, "json");
         assert_eq!(result.session_id, "export");
@@ -2091,21 +2610,107 @@ mod tests {
         assert_eq!(parsed.as_array().unwrap().len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_export_session_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.jsonl");
+        let content = r#"{"type":"summary","summary":"Fix the build"}
+{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"hi"}}
+{"type":"assistant","timestamp":"2026-01-01T00:00:01Z","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}
+"#;
+        fs::write(&path, content).unwrap();
+
+        let result = export_session(
+            path.to_string_lossy().to_string(),
+            "markdown".to_string(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.format, "markdown");
+        assert!(result.content.starts_with("# Session: Fix the build\n"));
+        assert!(result
+            .content
+            .contains("<summary>Tool: Bash(command: ls)</summary>"));
+    }
+
+    #[tokio::test]
+    async fn test_export_session_json_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.jsonl");
+        let content = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"use sk-ant-REDACTED"}}
+"#;
+        fs::write(&path, content).unwrap();
+
+        let result = export_session(
+            path.to_string_lossy().to_string(),
+            "json".to_string(),
+            Some(true),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.content.contains("sk-ant-"));
+        assert!(result.content.contains("[REDACTED:anthropic_api_key]"));
+        let report = result.redaction.unwrap();
+        assert_eq!(report.total, 1);
+        assert_eq!(report.findings[0].location, "/0/message/content");
+    }
+
+    #[tokio::test]
+    async fn test_export_session_dry_run_reports_without_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.jsonl");
+        let content = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"role":"user","content":"use sk-ant-REDACTED on db1.corp"}}
+"#;
+        fs::write(&path, content).unwrap();
+
+        let result = export_session(
+            path.to_string_lossy().to_string(),
+            "json".to_string(),
+            None,
+            Some(true),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.content.is_empty());
+        let report = result.redaction.unwrap();
+        assert_eq!(report.total, 2);
+        let rules: Vec<&str> = report.findings.iter().map(|f| f.rule.as_str()).collect();
+        assert!(rules.contains(&"anthropic_api_key"));
+        assert!(rules.contains(&"hostname"));
+    }
+
     #[tokio::test]
     async fn test_export_session_invalid_format() {
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("bad_fmt.jsonl");
         fs::write(&path, "").unwrap();
 
-        let result = export_session(path.to_string_lossy().to_string(), "pdf".to_string()).await;
+        let result = export_session(
+            path.to_string_lossy().to_string(),
+            "pdf".to_string(),
+            None,
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Unsupported export format"));
     }
 
     #[tokio::test]
     async fn test_export_session_relative_path_rejected() {
-        let result =
-            export_session("relative/path/file.jsonl".to_string(), "json".to_string()).await;
+        let result = export_session(
+            "relative/path/file.jsonl".to_string(),
+            "json".to_string(),
+            None,
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("absolute"));
     }
@@ -0,0 +1,734 @@
+//! Portable archive bundles for moving an archive between machines.
+//!
+//! A bundle is a single zstd-compressed tar file (`.chvarchive`):
+//!
+//! ```text
+//! bundle.json                          what the bundle holds, with checksums
+//! sessions/<file>.jsonl                archived session transcripts
+//! subagents/<session stem>/<file>.jsonl
+//! ```
+//!
+//! `bundle.json` always comes first and lists the SHA-256 and size of every
+//! other entry, together with the archive details and the user metadata (tags,
+//! notes, custom names, stars) of each session. Import refuses bundles with
+//! missing, extra or altered files, and stores an accepted bundle as a new
+//! compressed archive.
+
+use super::archive::{self, ArchiveEntry, ArchiveSessionInfo};
+use super::archive_store;
+use crate::models::{SessionMetadata, UserMetadata};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// `format` marker identifying a bundle.
+pub(crate) const BUNDLE_FORMAT: &str = "claude-history-viewer-archive";
+/// Newest bundle layout this build reads and the one it writes.
+pub(crate) const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const BUNDLE_MANIFEST_NAME: &str = "bundle.json";
+/// `bundle.json` is metadata only; anything bigger is not a bundle we wrote.
+const MAX_BUNDLE_MANIFEST_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Bundle entry names and their bytes, in write order.
+type BundleFiles = Vec<(String, Vec<u8>)>;
+
+/// Contents of `bundle.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub exported_at: String,
+    /// Version of the app that wrote the bundle.
+    pub app_version: String,
+    pub archive: BundleArchive,
+    pub sessions: Vec<BundleSession>,
+}
+
+/// Archive details carried by a bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleArchive {
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub source_provider: String,
+    pub source_project_path: String,
+    pub source_project_name: String,
+    pub include_subagents: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSession {
+    pub file_name: String,
+    pub original_file_path: String,
+    pub message_count: usize,
+    pub first_message_time: String,
+    pub last_message_time: String,
+    pub summary: Option<String>,
+    pub size_bytes: u64,
+    /// Lowercase hex SHA-256 of `sessions/<file_name>`.
+    pub sha256: String,
+    /// `messages` for provider snapshots; absent for Claude Code transcripts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub subagents: Vec<BundleFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<SessionMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFile {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    #[serde(default)]
+    pub message_count: usize,
+}
+
+/// Result of [`export_archive`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveExportSummary {
+    pub path: String,
+    pub session_count: usize,
+    pub subagent_count: usize,
+    /// Size of the bundle file.
+    pub bundle_bytes: u64,
+}
+
+/// Accepts plain `.jsonl` file names only, so bundle paths can't escape their folder.
+fn validate_bundle_file_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.contains('/')
+        || name.contains('\\')
+        || name.contains("..")
+        || name.contains('\0')
+        || name.starts_with('.')
+        || !Path::new(name)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+    {
+        return Err(format!("Invalid file name in bundle: '{name}'"));
+    }
+    Ok(())
+}
+
+fn session_entry_path(file_name: &str) -> String {
+    format!("sessions/{file_name}")
+}
+
+fn subagent_entry_path(session_file_name: &str, file_name: &str) -> String {
+    let stem = Path::new(session_file_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("session");
+    format!("subagents/{stem}/{file_name}")
+}
+
+/// Every file a bundle manifest promises, as entry path -> (sha256, size).
+/// Rejects unsafe or duplicate names and malformed checksums.
+fn expected_entries(manifest: &BundleManifest) -> Result<HashMap<String, (String, u64)>, String> {
+    if manifest.format != BUNDLE_FORMAT {
+        return Err("Not an archive bundle (unknown format)".to_string());
+    }
+    if manifest.format_version == 0 || manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported archive bundle version {} (this version reads up to {BUNDLE_FORMAT_VERSION})",
+            manifest.format_version
+        ));
+    }
+    if manifest.archive.name.trim().is_empty() {
+        return Err("Archive bundle has no archive name".to_string());
+    }
+
+    let mut expected = HashMap::new();
+    let mut add = |path: String, sha256: &str, size: u64| -> Result<(), String> {
+        if !archive_store::is_object_hash(sha256) {
+            return Err(format!("Invalid checksum for '{path}' in bundle"));
+        }
+        if expected
+            .insert(path.clone(), (sha256.to_string(), size))
+            .is_some()
+        {
+            return Err(format!("Duplicate file '{path}' in bundle"));
+        }
+        Ok(())
+    };
+    for session in &manifest.sessions {
+        validate_bundle_file_name(&session.file_name)?;
+        if let Some(format) = session.format.as_deref() {
+            if format != archive::SNAPSHOT_FORMAT {
+                return Err(format!("Unsupported session format '{format}' in bundle"));
+            }
+        }
+        add(
+            session_entry_path(&session.file_name),
+            &session.sha256,
+            session.size_bytes,
+        )?;
+        for subagent in &session.subagents {
+            validate_bundle_file_name(&subagent.file_name)?;
+            add(
+                subagent_entry_path(&session.file_name, &subagent.file_name),
+                &subagent.sha256,
+                subagent.size_bytes,
+            )?;
+        }
+    }
+    Ok(expected)
+}
+
+/// Reads an archived file's content, from the object store for compressed
+/// archives or from the plain copy for legacy ones.
+fn read_archived_file(
+    archives_dir: &Path,
+    object: Option<&str>,
+    legacy_path: &Path,
+) -> Result<Vec<u8>, String> {
+    if let Some(hash) = object {
+        return archive_store::read_object(archives_dir, hash);
+    }
+    archive::ensure_real_file(legacy_path, "Archive file")?;
+    fs::read(legacy_path).map_err(|e| format!("Failed to read '{}': {e}", legacy_path.display()))
+}
+
+fn object_of(value: Option<&serde_json::Value>) -> Option<&str> {
+    value?.get("object")?.as_str()
+}
+
+fn append_file<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    data: &[u8],
+    mtime: u64,
+) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, path, data)
+        .map_err(|e| format!("Failed to write '{path}' to bundle: {e}"))
+}
+
+/// Loads `user-data.json` straight from disk; missing or unreadable metadata
+/// only means the bundle carries none.
+fn load_user_metadata_from_disk() -> UserMetadata {
+    super::metadata::get_user_data_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Builds the bundle manifest and collects each file's bytes.
+fn collect_bundle(archive_id: &str) -> Result<(BundleManifest, BundleFiles), String> {
+    let entry: ArchiveEntry = archive::load_manifest()?
+        .archives
+        .into_iter()
+        .find(|a| a.id == archive_id)
+        .ok_or_else(|| format!("Archive not found: {archive_id}"))?;
+    let archives_dir = archive::get_archives_dir()?;
+    let archive_dir = archive::get_archive_dir(archive_id)?;
+    let per_manifest = archive::read_per_archive_manifest(&archive_dir);
+    let manifest_sessions: HashMap<&str, &serde_json::Value> = per_manifest
+        .as_ref()
+        .and_then(|pm| pm.get("sessions")?.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|s| Some((s.get("fileName")?.as_str()?, s)))
+        .collect();
+    let user_metadata = load_user_metadata_from_disk();
+
+    let sessions: Vec<ArchiveSessionInfo> = archive::list_archive_sessions(archive_id)?;
+    let mut files = Vec::new();
+    let mut bundle_sessions = Vec::new();
+    for info in sessions {
+        let meta = manifest_sessions.get(info.file_name.as_str()).copied();
+        let data = read_archived_file(
+            &archives_dir,
+            object_of(meta),
+            &archive_dir.join("sessions").join(&info.file_name),
+        )?;
+        let sha256 = format!("{:x}", Sha256::digest(&data));
+        let size_bytes = data.len() as u64;
+        files.push((session_entry_path(&info.file_name), data));
+
+        let stem = Path::new(&info.file_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("session")
+            .to_string();
+        let mut subagents = Vec::new();
+        for subagent in &info.subagents {
+            let sa_meta = meta
+                .and_then(|m| m.get("subagents")?.as_array())
+                .and_then(|list| {
+                    list.iter().find(|sa| {
+                        sa.get("fileName").and_then(serde_json::Value::as_str)
+                            == Some(subagent.file_name.as_str())
+                    })
+                });
+            let sa_data = read_archived_file(
+                &archives_dir,
+                object_of(sa_meta),
+                &archive_dir
+                    .join("subagents")
+                    .join(&stem)
+                    .join(&subagent.file_name),
+            )?;
+            subagents.push(BundleFile {
+                file_name: subagent.file_name.clone(),
+                size_bytes: sa_data.len() as u64,
+                sha256: format!("{:x}", Sha256::digest(&sa_data)),
+                message_count: subagent.message_count,
+            });
+            files.push((
+                subagent_entry_path(&info.file_name, &subagent.file_name),
+                sa_data,
+            ));
+        }
+
+        // Live metadata for the original session wins over what an earlier
+        // import brought along.
+        let live = user_metadata
+            .get_session(&info.original_file_path)
+            .filter(|m| !m.is_empty())
+            .cloned();
+        bundle_sessions.push(BundleSession {
+            file_name: info.file_name,
+            original_file_path: info.original_file_path,
+            message_count: info.message_count,
+            first_message_time: info.first_message_time,
+            last_message_time: info.last_message_time,
+            summary: info.summary,
+            size_bytes,
+            sha256,
+            format: meta
+                .and_then(|m| m.get("format")?.as_str())
+                .map(str::to_string),
+            subagents,
+            user_metadata: live.or(info.user_metadata),
+        });
+    }
+
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        archive: BundleArchive {
+            name: entry.name,
+            description: entry.description,
+            created_at: entry.created_at,
+            source_provider: entry.source_provider,
+            source_project_path: entry.source_project_path,
+            source_project_name: entry.source_project_name,
+            include_subagents: entry.include_subagents,
+        },
+        sessions: bundle_sessions,
+    };
+    Ok((manifest, files))
+}
+
+/// Blocking body of [`export_archive`].
+pub(crate) fn export_archive_blocking(
+    archive_id: &str,
+    output_path: &Path,
+) -> Result<ArchiveExportSummary, String> {
+    let (manifest, files) = collect_bundle(archive_id)?;
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize bundle manifest: {e}"))?;
+    let mtime = u64::try_from(Utc::now().timestamp()).unwrap_or(0);
+
+    let tmp_path = output_path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+    let result: Result<(), String> = (|| {
+        let file = fs::File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create bundle file: {e}"))?;
+        let mut encoder = zstd::Encoder::new(BufWriter::new(file), 9)
+            .map_err(|e| format!("Failed to start zstd encoder: {e}"))?;
+        encoder
+            .include_checksum(true)
+            .map_err(|e| format!("Failed to configure zstd encoder: {e}"))?;
+        let mut builder = tar::Builder::new(encoder);
+        append_file(&mut builder, BUNDLE_MANIFEST_NAME, &manifest_json, mtime)?;
+        for (path, data) in &files {
+            append_file(&mut builder, path, data, mtime)?;
+        }
+        let file = builder
+            .into_inner()
+            .map_err(|e| format!("Failed to finish bundle: {e}"))?
+            .finish()
+            .map_err(|e| format!("Failed to finish zstd stream: {e}"))?
+            .into_inner()
+            .map_err(|e| format!("Failed to flush bundle file: {e}"))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync bundle file: {e}"))
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    super::fs_utils::atomic_rename(&tmp_path, output_path)?;
+
+    Ok(ArchiveExportSummary {
+        path: output_path.to_string_lossy().to_string(),
+        session_count: manifest.sessions.len(),
+        subagent_count: manifest.sessions.iter().map(|s| s.subagents.len()).sum(),
+        bundle_bytes: output_path.metadata().map_or(0, |m| m.len()),
+    })
+}
+
+/// Blocking body of [`import_archive`].
+pub(crate) fn import_archive_blocking(bundle_path: &Path) -> Result<ArchiveEntry, String> {
+    let file = fs::File::open(bundle_path)
+        .map_err(|e| format!("Failed to open bundle '{}': {e}", bundle_path.display()))?;
+    let decoder = zstd::Decoder::new(BufReader::new(file))
+        .map_err(|e| format!("Failed to read bundle: {e}"))?;
+    let mut tar_archive = tar::Archive::new(decoder);
+    let mut entries = tar_archive
+        .entries()
+        .map_err(|e| format!("Failed to read bundle: {e}"))?;
+
+    let manifest: BundleManifest = {
+        let mut first = entries
+            .next()
+            .ok_or("Archive bundle is empty")?
+            .map_err(|e| format!("Failed to read bundle: {e}"))?;
+        let is_manifest = first
+            .path()
+            .ok()
+            .is_some_and(|p| p.as_ref() == Path::new(BUNDLE_MANIFEST_NAME));
+        if !is_manifest || first.size() > MAX_BUNDLE_MANIFEST_BYTES {
+            return Err("Not an archive bundle (bundle.json missing)".to_string());
+        }
+        let mut content = Vec::new();
+        first
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Failed to read bundle manifest: {e}"))?;
+        serde_json::from_slice(&content)
+            .map_err(|e| format!("Failed to parse bundle manifest: {e}"))?
+    };
+    let mut expected = expected_entries(&manifest)?;
+
+    archive::ensure_archives_dir()?;
+    let archives_dir = archive::get_archives_dir()?;
+    let archive_id = archive::new_archive_id(&manifest.archive.name);
+    let archive_dir = archives_dir.join(&archive_id);
+    fs::create_dir_all(&archive_dir)
+        .map_err(|e| format!("Failed to create archive directory: {e}"))?;
+
+    // Objects stay unreferenced until the archive manifest is written.
+    let _store = archive_store::lock_store();
+
+    let result: Result<ArchiveEntry, String> = (|| {
+        let mut stored: HashMap<String, archive_store::StoredObject> = HashMap::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read bundle: {e}"))?;
+            let path = entry
+                .path()
+                .ok()
+                .and_then(|p| p.to_str().map(str::to_string))
+                .ok_or("Archive bundle contains an unreadable file name")?;
+            if !entry.header().entry_type().is_file() {
+                // Directory entries written by other tar tools are harmless.
+                if entry.header().entry_type().is_dir() {
+                    continue;
+                }
+                return Err(format!("Unexpected entry '{path}' in bundle"));
+            }
+            let (sha256, size_bytes) = expected
+                .remove(&path)
+                .ok_or_else(|| format!("Unexpected file '{path}' in bundle"))?;
+            let object = archive_store::store_reader(&archives_dir, entry, &path)?;
+            if object.hash != sha256 || object.size_bytes != size_bytes {
+                return Err(format!(
+                    "Checksum mismatch for '{path}': the bundle is corrupted or was modified"
+                ));
+            }
+            stored.insert(path, object);
+        }
+        if let Some(missing) = expected.keys().min() {
+            return Err(format!("Archive bundle is missing '{missing}'"));
+        }
+
+        let mut total_size: u64 = 0;
+        let mut per_session_info = Vec::new();
+        for session in &manifest.sessions {
+            let object = &stored[&session_entry_path(&session.file_name)];
+            total_size += object.size_bytes;
+            let mut subagent_size: u64 = 0;
+            let subagents: Vec<serde_json::Value> = session
+                .subagents
+                .iter()
+                .map(|sa| {
+                    let sa_object =
+                        &stored[&subagent_entry_path(&session.file_name, &sa.file_name)];
+                    subagent_size += sa_object.size_bytes;
+                    serde_json::json!({
+                        "fileName": sa.file_name,
+                        "object": sa_object.hash,
+                        "sizeBytes": sa_object.size_bytes,
+                        "storedBytes": sa_object.stored_bytes,
+                        "messageCount": sa.message_count,
+                    })
+                })
+                .collect();
+            total_size += subagent_size;
+            let session_id = Path::new(&session.file_name)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("session");
+            per_session_info.push(serde_json::json!({
+                "sessionId": session_id,
+                "fileName": session.file_name,
+                "originalFilePath": session.original_file_path,
+                "format": session.format,
+                "messageCount": session.message_count,
+                "firstMessageTime": session.first_message_time,
+                "lastMessageTime": session.last_message_time,
+                "summary": session.summary,
+                "sizeBytes": object.size_bytes,
+                "object": object.hash,
+                "storedBytes": object.stored_bytes,
+                "subagentCount": subagents.len(),
+                "subagentSizeBytes": subagent_size,
+                "subagents": subagents,
+                "userMetadata": session.user_metadata,
+            }));
+        }
+
+        let bundle_archive = &manifest.archive;
+        let created_at = Utc::now().to_rfc3339();
+        let archive_manifest = serde_json::json!({
+            "version": 2,
+            "storage": archive::COMPRESSED_STORAGE,
+            "archiveId": archive_id,
+            "name": bundle_archive.name,
+            "description": bundle_archive.description,
+            "createdAt": &created_at,
+            "sourceProvider": bundle_archive.source_provider,
+            "sourceProjectPath": bundle_archive.source_project_path,
+            "sourceProjectName": bundle_archive.source_project_name,
+            "includeSubagents": bundle_archive.include_subagents,
+            "importedFrom": {
+                "createdAt": bundle_archive.created_at,
+                "exportedAt": manifest.exported_at,
+                "appVersion": manifest.app_version,
+            },
+            "sessions": per_session_info,
+        });
+        let content = serde_json::to_string_pretty(&archive_manifest)
+            .map_err(|e| format!("Failed to serialize per-archive manifest: {e}"))?;
+        archive::atomic_write_string(&archive_dir.join("manifest.json"), &content)?;
+
+        let entry = ArchiveEntry {
+            id: archive_id.clone(),
+            name: bundle_archive.name.clone(),
+            description: bundle_archive.description.clone(),
+            created_at,
+            source_provider: bundle_archive.source_provider.clone(),
+            source_project_path: bundle_archive.source_project_path.clone(),
+            source_project_name: bundle_archive.source_project_name.clone(),
+            session_count: u32::try_from(manifest.sessions.len()).unwrap_or(u32::MAX),
+            total_size_bytes: total_size,
+            include_subagents: bundle_archive.include_subagents,
+        };
+        let mut global = archive::load_manifest()?;
+        global.archives.push(entry.clone());
+        archive::save_manifest(&global)?;
+        Ok(entry)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&archive_dir);
+        if let Err(e) = archive::collect_unreferenced_objects(&archives_dir) {
+            log::warn!("Archive object cleanup skipped: {e}");
+        }
+    }
+    result
+}
+
+/// Writes an archive to a single bundle file at `output_path`.
+///
+/// # Arguments
+/// * `archive_id` - ID of the archive to export
+/// * `output_path` - Absolute path chosen by the user via save dialog
+#[tauri::command]
+pub async fn export_archive(
+    archive_id: String,
+    output_path: String,
+) -> Result<ArchiveExportSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        archive::validate_archive_id(&archive_id)?;
+        let output_path = PathBuf::from(output_path);
+        super::claude_settings::validate_dialog_path(&output_path)?;
+        export_archive_blocking(&archive_id, &output_path)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Imports a bundle written by [`export_archive`] as a new archive, after
+/// verifying every file against its checksum.
+///
+/// # Arguments
+/// * `bundle_path` - Absolute path chosen by the user via open dialog
+#[tauri::command]
+pub async fn import_archive(bundle_path: String) -> Result<ArchiveEntry, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let bundle_path = PathBuf::from(bundle_path);
+        super::claude_settings::validate_dialog_path(&bundle_path)?;
+        import_archive_blocking(&bundle_path)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use tempfile::TempDir;
+
+    /// NOTE: Must run with `--test-threads=1` because `env::set_var` is process-global.
+    fn setup_test_env() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        env::set_var("HOME", temp_dir.path());
+        temp_dir
+    }
+
+    fn archive_with_subagent(home: &Path) -> ArchiveEntry {
+        let project = home.join("project");
+        let subagents = project.join("sess").join("subagents");
+        fs::create_dir_all(&subagents).unwrap();
+        let session = project.join("sess.jsonl");
+        fs::write(
+            &session,
+            "{\"type\":\"user\",\"timestamp\":\"2026-01-01T00:00:00Z\",\"message\":{\"role\":\"user\",\"content\":\"hi\"}}\n",
+        )
+        .unwrap();
+        fs::write(
+            subagents.join("agent-1.jsonl"),
+            "{\"type\":\"assistant\",\"timestamp\":\"2026-01-01T00:00:01Z\",\"message\":{\"role\":\"assistant\",\"content\":\"ok\"}}\n",
+        )
+        .unwrap();
+
+        let mut metadata = UserMetadata::new();
+        metadata.get_session_mut(&session.to_string_lossy()).tags = vec!["keep".to_string()];
+        super::super::metadata::save_metadata_to_disk(&metadata).unwrap();
+
+        archive::create_archive_blocking(
+            "Shared Work".to_string(),
+            Some("for review".to_string()),
+            vec![session.to_string_lossy().to_string()],
+            "claude".to_string(),
+            project.to_string_lossy().to_string(),
+            "project".to_string(),
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bundle_round_trip_keeps_sessions_and_metadata() {
+        let home = setup_test_env();
+        let original = archive_with_subagent(home.path());
+        let bundle = home.path().join("shared.chvarchive");
+
+        let summary = export_archive_blocking(&original.id, &bundle).unwrap();
+        assert_eq!(summary.session_count, 1);
+        assert_eq!(summary.subagent_count, 1);
+
+        let imported = import_archive_blocking(&bundle).unwrap();
+        assert_ne!(imported.id, original.id);
+        assert_eq!(imported.name, "Shared Work");
+        assert_eq!(imported.total_size_bytes, original.total_size_bytes);
+
+        let sessions = archive::list_archive_sessions(&imported.id).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].subagents.len(), 1);
+        assert_eq!(
+            sessions[0].user_metadata.as_ref().map(|m| m.tags.clone()),
+            Some(vec!["keep".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_import_rejects_tampered_bundle() {
+        let home = setup_test_env();
+        let original = archive_with_subagent(home.path());
+        let bundle = home.path().join("shared.chvarchive");
+        export_archive_blocking(&original.id, &bundle).unwrap();
+
+        // Rewrite the bundle with one session byte changed but the same manifest.
+        let mut tar_archive =
+            tar::Archive::new(zstd::Decoder::new(fs::File::open(&bundle).unwrap()).unwrap());
+        let mut files: BundleFiles = Vec::new();
+        for entry in tar_archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            if path.starts_with("sessions/") {
+                data[0] = b' ';
+            }
+            files.push((path, data));
+        }
+        let tampered = home.path().join("tampered.chvarchive");
+        let encoder = zstd::Encoder::new(fs::File::create(&tampered).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        for (path, data) in &files {
+            append_file(&mut builder, path, data, 0).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let err = import_archive_blocking(&tampered).unwrap_err();
+        assert!(err.contains("Checksum mismatch"), "{err}");
+        assert_eq!(archive::load_manifest().unwrap().archives.len(), 1);
+    }
+
+    #[test]
+    fn test_bundle_manifest_rejects_unsafe_names() {
+        let manifest = BundleManifest {
+            format: BUNDLE_FORMAT.to_string(),
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: String::new(),
+            app_version: String::new(),
+            archive: BundleArchive {
+                name: "x".to_string(),
+                description: None,
+                created_at: String::new(),
+                source_provider: "claude".to_string(),
+                source_project_path: String::new(),
+                source_project_name: String::new(),
+                include_subagents: false,
+            },
+            sessions: vec![BundleSession {
+                file_name: "../escape.jsonl".to_string(),
+                original_file_path: String::new(),
+                message_count: 0,
+                first_message_time: String::new(),
+                last_message_time: String::new(),
+                summary: None,
+                size_bytes: 0,
+                sha256: "a".repeat(64),
+                format: None,
+                subagents: Vec::new(),
+                user_metadata: None,
+            }],
+        };
+        assert!(expected_entries(&manifest).is_err());
+    }
+}
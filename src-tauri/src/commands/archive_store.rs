@@ -0,0 +1,257 @@
+//! Content-addressed, zstd-compressed object store backing session archives.
+//!
+//! Archived session and subagent files are stored once under
+//! `~/.claude-history-viewer/archives/objects/<2 hex>/<sha256>.zst`, keyed by the
+//! SHA-256 of their uncompressed content. Archives refer to objects by hash
+//! from their per-archive `manifest.json`, so archiving the same session twice
+//! (or into two archives) stores it once. Objects no archive refers to any more
+//! are removed by [`collect_garbage`] after an archive is deleted.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+/// Directory under the archives root holding the objects.
+pub(crate) const OBJECTS_DIR: &str = "objects";
+
+/// zstd level for archived JSONL; transcripts compress well and are written once.
+const COMPRESSION_LEVEL: i32 = 9;
+
+/// Serializes object writes against garbage collection, so an object that was
+/// just stored for an archive whose manifest isn't saved yet is never collected.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+pub(crate) fn lock_store() -> MutexGuard<'static, ()> {
+    STORE_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// An object written (or found already present) by [`store_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StoredObject {
+    /// Lowercase hex SHA-256 of the uncompressed content.
+    pub hash: String,
+    /// Uncompressed size.
+    pub size_bytes: u64,
+    /// Compressed size on disk.
+    pub stored_bytes: u64,
+}
+
+pub(crate) fn is_object_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Path of the object with `hash`, rejecting anything that isn't a SHA-256 hex digest.
+pub(crate) fn object_path(archives_dir: &Path, hash: &str) -> Result<PathBuf, String> {
+    if !is_object_hash(hash) {
+        return Err(format!("Invalid archive object hash: {hash}"));
+    }
+    Ok(archives_dir
+        .join(OBJECTS_DIR)
+        .join(&hash[..2])
+        .join(format!("{hash}.zst")))
+}
+
+/// Compress `source` into the store, returning its hash. Content that is
+/// already stored is not written again.
+pub(crate) fn store_file(archives_dir: &Path, source: &Path) -> Result<StoredObject, String> {
+    let file = fs::File::open(source)
+        .map_err(|e| format!("Failed to open '{}': {e}", source.display()))?;
+    store_reader(archives_dir, file, &source.display().to_string())
+}
+
+/// Like [`store_file`], for content streamed from any reader (`label` names it
+/// in errors).
+pub(crate) fn store_reader(
+    archives_dir: &Path,
+    source: impl Read,
+    label: &str,
+) -> Result<StoredObject, String> {
+    let objects_dir = archives_dir.join(OBJECTS_DIR);
+    fs::create_dir_all(&objects_dir)
+        .map_err(|e| format!("Failed to create archive objects directory: {e}"))?;
+
+    let mut reader = BufReader::new(source);
+    let tmp_path = objects_dir.join(format!("{}.tmp", Uuid::new_v4()));
+    let result: Result<(String, u64), String> = (|| {
+        let tmp_file = fs::File::create(&tmp_path)
+            .map_err(|e| format!("Failed to create temp object file: {e}"))?;
+        let mut encoder = zstd::Encoder::new(BufWriter::new(tmp_file), COMPRESSION_LEVEL)
+            .map_err(|e| format!("Failed to start zstd encoder: {e}"))?;
+        let mut hasher = Sha256::new();
+        let mut size_bytes: u64 = 0;
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let read = reader
+                .read(&mut buf)
+                .map_err(|e| format!("Failed to read '{label}': {e}"))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+            encoder
+                .write_all(&buf[..read])
+                .map_err(|e| format!("Failed to compress '{label}': {e}"))?;
+            size_bytes += read as u64;
+        }
+        let file = encoder
+            .finish()
+            .map_err(|e| format!("Failed to finish zstd stream: {e}"))?
+            .into_inner()
+            .map_err(|e| format!("Failed to flush temp object file: {e}"))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to sync temp object file: {e}"))?;
+        Ok((format!("{:x}", hasher.finalize()), size_bytes))
+    })();
+    let (hash, size_bytes) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
+
+    let final_path = object_path(archives_dir, &hash)?;
+    if final_path.is_file() {
+        let _ = fs::remove_file(&tmp_path);
+    } else {
+        if let Some(parent) = final_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create archive objects directory: {e}"))?;
+        }
+        super::fs_utils::atomic_rename(&tmp_path, &final_path)?;
+    }
+    let stored_bytes = final_path.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok(StoredObject {
+        hash,
+        size_bytes,
+        stored_bytes,
+    })
+}
+
+/// Decompress an object, verifying it still matches its hash.
+pub(crate) fn read_object(archives_dir: &Path, hash: &str) -> Result<Vec<u8>, String> {
+    let path = object_path(archives_dir, hash)?;
+    let file = fs::File::open(&path).map_err(|e| format!("Archive object {hash} missing: {e}"))?;
+    let data = zstd::decode_all(BufReader::new(file))
+        .map_err(|e| format!("Failed to decompress archive object {hash}: {e}"))?;
+    if format!("{:x}", Sha256::digest(&data)) != hash {
+        return Err(format!(
+            "Archive object {hash} is corrupted (hash mismatch)"
+        ));
+    }
+    Ok(data)
+}
+
+/// Compressed size of an object on disk, or 0 when it is missing.
+pub(crate) fn stored_size(archives_dir: &Path, hash: &str) -> u64 {
+    object_path(archives_dir, hash)
+        .ok()
+        .and_then(|p| p.metadata().ok())
+        .map_or(0, |m| m.len())
+}
+
+/// Delete objects not in `referenced`, plus leftover temp files. Returns the
+/// bytes freed. Callers must hold [`lock_store`].
+pub(crate) fn collect_garbage(archives_dir: &Path, referenced: &HashSet<String>) -> u64 {
+    let objects_dir = archives_dir.join(OBJECTS_DIR);
+    let Ok(shards) = fs::read_dir(&objects_dir) else {
+        return 0;
+    };
+    let mut freed = 0u64;
+    for shard in shards.flatten() {
+        let shard_path = shard.path();
+        if !shard_path.is_dir() {
+            // Temp files from interrupted writes.
+            if shard_path.extension().is_some_and(|e| e == "tmp") {
+                freed += remove_counted(&shard_path);
+            }
+            continue;
+        }
+        let Ok(objects) = fs::read_dir(&shard_path) else {
+            continue;
+        };
+        for object in objects.flatten() {
+            let path = object.path();
+            let hash = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".zst"));
+            if hash.is_some_and(|h| is_object_hash(h) && !referenced.contains(h)) {
+                freed += remove_counted(&path);
+            }
+        }
+        // Drop the shard directory once it is empty.
+        let _ = fs::remove_dir(&shard_path);
+    }
+    freed
+}
+
+fn remove_counted(path: &Path) -> u64 {
+    let size = path.metadata().map_or(0, |m| m.len());
+    if fs::remove_file(path).is_ok() {
+        size
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_identical_content_is_stored_once() {
+        let dir = TempDir::new().unwrap();
+        let archives = dir.path().join("archives");
+        let a = dir.path().join("a.jsonl");
+        let b = dir.path().join("b.jsonl");
+        let content = "{\"type\":\"user\"}\n".repeat(200);
+        fs::write(&a, &content).unwrap();
+        fs::write(&b, &content).unwrap();
+
+        let first = store_file(&archives, &a).unwrap();
+        let second = store_file(&archives, &b).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.size_bytes, content.len() as u64);
+        assert!(first.stored_bytes < first.size_bytes);
+        assert_eq!(
+            read_object(&archives, &first.hash).unwrap(),
+            content.as_bytes()
+        );
+
+        let shard = archives.join(OBJECTS_DIR).join(&first.hash[..2]);
+        assert_eq!(fs::read_dir(shard).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_garbage_collection_keeps_referenced_objects() {
+        let dir = TempDir::new().unwrap();
+        let archives = dir.path().join("archives");
+        let keep_src = dir.path().join("keep.jsonl");
+        let drop_src = dir.path().join("drop.jsonl");
+        fs::write(&keep_src, "keep\n").unwrap();
+        fs::write(&drop_src, "drop\n").unwrap();
+        let keep = store_file(&archives, &keep_src).unwrap();
+        let dropped = store_file(&archives, &drop_src).unwrap();
+
+        let referenced = HashSet::from([keep.hash.clone()]);
+        assert!(collect_garbage(&archives, &referenced) > 0);
+        assert!(read_object(&archives, &keep.hash).is_ok());
+        assert!(read_object(&archives, &dropped.hash).is_err());
+    }
+
+    #[test]
+    fn test_object_hashes_are_validated() {
+        let dir = TempDir::new().unwrap();
+        assert!(object_path(dir.path(), "../../etc/passwd").is_err());
+        assert!(object_path(dir.path(), &"A".repeat(64)).is_err());
+        assert!(object_path(dir.path(), &"a".repeat(64)).is_ok());
+    }
+}
@@ -0,0 +1,658 @@
+//! Background archiving of Claude sessions before `cleanupPeriodDays` purges them.
+//!
+//! Configured in `~/.claude-history-viewer/auto-archive.json` and off by
+//! default. When enabled, a background thread (desktop and `--serve`) runs every
+//! `intervalHours`: for each Claude project it lists the sessions
+//! [`get_expiring_sessions`](super::archive::get_expiring_sessions) reports
+//! within `thresholdDays` of deletion and copies them, with their subagent
+//! files, into one new archive per project through the same code path as
+//! `create_archive`. Sessions already archived with the same size and mtime are
+//! skipped, so re-running is cheap and a session that keeps growing is archived
+//! again.
+//!
+//! Sessions that cannot be read are skipped and reported, and the rest of the
+//! project is archived. Retention rules then prune the archiver's own archives
+//! (never ones made by hand or renamed since); their unchanged sessions are not
+//! archived again. Every run is recorded in
+//! `~/.claude-history-viewer/auto-archive-log.json`.
+
+use super::archive::{
+    create_archive_blocking, delete_archive_blocking, find_expiring_sessions, load_manifest,
+    ArchiveEntry, ExpiringSession,
+};
+use crate::models::{
+    ArchivedSessionStamp, AutoArchiveConfig, AutoArchiveLog, AutoArchiveRetention, AutoArchiveRun,
+    AutoArchivedSession,
+};
+use crate::utils::find_subagent_files;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How often the background thread checks whether a run is due.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Runs kept in the log; older ones are dropped.
+const MAX_LOG_RUNS: usize = 100;
+
+/// Held for the duration of a run so scheduled and manual runs never overlap.
+static RUN_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_config_path() -> Result<PathBuf, String> {
+    Ok(super::metadata::get_metadata_folder()?.join("auto-archive.json"))
+}
+
+fn get_log_path() -> Result<PathBuf, String> {
+    Ok(super::metadata::get_metadata_folder()?.join("auto-archive-log.json"))
+}
+
+fn read_json_or_default<T>(path: &Path, label: &str) -> Result<T, String>
+where
+    T: Default + serde::de::DeserializeOwned,
+{
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {label} file: {e}"))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {label}: {e}"))
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, value: &T, label: &str) -> Result<(), String> {
+    super::metadata::ensure_metadata_folder()?;
+    let temp_path = path.with_extension("json.tmp");
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {label}: {e}"))?;
+    let mut file =
+        fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {e}"))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write temp file: {e}"))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {e}"))?;
+
+    super::fs_utils::atomic_rename(&temp_path, path)
+}
+
+fn read_config() -> Result<AutoArchiveConfig, String> {
+    read_json_or_default(&get_config_path()?, "auto-archive config")
+}
+
+fn read_log() -> Result<AutoArchiveLog, String> {
+    read_json_or_default(&get_log_path()?, "auto-archive log")
+}
+
+/// Reject settings the archiver could not act on.
+fn validate_config(config: &AutoArchiveConfig) -> Result<(), String> {
+    if !(0..=365).contains(&config.threshold_days) {
+        return Err("thresholdDays must be between 0 and 365".to_string());
+    }
+    if config.interval_hours == 0 {
+        return Err("intervalHours must be at least 1".to_string());
+    }
+    for project in &config.projects {
+        let path = Path::new(project);
+        if !path.is_absolute()
+            || path
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            return Err(format!(
+                "Project directory must be an absolute path without '..': {project}"
+            ));
+        }
+    }
+    let retention = &config.retention;
+    if retention.max_age_days == Some(0)
+        || retention.max_archives == Some(0)
+        || retention.max_total_bytes == Some(0)
+    {
+        return Err("Retention limits must be positive; leave them out to keep everything".into());
+    }
+    Ok(())
+}
+
+/// Whether a scheduled run is due, given the previous scheduled run's start.
+fn is_due(config: &AutoArchiveConfig, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    config.enabled
+        && last_run.map_or(true, |last| {
+            now - last >= Duration::hours(i64::from(config.interval_hours))
+        })
+}
+
+/// Automatic archives to delete, oldest first. Archives in `protected` (the
+/// ones the current run just wrote) are kept whatever the limits say.
+fn select_archives_to_prune(
+    archives: &[ArchiveEntry],
+    retention: &AutoArchiveRetention,
+    protected: &HashSet<String>,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let mut candidates: Vec<(DateTime<Utc>, &ArchiveEntry)> = archives
+        .iter()
+        .map(|a| {
+            let created = DateTime::parse_from_rfc3339(&a.created_at)
+                .map_or(DateTime::<Utc>::MIN_UTC, |t| t.with_timezone(&Utc));
+            (created, a)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.id.cmp(&b.1.id)));
+
+    let mut remaining_count = candidates.len();
+    let mut remaining_bytes: u64 = candidates.iter().map(|(_, a)| a.total_size_bytes).sum();
+    let mut pruned = Vec::new();
+    for (created, archive) in candidates {
+        if protected.contains(&archive.id) {
+            continue;
+        }
+        let too_old = retention
+            .max_age_days
+            .is_some_and(|days| now - created > Duration::days(i64::from(days)));
+        let too_many = retention
+            .max_archives
+            .is_some_and(|max| remaining_count > max as usize);
+        let too_big = retention
+            .max_total_bytes
+            .is_some_and(|max| remaining_bytes > max);
+        if too_old || too_many || too_big {
+            remaining_count -= 1;
+            remaining_bytes = remaining_bytes.saturating_sub(archive.total_size_bytes);
+            pruned.push(archive.id.clone());
+        }
+    }
+    pruned
+}
+
+/// Claude project storage directories the archiver should look at.
+fn project_dirs(config: &AutoArchiveConfig) -> Result<Vec<PathBuf>, String> {
+    if !config.projects.is_empty() {
+        return Ok(config.projects.iter().map(PathBuf::from).collect());
+    }
+    let projects_root = dirs::home_dir()
+        .ok_or("Could not find home directory")?
+        .join(".claude")
+        .join("projects");
+    let Ok(entries) = fs::read_dir(&projects_root) else {
+        return Ok(Vec::new());
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn session_stamp(expiring: &ExpiringSession, archive_id: &str) -> ArchivedSessionStamp {
+    ArchivedSessionStamp {
+        archive_id: archive_id.to_string(),
+        size_bytes: expiring.file_size_bytes,
+        last_modified: expiring.session.last_modified.clone(),
+        pruned: false,
+    }
+}
+
+fn is_unchanged(
+    sessions: &BTreeMap<String, ArchivedSessionStamp>,
+    expiring: &ExpiringSession,
+) -> bool {
+    sessions.get(&expiring.session.file_path).is_some_and(|s| {
+        s.size_bytes == expiring.file_size_bytes
+            && s.last_modified == expiring.session.last_modified
+    })
+}
+
+/// Check that `expiring` (and its subagent files, when included) can be read,
+/// so one vanished or unreadable session is skipped instead of failing the
+/// whole project's archive.
+fn check_archivable(expiring: &ExpiringSession, include_subagents: bool) -> Result<(), String> {
+    let path = Path::new(&expiring.session.file_path);
+    let mut files = vec![path.to_path_buf()];
+    if include_subagents {
+        files.extend(find_subagent_files(path));
+    }
+    for file in files {
+        match fs::symlink_metadata(&file) {
+            Ok(meta) if meta.is_file() => {}
+            Ok(_) => return Err(format!("Not a regular file: {}", file.display())),
+            Err(e) => return Err(format!("Cannot access {}: {e}", file.display())),
+        }
+        fs::File::open(&file).map_err(|e| format!("Cannot read {}: {e}", file.display()))?;
+    }
+    Ok(())
+}
+
+/// Archive expiring sessions of one project, recording what was copied.
+fn archive_project(
+    dir: &Path,
+    config: &AutoArchiveConfig,
+    log: &mut AutoArchiveLog,
+    run: &mut AutoArchiveRun,
+    created_ids: &mut HashSet<String>,
+) -> Result<(), String> {
+    let dir_str = dir.to_string_lossy().to_string();
+    let expiring = find_expiring_sessions(&dir_str, config.threshold_days)?;
+    let (unchanged, pending): (Vec<_>, Vec<_>) = expiring
+        .into_iter()
+        .partition(|e| is_unchanged(&log.sessions, e));
+    run.skipped_unchanged += unchanged.len() as u32;
+    let pending: Vec<ExpiringSession> = pending
+        .into_iter()
+        .filter(
+            |expiring| match check_archivable(expiring, config.include_subagents) {
+                Ok(()) => true,
+                Err(e) => {
+                    run.errors.push(e);
+                    false
+                }
+            },
+        )
+        .collect();
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let storage_name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("project");
+    let project_name = crate::utils::extract_project_name(storage_name);
+    let entry = create_archive_blocking(
+        format!(
+            "Auto-archive {project_name} {}",
+            Utc::now().format("%Y-%m-%d")
+        ),
+        Some(format!(
+            "Sessions within {} days of Claude's cleanupPeriodDays, archived automatically",
+            config.threshold_days
+        )),
+        pending
+            .iter()
+            .map(|e| e.session.file_path.clone())
+            .collect(),
+        "claude".to_string(),
+        crate::utils::decode_project_path(&dir_str),
+        project_name.clone(),
+        config.include_subagents,
+    )?;
+
+    created_ids.insert(entry.id.clone());
+    log.archive_ids.push(entry.id.clone());
+    for expiring in pending {
+        log.sessions.insert(
+            expiring.session.file_path.clone(),
+            session_stamp(&expiring, &entry.id),
+        );
+        run.archived.push(AutoArchivedSession {
+            file_path: expiring.session.file_path,
+            archive_id: entry.id.clone(),
+            project_name: project_name.clone(),
+            days_remaining: expiring.days_remaining,
+            size_bytes: expiring.file_size_bytes,
+            subagent_count: if config.include_subagents {
+                expiring.subagent_count
+            } else {
+                0
+            },
+        });
+    }
+    Ok(())
+}
+
+/// Forget archives deleted or renamed since the last run, so their sessions
+/// are archived again and retention leaves renamed ones alone. Tombstones of
+/// pruned archives last until their source session is gone.
+fn forget_missing_archives(log: &mut AutoArchiveLog, existing: &HashSet<String>) {
+    log.archive_ids.retain(|id| existing.contains(id));
+    log.sessions.retain(|path, s| {
+        if s.pruned {
+            Path::new(path).exists()
+        } else {
+            existing.contains(&s.archive_id)
+        }
+    });
+}
+
+/// Drop pruned archives from the log, leaving tombstones for their sessions.
+fn record_pruned(log: &mut AutoArchiveLog, pruned: &[String]) {
+    log.archive_ids.retain(|id| !pruned.contains(id));
+    for stamp in log.sessions.values_mut() {
+        if pruned.contains(&stamp.archive_id) {
+            stamp.pruned = true;
+        }
+    }
+}
+
+/// Run the archiver once with `config`, regardless of `enabled`, and append
+/// the run to the log.
+fn run_blocking(config: &AutoArchiveConfig, trigger: &str) -> Result<AutoArchiveRun, String> {
+    let _guard = match RUN_LOCK.try_lock() {
+        Ok(guard) => guard,
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => {
+            return Err("An automatic archive run is already in progress".to_string());
+        }
+    };
+
+    let mut run = AutoArchiveRun {
+        started_at: Utc::now().to_rfc3339(),
+        finished_at: String::new(),
+        trigger: trigger.to_string(),
+        archived: Vec::new(),
+        skipped_unchanged: 0,
+        pruned_archive_ids: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut log = read_log()?;
+
+    let existing: HashSet<String> = load_manifest()?
+        .archives
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    forget_missing_archives(&mut log, &existing);
+
+    let mut created_ids = HashSet::new();
+    for dir in project_dirs(config)? {
+        if let Err(e) = archive_project(&dir, config, &mut log, &mut run, &mut created_ids) {
+            run.errors.push(format!("{}: {e}", dir.display()));
+        }
+    }
+
+    let auto_ids: HashSet<&String> = log.archive_ids.iter().collect();
+    let auto_archives: Vec<ArchiveEntry> = load_manifest()?
+        .archives
+        .into_iter()
+        .filter(|a| auto_ids.contains(&a.id))
+        .collect();
+    for id in select_archives_to_prune(&auto_archives, &config.retention, &created_ids, Utc::now())
+    {
+        match delete_archive_blocking(&id) {
+            Ok(()) => run.pruned_archive_ids.push(id),
+            Err(e) => run
+                .errors
+                .push(format!("Failed to prune archive {id}: {e}")),
+        }
+    }
+    record_pruned(&mut log, &run.pruned_archive_ids);
+
+    run.finished_at = Utc::now().to_rfc3339();
+    log.runs.push(run.clone());
+    let overflow = log.runs.len().saturating_sub(MAX_LOG_RUNS);
+    log.runs.drain(..overflow);
+    write_json(&get_log_path()?, &log, "auto-archive log")?;
+
+    if !run.archived.is_empty() || !run.pruned_archive_ids.is_empty() {
+        log::info!(
+            "Auto-archive: archived {} session(s), pruned {} archive(s)",
+            run.archived.len(),
+            run.pruned_archive_ids.len()
+        );
+    }
+    Ok(run)
+}
+
+/// Start the background thread that runs the archiver when it is enabled
+/// and `intervalHours` have passed since the last scheduled run.
+pub fn spawn_auto_archiver() {
+    let spawned = std::thread::Builder::new()
+        .name("auto-archiver".to_string())
+        .spawn(|| loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let config = match read_config() {
+                Ok(config) if config.enabled => config,
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("Auto-archive config unavailable: {e}");
+                    continue;
+                }
+            };
+            let last_run = read_log().ok().and_then(|log| {
+                log.runs
+                    .iter()
+                    .rev()
+                    .find(|r| r.trigger == "schedule")
+                    .and_then(|r| DateTime::parse_from_rfc3339(&r.started_at).ok())
+                    .map(|t| t.with_timezone(&Utc))
+            });
+            if !is_due(&config, last_run, Utc::now()) {
+                continue;
+            }
+            if let Err(e) = run_blocking(&config, "schedule") {
+                log::warn!("Auto-archive run failed: {e}");
+            }
+        });
+    if let Err(e) = spawned {
+        log::error!("Failed to start auto-archiver: {e}");
+    }
+}
+
+/// Load the auto-archive settings. Returns the defaults (disabled) if none are saved.
+#[tauri::command]
+pub async fn load_auto_archive_config() -> Result<AutoArchiveConfig, String> {
+    tauri::async_runtime::spawn_blocking(read_config)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Validate and save the auto-archive settings.
+#[tauri::command]
+pub async fn save_auto_archive_config(config: AutoArchiveConfig) -> Result<(), String> {
+    validate_config(&config)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_json(&get_config_path()?, &config, "auto-archive config")
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Past archiver runs, oldest first, with the archives they created.
+#[tauri::command]
+pub async fn get_auto_archive_log() -> Result<AutoArchiveLog, String> {
+    tauri::async_runtime::spawn_blocking(read_log)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Run the archiver now with the saved settings, even when scheduling is off.
+#[tauri::command]
+pub async fn run_auto_archive_now() -> Result<AutoArchiveRun, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let config = read_config()?;
+        validate_config(&config)?;
+        run_blocking(&config, "manual")
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ClaudeSession;
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn archive(id: &str, created_at: &str, size: u64) -> ArchiveEntry {
+        ArchiveEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            created_at: created_at.to_string(),
+            source_provider: "claude".to_string(),
+            source_project_path: "/work/app".to_string(),
+            source_project_name: "app".to_string(),
+            session_count: 1,
+            total_size_bytes: size,
+            include_subagents: true,
+        }
+    }
+
+    fn expiring(path: &str, size: u64, modified: &str) -> ExpiringSession {
+        ExpiringSession {
+            session: ClaudeSession {
+                session_id: path.to_string(),
+                actual_session_id: "s".to_string(),
+                file_path: path.to_string(),
+                project_name: "app".to_string(),
+                message_count: 1,
+                first_message_time: String::new(),
+                last_message_time: String::new(),
+                last_modified: modified.to_string(),
+                has_tool_use: false,
+                has_errors: false,
+                summary: None,
+                is_renamed: false,
+                provider: None,
+                storage_type: None,
+                entrypoint: None,
+            },
+            days_remaining: 2,
+            file_size_bytes: size,
+            subagent_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_retention_prunes_oldest_first_and_keeps_protected() {
+        let now = utc("2026-10-17T00:00:00Z");
+        let archives = vec![
+            archive("newest", "2026-10-16T00:00:00Z", 100),
+            archive("oldest", "2026-08-01T00:00:00Z", 100),
+            archive("middle", "2026-10-01T00:00:00Z", 100),
+        ];
+        let none = HashSet::new();
+
+        let by_age = AutoArchiveRetention {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_archives_to_prune(&archives, &by_age, &none, now),
+            vec!["oldest"]
+        );
+
+        let by_count = AutoArchiveRetention {
+            max_archives: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_archives_to_prune(&archives, &by_count, &none, now),
+            vec!["oldest", "middle"]
+        );
+
+        let by_size = AutoArchiveRetention {
+            max_total_bytes: Some(250),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_archives_to_prune(&archives, &by_size, &none, now),
+            vec!["oldest"]
+        );
+
+        let protected = HashSet::from(["oldest".to_string()]);
+        assert_eq!(
+            select_archives_to_prune(&archives, &by_count, &protected, now),
+            vec!["middle", "newest"]
+        );
+        assert!(
+            select_archives_to_prune(&archives, &AutoArchiveRetention::default(), &none, now)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_schedule_and_config_validation() {
+        let now = utc("2026-10-17T12:00:00Z");
+        let mut config = AutoArchiveConfig::default();
+        assert!(!is_due(&config, None, now));
+
+        config.enabled = true;
+        assert!(is_due(&config, None, now));
+        assert!(!is_due(&config, Some(utc("2026-10-17T07:00:00Z")), now));
+        assert!(is_due(&config, Some(utc("2026-10-17T06:00:00Z")), now));
+
+        assert!(validate_config(&config).is_ok());
+        config.projects = vec!["relative/dir".to_string()];
+        assert!(validate_config(&config).is_err());
+        config.projects = vec!["/home/u/.claude/projects/-work-app".to_string()];
+        config.retention.max_archives = Some(0);
+        assert!(validate_config(&config).is_err());
+        config.retention.max_archives = None;
+        config.interval_hours = 0;
+        assert!(validate_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_unchanged_sessions_are_detected_by_size_and_mtime() {
+        let mut sessions = BTreeMap::new();
+        let archived = expiring("/p/a.jsonl", 10, "2026-09-01T00:00:00+00:00");
+        sessions.insert("/p/a.jsonl".to_string(), session_stamp(&archived, "auto_1"));
+
+        assert!(is_unchanged(&sessions, &archived));
+        assert!(!is_unchanged(
+            &sessions,
+            &expiring("/p/a.jsonl", 12, "2026-09-02T00:00:00+00:00")
+        ));
+        assert!(!is_unchanged(
+            &sessions,
+            &expiring("/p/b.jsonl", 10, "2026-09-01T00:00:00+00:00")
+        ));
+    }
+
+    #[test]
+    fn test_pruned_sessions_stay_archived_until_their_source_is_gone() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let kept = dir.path().join("kept.jsonl");
+        fs::write(&kept, "{}\n").unwrap();
+        let kept = expiring(&kept.to_string_lossy(), 3, "2026-09-01T00:00:00+00:00");
+        let gone = expiring(
+            &dir.path().join("gone.jsonl").to_string_lossy(),
+            3,
+            "2026-09-01T00:00:00+00:00",
+        );
+        let mut log = AutoArchiveLog {
+            archive_ids: vec!["auto_1".to_string()],
+            ..Default::default()
+        };
+        for session in [&kept, &gone] {
+            log.sessions.insert(
+                session.session.file_path.clone(),
+                session_stamp(session, "auto_1"),
+            );
+        }
+
+        record_pruned(&mut log, &["auto_1".to_string()]);
+        assert!(log.archive_ids.is_empty());
+        assert!(is_unchanged(&log.sessions, &kept));
+
+        forget_missing_archives(&mut log, &HashSet::new());
+        assert!(is_unchanged(&log.sessions, &kept));
+        assert!(!log.sessions.contains_key(&gone.session.file_path));
+    }
+
+    #[test]
+    fn test_unreadable_sessions_are_not_archivable() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("a.jsonl");
+        fs::write(&file, "{}\n").unwrap();
+        let modified = "2026-09-01T00:00:00+00:00";
+
+        assert!(check_archivable(&expiring(&file.to_string_lossy(), 3, modified), true).is_ok());
+        let missing = dir.path().join("missing.jsonl");
+        assert!(
+            check_archivable(&expiring(&missing.to_string_lossy(), 3, modified), true).is_err()
+        );
+        assert!(
+            check_archivable(&expiring(&dir.path().to_string_lossy(), 3, modified), true).is_err()
+        );
+    }
+}
@@ -0,0 +1,675 @@
+//! Token and spend budgets with threshold alerts.
+//!
+//! Budgets live in `~/.claude-history-viewer/budgets.json` and cap either
+//! tokens or USD for the whole history, one provider, or one project of any
+//! provider over the current local day, week (Monday-based) or month. Usage
+//! comes from the same summaries the stats views use, so a USD budget counts
+//! the source `costUSD` where logs have it and the pricing-table estimate for
+//! the remaining tokens.
+//!
+//! `get_budget_status` reports burn vs limit with a linear projection to the
+//! period end. Whenever a budget reaches one of its thresholds for the first
+//! time in a period, a [`BudgetAlert`] is emitted as the `budget-alert` event
+//! (Tauri event on desktop, `/api/events` SSE in server mode). Alerts are
+//! raised both from status requests and from a background monitor that
+//! re-checks every few minutes; alert state is kept in memory, so a budget
+//! already over a threshold alerts once more after a restart.
+
+use crate::commands::multi_provider::CustomClaudePathParam;
+use crate::models::{
+    Budget, BudgetAlert, BudgetConfig, BudgetMetric, BudgetPeriod, BudgetScope, BudgetStatus,
+    ModelStats, UserMetadata,
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+
+/// Event name shared by the Tauri event and the SSE stream.
+pub const BUDGET_ALERT_EVENT: &str = "budget-alert";
+
+/// How often the background monitor re-evaluates budgets.
+const MONITOR_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Highest threshold already alerted per budget id, with the period it
+/// belongs to (a new period starts from scratch).
+static ALERTED: OnceLock<Mutex<HashMap<String, (String, f64)>>> = OnceLock::new();
+
+/// Claude roots from the most recent desktop status request, reused by the
+/// monitor so both measure the same history.
+static MONITOR_ROOTS: Mutex<Option<(String, Option<Vec<CustomClaudePathParam>>)>> =
+    Mutex::new(None);
+
+fn get_budgets_path() -> Result<PathBuf, String> {
+    Ok(super::metadata::get_metadata_folder()?.join("budgets.json"))
+}
+
+fn read_budget_config() -> Result<BudgetConfig, String> {
+    let path = get_budgets_path()?;
+    if !path.exists() {
+        return Ok(BudgetConfig::default());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read budgets file: {e}"))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse budgets: {e}"))
+}
+
+fn write_budget_config(config: &BudgetConfig) -> Result<(), String> {
+    super::metadata::ensure_metadata_folder()?;
+    let path = get_budgets_path()?;
+
+    let temp_path = path.with_extension("json.tmp");
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize budgets: {e}"))?;
+    let mut file =
+        fs::File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {e}"))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write temp file: {e}"))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file: {e}"))?;
+
+    super::fs_utils::atomic_rename(&temp_path, &path)
+}
+
+/// Reject budgets that could never be evaluated meaningfully.
+fn validate_budget_config(config: &BudgetConfig) -> Result<(), String> {
+    let mut ids = std::collections::HashSet::new();
+    for budget in &config.budgets {
+        if budget.id.trim().is_empty() {
+            return Err("Budget id must not be empty".to_string());
+        }
+        if !ids.insert(budget.id.as_str()) {
+            return Err(format!("Duplicate budget id: {}", budget.id));
+        }
+        if !budget.limit.is_finite() || budget.limit <= 0.0 {
+            return Err(format!(
+                "Budget {} limit must be a positive number",
+                budget.id
+            ));
+        }
+        if let Some(threshold) = budget
+            .thresholds
+            .iter()
+            .find(|t| !t.is_finite() || **t <= 0.0)
+        {
+            return Err(format!(
+                "Budget {} threshold must be a positive fraction of the limit, got {threshold}",
+                budget.id
+            ));
+        }
+        match (budget.scope, budget.target.as_deref().map(str::trim)) {
+            (BudgetScope::Global, _) => {}
+            (_, None | Some("")) => {
+                return Err(format!(
+                    "Budget {} needs a target for {:?} scope",
+                    budget.id, budget.scope
+                ))
+            }
+            (BudgetScope::Project, Some(target)) => validate_project_target(target)?,
+            (BudgetScope::Provider, Some(_)) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Project targets are a Claude project path or any provider's project path
+/// or `provider://` key, which project stats measure from that provider.
+/// Unknown schemes are rejected instead of silently measuring nothing.
+fn validate_project_target(target: &str) -> Result<(), String> {
+    if !super::stats::is_provider_project_path(target)
+        || target.starts_with("codex://")
+        || target.starts_with("opencode://")
+    {
+        return super::metadata::validate_project_metadata_key(target);
+    }
+    if target
+        .split_once("://")
+        .is_some_and(|(_, rest)| rest.trim().is_empty())
+    {
+        return Err(format!("Project key must not be empty: {target}"));
+    }
+    Ok(())
+}
+
+/// `[start, end)` of the period containing `now`, in `now`'s time zone.
+fn period_bounds<Tz: TimeZone>(
+    period: BudgetPeriod,
+    now: &DateTime<Tz>,
+) -> (DateTime<Tz>, DateTime<Tz>) {
+    let tz = now.timezone();
+    let today = now.date_naive();
+    let (start, end) = match period {
+        BudgetPeriod::Day => (today, today + Duration::days(1)),
+        BudgetPeriod::Week => {
+            let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+            (monday, monday + Duration::days(7))
+        }
+        BudgetPeriod::Month => {
+            let first = today.with_day(1).expect("day 1 exists");
+            let next = if first.month() == 12 {
+                NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(first.year(), first.month() + 1, 1)
+            }
+            .expect("first of next month exists");
+            (first, next)
+        }
+    };
+    (start_of_day(&tz, start), start_of_day(&tz, end))
+}
+
+/// Start of the local day containing `date`; falls back to the latest valid
+/// instant when a DST change skips midnight.
+fn start_of_day<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> DateTime<Tz> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+        })
+        .unwrap_or_else(|| tz.from_utc_datetime(&midnight))
+}
+
+/// Usage within one period, in both metrics.
+#[derive(Debug, Default, Clone, Copy)]
+struct PeriodUsage {
+    tokens: u64,
+    usd: f64,
+    unpriced_tokens: u64,
+}
+
+impl PeriodUsage {
+    fn from_models(total_tokens: u64, models: &[ModelStats]) -> Self {
+        let mut usage = Self {
+            tokens: total_tokens,
+            ..Self::default()
+        };
+        for model in models {
+            usage.usd += model.total_cost_usd().unwrap_or(0.0);
+            if model.estimated_cost_usd.is_none() {
+                usage.unpriced_tokens += model.unreported_cost_tokens();
+            }
+        }
+        usage
+    }
+}
+
+async fn measure_usage(
+    budget: &Budget,
+    claude_path: &str,
+    custom_claude_paths: Option<&Vec<CustomClaudePathParam>>,
+    start: String,
+    end: String,
+) -> Result<PeriodUsage, String> {
+    let target = budget.target.clone().unwrap_or_default();
+    match budget.scope {
+        BudgetScope::Global | BudgetScope::Provider => {
+            let providers = (budget.scope == BudgetScope::Provider).then(|| vec![target]);
+            let summary = super::stats::get_global_stats_summary(
+                claude_path.to_string(),
+                providers,
+                None,
+                Some(start),
+                Some(end),
+                custom_claude_paths.cloned(),
+            )
+            .await?;
+            Ok(PeriodUsage::from_models(
+                summary.total_tokens,
+                &summary.model_distribution,
+            ))
+        }
+        BudgetScope::Project => {
+            let summary =
+                super::stats::get_project_stats_summary(target, Some(start), Some(end), None)
+                    .await?;
+            Ok(PeriodUsage::from_models(
+                summary.total_tokens,
+                &summary.model_distribution,
+            ))
+        }
+    }
+}
+
+/// Burn, projection and highest reached threshold for `budget`.
+fn budget_status<Tz: TimeZone>(
+    budget: &Budget,
+    usage: PeriodUsage,
+    now: &DateTime<Tz>,
+    start: &DateTime<Tz>,
+    end: &DateTime<Tz>,
+) -> BudgetStatus
+where
+    Tz::Offset: std::fmt::Display,
+{
+    #[allow(clippy::cast_precision_loss)]
+    let used = match budget.metric {
+        BudgetMetric::Tokens => usage.tokens as f64,
+        BudgetMetric::Usd => usage.usd,
+    };
+    // Linear projection; at least a minute elapsed so a fresh period does
+    // not project from a few seconds of data.
+    let elapsed = (now.clone() - start.clone()).num_seconds().max(60);
+    let length = (end.clone() - start.clone()).num_seconds().max(elapsed);
+    #[allow(clippy::cast_precision_loss)]
+    let projected = used * length as f64 / elapsed as f64;
+    let crossed_threshold = budget
+        .thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| used >= threshold * budget.limit)
+        .reduce(f64::max);
+
+    BudgetStatus {
+        budget: budget.clone(),
+        period_start: start.to_rfc3339(),
+        period_end: end.to_rfc3339(),
+        used,
+        used_ratio: used / budget.limit,
+        projected,
+        projected_ratio: projected / budget.limit,
+        crossed_threshold,
+        unpriced_tokens: match budget.metric {
+            BudgetMetric::Tokens => 0,
+            BudgetMetric::Usd => usage.unpriced_tokens,
+        },
+    }
+}
+
+/// Alerts for thresholds reached since the last evaluation of each budget
+/// period. Only the highest newly reached threshold is reported.
+fn take_new_alerts(statuses: &[BudgetStatus]) -> Vec<BudgetAlert> {
+    let mut alerted = ALERTED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut alerts = Vec::new();
+    for status in statuses {
+        let Some(threshold) = status.crossed_threshold else {
+            continue;
+        };
+        let budget = &status.budget;
+        if alerted
+            .get(&budget.id)
+            .is_some_and(|(period, level)| *period == status.period_start && *level >= threshold)
+        {
+            continue;
+        }
+        alerted.insert(budget.id.clone(), (status.period_start.clone(), threshold));
+        alerts.push(BudgetAlert {
+            budget_id: budget.id.clone(),
+            budget_name: budget.name.clone(),
+            scope: budget.scope,
+            target: budget.target.clone(),
+            period: budget.period,
+            metric: budget.metric,
+            threshold,
+            used: status.used,
+            limit: budget.limit,
+            projected: status.projected,
+            period_start: status.period_start.clone(),
+            period_end: status.period_end.clone(),
+        });
+    }
+    alerts
+}
+
+/// Evaluate every configured budget and collect newly raised alerts.
+///
+/// Budgets sharing scope, target and period are measured once. A budget
+/// whose usage cannot be measured (e.g. a removed project) is skipped with a
+/// warning rather than failing the whole report.
+pub async fn evaluate_budgets(
+    claude_path: String,
+    custom_claude_paths: Option<Vec<CustomClaudePathParam>>,
+) -> Result<(Vec<BudgetStatus>, Vec<BudgetAlert>), String> {
+    let config = tauri::async_runtime::spawn_blocking(read_budget_config)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??;
+
+    let now = Local::now();
+    let mut measured: HashMap<(BudgetScope, Option<String>, BudgetPeriod), PeriodUsage> =
+        HashMap::new();
+    let mut statuses = Vec::with_capacity(config.budgets.len());
+    for budget in &config.budgets {
+        let (start, end) = period_bounds(budget.period, &now);
+        let key = (budget.scope, budget.target.clone(), budget.period);
+        let usage = if let Some(usage) = measured.get(&key) {
+            *usage
+        } else {
+            match measure_usage(
+                budget,
+                &claude_path,
+                custom_claude_paths.as_ref(),
+                start.with_timezone(&Utc).to_rfc3339(),
+                end.with_timezone(&Utc).to_rfc3339(),
+            )
+            .await
+            {
+                Ok(usage) => *measured.entry(key).or_insert(usage),
+                Err(e) => {
+                    log::warn!("Skipping budget {}: {e}", budget.id);
+                    continue;
+                }
+            }
+        };
+        statuses.push(budget_status(budget, usage, &now, &start, &end));
+    }
+
+    let alerts = take_new_alerts(&statuses);
+    Ok((statuses, alerts))
+}
+
+/// The Claude roots this installation is configured with: `~/.claude` plus
+/// the custom Claude paths saved in the user settings. Server mode measures
+/// budgets against these instead of roots sent by a client.
+pub(crate) fn configured_roots() -> Option<(String, Option<Vec<CustomClaudePathParam>>)> {
+    let claude = dirs::home_dir()?.join(".claude");
+    let custom: Vec<CustomClaudePathParam> = super::metadata::get_user_data_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<UserMetadata>(&content).ok())
+        .map(|metadata| {
+            metadata
+                .settings
+                .custom_claude_paths
+                .into_iter()
+                .map(|custom| CustomClaudePathParam {
+                    path: custom.path,
+                    label: custom.label,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some((
+        claude.to_string_lossy().to_string(),
+        (!custom.is_empty()).then_some(custom),
+    ))
+}
+
+/// Remember the roots of a status request for the background monitor.
+fn remember_monitor_roots(
+    claude_path: &str,
+    custom_claude_paths: Option<&Vec<CustomClaudePathParam>>,
+) {
+    let mut roots = MONITOR_ROOTS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    *roots = Some((claude_path.to_string(), custom_claude_paths.cloned()));
+}
+
+/// Periodically evaluate budgets and pass new alerts to `emit`.
+///
+/// Uses the roots of the latest desktop status request, or the
+/// [`configured_roots`] until one arrives. Does nothing while no budgets are
+/// configured.
+pub fn spawn_budget_monitor<F>(emit: F)
+where
+    F: Fn(BudgetAlert) + Send + 'static,
+{
+    let spawned = std::thread::Builder::new()
+        .name("budget-monitor".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(MONITOR_INTERVAL);
+
+            if read_budget_config().is_ok_and(|config| config.budgets.is_empty()) {
+                continue;
+            }
+            let roots = MONITOR_ROOTS
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .clone()
+                .or_else(configured_roots);
+            let Some((claude_path, custom_claude_paths)) = roots else {
+                continue;
+            };
+            match tauri::async_runtime::block_on(evaluate_budgets(claude_path, custom_claude_paths))
+            {
+                Ok((_, alerts)) => alerts.into_iter().for_each(&emit),
+                Err(e) => log::warn!("Budget check failed: {e}"),
+            }
+        });
+    if let Err(e) = spawned {
+        log::error!("Failed to start budget monitor: {e}");
+    }
+}
+
+/// Load configured budgets. Returns an empty config if none are saved.
+#[tauri::command]
+pub async fn load_budgets() -> Result<BudgetConfig, String> {
+    tauri::async_runtime::spawn_blocking(read_budget_config)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Validate and save the budget configuration.
+#[tauri::command]
+pub async fn save_budgets(config: BudgetConfig) -> Result<(), String> {
+    validate_budget_config(&config)?;
+    tauri::async_runtime::spawn_blocking(move || write_budget_config(&config))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?
+}
+
+/// Report burn vs budget for the active periods, emitting `budget-alert`
+/// for thresholds reached since the last check.
+#[tauri::command]
+pub async fn get_budget_status(
+    app_handle: AppHandle,
+    claude_path: String,
+    custom_claude_paths: Option<Vec<CustomClaudePathParam>>,
+) -> Result<Vec<BudgetStatus>, String> {
+    remember_monitor_roots(&claude_path, custom_claude_paths.as_ref());
+    let (statuses, alerts) = evaluate_budgets(claude_path, custom_claude_paths).await?;
+    for alert in alerts {
+        if let Err(e) = app_handle.emit(BUDGET_ALERT_EVENT, &alert) {
+            log::error!("Failed to emit budget alert: {e}");
+        }
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(id: &str, metric: BudgetMetric, limit: f64) -> Budget {
+        Budget {
+            id: id.to_string(),
+            name: None,
+            scope: BudgetScope::Global,
+            target: None,
+            period: BudgetPeriod::Day,
+            metric,
+            limit,
+            thresholds: vec![0.5, 0.8, 1.0],
+        }
+    }
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        // Wednesday.
+        let now = utc("2026-12-16T15:30:00Z");
+        let bounds = |period| {
+            let (start, end) = period_bounds(period, &now);
+            (start.to_rfc3339(), end.to_rfc3339())
+        };
+        assert_eq!(
+            bounds(BudgetPeriod::Day),
+            (
+                "2026-12-16T00:00:00+00:00".to_string(),
+                "2026-12-17T00:00:00+00:00".to_string()
+            )
+        );
+        assert_eq!(
+            bounds(BudgetPeriod::Week),
+            (
+                "2026-12-14T00:00:00+00:00".to_string(),
+                "2026-12-21T00:00:00+00:00".to_string()
+            )
+        );
+        assert_eq!(
+            bounds(BudgetPeriod::Month),
+            (
+                "2026-12-01T00:00:00+00:00".to_string(),
+                "2027-01-01T00:00:00+00:00".to_string()
+            )
+        );
+
+        // Already Thursday the 17th nine hours east of UTC: periods follow
+        // the local calendar.
+        let tokyo = now.with_timezone(&chrono::FixedOffset::east_opt(9 * 3600).unwrap())
+            + Duration::hours(9);
+        let (start, end) = period_bounds(BudgetPeriod::Day, &tokyo);
+        assert_eq!(start.to_rfc3339(), "2026-12-17T00:00:00+09:00");
+        assert_eq!(end.to_rfc3339(), "2026-12-18T00:00:00+09:00");
+        let (start, _) = period_bounds(BudgetPeriod::Week, &tokyo);
+        assert_eq!(
+            start.with_timezone(&Utc).to_rfc3339(),
+            "2026-12-13T15:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_budget_status_projects_linearly() {
+        let now = utc("2026-12-16T06:00:00Z");
+        let (start, end) = period_bounds(BudgetPeriod::Day, &now);
+        let usage = PeriodUsage {
+            tokens: 600,
+            usd: 2.5,
+            unpriced_tokens: 40,
+        };
+
+        let tokens = budget_status(
+            &budget("t", BudgetMetric::Tokens, 1_000.0),
+            usage,
+            &now,
+            &start,
+            &end,
+        );
+        assert!((tokens.used - 600.0).abs() < 1e-9);
+        assert!((tokens.projected - 2_400.0).abs() < 1e-9);
+        assert_eq!(tokens.crossed_threshold, Some(0.5));
+        assert_eq!(tokens.unpriced_tokens, 0);
+
+        let usd = budget_status(
+            &budget("u", BudgetMetric::Usd, 2.0),
+            usage,
+            &now,
+            &start,
+            &end,
+        );
+        assert_eq!(usd.crossed_threshold, Some(1.0));
+        assert!((usd.used_ratio - 1.25).abs() < 1e-9);
+        assert_eq!(usd.unpriced_tokens, 40);
+    }
+
+    #[test]
+    fn test_alerts_fire_once_per_threshold_and_period() {
+        let now = utc("2026-12-16T06:00:00Z");
+        let (start, end) = period_bounds(BudgetPeriod::Day, &now);
+        let budget = budget("alert-once", BudgetMetric::Tokens, 100.0);
+        let status = |tokens| {
+            budget_status(
+                &budget,
+                PeriodUsage {
+                    tokens,
+                    ..PeriodUsage::default()
+                },
+                &now,
+                &start,
+                &end,
+            )
+        };
+
+        assert!(take_new_alerts(&[status(10)]).is_empty());
+        let alerts = take_new_alerts(&[status(85)]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold.to_bits(), 0.8_f64.to_bits());
+        assert!(take_new_alerts(&[status(90)]).is_empty());
+        assert_eq!(take_new_alerts(&[status(120)]).len(), 1);
+
+        let (next_start, next_end) = (end, end + Duration::days(1));
+        let next_day = budget_status(
+            &budget,
+            PeriodUsage {
+                tokens: 120,
+                ..PeriodUsage::default()
+            },
+            &next_start,
+            &next_start,
+            &next_end,
+        );
+        assert_eq!(
+            take_new_alerts(&[next_day]).len(),
+            1,
+            "new period re-alerts"
+        );
+    }
+
+    #[test]
+    fn test_validate_budget_config() {
+        let ok = BudgetConfig {
+            budgets: vec![budget("a", BudgetMetric::Usd, 10.0)],
+        };
+        assert!(validate_budget_config(&ok).is_ok());
+
+        let mut duplicate = ok.clone();
+        duplicate
+            .budgets
+            .push(budget("a", BudgetMetric::Tokens, 1.0));
+        assert!(validate_budget_config(&duplicate).is_err());
+
+        let mut zero_limit = ok.clone();
+        zero_limit.budgets[0].limit = 0.0;
+        assert!(validate_budget_config(&zero_limit).is_err());
+
+        let mut no_target = ok.clone();
+        no_target.budgets[0].scope = BudgetScope::Provider;
+        assert!(validate_budget_config(&no_target).is_err());
+
+        let mut relative_project = ok.clone();
+        relative_project.budgets[0].scope = BudgetScope::Project;
+        relative_project.budgets[0].target = Some("relative/path".to_string());
+        assert!(validate_budget_config(&relative_project).is_err());
+
+        let project = |target: &str| {
+            let mut config = ok.clone();
+            config.budgets[0].scope = BudgetScope::Project;
+            config.budgets[0].target = Some(target.to_string());
+            validate_budget_config(&config)
+        };
+        assert!(project("gemini:///home/me/.gemini/tmp/proj").is_ok());
+        assert!(project("codex:///work/app").is_ok());
+        assert!(project("opencode://../escape").is_err());
+        assert!(project("aider://").is_err());
+        assert!(project("unknown://proj").is_err());
+    }
+
+    #[test]
+    fn test_budget_config_defaults_thresholds() {
+        let config: BudgetConfig = serde_json::from_value(serde_json::json!({
+            "budgets": [{
+                "id": "daily",
+                "scope": "provider",
+                "target": "codex",
+                "period": "day",
+                "metric": "usd",
+                "limit": 5
+            }]
+        }))
+        .unwrap();
+        assert_eq!(config.budgets[0].thresholds, vec![0.8, 1.0]);
+        assert_eq!(config.budgets[0].scope, BudgetScope::Provider);
+    }
+}
@@ -56,7 +56,7 @@ impl Default for MetadataState {
 }
 
 /// Get the metadata folder path (~/.claude-history-viewer)
-fn get_metadata_folder() -> Result<PathBuf, String> {
+pub(crate) fn get_metadata_folder() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("Could not find home directory")?;
     Ok(home.join(".claude-history-viewer"))
 }
@@ -67,7 +67,7 @@ pub(crate) fn get_user_data_path() -> Result<PathBuf, String> {
 }
 
 /// Ensure the metadata folder exists
-fn ensure_metadata_folder() -> Result<PathBuf, String> {
+pub(crate) fn ensure_metadata_folder() -> Result<PathBuf, String> {
     let folder = get_metadata_folder()?;
     if !folder.exists() {
         fs::create_dir_all(&folder)
@@ -1,5 +1,9 @@
 pub mod antigravity;
 pub mod archive;
+pub mod archive_bundle;
+pub mod archive_store;
+pub mod auto_archive;
+pub mod budget;
 pub mod claude_settings;
 pub mod feedback;
 pub mod fs_utils;
@@ -1,6 +1,10 @@
+use crate::commands::session::{
+    annotate_search_hits, parse_search_query, snippet_context, sort_by_relevance, MatchMode,
+    MatchOptions, SearchFields, SearchSort, TextMatcher,
+};
 use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession, MessagePage};
 use crate::providers;
-use crate::utils::{parse_rfc3339_utc, search_result_priority};
+use crate::utils::parse_rfc3339_utc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
@@ -14,6 +18,44 @@ pub struct CustomClaudePathParam {
     pub label: Option<String>,
 }
 
+/// Every provider id understood by the multi-provider commands, in scan order.
+const ALL_PROVIDER_IDS: &[&str] = &[
+    "claude",
+    "codex",
+    "continue",
+    "pearai",
+    "copilot",
+    "gemini",
+    "goose",
+    "grok",
+    "kimi",
+    "forgecode",
+    "opencode",
+    "openinterpreter",
+    "pi",
+    "ompi",
+    "qwen",
+    "cline",
+    "crush",
+    "cursor",
+    "cursor-agent",
+    "aider",
+    "amazonq",
+    "antigravity",
+    "codebuddy",
+    "kiro",
+    "llm",
+    "zed",
+    "openhands",
+    "trae",
+    "vibe",
+];
+
+/// Whether `provider` is a provider id accepted by the multi-provider commands.
+pub(crate) fn is_known_provider(provider: &str) -> bool {
+    ALL_PROVIDER_IDS.contains(&provider)
+}
+
 // Only these providers have WSL-specific loaders that accept UNC-backed paths.
 // Other providers remain native-only even when WSL search is enabled.
 const WSL_SEARCHABLE_PROVIDER_IDS: &[&str] = &["claude", "copilot"];
@@ -42,13 +84,36 @@ fn select_wsl_search_providers(
         .collect()
 }
 
+/// Relevance tier for global-search results: user text, assistant text, other
+/// previewable text, then tool-only matches. Timestamps break ties later.
+fn search_result_priority(message: &ClaudeMessage, matcher: &TextMatcher) -> usize {
+    let preview: Vec<&Value> = match message.content.as_ref() {
+        Some(text @ Value::String(_)) => vec![text],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter(|item| item.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|item| item.get("text"))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if preview.is_empty() || !matcher.matches_values(preview) {
+        return 3;
+    }
+
+    match message.message_type.as_str() {
+        "user" => 0,
+        "assistant" => 1,
+        _ => 2,
+    }
+}
+
 fn compare_global_search_results(
     a: &ClaudeMessage,
     b: &ClaudeMessage,
-    query_lower: &str,
+    matcher: &TextMatcher,
 ) -> Ordering {
-    search_result_priority(a, query_lower)
-        .cmp(&search_result_priority(b, query_lower))
+    search_result_priority(a, matcher)
+        .cmp(&search_result_priority(b, matcher))
         .then_with(|| {
             match (
                 parse_rfc3339_utc(&a.timestamp),
@@ -78,37 +143,10 @@ pub async fn scan_all_projects(
     wsl_excluded_distros: Option<Vec<String>>,
 ) -> Result<Vec<ClaudeProject>, String> {
     let providers_to_scan = active_providers.unwrap_or_else(|| {
-        vec![
-            "claude".to_string(),
-            "codex".to_string(),
-            "continue".to_string(),
-            "pearai".to_string(),
-            "copilot".to_string(),
-            "gemini".to_string(),
-            "goose".to_string(),
-            "grok".to_string(),
-            "kimi".to_string(),
-            "forgecode".to_string(),
-            "opencode".to_string(),
-            "openinterpreter".to_string(),
-            "pi".to_string(),
-            "ompi".to_string(),
-            "qwen".to_string(),
-            "cline".to_string(),
-            "crush".to_string(),
-            "cursor".to_string(),
-            "cursor-agent".to_string(),
-            "aider".to_string(),
-            "amazonq".to_string(),
-            "antigravity".to_string(),
-            "codebuddy".to_string(),
-            "kiro".to_string(),
-            "llm".to_string(),
-            "zed".to_string(),
-            "openhands".to_string(),
-            "trae".to_string(),
-            "vibe".to_string(),
-        ]
+        ALL_PROVIDER_IDS
+            .iter()
+            .map(|provider| (*provider).to_string())
+            .collect()
     });
 
     let mut all_projects = Vec::new();
@@ -523,12 +561,24 @@ pub async fn load_provider_messages(
         }
         messages
     } else {
-        load_non_claude_messages(&provider, &session_path)?
+        return load_non_claude_session(&provider, &session_path);
     };
 
     Ok(merge_tool_execution_messages(messages))
 }
 
+/// Messages of a non-Claude session exactly as [`load_provider_messages`]
+/// returns them; archiving snapshots this form.
+pub(crate) fn load_non_claude_session(
+    provider: &str,
+    session_path: &str,
+) -> Result<Vec<ClaudeMessage>, String> {
+    Ok(merge_tool_execution_messages(load_non_claude_messages(
+        provider,
+        session_path,
+    )?))
+}
+
 /// Chat-style slice over an already-materialized, chronologically ordered
 /// message list: `offset` counts messages already loaded from the NEWEST end,
 /// so offset 0 returns the newest `limit` messages and increasing offsets walk
@@ -600,6 +650,74 @@ pub async fn load_provider_messages_paginated(
     Ok(paginate_messages_chat_style(merged, offset, limit))
 }
 
+/// Messages `offset..offset + limit` of a session counted from the OLDEST
+/// message, for callers that page forward rather than chat-style.
+///
+/// Claude counts the session's lines, then reads only that window through
+/// [`load_provider_messages_paginated`], so offsets share its pre-merge index
+/// space. Other providers load the session once, as the paginated loader
+/// does, and slice it after merging.
+#[cfg(feature = "webui-server")]
+pub(crate) async fn load_provider_messages_range(
+    provider: String,
+    session_path: String,
+    offset: usize,
+    limit: usize,
+    exclude_sidechain: bool,
+) -> Result<MessagePage, String> {
+    if provider != "claude" {
+        let mut messages = load_non_claude_session(&provider, &session_path)?;
+        if exclude_sidechain {
+            messages.retain(|m| !m.is_sidechain.unwrap_or(false));
+        }
+        let total_count = messages.len();
+        let page: Vec<ClaudeMessage> = messages.into_iter().skip(offset).take(limit).collect();
+        let next_offset = offset + page.len();
+        return Ok(MessagePage {
+            messages: page,
+            total_count,
+            has_more: next_offset < total_count,
+            next_offset,
+        });
+    }
+
+    let mut total_count = crate::commands::session::get_session_message_count(
+        session_path.clone(),
+        Some(exclude_sidechain),
+    )
+    .await?;
+    // A live session can grow between the count and the read; the window is
+    // anchored at the newest message, so re-read once with the new total.
+    let mut retried = false;
+    loop {
+        if offset >= total_count {
+            return Ok(MessagePage {
+                messages: Vec::new(),
+                total_count,
+                has_more: false,
+                next_offset: offset,
+            });
+        }
+        let to_load = limit.min(total_count - offset);
+        let mut page = load_provider_messages_paginated(
+            provider.clone(),
+            session_path.clone(),
+            Some(total_count - offset - to_load),
+            Some(to_load),
+            Some(exclude_sidechain),
+        )
+        .await?;
+        if page.total_count != total_count && !retried {
+            total_count = page.total_count;
+            retried = true;
+            continue;
+        }
+        page.next_offset = offset + to_load;
+        page.has_more = page.next_offset < page.total_count;
+        return Ok(page);
+    }
+}
+
 /// Find how far from the NEWEST visible message a uuid sits (0 = newest).
 /// Returns `None` when the uuid is not present. Loading a chat-style window
 /// with `offset = 0, limit = result + 1` guarantees the message is included —
@@ -649,6 +767,18 @@ pub async fn search_all_providers(
     let search_filters =
         filters.unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::default()));
     crate::commands::session::validate_search_filters(&search_filters)?;
+    // Claude parses the raw query itself. Other providers only match their
+    // longest term case-insensitively (no term matches everything), or every
+    // message in regex mode; the other terms, the regex, the case, the fields
+    // and every filter are checked on the merged results below. They must not
+    // truncate before those checks, so only the merged results are limited.
+    let match_options = MatchOptions::from_filters(&search_filters)?;
+    let (search_query, patterns, matcher) = parse_search_query(&query, match_options)?;
+    let text = provider_search_text(&patterns, match_options.mode);
+    let provider_limit = usize::MAX;
+    let fields = SearchFields::from_filters(&search_filters)?;
+    let context = snippet_context(&search_filters)?;
+    let sort = SearchSort::from_filters(&search_filters)?;
 
     let providers_to_search = active_providers.unwrap_or_else(|| {
         vec![
@@ -751,7 +881,7 @@ pub async fn search_all_providers(
 
     // Codex
     if providers_to_search.iter().any(|p| p == "codex") {
-        match providers::codex::search(&query, max_results, &search_filters) {
+        match providers::codex::search(&text, provider_limit, &search_filters) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Codex search failed: {e}");
@@ -761,7 +891,7 @@ pub async fn search_all_providers(
 
     // Continue.dev
     if providers_to_search.iter().any(|p| p == "continue") {
-        match providers::continue_dev::search(&query, max_results) {
+        match providers::continue_dev::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Continue search failed: {e}");
@@ -771,7 +901,7 @@ pub async fn search_all_providers(
 
     // PearAI
     if providers_to_search.iter().any(|p| p == "pearai") {
-        match providers::pearai::search(&query, max_results) {
+        match providers::pearai::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("PearAI search failed: {e}");
@@ -781,7 +911,7 @@ pub async fn search_all_providers(
 
     // Gemini
     if providers_to_search.iter().any(|p| p == "gemini") {
-        match providers::gemini::search(&query, max_results) {
+        match providers::gemini::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Gemini search failed: {e}");
@@ -791,7 +921,7 @@ pub async fn search_all_providers(
 
     // Goose
     if providers_to_search.iter().any(|p| p == "goose") {
-        match providers::goose::search(&query, max_results) {
+        match providers::goose::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Goose search failed: {e}");
@@ -801,7 +931,7 @@ pub async fn search_all_providers(
 
     // Grok
     if providers_to_search.iter().any(|p| p == "grok") {
-        match providers::grok::search(&query, max_results) {
+        match providers::grok::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Grok search failed: {e}");
@@ -811,7 +941,7 @@ pub async fn search_all_providers(
 
     // Kimi
     if providers_to_search.iter().any(|p| p == "kimi") {
-        match providers::kimi::search(&query, max_results) {
+        match providers::kimi::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Kimi search failed: {e}");
@@ -821,7 +951,7 @@ pub async fn search_all_providers(
 
     // Mistral Vibe
     if providers_to_search.iter().any(|p| p == "vibe") {
-        match providers::vibe::search(&query, max_results) {
+        match providers::vibe::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Vibe search failed: {e}");
@@ -831,7 +961,7 @@ pub async fn search_all_providers(
 
     // ForgeCode
     if providers_to_search.iter().any(|p| p == "forgecode") {
-        match providers::forgecode::search(&query, max_results) {
+        match providers::forgecode::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("ForgeCode search failed: {e}");
@@ -841,7 +971,7 @@ pub async fn search_all_providers(
 
     // OpenCode
     if providers_to_search.iter().any(|p| p == "opencode") {
-        match providers::opencode::search(&query, max_results) {
+        match providers::opencode::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("OpenCode search failed: {e}");
@@ -851,7 +981,7 @@ pub async fn search_all_providers(
 
     // Open Interpreter
     if providers_to_search.iter().any(|p| p == "openinterpreter") {
-        match providers::openinterpreter::search(&query, max_results) {
+        match providers::openinterpreter::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Open Interpreter search failed: {e}");
@@ -861,7 +991,7 @@ pub async fn search_all_providers(
 
     // Pi
     if providers_to_search.iter().any(|p| p == "pi") {
-        match providers::pi::search(&query, max_results) {
+        match providers::pi::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Pi search failed: {e}");
@@ -871,7 +1001,7 @@ pub async fn search_all_providers(
 
     // oh-my-pi
     if providers_to_search.iter().any(|p| p == "ompi") {
-        match providers::ompi::search(&query, max_results) {
+        match providers::ompi::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("oh-my-pi search failed: {e}");
@@ -881,7 +1011,7 @@ pub async fn search_all_providers(
 
     // Qwen Code
     if providers_to_search.iter().any(|p| p == "qwen") {
-        match providers::qwen::search(&query, max_results) {
+        match providers::qwen::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Qwen search failed: {e}");
@@ -891,7 +1021,7 @@ pub async fn search_all_providers(
 
     // Cline
     if providers_to_search.iter().any(|p| p == "cline") {
-        match providers::cline::search(&query, max_results) {
+        match providers::cline::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Cline search failed: {e}");
@@ -901,7 +1031,7 @@ pub async fn search_all_providers(
 
     // Crush
     if providers_to_search.iter().any(|p| p == "crush") {
-        match providers::crush::search(&query, max_results) {
+        match providers::crush::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Crush search failed: {e}");
@@ -911,7 +1041,7 @@ pub async fn search_all_providers(
 
     // Cursor
     if providers_to_search.iter().any(|p| p == "cursor") {
-        match providers::cursor::search(&query, max_results) {
+        match providers::cursor::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Cursor search failed: {e}");
@@ -921,7 +1051,7 @@ pub async fn search_all_providers(
 
     // Cursor Agent
     if providers_to_search.iter().any(|p| p == "cursor-agent") {
-        match providers::cursor_agent::search(&query, max_results) {
+        match providers::cursor_agent::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Cursor Agent search failed: {e}");
@@ -931,7 +1061,7 @@ pub async fn search_all_providers(
 
     // Aider
     if providers_to_search.iter().any(|p| p == "aider") {
-        match providers::aider::search(&query, max_results) {
+        match providers::aider::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Aider search failed: {e}");
@@ -941,7 +1071,7 @@ pub async fn search_all_providers(
 
     // Amazon Q Developer CLI
     if providers_to_search.iter().any(|p| p == "amazonq") {
-        match providers::amazon_q::search(&query, max_results) {
+        match providers::amazon_q::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Amazon Q search failed: {e}");
@@ -951,7 +1081,7 @@ pub async fn search_all_providers(
 
     // Antigravity
     if providers_to_search.iter().any(|p| p == "antigravity") {
-        match providers::antigravity::search(&query, max_results) {
+        match providers::antigravity::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Antigravity search failed: {e}");
@@ -961,7 +1091,7 @@ pub async fn search_all_providers(
 
     // CodeBuddy
     if providers_to_search.iter().any(|p| p == "codebuddy") {
-        match providers::codebuddy::search(&query, max_results) {
+        match providers::codebuddy::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("CodeBuddy search failed: {e}");
@@ -970,7 +1100,7 @@ pub async fn search_all_providers(
     }
     // Kiro
     if providers_to_search.iter().any(|p| p == "kiro") {
-        match providers::kiro::search(&query, max_results) {
+        match providers::kiro::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Kiro search failed: {e}");
@@ -980,7 +1110,7 @@ pub async fn search_all_providers(
 
     // llm (Simon Willison)
     if providers_to_search.iter().any(|p| p == "llm") {
-        match providers::llm::search(&query, max_results) {
+        match providers::llm::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("llm search failed: {e}");
@@ -990,7 +1120,7 @@ pub async fn search_all_providers(
 
     // Zed
     if providers_to_search.iter().any(|p| p == "zed") {
-        match providers::zed::search(&query, max_results) {
+        match providers::zed::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Zed search failed: {e}");
@@ -1000,7 +1130,7 @@ pub async fn search_all_providers(
 
     // OpenHands
     if providers_to_search.iter().any(|p| p == "openhands") {
-        match providers::openhands::search(&query, max_results) {
+        match providers::openhands::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("OpenHands search failed: {e}");
@@ -1010,7 +1140,7 @@ pub async fn search_all_providers(
 
     // Trae IDE
     if providers_to_search.iter().any(|p| p == "trae") {
-        match providers::trae::search(&query, max_results) {
+        match providers::trae::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Trae search failed: {e}");
@@ -1020,7 +1150,7 @@ pub async fn search_all_providers(
 
     // Unified GitHub Copilot search (CLI + Desktop + VS Code Copilot Chat).
     if providers_to_search.iter().any(|p| p == "copilot") {
-        match providers::copilot::search(&query, max_results) {
+        match providers::copilot::search(&text, provider_limit) {
             Ok(results) => all_results.extend(results),
             Err(e) => {
                 log::warn!("Copilot search failed: {e}");
@@ -1083,8 +1213,8 @@ pub async fn search_all_providers(
                     match providers::copilot::search_from_paths(
                         copilot_base.as_deref(),
                         canonical_vscode.as_deref(),
-                        &query,
-                        max_results,
+                        &text,
+                        provider_limit,
                     ) {
                         Ok(results) => all_results.extend(results),
                         Err(e) => {
@@ -1100,8 +1230,8 @@ pub async fn search_all_providers(
                     match providers::copilot::search_from_paths(
                         None,
                         Some(unc_path.as_path()),
-                        &query,
-                        max_results,
+                        &text,
+                        provider_limit,
                     ) {
                         Ok(results) => all_results.extend(results),
                         Err(e) => {
@@ -1117,16 +1247,39 @@ pub async fn search_all_providers(
     }
 
     all_results = crate::commands::session::apply_search_filters(all_results, &search_filters);
+    all_results.retain(|message| {
+        search_query.matches(message) && matcher.matches_message(message, fields)
+    });
 
-    // Prefer the user's matching prompts, then displayable assistant text,
-    // while preserving tool-only matches after conversational results.
-    let query_lower = query.to_lowercase();
-    all_results.sort_by(|a, b| compare_global_search_results(a, b, &query_lower));
+    match sort {
+        // Prefer the user's matching prompts, then displayable assistant
+        // text, while preserving tool-only matches after conversational
+        // results.
+        SearchSort::Newest => {
+            all_results.sort_by(|a, b| compare_global_search_results(a, b, &matcher));
+        }
+        SearchSort::Relevance => sort_by_relevance(&mut all_results, &matcher, fields),
+    }
     all_results.truncate(max_results);
+    annotate_search_hits(&mut all_results, &matcher, fields, context);
 
     Ok(all_results)
 }
 
+/// Text handed to the non-Claude provider searches as a prefilter: the longest
+/// literal term, or nothing (every message) for a regex the providers cannot
+/// evaluate.
+fn provider_search_text(patterns: &[String], mode: MatchMode) -> String {
+    if mode == MatchMode::Regex {
+        return String::new();
+    }
+    patterns
+        .iter()
+        .max_by_key(|term| term.chars().count())
+        .cloned()
+        .unwrap_or_default()
+}
+
 /// Resolve active (non-excluded) WSL distros with their home paths.
 fn resolve_active_wsl_distros(excluded: &[String]) -> Vec<(crate::wsl::WslDistro, PathBuf)> {
     let distros = crate::wsl::detect_distros();
@@ -1307,6 +1460,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("claude".to_string()),
+            ..Default::default()
         }
     }
 
@@ -1344,7 +1498,8 @@ mod tests {
         tool.timestamp = "2026-02-19T12:00:00Z".to_string();
 
         let mut results = [tool, assistant, user];
-        results.sort_by(|a, b| compare_global_search_results(a, b, "wallpaper"));
+        let matcher = TextMatcher::literal("wallpaper", false);
+        results.sort_by(|a, b| compare_global_search_results(a, b, &matcher));
 
         assert_eq!(
             results
@@ -1355,6 +1510,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn regex_search_prefilters_nothing_and_matches_provider_results() {
+        let options = MatchOptions {
+            mode: MatchMode::Regex,
+            ..MatchOptions::default()
+        };
+        let (_, patterns, matcher) = parse_search_query(r"wall\w+er", options).unwrap();
+        assert_eq!(provider_search_text(&patterns, options.mode), "");
+
+        let mut codex = make_message(
+            "assistant",
+            serde_json::json!([{ "type": "text", "text": "new wallpaper" }]),
+        );
+        codex.provider = Some("codex".to_string());
+        let other = make_message("assistant", serde_json::json!("nothing here"));
+        assert!(matcher.matches_message(&codex, SearchFields::ALL));
+        assert!(!matcher.matches_message(&other, SearchFields::ALL));
+
+        let (_, patterns, _) = parse_search_query("wall papers", MatchOptions::default()).unwrap();
+        assert_eq!(
+            provider_search_text(&patterns, MatchMode::Literal),
+            "papers"
+        );
+    }
+
     #[test]
     fn paginate_chat_style_returns_newest_window_first() {
         let messages: Vec<ClaudeMessage> = (1..=5)
@@ -1469,6 +1649,48 @@ mod tests {
         assert_eq!(window.next_offset, 2);
     }
 
+    #[cfg(feature = "webui-server")]
+    #[tokio::test]
+    async fn load_provider_messages_range_pages_from_the_oldest_message() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("session.jsonl");
+        let content: String = (1..=5)
+            .map(|i| {
+                format!(
+                    r#"{{"uuid":"uuid-{i}","sessionId":"s1","timestamp":"2025-06-26T10:00:0{i}Z","type":"user","message":{{"role":"user","content":"Message {i}"}}}}"#
+                ) + "\n"
+            })
+            .collect();
+        std::fs::write(&file_path, content).unwrap();
+        let path = file_path.to_string_lossy().to_string();
+        let uuids = |page: &MessagePage| {
+            page.messages
+                .iter()
+                .map(|m| m.uuid.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let first = load_provider_messages_range("claude".into(), path.clone(), 0, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(uuids(&first), vec!["uuid-1", "uuid-2"]);
+        assert_eq!(first.total_count, 5);
+        assert!(first.has_more);
+        assert_eq!(first.next_offset, 2);
+
+        let last = load_provider_messages_range("claude".into(), path.clone(), 3, 10, false)
+            .await
+            .unwrap();
+        assert_eq!(uuids(&last), vec!["uuid-4", "uuid-5"]);
+        assert!(!last.has_more);
+
+        let past_end = load_provider_messages_range("claude".into(), path, 9, 2, false)
+            .await
+            .unwrap();
+        assert!(past_end.messages.is_empty());
+        assert_eq!(past_end.total_count, 5);
+    }
+
     #[tokio::test]
     async fn get_provider_message_offset_claude_matches_pagination_space() {
         let temp_dir = tempfile::TempDir::new().unwrap();
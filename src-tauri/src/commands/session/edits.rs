@@ -320,6 +320,19 @@ fn parse_patch_file_paths(patch: &str) -> Vec<String> {
     files
 }
 
+/// Return the paths a tool call writes to, or nothing for non-editing tools.
+pub(crate) fn edited_file_paths(tool_name: &str, input: &serde_json::Value) -> Vec<String> {
+    if infer_operation_type(tool_name).is_none() {
+        return Vec::new();
+    }
+    if tool_name.eq_ignore_ascii_case("apply_patch") {
+        return get_first_string(input, &["patch"])
+            .map(|patch| parse_patch_file_paths(&patch))
+            .unwrap_or_default();
+    }
+    resolve_file_path_from_input(input).into_iter().collect()
+}
+
 fn get_tool_input_content(input: &serde_json::Value) -> String {
     if let Some(content) = get_first_string(input, &["content", "new_string", "newString", "patch"])
     {
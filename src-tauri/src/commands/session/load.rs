@@ -1627,6 +1627,7 @@ fn parse_line_to_message(
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         });
     }
 
@@ -1688,6 +1689,7 @@ fn parse_line_to_message(
         compact_metadata: log_entry.compact_metadata,
         microcompact_metadata: log_entry.microcompact_metadata,
         provider: None,
+        ..Default::default()
     })
 }
 
@@ -1757,6 +1759,7 @@ fn parse_line_simd(
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         });
     }
 
@@ -1831,6 +1834,7 @@ fn parse_line_simd(
         compact_metadata: log_entry.compact_metadata,
         microcompact_metadata: log_entry.microcompact_metadata,
         provider: None,
+        ..Default::default()
     })
 }
 
@@ -1893,21 +1897,28 @@ fn load_all_messages_from_file(session_path: &Path) -> Result<Vec<ClaudeMessage>
     let mmap = unsafe { Mmap::map(&file) }
         .map_err(|e| format!("Failed to memory-map session file: {e}"))?;
 
+    Ok(parse_session_bytes(&mmap))
+}
+
+/// Parse every viewer-visible message from in-memory JSONL, in order. Used for
+/// files read through mmap and for content that is not a plain file on disk,
+/// such as zstd-compressed archive objects.
+pub(crate) fn parse_session_bytes(data: &[u8]) -> Vec<ClaudeMessage> {
     // Find line boundaries efficiently using SIMD-accelerated memchr
-    let line_starts = find_line_starts(&mmap);
+    let line_starts = find_line_starts(data);
 
     // Parse lines in parallel using simd-json
     let mut messages: Vec<(usize, ClaudeMessage)> = line_starts
         .par_iter()
         .enumerate()
         .filter_map(|(line_num, &start)| {
-            let end = line_starts.get(line_num + 1).map_or(mmap.len(), |&e| e - 1);
+            let end = line_starts.get(line_num + 1).map_or(data.len(), |&e| e - 1);
             if start >= end {
                 return None;
             }
 
             // Create a mutable copy for simd-json (it requires mutable slice)
-            let mut line_bytes = mmap[start..end].to_vec();
+            let mut line_bytes = data[start..end].to_vec();
 
             parse_line_simd(line_num, &mut line_bytes, false)
                 .filter(|msg| {
@@ -1925,7 +1936,7 @@ fn load_all_messages_from_file(session_path: &Path) -> Result<Vec<ClaudeMessage>
 
     // Sort by line number to maintain original order
     messages.sort_by_key(|(line_num, _)| *line_num);
-    Ok(messages.into_iter().map(|(_, msg)| msg).collect())
+    messages.into_iter().map(|(_, msg)| msg).collect()
 }
 
 // ============================================================================
@@ -0,0 +1,643 @@
+//! Text matching for message search: literal terms or a regex pattern,
+//! matched case-insensitively or with exact case. A literal matcher holds the
+//! free-text terms of a query and matches when every term occurs somewhere in
+//! the searched text; with no terms it matches everything. A prefix term
+//! (`deser*` in a query) only counts where it starts a word.
+//!
+//! The mode travels in the search filters, next to the other filter keys:
+//!
+//! ```json
+//! { "matchMode": "regex", "caseSensitive": true }
+//! ```
+//!
+//! Both keys are optional; the default is the original case-insensitive
+//! literal match.
+//!
+//! A `fields` filter restricts matching to parts of a message, e.g. "which
+//! session ran this command" (`["toolInput"]`) or "which session edited this
+//! file" (`["toolInput", "toolResult"]`):
+//!
+//! | Field           | Matches                                                   |
+//! |-----------------|-----------------------------------------------------------|
+//! | `userText`      | user message text                                         |
+//! | `assistantText` | assistant message text                                    |
+//! | `thinking`      | `thinking` content blocks                                 |
+//! | `toolInput`     | `tool_use` content blocks and the `toolUse` field         |
+//! | `toolResult`    | `tool_result` content blocks and the `toolUseResult` field |
+//!
+//! Absent or empty, every field is searched.
+
+use super::query::QueryTerm;
+use crate::models::ClaudeMessage;
+use aho_corasick::AhoCorasick;
+use regex::{Regex, RegexBuilder};
+
+/// Longest accepted regex pattern, in bytes.
+const MAX_REGEX_PATTERN_LEN: usize = 1_000;
+
+/// Compiled-program and lazy-DFA budgets for user patterns (the crate
+/// defaults are 10 MiB / 2 MiB). The regex engine already guarantees
+/// linear-time matching; these caps bound memory and compile time for
+/// patterns like `(a{100}){100}` that expand enormously.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const REGEX_DFA_SIZE_LIMIT: usize = 1 << 20;
+
+/// Maximum group/repetition nesting depth (the crate default is 250).
+const REGEX_NEST_LIMIT: u32 = 32;
+
+/// BM25 term-frequency saturation and length normalization, as in `SQLite`'s
+/// `bm25()`.
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// Stand-in for the corpus' average searched text length, in bytes.
+const BM25_AVERAGE_LEN: f64 = 500.0;
+
+/// How the free text of a search query is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MatchMode {
+    /// The text is matched verbatim as a substring.
+    #[default]
+    Literal,
+    /// The whole query is a regular expression (`regex` crate syntax).
+    Regex,
+}
+
+/// Match mode and case handling selected by the search filters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MatchOptions {
+    pub mode: MatchMode,
+    pub case_sensitive: bool,
+}
+
+impl MatchOptions {
+    /// Read `matchMode` (`"literal"` or `"regex"`) and `caseSensitive` from
+    /// the search filters. Absent keys fall back to the defaults.
+    pub fn from_filters(filters: &serde_json::Value) -> Result<Self, String> {
+        let Some(obj) = filters.as_object() else {
+            return Ok(Self::default());
+        };
+
+        let mode = match obj.get("matchMode") {
+            None | Some(serde_json::Value::Null) => MatchMode::Literal,
+            Some(value) => match value.as_str() {
+                Some("literal") => MatchMode::Literal,
+                Some("regex") => MatchMode::Regex,
+                _ => {
+                    return Err(format!(
+                        "Invalid matchMode filter: {value} (expected \"literal\" or \"regex\")"
+                    ))
+                }
+            },
+        };
+
+        let case_sensitive = match obj.get("caseSensitive") {
+            None | Some(serde_json::Value::Null) => false,
+            Some(value) => value.as_bool().ok_or_else(|| {
+                format!("Invalid caseSensitive filter: {value} (expected a boolean)")
+            })?,
+        };
+
+        Ok(Self {
+            mode,
+            case_sensitive,
+        })
+    }
+}
+
+/// A part of a message that search can be restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    UserText,
+    AssistantText,
+    Thinking,
+    ToolInput,
+    ToolResult,
+}
+
+impl SearchField {
+    const ALL: [Self; 5] = [
+        Self::UserText,
+        Self::AssistantText,
+        Self::Thinking,
+        Self::ToolInput,
+        Self::ToolResult,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::UserText => "userText",
+            Self::AssistantText => "assistantText",
+            Self::Thinking => "thinking",
+            Self::ToolInput => "toolInput",
+            Self::ToolResult => "toolResult",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Set of [`SearchField`]s selected by the `fields` filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SearchFields(u8);
+
+impl SearchFields {
+    pub const ALL: Self = Self(0b1_1111);
+
+    /// Read the `fields` filter. Absent, `null` or empty selects every field.
+    pub fn from_filters(filters: &serde_json::Value) -> Result<Self, String> {
+        let Some(value) = filters.get("fields").filter(|value| !value.is_null()) else {
+            return Ok(Self::ALL);
+        };
+        let Some(names) = value.as_array() else {
+            return Err(format!(
+                "Invalid fields filter: {value} (expected an array of field names)"
+            ));
+        };
+
+        let mut bits = 0;
+        for name in names {
+            let field = SearchField::ALL
+                .into_iter()
+                .find(|field| name.as_str() == Some(field.name()))
+                .ok_or_else(|| {
+                    let expected: Vec<&str> = SearchField::ALL
+                        .into_iter()
+                        .map(SearchField::name)
+                        .collect();
+                    format!(
+                        "Invalid fields filter entry: {name} (expected one of {})",
+                        expected.join(", ")
+                    )
+                })?;
+            bits |= field.bit();
+        }
+        Ok(if bits == 0 { Self::ALL } else { Self(bits) })
+    }
+
+    pub fn contains(self, field: SearchField) -> bool {
+        self.0 & field.bit() != 0
+    }
+}
+
+impl Default for SearchFields {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// A searchable part of a message: a value inside one of its JSON fields.
+pub(super) struct SearchUnit<'a> {
+    /// Message field holding the value: `content`, `toolUseResult` or `toolUse`
+    pub field: &'static str,
+    /// JSON pointer of `value` within `field`
+    pub path: String,
+    /// Content block index, when `value` is a block of an array `content`
+    pub block_index: Option<usize>,
+    pub value: &'a serde_json::Value,
+}
+
+/// The parts of `message` that belong to `fields`. Array content is split
+/// into its blocks, classified by block `type`; blocks of other types count
+/// as message text.
+pub(super) fn search_units(message: &ClaudeMessage, fields: SearchFields) -> Vec<SearchUnit<'_>> {
+    let text_field = if message.message_type == "assistant" {
+        SearchField::AssistantText
+    } else {
+        SearchField::UserText
+    };
+    let mut units = Vec::new();
+
+    match message.content.as_ref() {
+        Some(serde_json::Value::Array(blocks)) => {
+            for (idx, block) in blocks.iter().enumerate() {
+                let field = match block.get("type").and_then(serde_json::Value::as_str) {
+                    Some("thinking" | "redacted_thinking") => SearchField::Thinking,
+                    Some("tool_use" | "server_tool_use") => SearchField::ToolInput,
+                    Some("tool_result") => SearchField::ToolResult,
+                    _ => text_field,
+                };
+                if fields.contains(field) {
+                    units.push(SearchUnit {
+                        field: "content",
+                        path: format!("/{idx}"),
+                        block_index: Some(idx),
+                        value: block,
+                    });
+                }
+            }
+        }
+        Some(value) if fields.contains(text_field) => units.push(SearchUnit {
+            field: "content",
+            path: String::new(),
+            block_index: None,
+            value,
+        }),
+        _ => {}
+    }
+
+    for (field, name, value) in [
+        (
+            SearchField::ToolResult,
+            "toolUseResult",
+            message.tool_use_result.as_ref(),
+        ),
+        (SearchField::ToolInput, "toolUse", message.tool_use.as_ref()),
+    ] {
+        if let Some(value) = value.filter(|_| fields.contains(field)) {
+            units.push(SearchUnit {
+                field: name,
+                path: String::new(),
+                block_index: None,
+                value,
+            });
+        }
+    }
+
+    units
+}
+
+/// A compiled search pattern.
+pub enum TextMatcher {
+    /// Literal terms, all of which must occur.
+    Literal(LiteralTerms),
+    Regex(Regex),
+}
+
+/// The terms of a literal matcher.
+pub struct LiteralTerms {
+    automaton: AhoCorasick,
+    /// Per pattern: only counts where it starts a word (a `word*` term).
+    word_start: Vec<bool>,
+}
+
+impl LiteralTerms {
+    fn len(&self) -> usize {
+        self.word_start.len()
+    }
+
+    /// Occurrences in `haystack` as `(pattern, start, end)`, overlapping.
+    fn occurrences<'h>(
+        &'h self,
+        haystack: &'h str,
+    ) -> impl Iterator<Item = (usize, usize, usize)> + 'h {
+        self.automaton
+            .find_overlapping_iter(haystack)
+            .map(|found| (found.pattern().as_usize(), found.start(), found.end()))
+            .filter(move |&(pattern, start, _)| {
+                !self.word_start[pattern] || starts_word(haystack, start)
+            })
+    }
+}
+
+/// Whether the text at byte `start` of `haystack` begins a word.
+fn starts_word(haystack: &str, start: usize) -> bool {
+    haystack[..start]
+        .chars()
+        .next_back()
+        .map_or(true, |before| !before.is_alphanumeric() && before != '_')
+}
+
+/// Terms of a literal matcher not seen yet while matching one message.
+struct PendingTerms {
+    missing: Vec<bool>,
+    remaining: usize,
+}
+
+impl PendingTerms {
+    fn new(terms: &LiteralTerms) -> Self {
+        Self {
+            missing: vec![true; terms.len()],
+            remaining: terms.len(),
+        }
+    }
+
+    /// Record the terms occurring in `haystack`; `true` once all were seen.
+    fn observe(&mut self, terms: &LiteralTerms, haystack: &str) -> bool {
+        for (pattern, _, _) in terms.occurrences(haystack) {
+            if self.remaining == 0 {
+                break;
+            }
+            let missing = &mut self.missing[pattern];
+            if *missing {
+                *missing = false;
+                self.remaining -= 1;
+            }
+        }
+        self.remaining == 0
+    }
+}
+
+/// Whether `f` accepts any string nested anywhere in `value`.
+fn any_string(value: &serde_json::Value, f: &mut impl FnMut(&str) -> bool) -> bool {
+    match value {
+        serde_json::Value::String(s) => f(s),
+        serde_json::Value::Array(arr) => arr.iter().any(|item| any_string(item, f)),
+        serde_json::Value::Object(obj) => obj.values().any(|val| any_string(val, f)),
+        _ => false,
+    }
+}
+
+impl TextMatcher {
+    /// Compile `pattern` for `options`. Regex patterns are rejected when they
+    /// are invalid or exceed the complexity caps.
+    pub fn new(pattern: &str, options: MatchOptions) -> Result<Self, String> {
+        match options.mode {
+            MatchMode::Literal => Ok(Self::literal(pattern, options.case_sensitive)),
+            MatchMode::Regex => {
+                if pattern.len() > MAX_REGEX_PATTERN_LEN {
+                    return Err(format!(
+                        "Regex pattern is too long ({} bytes, limit {MAX_REGEX_PATTERN_LEN})",
+                        pattern.len()
+                    ));
+                }
+                RegexBuilder::new(pattern)
+                    .case_insensitive(!options.case_sensitive)
+                    .size_limit(REGEX_SIZE_LIMIT)
+                    .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+                    .nest_limit(REGEX_NEST_LIMIT)
+                    .build()
+                    .map(Self::Regex)
+                    .map_err(|e| format!("Invalid regex pattern: {e}"))
+            }
+        }
+    }
+
+    /// Literal matcher for a single term.
+    pub fn literal(pattern: &str, case_sensitive: bool) -> Self {
+        Self::literal_terms(&[pattern], case_sensitive)
+    }
+
+    /// Literal matcher requiring every one of `terms`; empty terms are
+    /// ignored, and no terms at all match everything. Case-insensitive
+    /// matching is ASCII-only, which is sufficient for most search queries
+    /// and keeps matching allocation-free.
+    pub fn literal_terms<S: AsRef<str>>(terms: &[S], case_sensitive: bool) -> Self {
+        Self::build_literal(
+            terms.iter().map(|term| (term.as_ref(), false)).collect(),
+            case_sensitive,
+        )
+    }
+
+    /// Literal matcher for the free text of a parsed query: words and
+    /// phrases match anywhere, a `word*` prefix only where it starts a word.
+    pub fn query_terms(terms: &[QueryTerm], case_sensitive: bool) -> Self {
+        Self::build_literal(
+            terms
+                .iter()
+                .map(|term| match term {
+                    QueryTerm::Word(word) => match word.strip_suffix('*') {
+                        Some(prefix) => (prefix, true),
+                        None => (word.as_str(), false),
+                    },
+                    QueryTerm::Phrase(phrase) => (phrase.as_str(), false),
+                })
+                .collect(),
+            case_sensitive,
+        )
+    }
+
+    fn build_literal(mut terms: Vec<(&str, bool)>, case_sensitive: bool) -> Self {
+        terms.retain(|(term, _)| !term.is_empty());
+        terms.sort_unstable();
+        terms.dedup();
+        Self::Literal(LiteralTerms {
+            automaton: AhoCorasick::builder()
+                .ascii_case_insensitive(!case_sensitive)
+                .build(terms.iter().map(|(term, _)| term))
+                .expect("literal AhoCorasick build should never fail"),
+            word_start: terms.iter().map(|&(_, word_start)| word_start).collect(),
+        })
+    }
+
+    /// Whether `haystack` alone matches.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            Self::Literal(terms) => PendingTerms::new(terms).observe(terms, haystack),
+            Self::Regex(regex) => regex.is_match(haystack),
+        }
+    }
+
+    /// Whether the strings nested anywhere in `value` match.
+    pub fn matches_value(&self, value: &serde_json::Value) -> bool {
+        self.matches_values([value])
+    }
+
+    /// Whether the strings nested anywhere in `values` match together: each
+    /// literal term occurs in one of them, or the regex matches one.
+    pub fn matches_values<'v>(
+        &self,
+        values: impl IntoIterator<Item = &'v serde_json::Value>,
+    ) -> bool {
+        match self {
+            Self::Literal(terms) => {
+                let mut pending = PendingTerms::new(terms);
+                pending.remaining == 0
+                    || values.into_iter().any(|value| {
+                        any_string(value, &mut |haystack: &str| {
+                            pending.observe(terms, haystack)
+                        })
+                    })
+            }
+            Self::Regex(regex) => values
+                .into_iter()
+                .any(|value| any_string(value, &mut |haystack: &str| regex.is_match(haystack))),
+        }
+    }
+
+    /// Whether the parts of `message` within `fields` match.
+    pub fn matches_message(&self, message: &ClaudeMessage, fields: SearchFields) -> bool {
+        self.matches_values(search_units(message, fields).iter().map(|unit| unit.value))
+    }
+
+    /// BM25 score of a matching `message`, higher first: occurrences of each
+    /// term (or of the regex) saturate, and longer text weighs them less.
+    /// Every hit contains every term, so inverse document frequency is left
+    /// out. Ranks results for `"sort": "relevance"` wherever the persistent
+    /// index's `bm25()` is not available.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn relevance(&self, message: &ClaudeMessage, fields: SearchFields) -> f64 {
+        let mut counts = vec![0usize; self.pattern_count()];
+        let mut length = 0usize;
+        for unit in search_units(message, fields) {
+            any_string(unit.value, &mut |haystack: &str| {
+                length += haystack.len();
+                match self {
+                    Self::Literal(terms) => {
+                        for (pattern, _, _) in terms.occurrences(haystack) {
+                            counts[pattern] += 1;
+                        }
+                    }
+                    Self::Regex(regex) => counts[0] += regex.find_iter(haystack).count(),
+                }
+                false
+            });
+        }
+        let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length as f64 / BM25_AVERAGE_LEN);
+        counts
+            .into_iter()
+            .map(|count| {
+                let count = count as f64;
+                count * (BM25_K1 + 1.0) / (count + norm)
+            })
+            .sum()
+    }
+
+    fn pattern_count(&self) -> usize {
+        match self {
+            Self::Literal(terms) => terms.len(),
+            Self::Regex(_) => 1,
+        }
+    }
+
+    /// Non-empty match byte ranges in `haystack`, in order.
+    pub(super) fn find_ranges(&self, haystack: &str) -> Vec<(usize, usize)> {
+        match self {
+            Self::Literal(terms) => terms
+                .automaton
+                .find_iter(haystack)
+                .filter(|m| {
+                    !terms.word_start[m.pattern().as_usize()] || starts_word(haystack, m.start())
+                })
+                .map(|m| (m.start(), m.end()))
+                .filter(|(start, end)| start < end)
+                .collect(),
+            Self::Regex(regex) => regex
+                .find_iter(haystack)
+                .map(|m| (m.start(), m.end()))
+                .filter(|(start, end)| start < end)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::query::SearchQuery;
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_options_from_filters() {
+        assert_eq!(
+            MatchOptions::from_filters(&json!({})).unwrap(),
+            MatchOptions::default()
+        );
+        assert_eq!(
+            MatchOptions::from_filters(&json!({"matchMode": "regex", "caseSensitive": true}))
+                .unwrap(),
+            MatchOptions {
+                mode: MatchMode::Regex,
+                case_sensitive: true
+            }
+        );
+        assert!(MatchOptions::from_filters(&json!({"matchMode": "glob"})).is_err());
+        assert!(MatchOptions::from_filters(&json!({"caseSensitive": "yes"})).is_err());
+    }
+
+    #[test]
+    fn test_search_fields_from_filters() {
+        assert_eq!(
+            SearchFields::from_filters(&json!({})).unwrap(),
+            SearchFields::ALL
+        );
+        assert_eq!(
+            SearchFields::from_filters(&json!({"fields": []})).unwrap(),
+            SearchFields::ALL
+        );
+        let fields =
+            SearchFields::from_filters(&json!({"fields": ["toolInput", "thinking"]})).unwrap();
+        assert!(fields.contains(SearchField::ToolInput));
+        assert!(fields.contains(SearchField::Thinking));
+        assert!(!fields.contains(SearchField::UserText));
+        assert!(SearchFields::from_filters(&json!({"fields": "toolInput"})).is_err());
+        assert!(SearchFields::from_filters(&json!({"fields": ["stdout"]})).is_err());
+    }
+
+    #[test]
+    fn test_case_sensitivity_per_mode() {
+        let insensitive = TextMatcher::new("Foo", MatchOptions::default()).unwrap();
+        assert!(insensitive.is_match("a foo b"));
+
+        let exact = MatchOptions {
+            mode: MatchMode::Literal,
+            case_sensitive: true,
+        };
+        let sensitive = TextMatcher::new("Foo", exact).unwrap();
+        assert!(sensitive.is_match("a Foo b"));
+        assert!(!sensitive.is_match("a foo b"));
+
+        let regex = MatchOptions {
+            mode: MatchMode::Regex,
+            case_sensitive: false,
+        };
+        let pattern = TextMatcher::new(r"panicked at .*src/.*\.rs", regex).unwrap();
+        assert!(pattern.is_match("thread 'main' PANICKED AT 'boom', src/main.rs:3:5"));
+        assert!(!pattern.is_match("panicked at 'boom', lib.py"));
+    }
+
+    #[test]
+    fn test_literal_terms_must_all_match_across_strings() {
+        let matcher = TextMatcher::literal_terms(&["borrow", "Checker"], false);
+        assert!(matcher.is_match("the checker of borrows"));
+        assert!(!matcher.is_match("borrow only"));
+        assert!(matcher.matches_value(&json!(["a borrow", {"text": "the CHECKER"}])));
+        assert!(matcher.matches_values([&json!("borrow"), &json!("checker")]));
+        assert!(!matcher.matches_values([&json!("borrow"), &json!(42)]));
+
+        let overlapping = TextMatcher::literal_terms(&["borrowck", "borrow"], false);
+        assert!(overlapping.is_match("borrowck"));
+
+        let everything = TextMatcher::literal_terms::<&str>(&[], false);
+        assert!(everything.is_match(""));
+        assert!(everything.matches_values([]));
+    }
+
+    #[test]
+    fn test_prefix_terms_match_at_word_starts() {
+        let query = SearchQuery::parse("Deser* error").unwrap();
+        let matcher = TextMatcher::query_terms(query.terms(), false);
+        assert!(matcher.is_match("deserialize error"));
+        assert!(matcher.is_match("error in serde::deserialize"));
+        assert!(!matcher.is_match("undeserializable error"));
+        assert!(!matcher.is_match("my_deser error"));
+        assert_eq!(
+            matcher.find_ranges("undeser, deser error"),
+            [(9, 14), (15, 20)]
+        );
+    }
+
+    #[test]
+    fn test_relevance_favors_dense_matches() {
+        let message = |text: &str| ClaudeMessage {
+            message_type: "user".to_string(),
+            content: Some(json!(text)),
+            ..ClaudeMessage::default()
+        };
+        let matcher = TextMatcher::literal_terms(&["tokio"], false);
+        let once = matcher.relevance(&message("tokio once"), SearchFields::ALL);
+        let thrice = matcher.relevance(&message("tokio tokio tokio"), SearchFields::ALL);
+        let padded = matcher.relevance(
+            &message(&format!("tokio once{}", " padding".repeat(200))),
+            SearchFields::ALL,
+        );
+        assert!(thrice > once);
+        assert!(once > padded);
+        assert!(padded > 0.0);
+    }
+
+    #[test]
+    fn test_regex_complexity_caps() {
+        let regex = MatchOptions {
+            mode: MatchMode::Regex,
+            case_sensitive: false,
+        };
+        assert!(TextMatcher::new("(", regex).is_err());
+        assert!(TextMatcher::new(&"a".repeat(MAX_REGEX_PATTERN_LEN + 1), regex).is_err());
+        assert!(TextMatcher::new("((a{100}){100}){10}", regex).is_err());
+        assert!(
+            TextMatcher::new(&format!("{}a{}", "(".repeat(40), ")".repeat(40)), regex).is_err()
+        );
+    }
+}
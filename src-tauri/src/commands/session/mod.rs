@@ -2,7 +2,10 @@
 //!
 //! This module contains all session-related Tauri commands organized into submodules:
 //! - `load`: Session and message loading functions
-//! - `search`: Message search functions
+//! - `matcher`: Literal/regex text matching and match offsets for search
+//! - `query`: Structured search query syntax
+//! - `search`: Message search functions, match spans and snippets
+//! - `search_index`: Optional persistent full-text index for `search`
 //! - `edits`: File edit tracking and restore functions
 //! - `rename`: Native session renaming functions
 //! - `delete`: Session deletion
@@ -11,18 +14,24 @@ mod chain;
 mod delete;
 mod edits;
 mod load;
+mod matcher;
+mod query;
 mod rename;
 mod resume;
 mod search;
+mod search_index;
 
 // Re-export all commands
 pub use chain::{resolve_session_chain, superseded_chain_paths};
 pub use delete::*;
 pub use edits::*;
 pub use load::*;
+pub use matcher::{MatchMode, MatchOptions, SearchField, SearchFields, TextMatcher};
+pub use query::{QueryParseError, QueryTerm, SearchQuery};
 pub use rename::*;
 pub use resume::*;
 pub use search::*;
+pub use search_index::refresh_search_index;
 
 /// Reject session file paths that fall outside the on-disk roots used by
 /// the supported providers. Defends `WebUI` handlers (which accept untrusted
@@ -0,0 +1,369 @@
+//! Structured search query syntax shared by `search_messages` and
+//! `search_all_providers`.
+//!
+//! A query mixes free text with `field:value` filters:
+//!
+//! ```text
+//! tool:Bash model:opus after:2026-09-01 project:api -is:sidechain "exact phrase"
+//! ```
+//!
+//! - Free text is bare words and `"quoted phrases"`. Every term must occur
+//!   in the message, in any order; a phrase matches as one substring. A
+//!   trailing `*` (`deser*`) makes a word a prefix that must start a word;
+//!   other words also match inside words.
+//!   A query without free text matches every message its filters accept.
+//! - `field:value` filters compile into the same predicates as the JSON
+//!   `filters` object (`has:tools` is `hasToolCalls`, `is:error` is
+//!   `hasErrors`, `has:edits` is `hasFileChanges`, `after:`/`before:` bound
+//!   the message timestamp like `dateRange`; a bare date is local midnight,
+//!   as for the CLI's `--since`). Values may be quoted
+//!   (`project:"my api"`), and a leading `-` negates a filter.
+//! - `name:value` words whose name is not a known field stay free text, so
+//!   URLs and `error: …` snippets search as typed.
+//!
+//! Parse errors carry the 0-based character position of the offending
+//! token.
+
+use super::search::{has_errors, has_file_changes, has_tool_calls};
+use crate::export::parse_date_bound;
+use crate::models::ClaudeMessage;
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// A parsed search query: free-text terms plus filter predicates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    terms: Vec<QueryTerm>,
+    filters: Vec<QueryFilter>,
+}
+
+/// One free-text term, in query order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryTerm {
+    /// A bare word, possibly ending in `*` (prefix).
+    Word(String),
+    /// A `"quoted phrase"`, without the quotes.
+    Phrase(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct QueryFilter {
+    negated: bool,
+    predicate: Predicate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    /// Uses a tool with this name (case-insensitive).
+    Tool(String),
+    /// Model name contains this text (lowercased).
+    Model(String),
+    /// Project name contains this text (lowercased).
+    Project(String),
+    /// Provider id equals this value (lowercased; Claude when unset).
+    Provider(String),
+    /// `message_type` equals this value.
+    Type(String),
+    /// Timestamp at or after this instant.
+    After(DateTime<Utc>),
+    /// Timestamp strictly before this instant.
+    Before(DateTime<Utc>),
+    Sidechain,
+    Error,
+    ToolCalls,
+    FileChanges,
+}
+
+/// A query syntax error at a 0-based character position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid search query at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+const FIELDS: &[&str] = &[
+    "tool", "model", "project", "provider", "type", "after", "before", "is", "has",
+];
+
+impl SearchQuery {
+    /// Parse `input` into terms and filters.
+    pub fn parse(input: &str) -> Result<Self, QueryParseError> {
+        let error = |byte: usize, message: String| QueryParseError {
+            position: input[..byte].chars().count(),
+            message,
+        };
+        let mut query = Self::default();
+        let mut pos = 0;
+
+        while let Some(offset) = input[pos..].find(|c: char| !c.is_whitespace()) {
+            let start = pos + offset;
+            if input[start..].starts_with('"') {
+                let (phrase, next) = read_quoted(input, start)
+                    .ok_or_else(|| error(start, "unterminated quote".to_string()))?;
+                if !phrase.trim().is_empty() {
+                    query.terms.push(QueryTerm::Phrase(phrase.to_string()));
+                }
+                pos = next;
+                continue;
+            }
+
+            let end = input[start..]
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .map_or(input.len(), |offset| start + offset);
+            let word = &input[start..end];
+            pos = end;
+
+            let (negated, body) = match word.strip_prefix('-') {
+                Some(body) => (true, body),
+                None => (false, word),
+            };
+            let field = body
+                .split_once(':')
+                .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                .filter(|(name, _)| FIELDS.contains(&name.as_str()));
+            let Some((name, value)) = field else {
+                query.terms.push(QueryTerm::Word(word.to_string()));
+                continue;
+            };
+
+            let value_start = end - value.len();
+            let value = if value.is_empty() && input[end..].starts_with('"') {
+                let (quoted, next) = read_quoted(input, end)
+                    .ok_or_else(|| error(end, "unterminated quote".to_string()))?;
+                pos = next;
+                quoted
+            } else {
+                value
+            };
+            if value.trim().is_empty() {
+                return Err(error(start, format!("missing value for `{name}:`")));
+            }
+            let predicate =
+                build_predicate(&name, value).map_err(|message| error(value_start, message))?;
+            query.filters.push(QueryFilter { negated, predicate });
+        }
+
+        Ok(query)
+    }
+
+    /// Free-text terms in query order.
+    pub fn terms(&self) -> &[QueryTerm] {
+        &self.terms
+    }
+
+    /// Free text for substring matching, one entry per term: phrases
+    /// unquoted and prefix markers dropped. A message matches when it
+    /// contains every entry.
+    pub fn text_terms(&self) -> Vec<String> {
+        self.terms
+            .iter()
+            .map(|term| match term {
+                QueryTerm::Word(word) => word.strip_suffix('*').unwrap_or(word),
+                QueryTerm::Phrase(phrase) => phrase.as_str(),
+            })
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Whether `message` satisfies every filter of the query.
+    pub fn matches(&self, message: &ClaudeMessage) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.predicate.matches(message) != filter.negated)
+    }
+}
+
+/// `(contents, byte after the closing quote)` of the quoted string opening
+/// at `start`, or `None` when it is never closed.
+fn read_quoted(input: &str, start: usize) -> Option<(&str, usize)> {
+    let body_start = start + 1;
+    let close = input[body_start..].find('"')? + body_start;
+    Some((&input[body_start..close], close + 1))
+}
+
+fn build_predicate(name: &str, value: &str) -> Result<Predicate, String> {
+    let lower = value.to_lowercase();
+    Ok(match name {
+        "tool" => Predicate::Tool(lower),
+        "model" => Predicate::Model(lower),
+        "project" => Predicate::Project(lower),
+        "provider" => Predicate::Provider(lower),
+        "type" => match lower.as_str() {
+            "user" | "assistant" => Predicate::Type(lower),
+            _ => {
+                return Err(format!(
+                    "unknown message type `{value}` (expected user or assistant)"
+                ))
+            }
+        },
+        "after" => Predicate::After(parse_date_bound(value, false)?),
+        "before" => Predicate::Before(parse_date_bound(value, false)?),
+        "is" => match lower.as_str() {
+            "sidechain" => Predicate::Sidechain,
+            "error" => Predicate::Error,
+            _ => {
+                return Err(format!(
+                    "unknown `is:` value `{value}` (expected sidechain or error)"
+                ))
+            }
+        },
+        "has" => match lower.as_str() {
+            "tools" => Predicate::ToolCalls,
+            "edits" => Predicate::FileChanges,
+            "errors" => Predicate::Error,
+            _ => {
+                return Err(format!(
+                    "unknown `has:` value `{value}` (expected tools, edits or errors)"
+                ))
+            }
+        },
+        _ => unreachable!("field names are checked against FIELDS"),
+    })
+}
+
+impl Predicate {
+    fn matches(&self, message: &ClaudeMessage) -> bool {
+        match self {
+            Self::Tool(name) => uses_tool(message, name),
+            Self::Model(needle) => message
+                .model
+                .as_deref()
+                .is_some_and(|model| model.to_lowercase().contains(needle.as_str())),
+            Self::Project(needle) => message
+                .project_name
+                .as_deref()
+                .is_some_and(|project| project.to_lowercase().contains(needle.as_str())),
+            Self::Provider(id) => message.provider.as_deref().unwrap_or("claude") == id,
+            Self::Type(message_type) => message.message_type == *message_type,
+            Self::After(bound) => message_time(message).is_some_and(|ts| ts >= *bound),
+            Self::Before(bound) => message_time(message).is_some_and(|ts| ts < *bound),
+            Self::Sidechain => message.is_sidechain.unwrap_or(false),
+            Self::Error => has_errors(message),
+            Self::ToolCalls => has_tool_calls(message),
+            Self::FileChanges => has_file_changes(message),
+        }
+    }
+}
+
+fn message_time(message: &ClaudeMessage) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&message.timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Whether `message` calls a tool named `name` (lowercased), either as a
+/// `tool_use` content block or through the top-level `toolUse` payload.
+fn uses_tool(message: &ClaudeMessage, name: &str) -> bool {
+    let is_named = |value: &serde_json::Value| {
+        value
+            .get("name")
+            .and_then(serde_json::Value::as_str)
+            .is_some_and(|tool| tool.eq_ignore_ascii_case(name))
+    };
+    message.tool_use.as_ref().is_some_and(is_named)
+        || message
+            .content
+            .as_ref()
+            .and_then(serde_json::Value::as_array)
+            .is_some_and(|blocks| {
+                blocks.iter().any(|block| {
+                    block.get("type").and_then(serde_json::Value::as_str) == Some("tool_use")
+                        && is_named(block)
+                })
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(value: serde_json::Value) -> ClaudeMessage {
+        let mut base = json!({
+            "uuid": "u1",
+            "sessionId": "s1",
+            "timestamp": "2026-09-02T10:00:00Z",
+            "type": "assistant",
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn test_parse_splits_text_and_filters() {
+        let query = SearchQuery::parse(
+            r#"tool:Bash model:opus after:2026-09-01 project:"my api" -is:sidechain "exact phrase" deser*"#,
+        )
+        .unwrap();
+        assert_eq!(
+            query.terms(),
+            [
+                QueryTerm::Phrase("exact phrase".to_string()),
+                QueryTerm::Word("deser*".to_string()),
+            ]
+        );
+        assert_eq!(query.text_terms(), ["exact phrase", "deser"]);
+        assert_eq!(query.filters.len(), 5);
+        assert!(query.filters[4].negated);
+    }
+
+    #[test]
+    fn test_parse_keeps_unknown_fields_and_dashes_as_text() {
+        let query = SearchQuery::parse("https://example.com error: -rf").unwrap();
+        assert_eq!(query.text_terms(), ["https://example.com", "error:", "-rf"]);
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors_report_positions() {
+        let err = SearchQuery::parse("rust after:2026-13-01").unwrap_err();
+        assert_eq!(err.position, 11);
+        assert!(err.message.contains("Invalid date"));
+
+        let err = SearchQuery::parse(r#"fix "unterminated"#).unwrap_err();
+        assert_eq!(err.position, 4);
+
+        let err = SearchQuery::parse("ünï is:bogus").unwrap_err();
+        assert_eq!(err.position, 7, "positions count characters, not bytes");
+
+        let err = SearchQuery::parse("tool:").unwrap_err();
+        assert_eq!(err.position, 0);
+        assert!(err.to_string().contains("position 0"));
+    }
+
+    #[test]
+    fn test_filters_match_messages() {
+        let bash = message(json!({
+            "model": "claude-opus-4-20250514",
+            "projectName": "api-server",
+            "content": [{"type": "tool_use", "name": "Bash", "input": {}}],
+        }));
+        let accepts = |q: &str| SearchQuery::parse(q).unwrap().matches(&bash);
+
+        assert!(accepts("tool:bash model:OPUS project:api has:tools"));
+        assert!(!accepts("tool:Read"));
+        assert!(!accepts("has:edits"));
+        assert!(accepts("-is:sidechain -has:edits"));
+        // Bare dates are local midnight; stay a day clear of any time zone.
+        assert!(accepts("after:2026-09-01 before:2026-09-04"));
+        assert!(!accepts("after:2026-09-04"));
+        assert!(!accepts("before:2026-09-02T10:00:00Z"));
+        assert!(accepts("type:assistant provider:claude"));
+        assert!(!accepts("type:user"));
+    }
+}
@@ -209,9 +209,10 @@ fn write_jsonl_lines(file_path: &str, lines: &[String]) -> Result<(), String> {
         .map_err(|e| RenameError::IoError(e.to_string()).to_string())?;
 
     // This rewrite can land with the SAME byte size within mtime resolution,
-    // which the search cache's (size, mtime) signature cannot distinguish —
-    // evict the file explicitly so the next search re-scans it.
+    // which the search and stats caches' (size, mtime) signatures cannot
+    // distinguish — evict the file explicitly so the next call re-scans it.
     super::evict_file_from_search_cache(std::path::Path::new(file_path));
+    crate::commands::stats::evict_file_from_stats_cache(std::path::Path::new(file_path));
 
     Ok(())
 }
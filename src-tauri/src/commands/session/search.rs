@@ -1,8 +1,9 @@
 //! Session search functions
 
-use crate::models::{ClaudeMessage, RawLogEntry};
-use crate::utils::find_line_ranges;
-use aho_corasick::AhoCorasick;
+use super::matcher::{search_units, MatchMode, MatchOptions, SearchFields, TextMatcher};
+use super::query::{QueryTerm, SearchQuery};
+use crate::models::{ClaudeMessage, RawLogEntry, SearchMatch};
+use crate::utils::{find_line_ranges, CacheCounters};
 use chrono::{DateTime, Utc};
 use lru::LruCache;
 use memmap2::Mmap;
@@ -24,7 +25,7 @@ const PARSE_BUFFER_INITIAL_CAPACITY: usize = 4096;
 /// Initial capacity for search results (most searches find few matches)
 const SEARCH_RESULTS_INITIAL_CAPACITY: usize = 8;
 
-/// LRU cache capacity — distinct (`claude_path`, query) pairs.
+/// LRU cache capacity — distinct (`claude_path`, query, match options) keys.
 const SEARCH_CACHE_CAPACITY: usize = 64;
 
 /// Upper bound on cached raw matches per query entry; a query matching huge
@@ -33,11 +34,20 @@ const SEARCH_CACHE_CAPACITY: usize = 64;
 const MAX_CACHED_MATCHES_PER_QUERY: usize = 10_000;
 
 lazy_static::lazy_static! {
-    static ref ERROR_MATCHER: AhoCorasick = build_matcher("error");
+    static ref ERROR_MATCHER: TextMatcher = TextMatcher::literal("error", false);
     static ref SEARCH_CACHE: Mutex<LruCache<u64, CachedQuerySearch>> =
         Mutex::new(LruCache::new(NonZeroUsize::new(SEARCH_CACHE_CAPACITY).expect("non-zero")));
 }
 
+/// Per-file lookups in [`SEARCH_CACHE`]: a hit reuses a file's cached
+/// matches, a miss re-scans the file.
+static SEARCH_CACHE_COUNTERS: CacheCounters = CacheCounters::new();
+
+/// `(hits, misses)` of the search cache since process start.
+pub fn search_cache_counters() -> (u64, u64) {
+    SEARCH_CACHE_COUNTERS.snapshot()
+}
+
 /// (size, mtime) identity of a session file, captured BEFORE scanning so a
 /// concurrent append can never be cached under a newer signature (worst
 /// case: an unnecessary re-scan on the next call). Same validation scheme as
@@ -66,9 +76,9 @@ struct FileMatches {
     matches: Arc<Vec<ClaudeMessage>>,
 }
 
-/// Per-file match sets for one (`claude_path`, query) pair. Filters and limit
-/// are applied at serve time, so one entry answers every filter/limit
-/// combination for its query.
+/// Per-file match sets for one (`claude_path`, query, match options) key.
+/// Filters and limit are applied at serve time, so one entry answers every
+/// filter/limit combination for its query.
 struct CachedQuerySearch {
     files: HashMap<PathBuf, FileMatches>,
 }
@@ -85,8 +95,10 @@ type FileSlot = (Option<FileSignature>, Option<Arc<Vec<ClaudeMessage>>>);
 /// (append-only growth always changes the size, so the watcher path needs no
 /// such hook). Hashing content instead would cost a full read per file per
 /// search — defeating the cache — so targeted eviction at the rewrite site is
-/// the cheap, precise fix.
+/// the cheap, precise fix. The file is dropped from the persistent search
+/// index as well, for the same reason.
 pub fn evict_file_from_search_cache(path: &Path) {
+    super::search_index::forget_file(path);
     let Ok(mut cache) = SEARCH_CACHE.lock() else {
         return;
     };
@@ -99,37 +111,58 @@ pub fn evict_file_from_search_cache(path: &Path) {
     }
 }
 
-fn cache_key(claude_path: &str, query: &str) -> u64 {
+fn cache_key(
+    claude_path: &str,
+    patterns: &[String],
+    terms: &[QueryTerm],
+    options: MatchOptions,
+) -> u64 {
     let mut hasher = DefaultHasher::new();
     claude_path.hash(&mut hasher);
-    query.to_lowercase().hash(&mut hasher);
+    // `deser` and `deser*` share a pattern but not their matches.
+    for term in terms {
+        matches!(term, QueryTerm::Word(word) if word.ends_with('*')).hash(&mut hasher);
+    }
+    // Only the default literal match is case-folded; a case-sensitive or
+    // regex pattern is its own key.
+    for pattern in patterns {
+        if options == MatchOptions::default() {
+            pattern.to_lowercase().hash(&mut hasher);
+        } else {
+            pattern.hash(&mut hasher);
+        }
+    }
+    options.hash(&mut hasher);
     hasher.finish()
 }
 
-/// Recursively search for a query within a `serde_json::Value` using aho-corasick.
-/// Case-insensitive matching without per-string heap allocation from `.to_lowercase()`.
-#[inline]
-fn search_in_value(value: &serde_json::Value, matcher: &AhoCorasick) -> bool {
-    match value {
-        serde_json::Value::String(s) => matcher.is_match(s),
-        serde_json::Value::Array(arr) => arr.iter().any(|item| search_in_value(item, matcher)),
-        serde_json::Value::Object(obj) => obj.values().any(|val| search_in_value(val, matcher)),
-        _ => false,
+/// Split a search `query` into its filters, its patterns and their matcher.
+/// A literal query's patterns are its free-text terms, which must all match;
+/// a regex query is a single pattern whose field syntax is not parsed, since
+/// it would collide with regex syntax. Shared by `search_messages` and
+/// `search_all_providers` so both read a query the same way.
+pub(crate) fn parse_search_query(
+    query: &str,
+    options: MatchOptions,
+) -> Result<(SearchQuery, Vec<String>, TextMatcher), String> {
+    match options.mode {
+        MatchMode::Regex => Ok((
+            SearchQuery::default(),
+            vec![query.to_string()],
+            TextMatcher::new(query, options)?,
+        )),
+        MatchMode::Literal => {
+            let parsed = SearchQuery::parse(query).map_err(|e| e.to_string())?;
+            let terms = parsed.text_terms();
+            let matcher = TextMatcher::query_terms(parsed.terms(), options.case_sensitive);
+            Ok((parsed, terms, matcher))
+        }
     }
 }
 
-/// Build an aho-corasick matcher for case-insensitive single-pattern search.
-/// Uses ASCII case-insensitive mode (sufficient for most search queries).
-fn build_matcher(query: &str) -> AhoCorasick {
-    AhoCorasick::builder()
-        .ascii_case_insensitive(true)
-        .build([query])
-        .expect("single-pattern AhoCorasick build should never fail")
-}
-
 /// Extract project name from file path
 /// Path format: ~/.claude/projects/[project-name]/[session-file].jsonl
-fn extract_project_name(file_path: &PathBuf) -> Option<String> {
+pub(super) fn extract_project_name(file_path: &PathBuf) -> Option<String> {
     file_path
         .parent()
         .and_then(|p| p.file_name())
@@ -140,9 +173,9 @@ fn extract_project_name(file_path: &PathBuf) -> Option<String> {
 /// Search for messages matching the query in a single file
 ///
 /// Uses a reusable buffer to avoid repeated heap allocations during JSON parsing.
-/// Accepts a pre-built `AhoCorasick` matcher to avoid rebuilding per file.
+/// Accepts a pre-built matcher to avoid recompiling the pattern per file.
 #[allow(unsafe_code)] // Required for mmap performance optimization
-fn search_in_file(file_path: &PathBuf, matcher: &AhoCorasick) -> Vec<ClaudeMessage> {
+fn search_in_file(file_path: &PathBuf, matcher: &TextMatcher) -> Vec<ClaudeMessage> {
     let project_name = extract_project_name(file_path);
 
     let file = match fs::File::open(file_path) {
@@ -185,85 +218,267 @@ fn search_in_file(file_path: &PathBuf, matcher: &AhoCorasick) -> Vec<ClaudeMessa
             None => continue,
         };
 
-        // Use aho-corasick for case-insensitive matching without heap allocation
-        let content_matches = match &message_content.content {
-            serde_json::Value::String(s) => matcher.is_match(s),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                search_in_value(&message_content.content, matcher)
-            }
-            _ => false,
-        };
-
         // Also search the tool-call payloads. The in-session FlexSearch index
         // covers `toolUseResult` (file contents, command stdout/stderr) and tool
         // names, so global search must too — otherwise a query that only appears
         // in a tool result is found inside a conversation but missed by the
-        // global search (issue #394). `search_in_value` already recurses every
+        // global search (issue #394). `matches_values` already recurses every
         // nested string (e.g. `toolUseResult.file.content`, `.stdout`).
-        let matches = content_matches
-            || log_entry
-                .tool_use_result
-                .as_ref()
-                .is_some_and(|v| search_in_value(v, matcher))
-            || log_entry
-                .tool_use
-                .as_ref()
-                .is_some_and(|v| search_in_value(v, matcher));
+        let matches = matcher.matches_values(
+            std::iter::once(&message_content.content)
+                .chain(log_entry.tool_use_result.as_ref())
+                .chain(log_entry.tool_use.as_ref()),
+        );
 
         if !matches {
             continue;
         }
 
-        let claude_message = ClaudeMessage {
-            uuid: log_entry
-                .uuid
-                .unwrap_or_else(|| format!("{}-line-{}", Uuid::new_v4(), line_num + 1)),
-            parent_uuid: log_entry.parent_uuid,
-            session_id: log_entry
-                .session_id
-                .unwrap_or_else(|| "unknown-session".to_string()),
-            timestamp: log_entry
-                .timestamp
-                .unwrap_or_else(|| Utc::now().to_rfc3339()),
-            message_type: log_entry.message_type,
-            content: Some(message_content.content.clone()),
-            project_name: project_name.clone(),
-            tool_use: log_entry.tool_use,
-            tool_use_result: log_entry.tool_use_result,
-            is_sidechain: log_entry.is_sidechain,
-            usage: message_content.usage.clone(),
-            role: Some(message_content.role.clone()),
-            model: message_content.model.clone(),
-            stop_reason: message_content.stop_reason.clone(),
-            cost_usd: log_entry.cost_usd,
-            duration_ms: log_entry.duration_ms,
-            message_id: message_content.id.clone(),
-            snapshot: None,
-            is_snapshot_update: None,
-            data: None,
-            tool_use_id: None,
-            parent_tool_use_id: None,
-            operation: None,
-            subtype: None,
-            level: None,
-            hook_count: None,
-            hook_infos: None,
-            stop_reason_system: None,
-            prevented_continuation: None,
-            compact_metadata: None,
-            microcompact_metadata: None,
-            provider: None,
-        };
-        results.push(claude_message);
+        if let Some(message) = search_hit_message(log_entry, line_num, project_name.clone()) {
+            results.push(message);
+        }
     }
 
     results
 }
 
+/// Build the search-hit `ClaudeMessage` for one user/assistant log line.
+/// Shared by the file scan and the persistent index so both paths return
+/// identical messages. `None` when the line carries no message body.
+pub(super) fn search_hit_message(
+    log_entry: RawLogEntry,
+    line_num: usize,
+    project_name: Option<String>,
+) -> Option<ClaudeMessage> {
+    let message_content = log_entry.message?;
+    Some(ClaudeMessage {
+        uuid: log_entry
+            .uuid
+            .unwrap_or_else(|| format!("{}-line-{}", Uuid::new_v4(), line_num + 1)),
+        parent_uuid: log_entry.parent_uuid,
+        session_id: log_entry
+            .session_id
+            .unwrap_or_else(|| "unknown-session".to_string()),
+        timestamp: log_entry
+            .timestamp
+            .unwrap_or_else(|| Utc::now().to_rfc3339()),
+        message_type: log_entry.message_type,
+        content: Some(message_content.content),
+        project_name,
+        tool_use: log_entry.tool_use,
+        tool_use_result: log_entry.tool_use_result,
+        is_sidechain: log_entry.is_sidechain,
+        usage: message_content.usage,
+        role: Some(message_content.role),
+        model: message_content.model,
+        stop_reason: message_content.stop_reason,
+        cost_usd: log_entry.cost_usd,
+        duration_ms: log_entry.duration_ms,
+        message_id: message_content.id,
+        snapshot: None,
+        is_snapshot_update: None,
+        data: None,
+        tool_use_id: None,
+        parent_tool_use_id: None,
+        operation: None,
+        subtype: None,
+        level: None,
+        hook_count: None,
+        hook_infos: None,
+        stop_reason_system: None,
+        prevented_continuation: None,
+        compact_metadata: None,
+        microcompact_metadata: None,
+        provider: None,
+        ..Default::default()
+    })
+}
+
 /// Default limit for search results
 const DEFAULT_SEARCH_LIMIT: usize = 100;
 
-fn has_tool_calls(message: &ClaudeMessage) -> bool {
+/// Characters of context on each side of a match in its snippet, unless the
+/// `snippetContext` filter asks for another amount (up to the maximum).
+const DEFAULT_SNIPPET_CONTEXT: usize = 40;
+const MAX_SNIPPET_CONTEXT: usize = 500;
+
+/// Upper bound on spans reported per message; a pattern hitting every
+/// character of a large tool result must not bloat the response.
+const MAX_MATCHES_PER_MESSAGE: usize = 100;
+
+/// Read the `snippetContext` filter: characters of context around each match.
+pub(crate) fn snippet_context(filters: &serde_json::Value) -> Result<usize, String> {
+    match filters.get("snippetContext") {
+        None | Some(serde_json::Value::Null) => Ok(DEFAULT_SNIPPET_CONTEXT),
+        Some(value) => value
+            .as_u64()
+            .filter(|&chars| chars <= MAX_SNIPPET_CONTEXT as u64)
+            .map(|chars| chars as usize)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid snippetContext filter: {value} (expected 0..={MAX_SNIPPET_CONTEXT})"
+                )
+            }),
+    }
+}
+
+/// Order of search results, from the `sort` filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSort {
+    /// Newest message first.
+    #[default]
+    Newest,
+    /// Best match first (BM25), newest first among equals.
+    Relevance,
+}
+
+impl SearchSort {
+    /// Read the `sort` filter: `"newest"` (default) or `"relevance"`.
+    pub fn from_filters(filters: &serde_json::Value) -> Result<Self, String> {
+        match filters.get("sort") {
+            None | Some(serde_json::Value::Null) => Ok(Self::Newest),
+            Some(value) => match value.as_str() {
+                Some("newest") => Ok(Self::Newest),
+                Some("relevance") => Ok(Self::Relevance),
+                _ => Err(format!(
+                    "Invalid sort filter: {value} (expected \"newest\" or \"relevance\")"
+                )),
+            },
+        }
+    }
+}
+
+/// Sort `messages` best match first, newest first among equal scores.
+pub(crate) fn sort_by_relevance(
+    messages: &mut Vec<ClaudeMessage>,
+    matcher: &TextMatcher,
+    fields: SearchFields,
+) {
+    let mut scored: Vec<(f64, ClaudeMessage)> = messages
+        .drain(..)
+        .map(|message| (matcher.relevance(&message, fields), message))
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .total_cmp(a_score)
+            .then_with(|| b.timestamp.cmp(&a.timestamp))
+    });
+    messages.extend(scored.into_iter().map(|(_, message)| message));
+}
+
+/// Attach match spans and context snippets to search results, so the UI can
+/// show and highlight hits without searching the messages again.
+///
+/// Works on any provider's messages: it re-runs `matcher` over the parts of
+/// each message within `fields` (`content`, `toolUseResult`, `toolUse`).
+pub fn annotate_search_hits(
+    messages: &mut [ClaudeMessage],
+    matcher: &TextMatcher,
+    fields: SearchFields,
+    context: usize,
+) {
+    for message in messages {
+        let mut matches = Vec::new();
+        for mut unit in search_units(message, fields) {
+            let span = SpanSource {
+                field: unit.field,
+                block_index: unit.block_index,
+                matcher,
+                context,
+            };
+            collect_matches(unit.value, &mut unit.path, &span, &mut matches);
+        }
+        message.search_matches = Some(matches);
+    }
+}
+
+/// Per-unit constants for [`collect_matches`].
+struct SpanSource<'a> {
+    field: &'static str,
+    block_index: Option<usize>,
+    matcher: &'a TextMatcher,
+    context: usize,
+}
+
+fn collect_matches(
+    value: &serde_json::Value,
+    path: &mut String,
+    span: &SpanSource<'_>,
+    out: &mut Vec<SearchMatch>,
+) {
+    let SpanSource {
+        field,
+        block_index,
+        matcher,
+        context,
+    } = *span;
+    if out.len() >= MAX_MATCHES_PER_MESSAGE {
+        return;
+    }
+    match value {
+        serde_json::Value::String(s) => {
+            // Byte offsets -> UTF-16 offsets, counted incrementally since
+            // the ranges are ordered.
+            let (mut byte_pos, mut utf16_pos) = (0, 0);
+            for (start, end) in matcher.find_ranges(s) {
+                if out.len() >= MAX_MATCHES_PER_MESSAGE {
+                    return;
+                }
+                let start_utf16 = utf16_pos + utf16_len(&s[byte_pos..start]);
+                let end_utf16 = start_utf16 + utf16_len(&s[start..end]);
+                (byte_pos, utf16_pos) = (end, end_utf16);
+
+                let snippet_start = if context == 0 {
+                    start
+                } else {
+                    s[..start]
+                        .char_indices()
+                        .rev()
+                        .nth(context - 1)
+                        .map_or(0, |(idx, _)| idx)
+                };
+                let snippet_end = s[end..]
+                    .char_indices()
+                    .nth(context)
+                    .map_or(s.len(), |(idx, _)| end + idx);
+                out.push(SearchMatch {
+                    field: field.to_string(),
+                    path: path.clone(),
+                    block_index,
+                    start: start_utf16,
+                    end: end_utf16,
+                    snippet: s[snippet_start..snippet_end].to_string(),
+                    snippet_offset: utf16_len(&s[snippet_start..start]),
+                });
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for (idx, item) in arr.iter().enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&idx.to_string());
+                collect_matches(item, path, span, out);
+                path.truncate(len);
+            }
+        }
+        serde_json::Value::Object(obj) => {
+            for (key, item) in obj {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                collect_matches(item, path, span, out);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+pub(super) fn has_tool_calls(message: &ClaudeMessage) -> bool {
     message.tool_use.is_some()
         || message.tool_use_result.is_some()
         || message
@@ -280,7 +495,7 @@ fn has_tool_calls(message: &ClaudeMessage) -> bool {
             .unwrap_or(false)
 }
 
-fn has_errors(message: &ClaudeMessage) -> bool {
+pub(super) fn has_errors(message: &ClaudeMessage) -> bool {
     message.message_type == "error"
         || message.level.as_deref() == Some("error")
         || message
@@ -291,11 +506,11 @@ fn has_errors(message: &ClaudeMessage) -> bool {
         || message
             .content
             .as_ref()
-            .map(|v| search_in_value(v, &ERROR_MATCHER))
+            .map(|v| ERROR_MATCHER.matches_value(v))
             .unwrap_or(false)
 }
 
-fn has_file_changes(message: &ClaudeMessage) -> bool {
+pub(super) fn has_file_changes(message: &ClaudeMessage) -> bool {
     let Some(content) = message
         .content
         .as_ref()
@@ -370,7 +585,7 @@ pub(crate) fn validate_search_filters(filters: &serde_json::Value) -> Result<(),
     Ok(())
 }
 
-fn matches_filters(message: &ClaudeMessage, filters: &serde_json::Value) -> bool {
+pub(super) fn matches_filters(message: &ClaudeMessage, filters: &serde_json::Value) -> bool {
     let Some(obj) = filters.as_object() else {
         return true;
     };
@@ -466,12 +681,33 @@ pub async fn search_messages(
 
     let max_results = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
     validate_search_filters(&filters)?;
+    let options = MatchOptions::from_filters(&filters)?;
+    let (search_query, patterns, matcher) = parse_search_query(&query, options)?;
+    let fields = SearchFields::from_filters(&filters)?;
+    let context = snippet_context(&filters)?;
+    let sort = SearchSort::from_filters(&filters)?;
 
     let projects_path = PathBuf::from(&claude_path).join("projects");
     if !projects_path.exists() {
         return Ok(vec![]);
     }
 
+    // The index only answers the default case-insensitive literal match over
+    // every field.
+    if options == MatchOptions::default() && fields == SearchFields::ALL {
+        if let Some(mut results) = super::search_index::search_indexed(
+            &claude_path,
+            &search_query,
+            &matcher,
+            &filters,
+            sort,
+            max_results,
+        ) {
+            annotate_search_hits(&mut results, &matcher, fields, context);
+            return Ok(results);
+        }
+    }
+
     let file_paths: Vec<PathBuf> = WalkDir::new(&projects_path)
         .into_iter()
         .filter_map(std::result::Result::ok)
@@ -479,7 +715,9 @@ pub async fn search_messages(
         .map(|e| e.path().to_path_buf())
         .collect();
 
-    let key = cache_key(&claude_path, &query);
+    // Cached matches depend on the free text only; query filters are
+    // applied at serve time like the JSON filters.
+    let key = cache_key(&claude_path, &patterns, search_query.terms(), options);
     let cached_files: HashMap<PathBuf, FileMatches> = SEARCH_CACHE
         .lock()
         .ok()
@@ -509,6 +747,8 @@ pub async fn search_messages(
         .filter(|(_, (_, cached))| cached.is_none())
         .map(|(idx, _)| idx)
         .collect();
+    SEARCH_CACHE_COUNTERS.record_hits((file_paths.len() - scan_indices.len()) as u64);
+    SEARCH_CACHE_COUNTERS.record_misses(scan_indices.len() as u64);
 
     #[cfg(debug_assertions)]
     eprintln!(
@@ -518,8 +758,6 @@ pub async fn search_messages(
         scan_indices.len()
     );
 
-    let matcher = build_matcher(&query);
-
     let scanned: Vec<(usize, Arc<Vec<ClaudeMessage>>)> = scan_indices
         .into_par_iter()
         .map(|idx| {
@@ -542,12 +780,26 @@ pub async fn search_messages(
 
     let total_matches = raw.len();
     let mut filtered = apply_search_filters(raw, &filters);
+    filtered.retain(|message| search_query.matches(message));
+    // The scan (and cache) covers every field; scope to the selected ones.
+    if fields != SearchFields::ALL {
+        filtered.retain(|message| matcher.matches_message(message, fields));
+    }
 
-    if filtered.len() > max_results {
-        filtered.select_nth_unstable_by(max_results, |a, b| b.timestamp.cmp(&a.timestamp));
-        filtered.truncate(max_results);
+    match sort {
+        SearchSort::Newest => {
+            if filtered.len() > max_results {
+                filtered.select_nth_unstable_by(max_results, |a, b| b.timestamp.cmp(&a.timestamp));
+                filtered.truncate(max_results);
+            }
+            filtered.sort_unstable_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        }
+        SearchSort::Relevance => {
+            sort_by_relevance(&mut filtered, &matcher, fields);
+            filtered.truncate(max_results);
+        }
     }
-    filtered.sort_unstable_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    annotate_search_hits(&mut filtered, &matcher, fields, context);
 
     // Store the rebuilt per-file map; files whose signature could not be
     // captured are computed without caching (re-scanned next call).
@@ -776,6 +1028,375 @@ mod tests {
         assert!(result.unwrap().is_empty());
     }
 
+    #[tokio::test]
+    async fn test_search_messages_applies_query_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("projects").join("test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+
+        let content = format!(
+            "{}\n{}\n",
+            create_sample_user_message("uuid-1", "session-1", "Hello Rust programming"),
+            create_sample_assistant_message("uuid-2", "session-1", "Rust is great!")
+        );
+        std::fs::write(project_dir.join("test.jsonl"), content).unwrap();
+        let claude_path = temp_dir.path().to_string_lossy().to_string();
+
+        let result = search_messages(
+            claude_path.clone(),
+            "rust type:assistant model:opus".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid, "uuid-2");
+
+        let result = search_messages(
+            claude_path.clone(),
+            "rust -type:assistant".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid, "uuid-1");
+
+        let result = search_messages(
+            claude_path.clone(),
+            "programming hello".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1, "terms match in any order");
+        assert_eq!(result[0].uuid, "uuid-1");
+
+        let result = search_messages(
+            claude_path.clone(),
+            "type:assistant".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1, "a filter-only query matches everything");
+        assert_eq!(result[0].uuid, "uuid-2");
+
+        let err = search_messages(
+            claude_path,
+            "rust after:yesterday".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("position"), "unexpected error: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_regex_and_case_sensitive_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("projects").join("test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+
+        let content = format!(
+            "{}\n{}\n",
+            create_sample_user_message("uuid-1", "session-1", "see parseConfig here"),
+            create_tool_result_message(
+                "uuid-2",
+                "session-1",
+                "ParseConfig failed",
+                "thread main panicked at src/config.rs:42"
+            )
+        );
+        std::fs::write(project_dir.join("test.jsonl"), content).unwrap();
+        let claude_path = temp_dir.path().to_string_lossy().to_string();
+
+        let result = search_messages(
+            claude_path.clone(),
+            r"panicked at .*src/.*\.rs".to_string(),
+            serde_json::json!({"matchMode": "regex"}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid, "uuid-2");
+        let matches = result[0].search_matches.as_deref().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].field, "toolUseResult");
+        assert_eq!(matches[0].path, "/stdout");
+        assert_eq!((matches[0].start, matches[0].end), (12, 37));
+        assert_eq!(matches[0].block_index, None);
+
+        let result = search_messages(
+            claude_path.clone(),
+            "parseConfig".to_string(),
+            serde_json::json!({"caseSensitive": true}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].uuid, "uuid-1");
+        let matches = result[0].search_matches.as_deref().unwrap();
+        assert_eq!((matches[0].start, matches[0].end), (4, 15));
+
+        // The same pattern case-insensitively is a different cache entry.
+        let result = search_messages(
+            claude_path.clone(),
+            "parseConfig".to_string(),
+            serde_json::json!({}),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 2);
+
+        let err = search_messages(
+            claude_path,
+            "(unclosed".to_string(),
+            serde_json::json!({"matchMode": "regex"}),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.contains("Invalid regex pattern"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_annotate_search_hits_reports_spans_and_snippets() {
+        let mut message = serde_json::from_value::<ClaudeMessage>(serde_json::json!({
+            "uuid": "u1",
+            "sessionId": "s1",
+            "timestamp": "2025-06-26T10:00:00Z",
+            "type": "assistant",
+            "content": [
+                {"type": "text", "text": "héllo 😀 hello and a long tail"},
+                {"type": "tool_use", "name": "Bash", "input": {"a/b": "say hello"}}
+            ],
+            "toolUseResult": {"stdout": "HELLO"},
+        }))
+        .unwrap();
+
+        annotate_search_hits(
+            std::slice::from_mut(&mut message),
+            &TextMatcher::literal("hello", false),
+            SearchFields::ALL,
+            3,
+        );
+        let found: Vec<_> = message
+            .search_matches
+            .unwrap()
+            .into_iter()
+            .map(|m| {
+                (
+                    m.field,
+                    m.path,
+                    m.block_index,
+                    m.start,
+                    m.end,
+                    m.snippet,
+                    m.snippet_offset,
+                )
+            })
+            .collect();
+
+        // "héllo" only matches ASCII-insensitively on the second word; the
+        // emoji takes two UTF-16 units and one char of context.
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "content".into(),
+                    "/0/text".into(),
+                    Some(0),
+                    9,
+                    14,
+                    " 😀 hello an".into(),
+                    4
+                ),
+                (
+                    "content".into(),
+                    "/1/input/a~1b".into(),
+                    Some(1),
+                    4,
+                    9,
+                    "ay hello".into(),
+                    3
+                ),
+                (
+                    "toolUseResult".into(),
+                    "/stdout".into(),
+                    None,
+                    0,
+                    5,
+                    "HELLO".into(),
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_scoped_to_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("projects").join("test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+
+        let content = [
+            create_sample_user_message("uuid-1", "session-1", "please run cargo test"),
+            r#"{"uuid":"uuid-2","sessionId":"session-1","timestamp":"2025-06-26T10:01:00Z","type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"cargo test should be enough"},{"type":"text","text":"Running it now."},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"cargo test --lib"}}]}}"#.to_string(),
+            create_tool_result_message("uuid-3", "session-1", "ok", "running cargo test: 12 passed"),
+        ]
+        .join("\n");
+        std::fs::write(project_dir.join("test.jsonl"), content).unwrap();
+        let claude_path = temp_dir.path().to_string_lossy().to_string();
+
+        let search = |fields: serde_json::Value| {
+            let claude_path = claude_path.clone();
+            async move {
+                let mut uuids: Vec<String> = search_messages(
+                    claude_path,
+                    "cargo test".to_string(),
+                    serde_json::json!({ "fields": fields }),
+                    None,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|m| m.uuid)
+                .collect();
+                uuids.sort();
+                uuids
+            }
+        };
+
+        assert_eq!(
+            search(serde_json::json!([])).await,
+            vec!["uuid-1", "uuid-2", "uuid-3"]
+        );
+        assert_eq!(
+            search(serde_json::json!(["userText"])).await,
+            vec!["uuid-1"]
+        );
+        assert_eq!(
+            search(serde_json::json!(["toolInput"])).await,
+            vec!["uuid-2"]
+        );
+        assert_eq!(
+            search(serde_json::json!(["thinking"])).await,
+            vec!["uuid-2"]
+        );
+        assert_eq!(
+            search(serde_json::json!(["toolResult"])).await,
+            vec!["uuid-3"]
+        );
+        assert!(search(serde_json::json!(["assistantText"]))
+            .await
+            .is_empty());
+
+        let result = search_messages(
+            claude_path.clone(),
+            "cargo test".to_string(),
+            serde_json::json!({ "fields": ["toolInput"] }),
+            None,
+        )
+        .await
+        .unwrap();
+        let matches = result[0].search_matches.as_deref().unwrap();
+        assert_eq!(
+            matches.len(),
+            2,
+            "one span per term, limited to the selected fields"
+        );
+        assert!(matches
+            .iter()
+            .all(|m| m.path == "/2/input/command" && m.block_index == Some(2)));
+
+        let err = search_messages(
+            claude_path,
+            "cargo".to_string(),
+            serde_json::json!({ "fields": ["bash"] }),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.contains("Invalid fields filter"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn test_snippet_context_filter() {
+        assert_eq!(
+            snippet_context(&serde_json::json!({})).unwrap(),
+            DEFAULT_SNIPPET_CONTEXT
+        );
+        assert_eq!(
+            snippet_context(&serde_json::json!({"snippetContext": 10})).unwrap(),
+            10
+        );
+        assert!(snippet_context(&serde_json::json!({"snippetContext": -1})).is_err());
+        assert!(snippet_context(&serde_json::json!({"snippetContext": 100_000})).is_err());
+    }
+
+    #[test]
+    fn test_search_sort_filter() {
+        assert_eq!(
+            SearchSort::from_filters(&serde_json::json!({})).unwrap(),
+            SearchSort::Newest
+        );
+        assert_eq!(
+            SearchSort::from_filters(&serde_json::json!({"sort": "relevance"})).unwrap(),
+            SearchSort::Relevance
+        );
+        assert!(SearchSort::from_filters(&serde_json::json!({"sort": "oldest"})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_ranks_by_relevance_and_matches_prefixes() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("projects").join("test-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let content = format!(
+            "{}\n{}\n",
+            create_sample_user_message("uuid-1", "session-1", "tokio tokio tokio"),
+            create_sample_assistant_message("uuid-2", "session-1", "tokio once"),
+        );
+        std::fs::write(project_dir.join("test.jsonl"), content).unwrap();
+
+        let search = |query: &str, filters: serde_json::Value| {
+            search_messages(
+                temp_dir.path().to_string_lossy().to_string(),
+                query.to_string(),
+                filters,
+                None,
+            )
+        };
+        let newest = search("tokio", serde_json::json!({})).await.unwrap();
+        assert_eq!(uuids(&newest), ["uuid-2", "uuid-1"]);
+        let ranked = search("tokio", serde_json::json!({"sort": "relevance"}))
+            .await
+            .unwrap();
+        assert_eq!(uuids(&ranked), ["uuid-1", "uuid-2"]);
+
+        let prefix = search("tok*", serde_json::json!({})).await.unwrap();
+        assert_eq!(prefix.len(), 2);
+        let inside = search("okio*", serde_json::json!({})).await.unwrap();
+        assert!(inside.is_empty(), "a prefix only matches at word starts");
+        let substring = search("okio", serde_json::json!({})).await.unwrap();
+        assert_eq!(substring.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_search_messages_empty_projects_dir() {
         let temp_dir = TempDir::new().unwrap();
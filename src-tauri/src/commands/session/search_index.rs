@@ -0,0 +1,872 @@
+//! Optional persistent full-text index for Claude session search.
+//!
+//! `search_messages` normally scans every session file for each query (see
+//! the sibling `search` module). When the user enables `searchIndex` in their
+//! settings, Claude searches are answered from an `SQLite` FTS5 index at
+//! `~/.claude-history-viewer/cache/search-index.db` instead.
+//!
+//! # Design
+//!
+//! - Every searchable line (a user/assistant row with a message body) is one
+//!   FTS row holding the strings of `message.content`, `toolUseResult` and
+//!   `toolUse` — the fields the scan matches. The FTS table is contentless:
+//!   a hit points back to `(file, byte range, line)` and is re-read from
+//!   disk, so indexed results are built exactly like scanned ones.
+//! - Files are validated by `(size, mtime)` before every query. When the
+//!   already-indexed prefix is unchanged (whole-prefix checksum) only the
+//!   appended lines are indexed; anything else re-indexes the whole file.
+//!   Files that left the projects tree are dropped.
+//! - The file watchers push changed session files through
+//!   [`refresh_search_index`], so the query-time sync usually finds nothing
+//!   left to do.
+//!
+//! ## Matching
+//!
+//! The index only narrows the candidate lines; results must be exactly what
+//! the scan returns. The FTS table uses the `trigram` tokenizer, so every
+//! term is a substring match like the scan's, and each candidate is checked
+//! against the scan's matcher before it is returned. That check also keeps a
+//! `deser*` prefix to word starts, which trigrams cannot tell apart.
+//! Candidates are read newest first, the scan's default order, or by FTS5's
+//! `bm25()` under `"sort": "relevance"`. Queries with a term shorter than
+//! three characters cannot be answered by trigrams and fall back to the scan.
+//!
+//! The index is a rebuildable cache. Any change to the tables bumps
+//! [`SCHEMA_VERSION`] (older databases are reset on open), and every failure
+//! falls back to the scan.
+
+use super::matcher::{SearchFields, TextMatcher};
+use super::query::{QueryTerm, SearchQuery};
+use super::search::{extract_project_name, matches_filters, search_hit_message, SearchSort};
+use crate::models::{ClaudeMessage, RawLogEntry, UserMetadata};
+use crate::utils::{find_line_ranges, fnv1a, fnv1a_extend, open_cache_db, FNV1A_OFFSET};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// Bump whenever the table layout or the indexed text changes; older
+/// databases are reset on open.
+const SCHEMA_VERSION: i64 = 2;
+
+const SCHEMA: &str = "
+    DROP TABLE IF EXISTS entries;
+    DROP TABLE IF EXISTS lines;
+    DROP TABLE IF EXISTS files;
+    CREATE TABLE files (
+        id            INTEGER PRIMARY KEY,
+        root          TEXT    NOT NULL,
+        path          TEXT    NOT NULL UNIQUE,
+        signature     TEXT    NOT NULL,
+        indexed_len   INTEGER NOT NULL,
+        indexed_lines INTEGER NOT NULL,
+        checksum      TEXT    NOT NULL
+    );
+    CREATE INDEX files_root ON files (root);
+    CREATE TABLE lines (
+        id        INTEGER PRIMARY KEY,
+        file_id   INTEGER NOT NULL,
+        start     INTEGER NOT NULL,
+        end       INTEGER NOT NULL,
+        line      INTEGER NOT NULL,
+        timestamp TEXT
+    );
+    CREATE INDEX lines_file ON lines (file_id);
+    CREATE VIRTUAL TABLE entries USING fts5(
+        body,
+        content = '',
+        contentless_delete = 1,
+        tokenize = 'trigram'
+    );";
+
+const STORE_FILE_NAME: &str = "search-index.db";
+
+/// Shortest term the trigram tokenizer can match.
+const MIN_TERM_CHARS: usize = 3;
+
+/// SQLite-backed FTS5 index over Claude session files.
+pub(super) struct SearchIndex {
+    conn: Mutex<Connection>,
+}
+
+/// Index state of one session file.
+struct IndexedFile {
+    id: i64,
+    signature: String,
+    indexed_len: usize,
+    indexed_lines: usize,
+    checksum: String,
+}
+
+/// One searchable line: its byte range and 0-based line number in the file.
+struct IndexLine {
+    start: usize,
+    end: usize,
+    line: usize,
+    timestamp: Option<String>,
+    body: String,
+}
+
+/// Lines parsed from one file, ready to be written.
+struct ParsedFile {
+    path: String,
+    signature: String,
+    /// Existing file row to extend, or `None` to (re)index from scratch.
+    extend: Option<i64>,
+    indexed_len: usize,
+    indexed_lines: usize,
+    checksum: String,
+    lines: Vec<IndexLine>,
+}
+
+/// Whether the user enabled the index (`settings.searchIndex`).
+/// `user-data.json` is only re-read when its `(size, mtime)` changed since
+/// the last call, so toggling takes effect without a restart while queries
+/// and watcher events cost a `stat`.
+fn search_index_enabled() -> bool {
+    static CACHED: Mutex<Option<(String, bool)>> = Mutex::new(None);
+    let Ok(path) = crate::commands::metadata::get_user_data_path() else {
+        return false;
+    };
+    // Signature BEFORE reading, so a concurrent save is re-read next time.
+    let Some(signature) = file_signature(&path) else {
+        return false;
+    };
+    let mut cached = CACHED.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((cached_signature, enabled)) = cached.as_ref() {
+        if *cached_signature == signature {
+            return *enabled;
+        }
+    }
+    let enabled = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<UserMetadata>(&content).ok())
+        .is_some_and(|metadata| metadata.settings.search_index == Some(true));
+    *cached = Some((signature, enabled));
+    enabled
+}
+
+/// Default location: `~/.claude-history-viewer/cache/search-index.db`.
+fn default_index_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude-history-viewer")
+            .join("cache")
+            .join(STORE_FILE_NAME),
+    )
+}
+
+/// Process-wide index, opened lazily on first use. `None` when the home
+/// directory is unavailable or the database cannot be opened. Disabled under
+/// `cfg(test)` so unit tests never touch the developer's real index.
+fn persistent_index() -> Option<&'static SearchIndex> {
+    static INDEX: OnceLock<Option<SearchIndex>> = OnceLock::new();
+    INDEX
+        .get_or_init(|| {
+            if cfg!(test) {
+                return None;
+            }
+            let path = default_index_path()?;
+            match SearchIndex::open(&path) {
+                Ok(index) => Some(index),
+                Err(e) => {
+                    log::warn!(
+                        "Search index unavailable at {}: {e}",
+                        path.to_string_lossy()
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Answer a Claude search from the persistent index. `None` when the index is
+/// disabled or unavailable, or `query` has no terms the index can match —
+/// the caller then falls back to the scan. `matcher` is the scan's matcher
+/// for `query`.
+pub(super) fn search_indexed(
+    claude_path: &str,
+    query: &SearchQuery,
+    matcher: &TextMatcher,
+    filters: &serde_json::Value,
+    sort: SearchSort,
+    max_results: usize,
+) -> Option<Vec<ClaudeMessage>> {
+    let fts_query = to_fts_query(query)?;
+    if !search_index_enabled() {
+        return None;
+    }
+    let index = persistent_index()?;
+    let root = fs::canonicalize(Path::new(claude_path).join("projects")).ok()?;
+
+    if let Err(e) = index.sync_root(&root) {
+        log::warn!("Search index sync failed: {e}");
+        return None;
+    }
+    match index.search(
+        &root,
+        &fts_query,
+        matcher,
+        filters,
+        query,
+        sort,
+        max_results,
+    ) {
+        Ok(results) => Some(results),
+        Err(e) => {
+            log::warn!("Search index query failed: {e}");
+            None
+        }
+    }
+}
+
+/// Bring one changed Claude session file up to date in the persistent index.
+///
+/// Called by the file watchers for every content change. A no-op unless the
+/// index is enabled and already covers the file's projects tree (the first
+/// indexed search builds a tree; the watchers only keep it fresh).
+pub fn refresh_search_index(path: &Path) {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+        return;
+    }
+    if !search_index_enabled() {
+        return;
+    }
+    let Some(index) = persistent_index() else {
+        return;
+    };
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let Some(root) = path
+        .ancestors()
+        .find(|ancestor| ancestor.file_name().is_some_and(|name| name == "projects"))
+    else {
+        return;
+    };
+    if let Err(e) = index.refresh_file(root, &path) {
+        log::warn!("Search index refresh failed: {e}");
+    }
+}
+
+/// Drop `path` from the persistent index so the next query re-indexes it.
+/// Needed after in-place rewrites, for the same reason as the search cache
+/// eviction that calls this.
+pub(super) fn forget_file(path: &Path) {
+    // Also runs while the index is disabled, so re-enabling it never serves
+    // a rewrite that kept its signature; but never creates the database.
+    if !default_index_path().is_some_and(|index_path| index_path.exists()) {
+        return;
+    }
+    let Some(index) = persistent_index() else {
+        return;
+    };
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let conn = index.lock_conn();
+    if let Err(e) = delete_file(&conn, &path.to_string_lossy()) {
+        log::warn!("Search index delete failed: {e}");
+    }
+}
+
+/// Translate the free text of a parsed query into an FTS5 expression that
+/// every scan match satisfies: each term quoted (so FTS operators and
+/// punctuation are matched literally) as a trigram substring, and a `word*`
+/// prefix passed on as an FTS prefix query. `None` when the query has no
+/// terms or a term is too short for trigrams.
+fn to_fts_query(query: &SearchQuery) -> Option<String> {
+    let terms: Vec<(&str, bool)> = query
+        .terms()
+        .iter()
+        .map(|term| match term {
+            QueryTerm::Phrase(phrase) => (phrase.as_str(), false),
+            QueryTerm::Word(word) => match word.strip_suffix('*') {
+                Some(prefix) => (prefix, true),
+                None => (word.as_str(), false),
+            },
+        })
+        .filter(|(text, _)| !text.is_empty())
+        .collect();
+    if terms.is_empty()
+        || terms
+            .iter()
+            .any(|(text, _)| text.chars().count() < MIN_TERM_CHARS)
+    {
+        return None;
+    }
+    Some(
+        terms
+            .into_iter()
+            .map(|(text, prefix)| {
+                if prefix {
+                    format!("{}*", quote_fts(text))
+                } else {
+                    quote_fts(text)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn quote_fts(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+impl SearchIndex {
+    /// Open (or create) the index at `path`, resetting it on schema mismatch.
+    pub(super) fn open(path: &Path) -> Result<Self, String> {
+        let conn = open_cache_db(path, SCHEMA_VERSION, SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Index every new or changed `.jsonl` file under `root` and drop files
+    /// that are gone.
+    pub(super) fn sync_root(&self, root: &Path) -> rusqlite::Result<()> {
+        let on_disk: Vec<PathBuf> = WalkDir::new(root)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+            .map(walkdir::DirEntry::into_path)
+            .collect();
+        self.sync_paths(root, on_disk, true)
+    }
+
+    /// Re-index one file of an already indexed `root`, or drop it if it no
+    /// longer exists.
+    fn refresh_file(&self, root: &Path, path: &Path) -> rusqlite::Result<()> {
+        let root_key = root.to_string_lossy();
+        let known_root = self
+            .lock_conn()
+            .query_row(
+                "SELECT 1 FROM files WHERE root = ?1 LIMIT 1",
+                params![root_key],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !known_root {
+            return Ok(());
+        }
+        if path.is_file() {
+            self.sync_paths(root, vec![path.to_path_buf()], false)
+        } else {
+            delete_file(&self.lock_conn(), &path.to_string_lossy())
+        }
+    }
+
+    /// Bring `paths` up to date; with `prune`, also drop every indexed file
+    /// of `root` not listed in `paths`.
+    fn sync_paths(&self, root: &Path, paths: Vec<PathBuf>, prune: bool) -> rusqlite::Result<()> {
+        let root_key = root.to_string_lossy().into_owned();
+        let mut known = self.known_files(&root_key)?;
+
+        let stale: Vec<(PathBuf, Option<IndexedFile>)> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let key = path.to_string_lossy().into_owned();
+                let entry = known.remove(&key);
+                let signature = file_signature(&path)?;
+                match entry {
+                    Some(entry) if entry.signature == signature => None,
+                    entry => Some((path, entry)),
+                }
+            })
+            .collect();
+        let vanished: Vec<String> = if prune {
+            known.into_keys().collect()
+        } else {
+            Vec::new()
+        };
+        if stale.is_empty() && vanished.is_empty() {
+            return Ok(());
+        }
+
+        let parsed: Vec<ParsedFile> = stale
+            .into_par_iter()
+            .filter_map(|(path, entry)| parse_file(&path, entry.as_ref()))
+            .collect();
+
+        let mut conn = self.lock_conn();
+        let tx = conn.transaction()?;
+        for path in &vanished {
+            delete_file(&tx, path)?;
+        }
+        for file in parsed {
+            write_file(&tx, &root_key, file)?;
+        }
+        tx.commit()
+    }
+
+    fn known_files(&self, root_key: &str) -> rusqlite::Result<HashMap<String, IndexedFile>> {
+        let conn = self.lock_conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, path, signature, indexed_len, indexed_lines, checksum
+             FROM files WHERE root = ?1",
+        )?;
+        let rows = stmt.query_map(params![root_key], |row| {
+            Ok((
+                row.get::<_, String>(1)?,
+                IndexedFile {
+                    id: row.get(0)?,
+                    signature: row.get(2)?,
+                    indexed_len: row.get(3)?,
+                    indexed_lines: row.get(4)?,
+                    checksum: row.get(5)?,
+                },
+            ))
+        })?;
+        rows.collect()
+    }
+
+    /// Hits for `fts_query` under `root` that the scan would return, read
+    /// back from disk and filtered, newest or best match first.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn search(
+        &self,
+        root: &Path,
+        fts_query: &str,
+        matcher: &TextMatcher,
+        filters: &serde_json::Value,
+        query: &SearchQuery,
+        sort: SearchSort,
+        max_results: usize,
+    ) -> rusqlite::Result<Vec<ClaudeMessage>> {
+        let conn = self.lock_conn();
+        // The scan stamps lines without a timestamp with the current time,
+        // so they sort first there too. `bm25()` is lower for better matches.
+        let rank = match sort {
+            SearchSort::Newest => "",
+            SearchSort::Relevance => "bm25(entries), ",
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT f.path, l.start, l.end, l.line
+             FROM entries
+             JOIN lines l ON l.id = entries.rowid
+             JOIN files f ON f.id = l.file_id
+             WHERE entries MATCH ?1 AND f.root = ?2
+             ORDER BY {rank}l.timestamp IS NULL DESC, l.timestamp DESC"
+        ))?;
+        let mut rows = stmt.query(params![fts_query, root.to_string_lossy()])?;
+
+        let mut files: HashMap<String, Option<Mmap>> = HashMap::new();
+        let mut results = Vec::new();
+        while results.len() < max_results {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+            let path: String = row.get(0)?;
+            let (start, end, line): (usize, usize, usize) = (row.get(1)?, row.get(2)?, row.get(3)?);
+            let Some(mmap) = files.entry(path.clone()).or_insert_with(|| map_file(&path)) else {
+                continue;
+            };
+            // A file that shrank since the sync is re-indexed next query.
+            let Some(bytes) = mmap.get(start..end) else {
+                continue;
+            };
+            let mut buffer = bytes.to_vec();
+            let Ok(entry) = simd_json::serde::from_slice::<RawLogEntry>(&mut buffer) else {
+                continue;
+            };
+            let project_name = extract_project_name(&PathBuf::from(&path));
+            if let Some(message) = search_hit_message(entry, line, project_name) {
+                if matcher.matches_message(&message, SearchFields::ALL)
+                    && matches_filters(&message, filters)
+                    && query.matches(&message)
+                {
+                    results.push(message);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Lock the connection, recovering from a poisoned lock.
+    fn lock_conn(&self) -> MutexGuard<'_, Connection> {
+        match self.conn.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Remove one file's rows (all three tables).
+fn delete_file(conn: &Connection, path: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM entries WHERE rowid IN (
+             SELECT l.id FROM lines l JOIN files f ON f.id = l.file_id WHERE f.path = ?1
+         )",
+        params![path],
+    )?;
+    conn.execute(
+        "DELETE FROM lines WHERE file_id IN (SELECT id FROM files WHERE path = ?1)",
+        params![path],
+    )?;
+    conn.execute("DELETE FROM files WHERE path = ?1", params![path])?;
+    Ok(())
+}
+
+fn write_file(tx: &Transaction<'_>, root_key: &str, file: ParsedFile) -> rusqlite::Result<()> {
+    let file_id = if let Some(id) = file.extend {
+        tx.execute(
+            "UPDATE files SET signature = ?1, indexed_len = ?2, indexed_lines = ?3, checksum = ?4
+             WHERE id = ?5",
+            params![
+                file.signature,
+                file.indexed_len,
+                file.indexed_lines,
+                file.checksum,
+                id
+            ],
+        )?;
+        id
+    } else {
+        delete_file(tx, &file.path)?;
+        tx.execute(
+            "INSERT INTO files (root, path, signature, indexed_len, indexed_lines, checksum)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                root_key,
+                file.path,
+                file.signature,
+                file.indexed_len,
+                file.indexed_lines,
+                file.checksum
+            ],
+        )?;
+        tx.last_insert_rowid()
+    };
+
+    let mut insert_line = tx.prepare_cached(
+        "INSERT INTO lines (file_id, start, end, line, timestamp) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let mut insert_entry =
+        tx.prepare_cached("INSERT INTO entries (rowid, body) VALUES (?1, ?2)")?;
+    for line in file.lines {
+        insert_line.execute(params![
+            file_id,
+            line.start,
+            line.end,
+            line.line,
+            line.timestamp
+        ])?;
+        insert_entry.execute(params![tx.last_insert_rowid(), line.body])?;
+    }
+    Ok(())
+}
+
+/// `<size>:<mtime ns>` of a regular file, `None` when it cannot be stat'ed.
+fn file_signature(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok().filter(fs::Metadata::is_file)?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        .map_or_else(|| "-".to_string(), |elapsed| elapsed.as_nanos().to_string());
+    Some(format!("{}:{mtime}", metadata.len()))
+}
+
+#[allow(unsafe_code)] // Required for mmap performance optimization
+fn map_file(path: &str) -> Option<Mmap> {
+    let file = fs::File::open(path).ok()?;
+    // SAFETY: read-only mmap; the map owns its own reference to the file.
+    // Byte ranges are bounds-checked against the current length on use.
+    unsafe { Mmap::map(&file) }.ok()
+}
+
+/// Parse the lines of `path` that `entry` does not cover yet: only the
+/// appended tail when the indexed prefix is unchanged, the whole file
+/// otherwise. The trailing partial line (still being written) is left for
+/// the next sync.
+fn parse_file(path: &Path, entry: Option<&IndexedFile>) -> Option<ParsedFile> {
+    // Signature BEFORE reading, so a concurrent append is re-synced next time.
+    let signature = file_signature(path)?;
+    let mmap = map_file(&path.to_string_lossy());
+    // Empty files cannot be mapped on every platform; any other mapping
+    // failure is retried on the next sync.
+    if mmap.is_none() && !signature.starts_with("0:") {
+        return None;
+    }
+    let data: &[u8] = mmap.as_deref().unwrap_or_default();
+
+    let resume = entry
+        .filter(|entry| entry.indexed_len <= data.len())
+        .map(|entry| (entry, fnv1a(&data[..entry.indexed_len])))
+        .filter(|(entry, hash)| format!("{hash:016x}") == entry.checksum);
+    let (from, first_line, prefix_hash) = resume.map_or((0, 0, FNV1A_OFFSET), |(entry, hash)| {
+        (entry.indexed_len, entry.indexed_lines, hash)
+    });
+    let complete_end = data[from..]
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(from, |newline| from + newline + 1);
+
+    let ranges = find_line_ranges(&data[from..complete_end]);
+    let mut lines = Vec::new();
+    let mut buffer = Vec::new();
+    for (offset, (start, end)) in ranges.iter().enumerate() {
+        buffer.clear();
+        buffer.extend_from_slice(&data[from + start..from + end]);
+        let Ok(log_entry) = simd_json::serde::from_slice::<RawLogEntry>(&mut buffer) else {
+            continue;
+        };
+        if let Some(body) = searchable_text(&log_entry) {
+            lines.push(IndexLine {
+                start: from + start,
+                end: from + end,
+                line: first_line + offset,
+                timestamp: log_entry.timestamp.clone(),
+                body,
+            });
+        }
+    }
+
+    let checksum = fnv1a_extend(prefix_hash, &data[from..complete_end]);
+    Some(ParsedFile {
+        path: path.to_string_lossy().into_owned(),
+        signature,
+        extend: resume.map(|(entry, _)| entry.id),
+        indexed_len: complete_end,
+        indexed_lines: first_line + ranges.len(),
+        checksum: format!("{checksum:016x}"),
+        lines,
+    })
+}
+
+/// Text the scan would match for this row, or `None` when the scan skips it.
+fn searchable_text(entry: &RawLogEntry) -> Option<String> {
+    if entry.message_type != "user" && entry.message_type != "assistant" {
+        return None;
+    }
+    let mut body = String::new();
+    collect_strings(&entry.message.as_ref()?.content, &mut body);
+    for value in [&entry.tool_use_result, &entry.tool_use]
+        .into_iter()
+        .flatten()
+    {
+        collect_strings(value, &mut body);
+    }
+    (!body.is_empty()).then_some(body)
+}
+
+fn collect_strings(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::String(text) => {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(text);
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|item| collect_strings(item, out)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn user_line(uuid: &str, content: &str) -> String {
+        format!(
+            r#"{{"uuid":"{uuid}","sessionId":"s1","timestamp":"2025-06-26T10:00:00Z","type":"user","message":{{"role":"user","content":"{content}"}}}}"#
+        )
+    }
+
+    fn tool_result_line(uuid: &str, stdout: &str) -> String {
+        format!(
+            r#"{{"uuid":"{uuid}","sessionId":"s1","timestamp":"2025-06-26T10:02:00Z","type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t1","content":"ok"}}]}},"toolUseResult":{{"stdout":"{stdout}"}}}}"#
+        )
+    }
+
+    /// `(index, projects root, session file)` over a fresh temp tree.
+    fn fixture(temp_dir: &TempDir, lines: &[String]) -> (SearchIndex, PathBuf, PathBuf) {
+        let root = temp_dir.path().join("projects");
+        let project = root.join("demo");
+        fs::create_dir_all(&project).unwrap();
+        let session = project.join("session.jsonl");
+        fs::write(&session, format!("{}\n", lines.join("\n"))).unwrap();
+        let index = SearchIndex::open(&temp_dir.path().join("index.db")).unwrap();
+        (index, fs::canonicalize(root).unwrap(), session)
+    }
+
+    fn fts(query: &str) -> Option<String> {
+        to_fts_query(&SearchQuery::parse(query).unwrap())
+    }
+
+    fn uuids(index: &SearchIndex, root: &Path, query: &str) -> Vec<String> {
+        sorted_uuids(index, root, query, SearchSort::Newest)
+    }
+
+    fn sorted_uuids(
+        index: &SearchIndex,
+        root: &Path,
+        query: &str,
+        sort: SearchSort,
+    ) -> Vec<String> {
+        let parsed = SearchQuery::parse(query).unwrap();
+        let matcher = TextMatcher::query_terms(parsed.terms(), false);
+        index.sync_root(root).unwrap();
+        index
+            .search(
+                root,
+                &to_fts_query(&parsed).unwrap(),
+                &matcher,
+                &serde_json::json!({}),
+                &parsed,
+                sort,
+                100,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|message| message.uuid)
+            .collect()
+    }
+
+    fn line_ids(index: &SearchIndex) -> Vec<i64> {
+        let conn = index.lock_conn();
+        let mut stmt = conn.prepare("SELECT id FROM lines ORDER BY id").unwrap();
+        let ids = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        ids
+    }
+
+    #[test]
+    fn test_to_fts_query_quotes_terms_phrases_and_prefixes() {
+        assert_eq!(fts("rust  macro").as_deref(), Some(r#""rust" "macro""#));
+        assert_eq!(
+            fts(r#""borrow checker" deser*"#).as_deref(),
+            Some(r#""borrow checker" "deser"*"#)
+        );
+        assert_eq!(
+            fts("NOT a-b ORDER").as_deref(),
+            Some(r#""NOT" "a-b" "ORDER""#)
+        );
+        assert_eq!(fts(r#"  "" * "#), None);
+        assert_eq!(
+            fts("go build"),
+            None,
+            "terms shorter than a trigram fall back to the scan"
+        );
+        assert_eq!(
+            fts("tool:Bash"),
+            None,
+            "filter-only queries fall back to the scan"
+        );
+    }
+
+    #[test]
+    fn test_index_matches_substrings_phrases_and_tool_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let (index, root, _) = fixture(
+            &temp_dir,
+            &[
+                user_line("u1", "the borrow checker rejects this"),
+                user_line("u2", "checker of borrow semantics"),
+                tool_result_line("u3", "deserialization failed"),
+                r#"{"type":"summary","summary":"borrow checker"}"#.to_string(),
+            ],
+        );
+
+        let mut both = uuids(&index, &root, "borrow checker");
+        both.sort();
+        assert_eq!(both, ["u1", "u2"]);
+        assert_eq!(uuids(&index, &root, r#""borrow checker""#), ["u1"]);
+        assert_eq!(uuids(&index, &root, "deserial*"), ["u3"]);
+        assert!(
+            uuids(&index, &root, "serial*").is_empty(),
+            "a prefix only matches at the start of a word"
+        );
+        assert_eq!(
+            uuids(&index, &root, "ERIALIZ"),
+            ["u3"],
+            "bare terms match inside words, case-insensitively, like the scan"
+        );
+    }
+
+    #[test]
+    fn test_index_returns_newest_matches_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let (index, root, _) = fixture(
+            &temp_dir,
+            &[
+                user_line("older", "tokio tokio tokio"),
+                tool_result_line("newer", "tokio once"),
+            ],
+        );
+        assert_eq!(uuids(&index, &root, "tokio"), ["newer", "older"]);
+        assert_eq!(
+            sorted_uuids(&index, &root, "tokio", SearchSort::Relevance),
+            ["older", "newer"]
+        );
+    }
+
+    #[test]
+    fn test_index_appends_tail_and_reindexes_rewrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let (index, root, session) = fixture(&temp_dir, &[user_line("u1", "alpha")]);
+        assert_eq!(uuids(&index, &root, "alpha"), ["u1"]);
+        let before = line_ids(&index);
+
+        let mut content = fs::read_to_string(&session).unwrap();
+        content.push_str(&format!("{}\n", user_line("u2", "alpha beta")));
+        fs::write(&session, &content).unwrap();
+        assert_eq!(uuids(&index, &root, "beta"), ["u2"]);
+        let after = line_ids(&index);
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0], before[0], "indexed prefix is kept on append");
+
+        fs::write(&session, format!("{}\n", user_line("u9", "gamma"))).unwrap();
+        assert!(uuids(&index, &root, "alpha").is_empty());
+        assert_eq!(uuids(&index, &root, "gamma"), ["u9"]);
+        assert_eq!(line_ids(&index).len(), 1, "rewrite drops the old rows");
+    }
+
+    #[test]
+    fn test_index_drops_deleted_files_and_refreshes_single_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let (index, root, session) = fixture(&temp_dir, &[user_line("u1", "delta")]);
+        assert_eq!(uuids(&index, &root, "delta"), ["u1"]);
+
+        let other = root.join("demo").join("other.jsonl");
+        fs::write(&other, format!("{}\n", user_line("u2", "delta"))).unwrap();
+        index.refresh_file(&root, &other).unwrap();
+        assert_eq!(
+            line_ids(&index).len(),
+            2,
+            "watcher refresh indexes the new file"
+        );
+
+        fs::remove_file(&session).unwrap();
+        assert_eq!(uuids(&index, &root, "delta"), ["u2"]);
+        assert_eq!(line_ids(&index).len(), 1);
+    }
+
+    #[test]
+    fn test_index_leaves_partial_last_line_for_next_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let (index, root, session) = fixture(&temp_dir, &[user_line("u1", "epsilon")]);
+        let partial = user_line("u2", "epsilon zeta");
+        let (head, tail) = partial.split_at(partial.len() / 2);
+        let mut content = fs::read_to_string(&session).unwrap();
+        content.push_str(head);
+        fs::write(&session, &content).unwrap();
+        assert_eq!(uuids(&index, &root, "epsilon"), ["u1"]);
+
+        content.push_str(tail);
+        content.push('\n');
+        fs::write(&session, &content).unwrap();
+        assert_eq!(uuids(&index, &root, "zeta"), ["u2"]);
+    }
+}
@@ -1,9 +1,10 @@
 #[cfg(test)]
 use crate::models::MessageContent;
 use crate::models::{
-    ActivityHeatmap, ClaudeMessage, DailyStats, GlobalStatsSummary, ModelContextStats, ModelStats,
-    ProjectRanking, ProjectStatsSummary, ProviderUsageStats, RawLogEntry, SessionComparison,
-    SessionTokenStats, TokenDistribution, TokenUsage, ToolUsageStats,
+    models_total_cost_usd, ActivityHeatmap, ClaudeMessage, DailyStats, GlobalStatsSummary,
+    ModelContextStats, ModelStats, ProjectRanking, ProjectStatsSummary, ProviderUsageStats,
+    RawLogEntry, SessionComparison, SessionTokenStats, TokenDistribution, TokenUsage,
+    ToolUsageStats,
 };
 use crate::providers;
 use crate::utils::find_line_ranges;
@@ -18,6 +19,12 @@ use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 mod cache;
+mod compare;
+mod pricing;
+mod store;
+
+pub use cache::stats_cache_counters;
+pub use compare::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 enum StatsProvider {
@@ -445,6 +452,12 @@ fn detect_project_provider(project_path: &str) -> StatsProvider {
     }
 }
 
+/// Whether `project_path` is a project of a provider other than Claude, so
+/// project stats route to that provider.
+pub(crate) fn is_provider_project_path(project_path: &str) -> bool {
+    detect_project_provider(project_path) != StatsProvider::Claude
+}
+
 /// Detect the provider encoded in a session path.
 fn detect_session_provider(session_path: &str) -> StatsProvider {
     if session_path.starts_with("aider://") || session_path.ends_with(".aider.chat.history.md") {
@@ -973,7 +986,11 @@ fn track_tool_usage_from_global_entry(
 /// Intermediate stats collected from a single session file (for parallel processing)
 type ModelUsageAggregate = (u32, u64, u64, u64, u64, u64, u64);
 type ModelContextUsageMap = HashMap<String, HashMap<u64, ModelContextStats>>;
+/// Model key → (source cost in USD, tokens of the messages that reported it).
+type ModelCostMap = HashMap<String, (f64, u64)>;
 const UNKNOWN_MODEL_NAME: &str = "unknown";
+/// Projects kept in the global summary's `top_projects`.
+const TOP_PROJECTS_LIMIT: usize = 10;
 const MODEL_USAGE_KEY_SEPARATOR: char = '\u{1f}';
 
 fn normalize_service_tier(service_tier: Option<&str>) -> Option<String> {
@@ -1072,7 +1089,7 @@ struct SessionFileStats {
     activity_data: HashMap<(u8, u8), (u32, u64)>, // (hour, day) -> (count, tokens)
     model_usage: HashMap<String, ModelUsageAggregate>, // model -> (msg_count, total, input, output, cache_create, cache_read, reasoning)
     model_context_usage: ModelContextUsageMap,
-    model_costs: HashMap<String, f64>, // model -> authoritative source cost when present
+    model_costs: ModelCostMap, // model -> authoritative source cost when present
     session_duration_minutes: u64,
     first_message: Option<DateTime<Utc>>,
     last_message: Option<DateTime<Utc>>,
@@ -1090,6 +1107,17 @@ fn claude_session_project_name(session_path: &Path) -> String {
         .to_string()
 }
 
+/// Drop everything the stats caches hold for `path`.
+///
+/// Needed by commands that REWRITE a session file in place (temp + rename):
+/// the rewrite can keep the `(size, mtime)` signature within mtime resolution
+/// and changes already-parsed bytes, which the tail-extension path must never
+/// build on. Append-only growth needs no such hook.
+pub fn evict_file_from_stats_cache(path: &Path) {
+    cache::global_stats_cache().evict(path);
+    cache::message_stats_cache().evict(path);
+}
+
 /// Process a session file into the lightweight global stats representation.
 /// Served from the per-file daily-aggregate cache when the file is unchanged
 /// and the date filter composes from daily buckets; otherwise falls back to
@@ -1100,11 +1128,10 @@ fn process_session_file_for_global_stats(
     s_limit: Option<&DateTime<Utc>>,
     e_limit: Option<&DateTime<Utc>>,
 ) -> Option<SessionFileStats> {
-    if let Some(aggregate) = cache::global_stats_cache().get_or_build(session_path, mode, || {
-        cache::build_global_file_aggregate(session_path, mode)
-    }) {
+    if let Some(aggregate) = cache::global_stats_cache().get_or_build(session_path, mode) {
         if let cache::Composed::Ready(stats) = cache::compose_global(
             &aggregate,
+            StatsProvider::Claude,
             claude_session_project_name(session_path),
             s_limit,
             e_limit,
@@ -1217,10 +1244,7 @@ fn scan_session_file_for_global_stats(
             model_entry.5 += cache_read_tokens;
             model_entry.6 += reasoning_tokens;
             if let Some(cost_usd) = deduped_source_cost {
-                *stats
-                    .model_costs
-                    .entry(model_name.to_string())
-                    .or_insert(0.0) += cost_usd;
+                add_model_cost(&mut stats.model_costs, model_name, cost_usd, tokens);
             }
         }
 
@@ -1397,10 +1421,7 @@ fn build_global_session_file_stats_from_messages(
             model_entry.5 += cache_read_tokens;
             model_entry.6 += reasoning_tokens;
             if let Some(cost_usd) = deduped_source_cost {
-                *stats
-                    .model_costs
-                    .entry(model_name.to_string())
-                    .or_insert(0.0) += cost_usd;
+                add_model_cost(&mut stats.model_costs, model_name, cost_usd, tokens);
             }
         }
 
@@ -1779,12 +1800,10 @@ fn collect_provider_global_file_stats(
     let all_stats: Vec<SessionFileStats> = session_tasks
         .par_iter()
         .filter_map(|(project_name, file_path)| {
-            let messages = load_stats_messages(provider, file_path).unwrap_or_default();
-
-            build_global_session_file_stats_from_messages(
+            process_provider_session_for_global_stats(
                 provider,
-                project_name.clone(),
-                &messages,
+                project_name,
+                file_path,
                 mode,
                 s_limit,
                 e_limit,
@@ -1795,6 +1814,47 @@ fn collect_provider_global_file_stats(
     (all_stats, project_keys)
 }
 
+/// Process one provider session into global stats. Served from the provider
+/// aggregate cache while the session's content identity is unchanged and the
+/// date filter composes from daily buckets; otherwise the messages are
+/// loaded and scanned.
+fn process_provider_session_for_global_stats(
+    provider: StatsProvider,
+    project_name: &str,
+    session_path: &str,
+    mode: StatsMode,
+    s_limit: Option<&DateTime<Utc>>,
+    e_limit: Option<&DateTime<Utc>>,
+) -> Option<SessionFileStats> {
+    let load = || load_stats_messages(provider, session_path).unwrap_or_default();
+    let mut loaded = None;
+    let aggregate = cache::provider_stats_cache().get_or_build(
+        provider,
+        session_path,
+        mode,
+        &mut loaded,
+        load,
+    )?;
+    if let cache::Composed::Ready(stats) = cache::compose_global(
+        &aggregate,
+        provider,
+        project_name.to_string(),
+        s_limit,
+        e_limit,
+    ) {
+        return Some(stats);
+    }
+    let messages = loaded.unwrap_or_else(load);
+    build_global_session_file_stats_from_messages(
+        provider,
+        project_name.to_string(),
+        &messages,
+        mode,
+        s_limit,
+        e_limit,
+    )
+}
+
 /// Intermediate stats collected from a single session file (for project stats)
 #[derive(Default)]
 struct ProjectSessionFileStats {
@@ -1802,7 +1862,7 @@ struct ProjectSessionFileStats {
     token_distribution: TokenDistribution,
     model_usage: HashMap<String, ModelUsageAggregate>,
     model_context_usage: ModelContextUsageMap,
-    model_costs: HashMap<String, f64>,
+    model_costs: ModelCostMap,
     tool_usage: HashMap<String, (u32, u32)>,
     skill_usage: HashMap<String, (u32, u32)>, // Skill tool, keyed by input.skill (#321)
     subagent_usage: HashMap<String, (u32, u32)>, // Agent tool, keyed by input.subagent_type (#321)
@@ -1822,9 +1882,7 @@ fn process_session_file_for_project_stats(
     s_limit: Option<&DateTime<Utc>>,
     e_limit: Option<&DateTime<Utc>>,
 ) -> Option<ProjectSessionFileStats> {
-    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode, || {
-        cache::build_message_file_aggregate(session_path, mode)
-    }) {
+    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode) {
         if let cache::Composed::Ready(stats) = cache::compose_project(&aggregate, s_limit, e_limit)
         {
             return stats;
@@ -2446,7 +2504,7 @@ fn build_antigravity_session_token_stats(
 
     let mut model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut model_costs: HashMap<String, f64> = HashMap::new();
+    let mut model_costs: ModelCostMap = HashMap::new();
     for record in &records {
         let (input_tokens, cache_creation_tokens, cache_read_tokens, token_count) = match mode {
             StatsMode::BillingTotal => (
@@ -2487,6 +2545,12 @@ fn build_antigravity_session_token_stats(
         );
     }
 
+    let model_distribution = build_model_stats(
+        StatsProvider::Antigravity,
+        model_usage,
+        model_context_usage,
+        model_costs,
+    );
     let stats = SessionTokenStats {
         session_id: session.actual_session_id.clone(),
         project_name: session.project_name.clone(),
@@ -2531,12 +2595,8 @@ fn build_antigravity_session_token_stats(
         last_message_time,
         summary: session.summary.clone(),
         most_used_tools: Vec::new(),
-        model_distribution: build_model_stats(
-            StatsProvider::Antigravity,
-            model_usage,
-            model_context_usage,
-            model_costs,
-        ),
+        total_cost_usd: models_total_cost_usd(&model_distribution),
+        model_distribution,
     };
 
     Ok(Some((stats, records)))
@@ -2576,7 +2636,7 @@ struct ModelUsageUpdate<'a> {
 fn accumulate_model_usage(
     model_usage: &mut HashMap<String, ModelUsageAggregate>,
     model_context_usage: &mut ModelContextUsageMap,
-    model_costs: &mut HashMap<String, f64>,
+    model_costs: &mut ModelCostMap,
     update: ModelUsageUpdate<'_>,
 ) {
     let ModelUsageUpdate {
@@ -2627,7 +2687,19 @@ fn accumulate_model_usage(
     context.cache_read_tokens += cache_read_tokens;
     context.reasoning_tokens += reasoning_tokens;
     if let Some(cost_usd) = source_cost {
-        *model_costs.entry(model_key).or_insert(0.0) += cost_usd;
+        add_model_cost(model_costs, &model_key, cost_usd, tokens);
+    }
+}
+
+fn add_model_cost(model_costs: &mut ModelCostMap, model_key: &str, cost_usd: f64, tokens: u64) {
+    let entry = model_costs.entry(model_key.to_string()).or_insert((0.0, 0));
+    entry.0 += cost_usd;
+    entry.1 += tokens;
+}
+
+fn merge_model_costs(target: &mut ModelCostMap, source: &ModelCostMap) {
+    for (model_key, (cost_usd, tokens)) in source {
+        add_model_cost(target, model_key, *cost_usd, *tokens);
     }
 }
 
@@ -2660,9 +2732,10 @@ fn build_model_stats(
     provider: StatsProvider,
     model_usage: HashMap<String, ModelUsageAggregate>,
     model_context_usage: ModelContextUsageMap,
-    model_costs: HashMap<String, f64>,
+    model_costs: ModelCostMap,
 ) -> Vec<ModelStats> {
     let provider_id = stats_provider_id(provider).to_string();
+    let pricing = pricing::current_pricing();
     let mut models = model_usage
         .into_iter()
         .map(
@@ -2684,6 +2757,8 @@ fn build_model_stats(
                     .map(|buckets| buckets.values().cloned().collect::<Vec<_>>())
                     .unwrap_or_default();
                 context_breakdown.sort_by_key(|bucket| bucket.min_context_tokens);
+                let estimated_cost_usd =
+                    pricing.estimate(model_name, service_tier, &context_breakdown);
                 ModelStats {
                     provider_id: Some(provider_id.clone()),
                     model_name: model_name.to_string(),
@@ -2695,7 +2770,11 @@ fn build_model_stats(
                     cache_creation_tokens,
                     cache_read_tokens,
                     reasoning_tokens,
-                    cost_usd: model_costs.get(&model_key).copied(),
+                    cost_usd: model_costs.get(&model_key).map(|(cost, _)| *cost),
+                    reported_cost_tokens: model_costs
+                        .get(&model_key)
+                        .map_or(0, |(_, tokens)| *tokens),
+                    estimated_cost_usd,
                     context_breakdown,
                 }
             },
@@ -2708,7 +2787,7 @@ fn build_model_stats(
 fn merge_model_stats(
     model_usage: &mut HashMap<String, ModelUsageAggregate>,
     model_context_usage: &mut ModelContextUsageMap,
-    model_costs: &mut HashMap<String, f64>,
+    model_costs: &mut ModelCostMap,
     models: Vec<ModelStats>,
 ) {
     for model in models {
@@ -2724,7 +2803,12 @@ fn merge_model_stats(
         entry.5 += model.cache_read_tokens;
         entry.6 += model.reasoning_tokens;
         if let Some(cost_usd) = model.cost_usd {
-            *model_costs.entry(model_key.clone()).or_insert(0.0) += cost_usd;
+            add_model_cost(
+                model_costs,
+                &model_key,
+                cost_usd,
+                model.reported_cost_tokens,
+            );
         }
         if model.context_breakdown.is_empty() {
             let context_tier = context_tier_min_tokens(
@@ -3168,7 +3252,7 @@ fn build_session_token_stats_from_messages(
     let mut seen_cost_keys: HashSet<String> = HashSet::with_capacity(messages.len());
     let mut model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut model_costs: HashMap<String, f64> = HashMap::new();
+    let mut model_costs: ModelCostMap = HashMap::new();
 
     let mut first_time: Option<DateTime<Utc>> = None;
     let mut last_time: Option<DateTime<Utc>> = None;
@@ -3256,6 +3340,8 @@ fn build_session_token_stats_from_messages(
         return None;
     }
 
+    let model_distribution =
+        build_model_stats(provider, model_usage, model_context_usage, model_costs);
     Some(SessionTokenStats {
         session_id,
         project_name,
@@ -3270,12 +3356,8 @@ fn build_session_token_stats_from_messages(
         last_message_time: last_time_raw.unwrap_or_else(|| "unknown".to_string()),
         summary,
         most_used_tools: build_tool_usage_stats(tool_usage),
-        model_distribution: build_model_stats(
-            provider,
-            model_usage,
-            model_context_usage,
-            model_costs,
-        ),
+        total_cost_usd: models_total_cost_usd(&model_distribution),
+        model_distribution,
     })
 }
 
@@ -3392,7 +3474,7 @@ fn get_provider_project_stats_summary(
         let mut activity_map: HashMap<(u8, u8), (u32, u64)> = HashMap::new();
         let mut project_model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
         let mut project_model_context_usage: ModelContextUsageMap = HashMap::new();
-        let mut project_model_costs: HashMap<String, f64> = HashMap::new();
+        let mut project_model_costs: ModelCostMap = HashMap::new();
 
         for session in &sessions {
             let Some((session_stats, records)) = build_antigravity_session_token_stats(
@@ -3507,6 +3589,7 @@ fn get_provider_project_stats_summary(
             project_model_context_usage,
             project_model_costs,
         );
+        summary.total_cost_usd = models_total_cost_usd(&summary.model_distribution);
         summary.activity_heatmap = activity_map
             .into_iter()
             .map(|((hour, day), (count, tokens))| ActivityHeatmap {
@@ -3549,7 +3632,7 @@ fn get_provider_project_stats_summary(
     let mut tool_usage_map: HashMap<String, (u32, u32)> = HashMap::new();
     let mut project_model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut project_model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut project_model_costs: HashMap<String, f64> = HashMap::new();
+    let mut project_model_costs: ModelCostMap = HashMap::new();
     let mut daily_stats_map: HashMap<String, DailyStats> = HashMap::new();
     let mut activity_map: HashMap<(u8, u8), (u32, u64)> = HashMap::new();
 
@@ -3692,6 +3775,7 @@ fn get_provider_project_stats_summary(
         project_model_context_usage,
         project_model_costs,
     );
+    summary.total_cost_usd = models_total_cost_usd(&summary.model_distribution);
     summary.daily_stats = daily_stats_map.into_values().collect();
     summary.daily_stats.sort_by(|a, b| a.date.cmp(&b.date));
     summary.activity_heatmap = activity_map
@@ -4063,9 +4147,7 @@ fn extract_session_token_stats_sync(
     s_limit: Option<&DateTime<Utc>>,
     e_limit: Option<&DateTime<Utc>>,
 ) -> Option<SessionTokenStats> {
-    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode, || {
-        cache::build_message_file_aggregate(session_path, mode)
-    }) {
+    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode) {
         if let cache::Composed::Ready(stats) = cache::compose_session_token(
             &aggregate,
             claude_session_project_name(session_path),
@@ -4108,7 +4190,7 @@ fn scan_session_token_stats(
     let mut tool_usage: HashMap<String, (u32, u32)> = HashMap::new();
     let mut model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut model_costs: HashMap<String, f64> = HashMap::new();
+    let mut model_costs: ModelCostMap = HashMap::new();
     let mut included_message_count = 0usize;
 
     // Use SIMD-accelerated line detection
@@ -4230,6 +4312,12 @@ fn scan_session_token_stats(
         + total_cache_read_tokens
         + total_reasoning_tokens;
 
+    let model_distribution = build_model_stats(
+        StatsProvider::Claude,
+        model_usage,
+        model_context_usage,
+        model_costs,
+    );
     Some(SessionTokenStats {
         session_id,
         project_name,
@@ -4243,12 +4331,8 @@ fn scan_session_token_stats(
         first_message_time: first_time.unwrap_or_else(|| "unknown".to_string()),
         last_message_time: last_time.unwrap_or_else(|| "unknown".to_string()),
         summary,
-        model_distribution: build_model_stats(
-            StatsProvider::Claude,
-            model_usage,
-            model_context_usage,
-            model_costs,
-        ),
+        total_cost_usd: models_total_cost_usd(&model_distribution),
+        model_distribution,
         most_used_tools: tool_usage
             .into_iter()
             .map(|(name, (usage, success))| ToolUsageStats {
@@ -4427,7 +4511,7 @@ pub async fn get_project_stats_summary(
     let mut subagent_usage_map: HashMap<String, (u32, u32)> = HashMap::new();
     let mut project_model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut project_model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut project_model_costs: HashMap<String, f64> = HashMap::new();
+    let mut project_model_costs: ModelCostMap = HashMap::new();
     let mut daily_stats_map: HashMap<String, DailyStats> = HashMap::new();
     let mut activity_map: HashMap<(u8, u8), (u32, u64)> = HashMap::new();
     let mut session_count_by_date: HashMap<String, usize> = HashMap::new();
@@ -4540,6 +4624,7 @@ pub async fn get_project_stats_summary(
         project_model_costs,
     );
     summary.model_distribution = project_model_distribution;
+    summary.total_cost_usd = models_total_cost_usd(&summary.model_distribution);
 
     summary.daily_stats = daily_stats_map.into_values().collect();
     summary.daily_stats.sort_by(|a, b| a.date.cmp(&b.date));
@@ -4607,9 +4692,7 @@ fn process_session_file_for_comparison(
     s_limit: Option<&DateTime<Utc>>,
     e_limit: Option<&DateTime<Utc>>,
 ) -> Option<SessionComparisonStats> {
-    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode, || {
-        cache::build_message_file_aggregate(session_path, mode)
-    }) {
+    if let Some(aggregate) = cache::message_stats_cache().get_or_build(session_path, mode) {
         if let cache::Composed::Ready(stats) =
             cache::compose_comparison(&aggregate, s_limit, e_limit)
         {
@@ -4883,6 +4966,7 @@ impl TryFrom<RawLogEntry> for ClaudeMessage {
             compact_metadata: log_entry.compact_metadata,
             microcompact_metadata: log_entry.microcompact_metadata,
             provider: None,
+            ..Default::default()
         })
     }
 }
@@ -4896,6 +4980,29 @@ pub async fn get_global_stats_summary(
     start_date: Option<String>,
     end_date: Option<String>,
     custom_claude_paths: Option<Vec<crate::commands::multi_provider::CustomClaudePathParam>>,
+) -> Result<GlobalStatsSummary, String> {
+    global_stats_summary(
+        claude_path,
+        active_providers,
+        stats_mode,
+        start_date,
+        end_date,
+        custom_claude_paths,
+        Some(TOP_PROJECTS_LIMIT),
+    )
+}
+
+/// [`get_global_stats_summary`] keeping the `project_limit` projects with the
+/// most tokens in `top_projects`, or every project for `None`.
+#[allow(clippy::unnecessary_wraps)] // Result kept to match public API shape.
+pub(crate) fn global_stats_summary(
+    claude_path: String,
+    active_providers: Option<Vec<String>>,
+    stats_mode: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    custom_claude_paths: Option<Vec<crate::commands::multi_provider::CustomClaudePathParam>>,
+    project_limit: Option<usize>,
 ) -> Result<GlobalStatsSummary, String> {
     let mode = parse_stats_mode(stats_mode);
     let providers_to_include = parse_active_stats_providers(active_providers);
@@ -5126,8 +5233,10 @@ pub async fn get_global_stats_summary(
     let mut model_usage_map: HashMap<(StatsProvider, String), ModelUsageAggregate> = HashMap::new();
     let mut model_context_map: HashMap<(StatsProvider, String), HashMap<u64, ModelContextStats>> =
         HashMap::new();
-    let mut model_cost_map: HashMap<(StatsProvider, String), f64> = HashMap::new();
+    let mut model_cost_map: HashMap<(StatsProvider, String), (f64, u64)> = HashMap::new();
     let mut project_stats_map: HashMap<String, (u32, u32, u64)> = HashMap::new();
+    let mut provider_project_map: HashMap<(StatsProvider, String), (u32, u32, u64)> =
+        HashMap::new();
     let mut provider_stats_map: HashMap<StatsProvider, (u32, u32, u64)> = HashMap::new();
     let mut provider_projects_map: HashMap<StatsProvider, HashSet<String>> = HashMap::new();
     let mut global_first_message: Option<DateTime<Utc>> = None;
@@ -5191,10 +5300,12 @@ pub async fn get_global_stats_summary(
         for (model, (msg_count, total, input, output, cache_create, cache_read, reasoning)) in
             stats.model_usage
         {
-            if let Some(cost_usd) = stats.model_costs.get(&model) {
-                *model_cost_map
+            if let Some((cost_usd, cost_tokens)) = stats.model_costs.get(&model) {
+                let entry = model_cost_map
                     .entry((provider, model.clone()))
-                    .or_insert(0.0) += cost_usd;
+                    .or_insert((0.0, 0));
+                entry.0 += cost_usd;
+                entry.1 += cost_tokens;
             }
             let entry = model_usage_map
                 .entry((provider, model))
@@ -5238,10 +5349,18 @@ pub async fn get_global_stats_summary(
             .insert(project_name.clone());
 
         // Aggregate project stats
-        let project_entry = project_stats_map.entry(project_name).or_insert((0, 0, 0));
-        project_entry.0 += 1; // sessions
-        project_entry.1 += stats.total_messages; // messages
-        project_entry.2 += stats.total_tokens; // tokens
+        for project_entry in [
+            project_stats_map
+                .entry(project_name.clone())
+                .or_insert((0, 0, 0)),
+            provider_project_map
+                .entry((provider, project_name))
+                .or_insert((0, 0, 0)),
+        ] {
+            project_entry.0 += 1; // sessions
+            project_entry.1 += stats.total_messages; // messages
+            project_entry.2 += stats.total_tokens; // tokens
+        }
 
         // Track global first/last message
         if let Some(first) = stats.first_message {
@@ -5294,6 +5413,7 @@ pub async fn get_global_stats_summary(
         .provider_distribution
         .sort_by_key(|provider| Reverse(provider.tokens));
 
+    let pricing = pricing::current_pricing();
     summary.model_distribution = model_usage_map
         .into_iter()
         .map(
@@ -5315,9 +5435,14 @@ pub async fn get_global_stats_summary(
                     .map(|buckets| buckets.values().cloned().collect::<Vec<_>>())
                     .unwrap_or_default();
                 context_breakdown.sort_by_key(|bucket| bucket.min_context_tokens);
-                let cost_usd = model_cost_map.get(&(provider, model_key.clone())).copied();
+                let (cost_usd, reported_cost_tokens) = model_cost_map
+                    .get(&(provider, model_key.clone()))
+                    .map_or((None, 0), |(cost, tokens)| (Some(*cost), *tokens));
+                let provider_id = stats_provider_id(provider);
+                let estimated_cost_usd =
+                    pricing.estimate(model_name, service_tier, &context_breakdown);
                 ModelStats {
-                    provider_id: Some(stats_provider_id(provider).to_string()),
+                    provider_id: Some(provider_id.to_string()),
                     model_name: model_name.to_string(),
                     service_tier: service_tier.map(str::to_string),
                     message_count,
@@ -5328,6 +5453,8 @@ pub async fn get_global_stats_summary(
                     cache_read_tokens,
                     reasoning_tokens,
                     cost_usd,
+                    reported_cost_tokens,
+                    estimated_cost_usd,
                     context_breakdown,
                 }
             },
@@ -5336,11 +5463,13 @@ pub async fn get_global_stats_summary(
     summary
         .model_distribution
         .sort_by_key(|model| Reverse(model.token_count));
+    summary.total_cost_usd = models_total_cost_usd(&summary.model_distribution);
 
     summary.top_projects = project_stats_map
         .into_iter()
         .map(
             |(project_name, (sessions, messages, tokens))| ProjectRanking {
+                provider_id: None,
                 project_name,
                 sessions,
                 messages,
@@ -5351,7 +5480,25 @@ pub async fn get_global_stats_summary(
     summary
         .top_projects
         .sort_by_key(|project| Reverse(project.tokens));
-    summary.top_projects.truncate(10);
+    if let Some(limit) = project_limit {
+        summary.top_projects.truncate(limit);
+    } else {
+        summary.provider_projects = provider_project_map
+            .into_iter()
+            .map(
+                |((provider, project_name), (sessions, messages, tokens))| ProjectRanking {
+                    provider_id: Some(stats_provider_id(provider).to_string()),
+                    project_name,
+                    sessions,
+                    messages,
+                    tokens,
+                },
+            )
+            .collect();
+        summary
+            .provider_projects
+            .sort_by_key(|project| Reverse(project.tokens));
+    }
 
     summary.daily_stats = daily_stats_map.into_values().collect();
     summary.daily_stats.sort_by(|a, b| a.date.cmp(&b.date));
@@ -5447,6 +5594,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: provider.map(std::string::ToString::to_string),
+            ..Default::default()
         }
     }
 
@@ -5810,6 +5958,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let usage = extract_token_usage(&msg);
@@ -5861,6 +6010,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let usage = extract_token_usage(&msg);
@@ -5910,6 +6060,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let usage = extract_token_usage(&msg);
@@ -5955,6 +6106,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let usage = extract_token_usage(&msg);
@@ -5998,6 +6150,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let usage = extract_token_usage(&msg);
@@ -6775,7 +6928,8 @@ mod tests {
             "unchanged file must be served from cache"
         );
 
-        // Append a new message: (size, mtime) changes force a re-parse.
+        // Append a new message: (size, mtime) changes force a re-parse of
+        // the appended tail only.
         let mut appender = fs::OpenOptions::new()
             .append(true)
             .open(&session_path)
@@ -6798,8 +6952,13 @@ mod tests {
         assert_eq!(third.total_messages, 3);
         assert_eq!(
             cache::test_build_count(&session_path),
-            2,
-            "appended file must be re-parsed exactly once"
+            1,
+            "appended file must not be re-parsed from the start"
+        );
+        assert_eq!(
+            cache::test_extend_count(&session_path),
+            1,
+            "appended tail must be parsed exactly once"
         );
     }
 
@@ -6849,6 +7008,15 @@ mod tests {
         assert_eq!(model.provider_id.as_deref(), Some("claude"));
         assert_eq!(model.token_count, 150);
         assert_eq!(model.cost_usd, Some(0.005));
+        // The table estimate is reported alongside, not instead of, the
+        // source cost: 100 input * $3/M + 50 output * $15/M.
+        let estimated = model.estimated_cost_usd.expect("estimated cost");
+        assert!(
+            (estimated - 0.001_05).abs() < 1e-12,
+            "estimated = {estimated}"
+        );
+        // Every token has a source cost, so the summary total is that cost.
+        assert_eq!(summary.total_cost_usd, Some(0.005));
     }
 
     #[tokio::test]
@@ -7468,6 +7636,40 @@ mod tests {
         assert_eq!(default_only.total_tokens, 11);
     }
 
+    #[test]
+    fn test_global_summary_project_limit() {
+        let dir = TempDir::new().expect("tempdir");
+        for i in 0..12 {
+            write_claude_session(dir.path(), &format!("proj-{i:02}"), 10 + i, 1);
+        }
+        let summary = |project_limit| {
+            global_stats_summary(
+                dir.path().to_string_lossy().to_string(),
+                Some(vec!["claude".to_string()]),
+                Some("billing_total".to_string()),
+                None,
+                None,
+                None,
+                project_limit,
+            )
+        };
+
+        let top = summary(Some(TOP_PROJECTS_LIMIT)).expect("top projects");
+        assert_eq!(top.top_projects.len(), TOP_PROJECTS_LIMIT);
+        assert_eq!(top.top_projects[0].project_name, "proj-11");
+        assert!(top.provider_projects.is_empty());
+
+        let all = summary(None).expect("all projects");
+        assert_eq!(all.top_projects.len(), 12);
+        assert_eq!(all.top_projects[11].project_name, "proj-00");
+        assert_eq!(all.provider_projects.len(), 12);
+        assert_eq!(all.provider_projects[0].project_name, "proj-11");
+        assert_eq!(
+            all.provider_projects[0].provider_id.as_deref(),
+            Some("claude")
+        );
+    }
+
     #[tokio::test]
     /// An invalid custom Claude path (no projects/ dir) is skipped, not fatal.
     async fn test_global_summary_skips_invalid_custom_claude_path() {
@@ -1,4 +1,4 @@
-//! In-memory per-file stats cache for the Claude-provider stats commands.
+//! In-memory per-file stats cache for the stats commands.
 //!
 //! # Design
 //!
@@ -31,8 +31,31 @@
 //! `(size, mtime)` captured **before** parsing, so a concurrent append can
 //! never be cached under a newer signature (worst case: an unnecessary
 //! rebuild on the next call). Each entry holds one slot per stats mode.
-//! In-memory only; capped at [`MAX_CACHE_ENTRIES`] per cache — beyond the cap
-//! new files are computed but not stored.
+//! The in-memory map is capped at [`MAX_CACHE_ENTRIES`] per cache — beyond
+//! the cap new files are computed but not stored in memory.
+//!
+//! ## Persistence
+//!
+//! Both caches are backed by the `SQLite` store in the sibling `store` module
+//! under the same key and signature, so a cold start (app launch or
+//! `--serve` restart) loads unchanged files' aggregates from disk instead of
+//! re-parsing them. Lookup order is memory → disk → tail extension → full
+//! build; a (re)build is written through to both.
+//!
+//! ## Incremental tail parsing
+//!
+//! Live sessions append one line at a time, which changes the signature on
+//! every refresh. Each cache therefore keeps a small LRU of
+//! [`ResumePoint`]s — the aggregate plus the builder's dedup/row-order state
+//! at the byte offset it was built to, and a checksum of all of those bytes.
+//! When a file has grown past a resume point whose checksum still matches,
+//! only the new bytes are parsed and merged into a copy of the aggregate; the
+//! prefix is hashed again but not parsed, and the checksum is chained over the
+//! tail so the next point costs no extra pass. Anything else (shrunk, same
+//! size, any changed byte in the prefix, prefix not ending in a newline,
+//! evicted point) falls back to a full build. An in-place rewrite that keeps
+//! the size within mtime resolution is not seen at all, so such writers must
+//! call [`StatsFileCache::evict`]; resume points are not persisted.
 //!
 //! ## Composition / fallback to full scan
 //!
@@ -49,29 +72,50 @@
 //! Known envelope: Claude rows missing a timestamp are stamped with
 //! `Utc::now()` by `ClaudeMessage::try_from` (pre-existing behavior), so the
 //! message pipeline's day bucket for such rows is frozen at build time — the
-//! cold scan itself is already nondeterministic for them. Provider
-//! (non-Claude) stats paths are not cached: their session paths are virtual
-//! (`opencode://` …) and carry no `(size, mtime)` identity.
-
+//! cold scan itself is already nondeterministic for them.
+//!
+//! ## Provider sessions
+//!
+//! Non-Claude sessions feeding `get_global_stats_summary` go through
+//! [`provider_stats_cache`]: the same [`FileAggregate`] shape, built from the
+//! provider's loaded `ClaudeMessage`s (mirroring
+//! `build_global_session_file_stats_from_messages`) and keyed by provider id
+//! plus session path. Their paths are often virtual (`crush://…`,
+//! `zed://…`), so entries are validated by the token from
+//! `providers::content_identity` — file/directory signatures, `SQLite`
+//! database signatures, or per-row revisions — instead of `(size, mtime)`.
+//! Sessions without an identity (Cursor, Antigravity) are computed uncached.
+//! Only the global summary reads this cache; the per-project and per-session
+//! provider views still load the session's messages on every request.
+
+use super::store::{persistent_store, FileSignature, PROVIDER_CACHE};
 use super::{
-    build_model_stats, dedup_usage_key, extract_token_usage, extract_token_usage_from_global_entry,
-    merge_model_context_usage, parse_global_stats_entry_simd, parse_raw_log_entry_simd,
-    parse_timestamp_utc, should_include_stats_entry, token_usage_has_token_fields,
-    token_usage_totals, track_skill_and_subagent_usage,
+    add_model_cost, build_model_stats, dedup_usage_key, extract_token_usage,
+    extract_token_usage_from_global_entry, merge_model_context_usage, merge_model_costs,
+    parse_global_stats_entry_simd, parse_raw_log_entry_simd, parse_timestamp_utc,
+    should_include_stats_entry, should_include_stats_message, stats_provider_id,
+    token_usage_has_token_fields, token_usage_totals, track_skill_and_subagent_usage,
     track_skill_and_subagent_usage_from_global_entry, track_tool_usage,
-    track_tool_usage_from_global_entry, ModelContextUsageMap, ModelUsageAggregate,
+    track_tool_usage_from_global_entry, ModelContextUsageMap, ModelCostMap, ModelUsageAggregate,
     ProjectSessionFileStats, SessionComparisonStats, SessionFileStats, StatsMode, StatsProvider,
     UNKNOWN_MODEL_NAME,
 };
-use crate::models::{ClaudeMessage, DailyStats, SessionTokenStats, TokenUsage, ToolUsageStats};
-use crate::utils::find_line_ranges;
+use crate::models::{
+    models_total_cost_usd, ClaudeMessage, DailyStats, SessionTokenStats, TokenUsage, ToolUsageStats,
+};
+use crate::providers::content_identity::session_content_identity;
+use crate::providers::ProviderId;
+use crate::utils::{find_line_ranges, fnv1a, fnv1a_extend, CacheCounters};
 use chrono::{DateTime, Datelike, Timelike, Utc};
+use lru::LruCache;
 use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::time::SystemTime;
 
 /// Mirrors the session-break threshold used by the scan paths.
@@ -81,8 +125,12 @@ const SESSION_BREAK_THRESHOLD_MINUTES: i64 = 120;
 /// tens of MB even for very large histories (aggregates are a few KB each).
 const MAX_CACHE_ENTRIES: usize = 16_384;
 
+/// Resume points kept per cache. Only actively growing sessions benefit, and
+/// each point holds its file's dedup keys, so the bound stays small.
+const RESUME_POINT_CAPACITY: usize = 64;
+
 /// Filter-independent aggregates for one UTC day (or the undated bucket).
-#[derive(Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub(super) struct DayBucket {
     message_count: u32,
     input_tokens: u64,
@@ -97,7 +145,7 @@ pub(super) struct DayBucket {
     /// breakdowns. Project/session composers may consume this map directly.
     model_usage: HashMap<String, ModelUsageAggregate>,
     model_context_usage: ModelContextUsageMap,
-    model_costs: HashMap<String, f64>,
+    model_costs: ModelCostMap,
     /// hour-of-day → (message count, deduped tokens).
     hourly_activity: HashMap<u8, (u32, u64)>,
     first_ts: Option<DateTime<Utc>>,
@@ -128,7 +176,9 @@ impl DayBucket {
 }
 
 /// Date-filter-independent per-file aggregate (one per parse pipeline/mode).
-#[derive(Default)]
+/// Persisted as JSON by the `store` module — bump its `SCHEMA_VERSION` when
+/// this shape (or [`DayBucket`]'s) changes.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub(super) struct FileAggregate {
     /// UTC date ("%Y-%m-%d") → bucket; `BTreeMap` keeps chronological order
     /// for run merging.
@@ -141,6 +191,9 @@ pub(super) struct FileAggregate {
     /// A deduped usage key was first seen in one bucket and repeated in
     /// another; filtered composition would misattribute its tokens.
     dedup_spans_buckets: bool,
+    /// Provider sessions only: the session loaded no messages at all. The
+    /// uncached path skips such sessions instead of reporting zero rows.
+    empty_source: bool,
 }
 
 impl FileAggregate {
@@ -159,6 +212,69 @@ pub(super) enum Composed<T> {
     NeedsFullScan,
 }
 
+/// Parse pipeline a cache's aggregates are built with.
+#[derive(Clone, Copy)]
+pub(super) enum Pipeline {
+    /// Lightweight `GlobalStatsLogEntry` rows (`get_global_stats_summary`).
+    Global,
+    /// `RawLogEntry → ClaudeMessage` rows (project/session stats).
+    Message,
+}
+
+impl Pipeline {
+    fn ingest(
+        self,
+        data: &[u8],
+        mode: StatsMode,
+        aggregate: &mut FileAggregate,
+        state: &mut BuildState,
+    ) {
+        match self {
+            Self::Global => ingest_global_rows(data, mode, aggregate, state),
+            Self::Message => ingest_message_rows(data, mode, aggregate, state),
+        }
+    }
+}
+
+/// Builder bookkeeping that must survive between a build and a later tail
+/// extension: the #283 dedup indexes and the file-order row counter.
+#[derive(Default)]
+pub(super) struct BuildState {
+    first_bucket_by_key: HashMap<String, Option<String>>,
+    first_cost_bucket_by_key: HashMap<String, Option<String>>,
+    row_seq: u64,
+}
+
+/// Where a later build may resume: the aggregate and builder state after
+/// parsing exactly `len` bytes, which ended on a line boundary.
+struct ResumePoint {
+    len: usize,
+    /// [`fnv1a`] of those `len` bytes.
+    checksum: u64,
+    aggregate: Arc<FileAggregate>,
+    state: BuildState,
+}
+
+/// Resume point for a freshly parsed `data` whose [`fnv1a`] is `checksum`,
+/// or `None` when the data ends mid-line: that partial row may still be
+/// completed by the writer, so the next build must re-read it from the start.
+fn resume_point_for(
+    data: &[u8],
+    checksum: u64,
+    aggregate: &Arc<FileAggregate>,
+    state: BuildState,
+) -> Option<ResumePoint> {
+    if data.last().is_some_and(|&byte| byte != b'\n') {
+        return None;
+    }
+    Some(ResumePoint {
+        len: data.len(),
+        checksum,
+        aggregate: Arc::clone(aggregate),
+        state,
+    })
+}
+
 struct CacheEntry {
     size: u64,
     mtime: Option<SystemTime>,
@@ -168,7 +284,12 @@ struct CacheEntry {
 
 /// Process-global cache of per-file aggregates, validated by (size, mtime).
 pub(super) struct StatsFileCache {
+    /// Discriminator for this cache's rows in the persistent store.
+    name: &'static str,
+    pipeline: Pipeline,
     entries: Mutex<HashMap<PathBuf, CacheEntry>>,
+    /// Keyed by (canonical path, mode slot).
+    resume_points: Mutex<LruCache<(PathBuf, usize), ResumePoint>>,
 }
 
 fn mode_slot(mode: StatsMode) -> usize {
@@ -181,44 +302,54 @@ fn mode_slot(mode: StatsMode) -> usize {
 static GLOBAL_STATS_CACHE: OnceLock<StatsFileCache> = OnceLock::new();
 static MESSAGE_STATS_CACHE: OnceLock<StatsFileCache> = OnceLock::new();
 
+/// Lookups across every stats cache: a hit is served from memory or the
+/// persistent store, a miss parses the file (fully or its appended tail).
+static STATS_CACHE_COUNTERS: CacheCounters = CacheCounters::new();
+
+/// `(hits, misses)` of the stats caches since process start.
+pub fn stats_cache_counters() -> (u64, u64) {
+    STATS_CACHE_COUNTERS.snapshot()
+}
+
 /// Cache for the lightweight global-stats parse pipeline.
 pub(super) fn global_stats_cache() -> &'static StatsFileCache {
-    GLOBAL_STATS_CACHE.get_or_init(StatsFileCache::new)
+    GLOBAL_STATS_CACHE.get_or_init(|| StatsFileCache::new("global", Pipeline::Global))
 }
 
 /// Cache for the `RawLogEntry → ClaudeMessage` parse pipeline.
 pub(super) fn message_stats_cache() -> &'static StatsFileCache {
-    MESSAGE_STATS_CACHE.get_or_init(StatsFileCache::new)
+    MESSAGE_STATS_CACHE.get_or_init(|| StatsFileCache::new("message", Pipeline::Message))
 }
 
 impl StatsFileCache {
-    fn new() -> Self {
+    fn new(name: &'static str, pipeline: Pipeline) -> Self {
         Self {
+            name,
+            pipeline,
             entries: Mutex::new(HashMap::new()),
+            resume_points: Mutex::new(LruCache::new(
+                NonZeroUsize::new(RESUME_POINT_CAPACITY).expect("non-zero"),
+            )),
         }
     }
 
-    /// Return the cached aggregate for `path`/`mode`, rebuilding via `build`
-    /// when the entry is missing or the file's (size, mtime) changed.
-    pub(super) fn get_or_build<F>(
-        &self,
-        path: &Path,
-        mode: StatsMode,
-        build: F,
-    ) -> Option<Arc<FileAggregate>>
-    where
-        F: FnOnce() -> Option<FileAggregate>,
-    {
+    /// Return the cached aggregate for `path`/`mode`, loading it from the
+    /// persistent store, extending it with an appended tail, or rebuilding
+    /// it when the in-memory entry is missing or the file's (size, mtime)
+    /// changed.
+    pub(super) fn get_or_build(&self, path: &Path, mode: StatsMode) -> Option<Arc<FileAggregate>> {
         // Files that cannot be canonicalized or stat'ed (e.g. vanished
         // mid-scan) are computed without caching.
         let Ok(key) = fs::canonicalize(path) else {
-            return build().map(Arc::new);
+            STATS_CACHE_COUNTERS.record_misses(1);
+            return build_file_aggregate(path, self.pipeline, mode).map(Arc::new);
         };
         // The signature is taken BEFORE parsing: a concurrent append after
         // this stat makes the stored entry look stale on the next call,
         // never the other way around.
         let Ok(metadata) = fs::metadata(&key) else {
-            return build().map(Arc::new);
+            STATS_CACHE_COUNTERS.record_misses(1);
+            return build_file_aggregate(path, self.pipeline, mode).map(Arc::new);
         };
         let size = metadata.len();
         let mtime = metadata.modified().ok();
@@ -229,15 +360,28 @@ impl StatsFileCache {
             if let Some(entry) = entries.get(&key) {
                 if entry.size == size && entry.mtime == mtime {
                     if let Some(aggregate) = &entry.slots[slot] {
+                        STATS_CACHE_COUNTERS.record_hits(1);
                         return Some(Arc::clone(aggregate));
                     }
                 }
             }
         }
 
-        #[cfg(test)]
-        note_build(&key);
-        let built = Arc::new(build()?);
+        let signature = FileSignature { size, mtime }.token();
+        let store_key = key.to_string_lossy();
+        let store = persistent_store();
+        let loaded = store.and_then(|store| store.load(self.name, &store_key, slot, &signature));
+        let built = if let Some(loaded) = loaded {
+            STATS_CACHE_COUNTERS.record_hits(1);
+            Arc::new(loaded)
+        } else {
+            STATS_CACHE_COUNTERS.record_misses(1);
+            let built = self.extend_or_build(&key, slot, mode)?;
+            if let Some(store) = store {
+                store.save(self.name, &store_key, slot, &signature, &built);
+            }
+            built
+        };
 
         let mut entries = self.lock_entries();
         match entries.get_mut(&key) {
@@ -262,9 +406,192 @@ impl StatsFileCache {
         Some(built)
     }
 
+    /// Parse only the bytes appended since this file's resume point when
+    /// the already-parsed prefix is unchanged; otherwise parse the whole
+    /// file. Either way a fresh resume point is recorded.
+    #[allow(unsafe_code)] // mmap read-only access, same pattern as the scan paths
+    fn extend_or_build(
+        &self,
+        key: &Path,
+        slot: usize,
+        mode: StatsMode,
+    ) -> Option<Arc<FileAggregate>> {
+        let file = fs::File::open(key).ok()?;
+        // SAFETY: read-only mmap; the file handle outlives the map. Session
+        // files are append-only, and concurrent growth is caught by the
+        // (size, mtime) signature taken before this builder runs.
+        let mmap = unsafe { Mmap::map(&file) }.ok()?;
+        let data: &[u8] = &mmap;
+
+        // Taken out of the LRU: a concurrent extension of the same file
+        // simply misses and does a full build.
+        let resume_key = (key.to_path_buf(), slot);
+        let resumable = self
+            .lock_resume_points()
+            .pop(&resume_key)
+            .filter(|point| point.len < data.len())
+            .filter(|point| fnv1a(&data[..point.len]) == point.checksum);
+
+        let (aggregate, state, checksum) = if let Some(point) = resumable {
+            #[cfg(test)]
+            note_extend(key);
+            let tail = &data[point.len..];
+            let mut aggregate = Arc::unwrap_or_clone(point.aggregate);
+            let mut state = point.state;
+            self.pipeline.ingest(tail, mode, &mut aggregate, &mut state);
+            (aggregate, state, fnv1a_extend(point.checksum, tail))
+        } else {
+            #[cfg(test)]
+            note_build(key);
+            let mut aggregate = FileAggregate::default();
+            let mut state = BuildState::default();
+            self.pipeline.ingest(data, mode, &mut aggregate, &mut state);
+            (aggregate, state, fnv1a(data))
+        };
+
+        let aggregate = Arc::new(aggregate);
+        if let Some(point) = resume_point_for(data, checksum, &aggregate, state) {
+            self.lock_resume_points().put(resume_key, point);
+        }
+        Some(aggregate)
+    }
+
+    /// Forget everything cached for `path` (memory, resume points, and the
+    /// persistent store). Required after an in-place rewrite that can keep the
+    /// same size within mtime resolution, which the signature cannot detect.
+    pub(super) fn evict(&self, path: &Path) {
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.lock_entries().remove(&key);
+        {
+            let mut resume_points = self.lock_resume_points();
+            for slot in 0..2 {
+                resume_points.pop(&(key.clone(), slot));
+            }
+        }
+        if let Some(store) = persistent_store() {
+            store.remove(self.name, &key.to_string_lossy());
+        }
+    }
+
     /// Lock the entry map, recovering from a poisoned lock — a panic in an
     /// unrelated parse must not disable stats for the rest of the process.
-    fn lock_entries(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, CacheEntry>> {
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<PathBuf, CacheEntry>> {
+        match self.entries.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn lock_resume_points(&self) -> MutexGuard<'_, LruCache<(PathBuf, usize), ResumePoint>> {
+        match self.resume_points.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+struct ProviderCacheEntry {
+    /// Content identity from `providers::content_identity` captured before
+    /// the messages were loaded.
+    identity: String,
+    slots: [Option<Arc<FileAggregate>>; 2],
+}
+
+/// Process-global cache of provider (non-Claude) session aggregates, keyed
+/// by `"<provider id>:<session path>"` and validated by the provider's
+/// content identity instead of a file signature.
+pub(super) struct ProviderStatsCache {
+    entries: Mutex<HashMap<String, ProviderCacheEntry>>,
+}
+
+static PROVIDER_STATS_CACHE: OnceLock<ProviderStatsCache> = OnceLock::new();
+
+/// Cache for provider sessions, built from their loaded `ClaudeMessage`s.
+pub(super) fn provider_stats_cache() -> &'static ProviderStatsCache {
+    PROVIDER_STATS_CACHE.get_or_init(|| ProviderStatsCache {
+        entries: Mutex::new(HashMap::new()),
+    })
+}
+
+impl ProviderStatsCache {
+    /// Return the aggregate for one provider session, calling `load` only
+    /// when the session's content identity changed (or cannot be computed,
+    /// in which case nothing is cached). `None` when the session has no
+    /// messages, matching the uncached path. Messages loaded on the way are
+    /// left in `loaded`, so a caller falling back to a full scan does not
+    /// load them a second time.
+    pub(super) fn get_or_build(
+        &self,
+        provider: StatsProvider,
+        session_path: &str,
+        mode: StatsMode,
+        loaded: &mut Option<Vec<ClaudeMessage>>,
+        load: impl FnOnce() -> Vec<ClaudeMessage>,
+    ) -> Option<Arc<FileAggregate>> {
+        let provider_id = stats_provider_id(provider);
+        // Taken BEFORE loading, like the file signature: a write that lands
+        // while loading makes the entry look stale next time, never fresh.
+        let identity = ProviderId::parse(provider_id)
+            .and_then(|id| session_content_identity(&id, session_path));
+        let Some(identity) = identity else {
+            STATS_CACHE_COUNTERS.record_misses(1);
+            let aggregate = build_provider_aggregate(loaded.get_or_insert_with(load), mode);
+            return (!aggregate.empty_source).then(|| Arc::new(aggregate));
+        };
+        let key = format!("{provider_id}:{session_path}");
+        let slot = mode_slot(mode);
+
+        let cached = {
+            let entries = self.lock_entries();
+            entries
+                .get(&key)
+                .filter(|entry| entry.identity == identity)
+                .and_then(|entry| entry.slots[slot].clone())
+        };
+        let aggregate = if let Some(aggregate) = cached {
+            STATS_CACHE_COUNTERS.record_hits(1);
+            aggregate
+        } else {
+            let store = persistent_store();
+            let stored = store.and_then(|store| store.load(PROVIDER_CACHE, &key, slot, &identity));
+            let aggregate = if let Some(stored) = stored {
+                STATS_CACHE_COUNTERS.record_hits(1);
+                Arc::new(stored)
+            } else {
+                STATS_CACHE_COUNTERS.record_misses(1);
+                let built = build_provider_aggregate(loaded.get_or_insert_with(load), mode);
+                if let Some(store) = store {
+                    store.save(PROVIDER_CACHE, &key, slot, &identity, &built);
+                }
+                Arc::new(built)
+            };
+            self.insert(key, identity, slot, &aggregate);
+            aggregate
+        };
+        (!aggregate.empty_source).then_some(aggregate)
+    }
+
+    fn insert(&self, key: String, identity: String, slot: usize, aggregate: &Arc<FileAggregate>) {
+        let mut entries = self.lock_entries();
+        match entries.get_mut(&key) {
+            Some(entry) => {
+                if entry.identity != identity {
+                    entry.identity = identity;
+                    entry.slots = [None, None];
+                }
+                entry.slots[slot] = Some(Arc::clone(aggregate));
+            }
+            None => {
+                if entries.len() < MAX_CACHE_ENTRIES {
+                    let mut slots = [None, None];
+                    slots[slot] = Some(Arc::clone(aggregate));
+                    entries.insert(key, ProviderCacheEntry { identity, slots });
+                }
+            }
+        }
+    }
+
+    fn lock_entries(&self) -> MutexGuard<'_, HashMap<String, ProviderCacheEntry>> {
         match self.entries.lock() {
             Ok(guard) => guard,
             Err(poisoned) => poisoned.into_inner(),
@@ -279,6 +606,13 @@ fn note_build(key: &Path) {
     }
 }
 
+#[cfg(test)]
+fn note_extend(key: &Path) {
+    if let Ok(mut counts) = extend_counts().lock() {
+        *counts.entry(key.to_path_buf()).or_insert(0) += 1;
+    }
+}
+
 /// Dedup-aware token totals that also record which bucket first claimed the
 /// usage key (#283). A duplicate landing in a different bucket than its
 /// first occurrence makes filtered composition unsound — the cold scan would
@@ -411,45 +745,54 @@ impl DayBucket {
     }
 }
 
-/// Sort each day's timestamps and store gap-split activity runs.
+/// Merge each day's newly ingested timestamps into its gap-split activity
+/// runs (empty for a fresh build).
 fn finalize_runs(
     aggregate: &mut FileAggregate,
     day_timestamps: HashMap<String, Vec<DateTime<Utc>>>,
 ) {
-    for (date, mut timestamps) in day_timestamps {
-        timestamps.sort_unstable();
+    for (date, timestamps) in day_timestamps {
         if let Some(bucket) = aggregate.days.get_mut(&date) {
-            bucket.runs = split_into_runs(&timestamps);
+            bucket.runs = merge_into_runs(&bucket.runs, timestamps);
         }
     }
 }
 
-/// Split sorted timestamps into maximal runs whose internal gaps stay within
-/// the session-break threshold.
-fn split_into_runs(sorted: &[DateTime<Utc>]) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
-    let Some(&first) = sorted.first() else {
+/// Merge timestamps into existing activity runs, splitting only where the
+/// gap exceeds the session-break threshold. Existing runs already have
+/// internal gaps within the threshold and new points can only shrink gaps,
+/// so the result equals splitting the union of all timestamps from scratch.
+fn merge_into_runs(
+    runs: &[(DateTime<Utc>, DateTime<Utc>)],
+    timestamps: Vec<DateTime<Utc>>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = runs
+        .iter()
+        .copied()
+        .chain(timestamps.into_iter().map(|ts| (ts, ts)))
+        .collect();
+    intervals.sort_unstable();
+    let mut intervals = intervals.into_iter();
+    let Some((mut run_start, mut run_end)) = intervals.next() else {
         return Vec::new();
     };
-    let mut runs = Vec::new();
-    let mut run_start = first;
-    let mut previous = first;
-    for &ts in &sorted[1..] {
-        if (ts - previous).num_minutes() > SESSION_BREAK_THRESHOLD_MINUTES {
-            runs.push((run_start, previous));
-            run_start = ts;
+    let mut merged = Vec::new();
+    for (start, end) in intervals {
+        if (start - run_end).num_minutes() > SESSION_BREAK_THRESHOLD_MINUTES {
+            merged.push((run_start, run_end));
+            run_start = start;
         }
-        previous = ts;
+        run_end = run_end.max(end);
     }
-    runs.push((run_start, previous));
-    runs
+    merged.push((run_start, run_end));
+    merged
 }
 
-/// Build the aggregate with the lightweight global-stats parse pipeline.
-/// Mirrors `scan_session_file_for_global_stats` row handling, minus the date
-/// filter (applied at composition time).
+/// Parse a whole file into a fresh aggregate (no caching, no resume point).
 #[allow(unsafe_code)] // mmap read-only access, same pattern as the scan paths
-pub(super) fn build_global_file_aggregate(
+fn build_file_aggregate(
     session_path: &Path,
+    pipeline: Pipeline,
     mode: StatsMode,
 ) -> Option<FileAggregate> {
     let file = fs::File::open(session_path).ok()?;
@@ -459,13 +802,41 @@ pub(super) fn build_global_file_aggregate(
     let mmap = unsafe { Mmap::map(&file) }.ok()?;
 
     let mut aggregate = FileAggregate::default();
-    let mut first_bucket_by_key: HashMap<String, Option<String>> = HashMap::new();
-    let mut first_cost_bucket_by_key: HashMap<String, Option<String>> = HashMap::new();
+    pipeline.ingest(&mmap, mode, &mut aggregate, &mut BuildState::default());
+    Some(aggregate)
+}
+
+/// Build the aggregate with the lightweight global-stats parse pipeline.
+#[cfg(test)]
+pub(super) fn build_global_file_aggregate(
+    session_path: &Path,
+    mode: StatsMode,
+) -> Option<FileAggregate> {
+    build_file_aggregate(session_path, Pipeline::Global, mode)
+}
+
+/// Build the aggregate with the `RawLogEntry → ClaudeMessage` pipeline.
+#[cfg(test)]
+pub(super) fn build_message_file_aggregate(
+    session_path: &Path,
+    mode: StatsMode,
+) -> Option<FileAggregate> {
+    build_file_aggregate(session_path, Pipeline::Message, mode)
+}
+
+/// Fold `data` (whole lines) into `aggregate` with the lightweight
+/// global-stats parse pipeline. Mirrors `scan_session_file_for_global_stats`
+/// row handling, minus the date filter (applied at composition time).
+fn ingest_global_rows(
+    data: &[u8],
+    mode: StatsMode,
+    aggregate: &mut FileAggregate,
+    state: &mut BuildState,
+) {
     let mut day_timestamps: HashMap<String, Vec<DateTime<Utc>>> = HashMap::new();
-    let mut row_seq = 0u64;
 
-    for (start, end) in find_line_ranges(&mmap) {
-        let mut line_bytes = mmap[start..end].to_vec();
+    for (start, end) in find_line_ranges(data) {
+        let mut line_bytes = data[start..end].to_vec();
         let Some(entry) = parse_global_stats_entry_simd(&mut line_bytes) else {
             continue;
         };
@@ -479,14 +850,14 @@ pub(super) fn build_global_file_aggregate(
         let raw_timestamp = entry.timestamp.as_deref().unwrap_or("");
         let parsed_ts = parse_timestamp_utc(raw_timestamp);
         let date = parsed_ts.map(|ts| ts.format("%Y-%m-%d").to_string());
-        row_seq += 1;
+        state.row_seq += 1;
 
         // The global pipeline dedups without a session-id prefix (#283).
         let message_id = entry.message.as_ref().and_then(|m| m.id.as_deref());
         let uuid = entry.uuid.as_deref().unwrap_or("");
         let dedup_key = dedup_usage_key("", message_id, uuid);
         let totals = bucketed_dedup_totals(
-            &mut first_bucket_by_key,
+            &mut state.first_bucket_by_key,
             dedup_key,
             date.as_deref(),
             &usage,
@@ -496,7 +867,7 @@ pub(super) fn build_global_file_aggregate(
             .cost_usd
             .or_else(|| entry.message.as_ref().and_then(|message| message.cost_usd));
         let deduped_source_cost = bucketed_dedup_source_cost(
-            &mut first_cost_bucket_by_key,
+            &mut state.first_cost_bucket_by_key,
             dedup_usage_key("", message_id, uuid),
             date.as_deref(),
             source_cost,
@@ -515,7 +886,7 @@ pub(super) fn build_global_file_aggregate(
             totals,
             parsed_ts.map(|ts| (ts, raw_timestamp)),
             None,
-            row_seq,
+            state.row_seq,
         );
         let model_name = entry
             .message
@@ -542,33 +913,24 @@ pub(super) fn build_global_file_aggregate(
         );
     }
 
-    finalize_runs(&mut aggregate, day_timestamps);
-    Some(aggregate)
+    finalize_runs(aggregate, day_timestamps);
 }
 
-/// Build the aggregate with the `RawLogEntry → ClaudeMessage` pipeline.
-/// Mirrors the row handling shared by `scan_session_file_for_project_stats`,
-/// `scan_session_token_stats`, and `scan_session_file_for_comparison`, minus
-/// the date filter (applied at composition time).
-#[allow(unsafe_code)] // mmap read-only access, same pattern as the scan paths
-pub(super) fn build_message_file_aggregate(
-    session_path: &Path,
+/// Fold `data` (whole lines) into `aggregate` with the
+/// `RawLogEntry → ClaudeMessage` pipeline. Mirrors the row handling shared by
+/// `scan_session_file_for_project_stats`, `scan_session_token_stats`, and
+/// `scan_session_file_for_comparison`, minus the date filter (applied at
+/// composition time).
+fn ingest_message_rows(
+    data: &[u8],
     mode: StatsMode,
-) -> Option<FileAggregate> {
-    let file = fs::File::open(session_path).ok()?;
-    // SAFETY: read-only mmap; the file handle outlives the map. Session
-    // files are append-only, and concurrent growth is caught by the
-    // (size, mtime) signature taken before this builder runs.
-    let mmap = unsafe { Mmap::map(&file) }.ok()?;
-
-    let mut aggregate = FileAggregate::default();
-    let mut first_bucket_by_key: HashMap<String, Option<String>> = HashMap::new();
-    let mut first_cost_bucket_by_key: HashMap<String, Option<String>> = HashMap::new();
+    aggregate: &mut FileAggregate,
+    state: &mut BuildState,
+) {
     let mut day_timestamps: HashMap<String, Vec<DateTime<Utc>>> = HashMap::new();
-    let mut row_seq = 0u64;
 
-    for (start, end) in find_line_ranges(&mmap) {
-        let mut line_bytes = mmap[start..end].to_vec();
+    for (start, end) in find_line_ranges(data) {
+        let mut line_bytes = data[start..end].to_vec();
         let Some(log_entry) = parse_raw_log_entry_simd(&mut line_bytes) else {
             continue;
         };
@@ -592,7 +954,7 @@ pub(super) fn build_message_file_aggregate(
 
         let parsed_ts = parse_timestamp_utc(&message.timestamp);
         let date = parsed_ts.map(|ts| ts.format("%Y-%m-%d").to_string());
-        row_seq += 1;
+        state.row_seq += 1;
 
         let dedup_key = dedup_usage_key(
             &message.session_id,
@@ -600,14 +962,14 @@ pub(super) fn build_message_file_aggregate(
             &message.uuid,
         );
         let totals = bucketed_dedup_totals(
-            &mut first_bucket_by_key,
+            &mut state.first_bucket_by_key,
             dedup_key,
             date.as_deref(),
             &usage,
             &mut aggregate.dedup_spans_buckets,
         );
         let deduped_source_cost = bucketed_dedup_source_cost(
-            &mut first_cost_bucket_by_key,
+            &mut state.first_cost_bucket_by_key,
             dedup_usage_key(
                 &message.session_id,
                 message.message_id.as_deref(),
@@ -630,7 +992,7 @@ pub(super) fn build_message_file_aggregate(
             totals,
             parsed_ts.map(|ts| (ts, message.timestamp.as_str())),
             Some(&message.session_id),
-            row_seq,
+            state.row_seq,
         );
         let model_name = message.model.as_deref().unwrap_or(UNKNOWN_MODEL_NAME);
         if message.model.is_some() || totals.5 > 0 || deduped_source_cost.is_some() {
@@ -650,8 +1012,94 @@ pub(super) fn build_message_file_aggregate(
         );
     }
 
+    finalize_runs(aggregate, day_timestamps);
+}
+
+/// Fold a provider session's loaded messages into a fresh aggregate.
+/// Mirrors `build_global_session_file_stats_from_messages` row handling,
+/// minus the date filter (applied at composition time): plain model-name
+/// keys, session-scoped dedup, no context-tier breakdown.
+fn build_provider_aggregate(messages: &[ClaudeMessage], mode: StatsMode) -> FileAggregate {
+    let mut aggregate = FileAggregate {
+        empty_source: messages.is_empty(),
+        ..Default::default()
+    };
+    let mut state = BuildState::default();
+    let mut day_timestamps: HashMap<String, Vec<DateTime<Utc>>> = HashMap::new();
+
+    for message in messages {
+        if !should_include_stats_message(message, mode) {
+            continue;
+        }
+        let usage = extract_token_usage(message);
+        let parsed_ts = parse_timestamp_utc(&message.timestamp);
+        let date = parsed_ts.map(|ts| ts.format("%Y-%m-%d").to_string());
+        state.row_seq += 1;
+
+        let totals = bucketed_dedup_totals(
+            &mut state.first_bucket_by_key,
+            dedup_usage_key(
+                &message.session_id,
+                message.message_id.as_deref(),
+                &message.uuid,
+            ),
+            date.as_deref(),
+            &usage,
+            &mut aggregate.dedup_spans_buckets,
+        );
+        let deduped_source_cost = bucketed_dedup_source_cost(
+            &mut state.first_cost_bucket_by_key,
+            dedup_usage_key(
+                &message.session_id,
+                message.message_id.as_deref(),
+                &message.uuid,
+            ),
+            date.as_deref(),
+            message.cost_usd,
+            &mut aggregate.dedup_spans_buckets,
+        );
+
+        if let Some(ts) = parsed_ts {
+            day_timestamps
+                .entry(date.clone().expect("dated row has a date key"))
+                .or_default()
+                .push(ts);
+        }
+
+        let bucket = aggregate.bucket_mut(date.as_deref());
+        bucket.record_row(
+            totals,
+            parsed_ts.map(|ts| (ts, message.timestamp.as_str())),
+            None,
+            state.row_seq,
+        );
+        if message.model.is_some() || totals.5 > 0 || deduped_source_cost.is_some() {
+            let model_name = message.model.as_deref().unwrap_or(UNKNOWN_MODEL_NAME);
+            let entry = bucket
+                .model_usage
+                .entry(model_name.to_string())
+                .or_insert((0, 0, 0, 0, 0, 0, 0));
+            entry.0 += 1;
+            entry.1 += totals.5;
+            entry.2 += totals.0;
+            entry.3 += totals.1;
+            entry.4 += totals.2;
+            entry.5 += totals.3;
+            entry.6 += totals.4;
+            if let Some(cost_usd) = deduped_source_cost {
+                add_model_cost(&mut bucket.model_costs, model_name, cost_usd, totals.5);
+            }
+        }
+        track_tool_usage(message, &mut bucket.tool_usage);
+        track_skill_and_subagent_usage(
+            message,
+            &mut bucket.skill_usage,
+            &mut bucket.subagent_usage,
+        );
+    }
+
     finalize_runs(&mut aggregate, day_timestamps);
-    Some(aggregate)
+    aggregate
 }
 
 /// Day buckets answering a date filter, or `None` when the filter cannot be
@@ -757,6 +1205,7 @@ fn first_included_session_id<'a>(
 /// Compose the global-stats per-file result from the cached aggregate.
 pub(super) fn compose_global(
     aggregate: &FileAggregate,
+    provider: StatsProvider,
     project_name: String,
     s_limit: Option<&DateTime<Utc>>,
     e_limit: Option<&DateTime<Utc>>,
@@ -767,7 +1216,7 @@ pub(super) fn compose_global(
 
     let mut stats = SessionFileStats {
         project_name,
-        provider: StatsProvider::Claude,
+        provider,
         ..Default::default()
     };
     let mut runs: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
@@ -785,9 +1234,7 @@ pub(super) fn compose_global(
         merge_counter_map(&mut stats.subagent_usage, &bucket.subagent_usage);
         merge_model_map(&mut stats.model_usage, &bucket.model_usage);
         merge_model_context_usage(&mut stats.model_context_usage, &bucket.model_context_usage);
-        for (model, cost) in &bucket.model_costs {
-            *stats.model_costs.entry(model.clone()).or_insert(0.0) += cost;
-        }
+        merge_model_costs(&mut stats.model_costs, &bucket.model_costs);
 
         stats.daily_stats.insert(
             (*date).clone(),
@@ -843,9 +1290,7 @@ pub(super) fn compose_global(
         merge_counter_map(&mut stats.subagent_usage, &undated.subagent_usage);
         merge_model_map(&mut stats.model_usage, &undated.model_usage);
         merge_model_context_usage(&mut stats.model_context_usage, &undated.model_context_usage);
-        for (model, cost) in &undated.model_costs {
-            *stats.model_costs.entry(model.clone()).or_insert(0.0) += cost;
-        }
+        merge_model_costs(&mut stats.model_costs, &undated.model_costs);
     }
 
     stats.session_duration_minutes = merged_active_minutes(&runs);
@@ -877,9 +1322,7 @@ pub(super) fn compose_project(
         merge_counter_map(&mut stats.subagent_usage, &bucket.subagent_usage);
         merge_model_map(&mut stats.model_usage, &bucket.model_usage);
         merge_model_context_usage(&mut stats.model_context_usage, &bucket.model_context_usage);
-        for (model, cost) in &bucket.model_costs {
-            *stats.model_costs.entry(model.clone()).or_insert(0.0) += cost;
-        }
+        merge_model_costs(&mut stats.model_costs, &bucket.model_costs);
 
         stats.session_dates.insert((*date).clone());
         stats.daily_stats.insert(
@@ -923,9 +1366,7 @@ pub(super) fn compose_project(
         merge_counter_map(&mut stats.subagent_usage, &undated.subagent_usage);
         merge_model_map(&mut stats.model_usage, &undated.model_usage);
         merge_model_context_usage(&mut stats.model_context_usage, &undated.model_context_usage);
-        for (model, cost) in &undated.model_costs {
-            *stats.model_costs.entry(model.clone()).or_insert(0.0) += cost;
-        }
+        merge_model_costs(&mut stats.model_costs, &undated.model_costs);
     }
 
     if stats.total_messages == 0 {
@@ -961,7 +1402,7 @@ pub(super) fn compose_session_token(
     let mut tool_usage: HashMap<String, (u32, u32)> = HashMap::new();
     let mut model_usage: HashMap<String, ModelUsageAggregate> = HashMap::new();
     let mut model_context_usage: ModelContextUsageMap = HashMap::new();
-    let mut model_costs: HashMap<String, f64> = HashMap::new();
+    let mut model_costs: ModelCostMap = HashMap::new();
     let mut first: Option<(DateTime<Utc>, &String)> = None;
     let mut last: Option<(DateTime<Utc>, &String)> = None;
 
@@ -984,9 +1425,7 @@ pub(super) fn compose_session_token(
         merge_counter_map(&mut tool_usage, &bucket.tool_usage);
         merge_model_map(&mut model_usage, &bucket.model_usage);
         merge_model_context_usage(&mut model_context_usage, &bucket.model_context_usage);
-        for (model, cost) in &bucket.model_costs {
-            *model_costs.entry(model.clone()).or_insert(0.0) += cost;
-        }
+        merge_model_costs(&mut model_costs, &bucket.model_costs);
 
         if let (Some(ts), Some(raw)) = (bucket.first_ts, bucket.first_ts_raw.as_ref()) {
             if first.map_or(true, |(current, _)| ts < current) {
@@ -1010,6 +1449,12 @@ pub(super) fn compose_session_token(
         + total_cache_read_tokens
         + total_reasoning_tokens;
 
+    let model_distribution = build_model_stats(
+        StatsProvider::Claude,
+        model_usage,
+        model_context_usage,
+        model_costs,
+    );
     Composed::Ready(Some(SessionTokenStats {
         session_id: session_id.to_string(),
         project_name,
@@ -1027,12 +1472,8 @@ pub(super) fn compose_session_token(
             .map(|(_, raw)| raw.clone())
             .unwrap_or_else(|| "unknown".to_string()),
         summary: aggregate.summary.clone(),
-        model_distribution: build_model_stats(
-            StatsProvider::Claude,
-            model_usage,
-            model_context_usage,
-            model_costs,
-        ),
+        total_cost_usd: models_total_cost_usd(&model_distribution),
+        model_distribution,
         // The scan path emits the map unsorted; keep that shape.
         most_used_tools: tool_usage
             .into_iter()
@@ -1142,13 +1583,30 @@ fn build_counts() -> &'static Mutex<HashMap<PathBuf, u64>> {
     BUILD_COUNTS.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+/// Number of tail extensions recorded for `path` (canonicalized), across
+/// both caches. Test-only observability for the incremental path.
+#[cfg(test)]
+pub(super) fn test_extend_count(path: &Path) -> u64 {
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    extend_counts()
+        .lock()
+        .map(|counts| counts.get(&key).copied().unwrap_or(0))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+fn extend_counts() -> &'static Mutex<HashMap<PathBuf, u64>> {
+    static EXTEND_COUNTS: OnceLock<Mutex<HashMap<PathBuf, u64>>> = OnceLock::new();
+    EXTEND_COUNTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::{
-        claude_session_project_name, process_session_file_for_global_stats,
-        process_session_file_for_project_stats, scan_session_file_for_comparison,
-        scan_session_file_for_global_stats, scan_session_file_for_project_stats,
-        scan_session_token_stats,
+        build_global_session_file_stats_from_messages, claude_session_project_name,
+        process_session_file_for_global_stats, process_session_file_for_project_stats,
+        scan_session_file_for_comparison, scan_session_file_for_global_stats,
+        scan_session_file_for_project_stats, scan_session_token_stats,
     };
     use super::*;
     use crate::models::TokenDistribution;
@@ -1323,8 +1781,9 @@ mod tests {
 
     #[test]
     /// Repeat calls on an unchanged file are served from cache (single build);
-    /// appending to the file changes (size, mtime) and forces a re-parse that
-    /// reflects the new data, while a sibling file stays cached.
+    /// appending to the file changes (size, mtime) and parses only the new
+    /// tail, which is reflected in the result, while a sibling file stays
+    /// cached.
     fn test_cache_serves_unchanged_file_and_reparses_after_append() {
         let temp_dir = TempDir::new().expect("temp dir");
         let project_dir = temp_dir.path().join("demo-project");
@@ -1366,7 +1825,7 @@ mod tests {
         );
         assert_project_stats_eq(&second_a, &first_a);
 
-        // Append to file a: (size, mtime) changes, so it must re-parse.
+        // Append to file a: (size, mtime) changes, so the tail is parsed.
         let mut appender = OpenOptions::new()
             .append(true)
             .open(&file_a)
@@ -1383,7 +1842,12 @@ mod tests {
             .expect("stats for mutated file a");
         let _third_b = process_session_file_for_project_stats(&file_b, mode, None, None)
             .expect("stats for file b (still cached)");
-        assert_eq!(test_build_count(&file_a), 2, "mutated file a must re-parse");
+        assert_eq!(
+            test_build_count(&file_a),
+            1,
+            "appended file a must not re-parse from scratch"
+        );
+        assert_eq!(test_extend_count(&file_a), 1, "only the tail is parsed");
         assert_eq!(
             test_build_count(&file_b),
             1,
@@ -1465,6 +1929,7 @@ mod tests {
             for (s, e) in &filters {
                 let composed = match compose_global(
                     &aggregate,
+                    StatsProvider::Claude,
                     claude_session_project_name(&file),
                     s.as_ref(),
                     e.as_ref(),
@@ -1612,12 +2077,19 @@ mod tests {
             if pipeline == "global" {
                 let aggregate = build_global_file_aggregate(&file, mode).expect("aggregate builds");
                 assert!(matches!(
-                    compose_global(&aggregate, "p".to_string(), Some(&s), Some(&e)),
+                    compose_global(
+                        &aggregate,
+                        StatsProvider::Claude,
+                        "p".to_string(),
+                        Some(&s),
+                        Some(&e)
+                    ),
                     Composed::NeedsFullScan
                 ));
                 // Unfiltered composition remains exact.
                 let composed = match compose_global(
                     &aggregate,
+                    StatsProvider::Claude,
                     claude_session_project_name(&file),
                     None,
                     None,
@@ -1708,4 +2180,334 @@ mod tests {
         // Day 2 of the fixture: skill row (33) + sidechain row (1100).
         assert_eq!(cached.total_tokens, 1133);
     }
+
+    fn append_lines(path: &Path, lines: &[String]) {
+        let mut appender = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .expect("open session for append");
+        for line in lines {
+            writeln!(appender, "{line}").expect("append session line");
+        }
+    }
+
+    #[test]
+    /// Extending a cached aggregate line by line yields exactly the cold
+    /// scan for every pipeline, mode, and filter — including a duplicate
+    /// message id whose first occurrence was in the already-parsed prefix.
+    fn test_tail_extension_matches_cold_scan() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let project_dir = temp_dir.path().join("demo-project");
+        fs::create_dir_all(&project_dir).expect("project dir");
+        let file = project_dir.join("session-tail.jsonl");
+        let fixture = multi_day_fixture();
+        write_session(&file, &fixture[..1]);
+
+        let filters: [FilterRange; 2] = [
+            (None, None),
+            (
+                Some(dt("2025-03-02T00:00:00Z")),
+                Some(dt("2025-03-05T23:59:59.999Z")),
+            ),
+        ];
+        let mode = StatsMode::BillingTotal;
+        process_session_file_for_global_stats(&file, mode, None, None).expect("warm global");
+        process_session_file_for_project_stats(&file, mode, None, None).expect("warm message");
+        assert_eq!(test_build_count(&file), 2, "one build per pipeline");
+
+        for (appended, line) in fixture[1..].iter().enumerate() {
+            append_lines(&file, std::slice::from_ref(line));
+            for (s, e) in &filters {
+                let global =
+                    process_session_file_for_global_stats(&file, mode, s.as_ref(), e.as_ref());
+                let scanned_global =
+                    scan_session_file_for_global_stats(&file, mode, s.as_ref(), e.as_ref());
+                assert_global_stats_eq(
+                    &global.expect("global stats"),
+                    &scanned_global.expect("scanned global stats"),
+                );
+
+                let project =
+                    process_session_file_for_project_stats(&file, mode, s.as_ref(), e.as_ref());
+                let scanned_project =
+                    scan_session_file_for_project_stats(&file, mode, s.as_ref(), e.as_ref());
+                match (&project, &scanned_project) {
+                    (Some(a), Some(b)) => assert_project_stats_eq(a, b),
+                    (None, None) => {}
+                    _ => panic!("extended/scanned project stats presence must match"),
+                }
+            }
+            assert_eq!(
+                test_extend_count(&file),
+                2 * (appended as u64 + 1),
+                "each append extends both pipelines"
+            );
+        }
+        assert_eq!(test_build_count(&file), 2, "no full rebuild after appends");
+
+        // The extended aggregate also composes session token stats exactly.
+        let aggregate = message_stats_cache()
+            .get_or_build(&file, mode)
+            .expect("cached aggregate");
+        let Composed::Ready(composed) =
+            compose_session_token(&aggregate, claude_session_project_name(&file), None, None)
+        else {
+            panic!("unfiltered composition is always exact");
+        };
+        let scanned = scan_session_token_stats(&file, mode, None, None);
+        assert_token_stats_eq(
+            &composed.expect("composed token stats"),
+            &scanned.expect("scanned token stats"),
+        );
+    }
+
+    #[test]
+    /// A rewritten prefix or an unterminated last line is never extended:
+    /// both force a full rebuild.
+    fn test_tail_extension_falls_back_to_full_build() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("rewrite.jsonl");
+        let mode = StatsMode::BillingTotal;
+        write_session(
+            &file,
+            &[asst_line("u1", "m1", "2025-03-01T10:00:00Z", 100, 10, "")],
+        );
+        process_session_file_for_project_stats(&file, mode, None, None).expect("first build");
+        assert_eq!(test_build_count(&file), 1);
+
+        // Rewrite the first line (different tokens, same length) and grow.
+        write_session(
+            &file,
+            &[
+                asst_line("u1", "m1", "2025-03-01T10:00:00Z", 900, 90, ""),
+                asst_line("u2", "m2", "2025-03-01T10:05:00Z", 5, 5, ""),
+            ],
+        );
+        let rewritten =
+            process_session_file_for_project_stats(&file, mode, None, None).expect("rebuild");
+        assert_eq!(
+            test_build_count(&file),
+            2,
+            "changed prefix forces a rebuild"
+        );
+        assert_eq!(test_extend_count(&file), 0);
+        assert_eq!(rewritten.token_distribution.input, 905);
+
+        // A partial trailing line leaves no resume point behind.
+        let partial = asst_line("u3", "m3", "2025-03-01T10:10:00Z", 7, 7, "");
+        let mut appender = OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .expect("open for append");
+        write!(appender, "{}", &partial[..partial.len() / 2]).expect("append partial line");
+        drop(appender);
+        process_session_file_for_project_stats(&file, mode, None, None).expect("partial build");
+        assert_eq!(test_build_count(&file), 2, "partial append still extends");
+        assert_eq!(test_extend_count(&file), 1);
+
+        let mut appender = OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .expect("open for append");
+        writeln!(appender, "{}", &partial[partial.len() / 2..]).expect("complete line");
+        drop(appender);
+        let completed =
+            process_session_file_for_project_stats(&file, mode, None, None).expect("completed");
+        assert_eq!(
+            test_build_count(&file),
+            3,
+            "an unterminated prefix cannot be resumed"
+        );
+        assert_eq!(completed.token_distribution.input, 912);
+    }
+
+    #[test]
+    /// A same-length rewrite deep inside a large prefix (far from both ends)
+    /// is detected: the whole parsed prefix is checksummed, not just its edges.
+    fn test_tail_extension_detects_mid_file_rewrite() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("mid-rewrite.jsonl");
+        let mode = StatsMode::BillingTotal;
+        let lines = |middle_input: u64| -> Vec<String> {
+            (0..100)
+                .map(|i| {
+                    let input = if i == 50 { middle_input } else { 100 };
+                    asst_line(
+                        &format!("u{i:03}"),
+                        &format!("m{i:03}"),
+                        "2025-03-01T10:00:00Z",
+                        input,
+                        10,
+                        "",
+                    )
+                })
+                .collect()
+        };
+        write_session(&file, &lines(100));
+        assert!(fs::metadata(&file).expect("metadata").len() > 3 * 4096);
+        process_session_file_for_project_stats(&file, mode, None, None).expect("first build");
+
+        let mut rewritten = lines(900);
+        rewritten.push(asst_line("u100", "m100", "2025-03-01T10:05:00Z", 1, 1, ""));
+        write_session(&file, &rewritten);
+        let stats =
+            process_session_file_for_project_stats(&file, mode, None, None).expect("rebuild");
+        assert_eq!(
+            test_build_count(&file),
+            2,
+            "mid-file rewrite forces a rebuild"
+        );
+        assert_eq!(test_extend_count(&file), 0);
+        assert_eq!(stats.token_distribution.input, 99 * 100 + 900 + 1);
+    }
+
+    #[test]
+    /// Merging timestamps into existing runs equals splitting all of them
+    /// from scratch, including points landing between and inside runs.
+    fn test_merge_into_runs_matches_from_scratch_split() {
+        let all = vec![
+            dt("2025-03-01T08:00:00Z"),
+            dt("2025-03-01T09:00:00Z"),
+            dt("2025-03-01T12:00:00Z"),
+            dt("2025-03-01T13:00:00Z"),
+            dt("2025-03-01T18:00:00Z"),
+        ];
+        let from_scratch = merge_into_runs(&[], all.clone());
+        assert_eq!(
+            from_scratch,
+            vec![(all[0], all[1]), (all[2], all[3]), (all[4], all[4]),]
+        );
+
+        // 10:30 bridges the first two runs (gaps of 90 minutes each).
+        let bridged = merge_into_runs(&from_scratch, vec![dt("2025-03-01T10:30:00Z")]);
+        assert_eq!(bridged, vec![(all[0], all[3]), (all[4], all[4])]);
+        assert_eq!(
+            bridged,
+            merge_into_runs(
+                &[],
+                all.iter()
+                    .copied()
+                    .chain([dt("2025-03-01T10:30:00Z")])
+                    .collect()
+            )
+        );
+
+        // A point inside an existing run changes nothing.
+        assert_eq!(
+            merge_into_runs(&bridged, vec![dt("2025-03-01T08:30:00Z")]),
+            bridged
+        );
+    }
+
+    fn fixture_messages() -> Vec<ClaudeMessage> {
+        multi_day_fixture()
+            .into_iter()
+            .filter_map(|line| parse_raw_log_entry_simd(&mut line.into_bytes()))
+            .filter_map(|entry| ClaudeMessage::try_from(entry).ok())
+            .collect()
+    }
+
+    #[test]
+    /// Provider aggregates compose to exactly what the uncached provider path
+    /// computes from the same messages, unfiltered and day-filtered.
+    fn test_provider_compose_matches_message_scan() {
+        let messages = fixture_messages();
+        let filters: [FilterRange; 2] = [
+            (None, None),
+            (
+                Some(dt("2025-03-02T00:00:00Z")),
+                Some(dt("2025-03-05T23:59:59.999Z")),
+            ),
+        ];
+
+        for mode in [StatsMode::BillingTotal, StatsMode::ConversationOnly] {
+            let aggregate = build_provider_aggregate(&messages, mode);
+            for (s, e) in &filters {
+                let composed = match compose_global(
+                    &aggregate,
+                    StatsProvider::Qwen,
+                    "demo [qwen]".to_string(),
+                    s.as_ref(),
+                    e.as_ref(),
+                ) {
+                    Composed::Ready(stats) => stats,
+                    Composed::NeedsFullScan => {
+                        panic!("day-aligned filter must compose from daily buckets")
+                    }
+                };
+                let scanned = build_global_session_file_stats_from_messages(
+                    StatsProvider::Qwen,
+                    "demo [qwen]".to_string(),
+                    &messages,
+                    mode,
+                    s.as_ref(),
+                    e.as_ref(),
+                )
+                .expect("non-empty session produces stats");
+                assert_global_stats_eq(&composed, &scanned);
+            }
+        }
+        assert!(build_provider_aggregate(&[], StatsMode::BillingTotal).empty_source);
+    }
+
+    #[test]
+    /// Provider sessions are reloaded only when their content identity
+    /// changes; sessions without an identity are never cached.
+    fn test_provider_cache_reloads_only_when_identity_changes() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let file = temp_dir.path().join("qwen-session.jsonl");
+        fs::write(&file, "{}\n").expect("session file");
+        let session_path = file.to_string_lossy().into_owned();
+        let messages = fixture_messages();
+        let loads = std::cell::Cell::new(0);
+        let cache = provider_stats_cache();
+        let lookup = |provider: StatsProvider, path: &str| {
+            let before = loads.get();
+            let mut loaded = None;
+            let aggregate =
+                cache.get_or_build(provider, path, StatsMode::BillingTotal, &mut loaded, || {
+                    loads.set(loads.get() + 1);
+                    messages.clone()
+                });
+            assert_eq!(
+                loaded.is_some(),
+                loads.get() > before,
+                "messages loaded for a build are handed back to the caller"
+            );
+            aggregate
+        };
+
+        assert!(lookup(StatsProvider::Qwen, &session_path).is_some());
+        assert!(lookup(StatsProvider::Qwen, &session_path).is_some());
+        assert_eq!(loads.get(), 1, "unchanged session is served from cache");
+
+        fs::write(&file, "{}\n{}\n").expect("grow session file");
+        assert!(lookup(StatsProvider::Qwen, &session_path).is_some());
+        assert_eq!(loads.get(), 2, "changed identity reloads the session");
+
+        lookup(StatsProvider::Cursor, "cursor://composer-1");
+        lookup(StatsProvider::Cursor, "cursor://composer-1");
+        assert_eq!(
+            loads.get(),
+            4,
+            "sessions without an identity are not cached"
+        );
+
+        let empty = temp_dir.path().join("empty.jsonl");
+        fs::write(&empty, "").expect("empty session file");
+        let empty_path = empty.to_string_lossy().into_owned();
+        for _ in 0..2 {
+            let aggregate = cache.get_or_build(
+                StatsProvider::Qwen,
+                &empty_path,
+                StatsMode::BillingTotal,
+                &mut None,
+                Vec::new,
+            );
+            assert!(
+                aggregate.is_none(),
+                "empty sessions are skipped like the scan"
+            );
+        }
+    }
 }
@@ -0,0 +1,612 @@
+//! Turn-by-turn comparison of two sessions (`compare_sessions`).
+//!
+//! The sessions may come from different providers, e.g. the same task run
+//! once with Claude Code and once with Codex. Each session is split into
+//! turns at every user prompt; tool results are not prompts. Turns are then
+//! aligned by the longest common subsequence of their normalized prompt
+//! text. Unmatched turns between two matches are paired by position, and
+//! whatever is left over is reported as one-sided.
+//!
+//! Tokens and cost reuse the same dedup and pricing path as the session
+//! token stats, so a side's totals agree with `get_session_token_stats`.
+
+use super::{
+    all_stats_providers, build_session_token_stats_from_messages, parse_timestamp_utc,
+    stats_provider_id, track_tool_usage, SessionTokenStatsOptions, StatsMode, StatsProvider,
+};
+use crate::commands::multi_provider::load_provider_messages;
+use crate::commands::session::edited_file_paths;
+use crate::models::{
+    ClaudeMessage, ComparedSession, ComparedTurn, ComparisonDelta, ComparisonMetrics,
+    SessionPairComparison,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Prompt text kept per aligned turn.
+const PROMPT_PREVIEW_CHARS: usize = 200;
+
+/// Above this many LCS cells turns are paired by position only, which keeps
+/// the alignment table for two very long sessions bounded.
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+struct LoadedSession {
+    provider_id: String,
+    provider: StatsProvider,
+    session_path: String,
+    messages: Vec<ClaudeMessage>,
+}
+
+struct Turn<'a> {
+    prompt: String,
+    messages: &'a [ClaudeMessage],
+}
+
+/// Resolve a frontend provider id to its stats provider.
+fn stats_provider_from_id(provider: &str) -> Result<StatsProvider, String> {
+    all_stats_providers()
+        .into_iter()
+        .find(|candidate| stats_provider_id(*candidate) == provider)
+        .ok_or_else(|| format!("Unknown provider: {provider}"))
+}
+
+/// Return the prompt text of a user message, or `None` when the message
+/// does not start a turn (assistant output, sidechains, tool results).
+fn prompt_text(message: &ClaudeMessage) -> Option<String> {
+    if message.message_type != "user" || message.is_sidechain == Some(true) {
+        return None;
+    }
+    match message.content.as_ref()? {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(blocks) => {
+            if blocks
+                .iter()
+                .all(|block| block.get("type").and_then(Value::as_str) == Some("tool_result"))
+            {
+                return None;
+            }
+            let texts: Vec<&str> = blocks
+                .iter()
+                .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect();
+            Some(texts.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+/// Split a session into turns. Messages before the first prompt form a
+/// leading turn with an empty prompt.
+fn split_turns(messages: &[ClaudeMessage]) -> Vec<Turn<'_>> {
+    let mut turns = Vec::new();
+    let mut start = 0;
+    let mut prompt = String::new();
+    for (index, message) in messages.iter().enumerate() {
+        let Some(text) = prompt_text(message) else {
+            continue;
+        };
+        if index > start {
+            turns.push(Turn {
+                prompt: std::mem::take(&mut prompt),
+                messages: &messages[start..index],
+            });
+        }
+        start = index;
+        prompt = text;
+    }
+    if start < messages.len() {
+        turns.push(Turn {
+            prompt,
+            messages: &messages[start..],
+        });
+    }
+    turns
+}
+
+fn normalize_prompt(prompt: &str) -> String {
+    prompt
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Align two prompt lists, returning `(left, right, alignment)` rows in order.
+fn align_turns(
+    left: &[String],
+    right: &[String],
+) -> Vec<(Option<usize>, Option<usize>, &'static str)> {
+    let (n, m) = (left.len(), right.len());
+    let same = |i: usize, j: usize| !left[i].is_empty() && left[i] == right[j];
+
+    let mut anchors = Vec::new();
+    if (n + 1) * (m + 1) <= MAX_ALIGNMENT_CELLS {
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if same(i, j) {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if same(i, j) {
+                anchors.push((i, j));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    let mut rows = Vec::with_capacity(n.max(m));
+    let (mut next_left, mut next_right) = (0, 0);
+    for (anchor_left, anchor_right) in anchors.into_iter().chain(std::iter::once((n, m))) {
+        let gap = (anchor_left - next_left).max(anchor_right - next_right);
+        for offset in 0..gap {
+            let l = (next_left + offset < anchor_left).then_some(next_left + offset);
+            let r = (next_right + offset < anchor_right).then_some(next_right + offset);
+            let alignment = match (l, r) {
+                (Some(_), Some(_)) => "position",
+                (Some(_), None) => "left_only",
+                _ => "right_only",
+            };
+            rows.push((l, r, alignment));
+        }
+        if anchor_left < n {
+            rows.push((Some(anchor_left), Some(anchor_right), "prompt"));
+        }
+        next_left = anchor_left + 1;
+        next_right = anchor_right + 1;
+    }
+    rows
+}
+
+/// Count failed tool results and error entries in a message.
+fn count_errors(message: &ClaudeMessage) -> u32 {
+    let mut count =
+        u32::from(message.message_type == "error" || message.level.as_deref() == Some("error"));
+    let failed_results = message
+        .content
+        .as_ref()
+        .and_then(Value::as_array)
+        .map_or(0, |blocks| {
+            blocks
+                .iter()
+                .filter(|block| {
+                    block.get("type").and_then(Value::as_str) == Some("tool_result")
+                        && block.get("is_error").and_then(Value::as_bool) == Some(true)
+                })
+                .count()
+        });
+    count += u32::try_from(failed_results).unwrap_or(u32::MAX);
+    // Claude mirrors the content block into `toolUseResult`; only count it
+    // when the content did not already report the failure.
+    if failed_results == 0
+        && message
+            .tool_use_result
+            .as_ref()
+            .and_then(|result| result.get("is_error"))
+            .and_then(Value::as_bool)
+            == Some(true)
+    {
+        count += 1;
+    }
+    count
+}
+
+/// Add the files edited by a message's tool calls.
+fn collect_edited_files(message: &ClaudeMessage, files: &mut BTreeSet<String>) {
+    let blocks = message
+        .content
+        .as_ref()
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("tool_use"));
+    for call in blocks.chain(message.tool_use.iter()) {
+        if let Some(name) = call.get("name").and_then(Value::as_str) {
+            let input = call.get("input").unwrap_or(&Value::Null);
+            files.extend(edited_file_paths(name, input));
+        }
+    }
+}
+
+fn collect_metrics(provider: StatsProvider, messages: &[ClaudeMessage]) -> ComparisonMetrics {
+    let mut tool_usage: HashMap<String, (u32, u32)> = HashMap::new();
+    let mut files = BTreeSet::new();
+    let mut error_count = 0u32;
+    let mut first_time = None;
+    let mut last_time = None;
+    for message in messages {
+        track_tool_usage(message, &mut tool_usage);
+        collect_edited_files(message, &mut files);
+        error_count += count_errors(message);
+        if let Some(ts) = parse_timestamp_utc(&message.timestamp) {
+            first_time =
+                Some(first_time.map_or(ts, |current: chrono::DateTime<_>| current.min(ts)));
+            last_time = Some(last_time.map_or(ts, |current: chrono::DateTime<_>| current.max(ts)));
+        }
+    }
+
+    let stats = build_session_token_stats_from_messages(
+        SessionTokenStatsOptions {
+            provider,
+            session_id: String::new(),
+            project_name: String::new(),
+            summary: None,
+            mode: StatsMode::BillingTotal,
+            start_date: None,
+            end_date: None,
+        },
+        messages,
+    );
+    let (total_tokens, input_tokens, output_tokens, cost_usd) =
+        stats.map_or((0, 0, 0, None), |stats| {
+            (
+                stats.total_tokens,
+                stats.total_input_tokens,
+                stats.total_output_tokens,
+                stats.total_cost_usd,
+            )
+        });
+
+    let tools: BTreeMap<String, u32> = tool_usage
+        .into_iter()
+        .map(|(name, (count, _))| (name, count))
+        .collect();
+    ComparisonMetrics {
+        message_count: messages.len(),
+        total_tokens,
+        input_tokens,
+        output_tokens,
+        cost_usd,
+        tool_calls: tools.values().sum(),
+        tools,
+        files_edited: files.into_iter().collect(),
+        error_count,
+        duration_ms: first_time
+            .zip(last_time)
+            .map(|(first, last)| u64::try_from((last - first).num_milliseconds()).unwrap_or(0)),
+    }
+}
+
+fn signed_diff(left: u64, right: u64) -> i64 {
+    if right >= left {
+        i64::try_from(right - left).unwrap_or(i64::MAX)
+    } else {
+        -i64::try_from(left - right).unwrap_or(i64::MAX)
+    }
+}
+
+fn only_in<'a>(
+    ours: impl Iterator<Item = &'a String>,
+    theirs: impl Iterator<Item = &'a String>,
+) -> Vec<String> {
+    let theirs: BTreeSet<&String> = theirs.collect();
+    ours.filter(|item| !theirs.contains(item))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn metrics_delta(left: &ComparisonMetrics, right: &ComparisonMetrics) -> ComparisonDelta {
+    ComparisonDelta {
+        total_tokens: signed_diff(left.total_tokens, right.total_tokens),
+        cost_usd: left
+            .cost_usd
+            .zip(right.cost_usd)
+            .map(|(left, right)| right - left),
+        tool_calls: i64::from(right.tool_calls) - i64::from(left.tool_calls),
+        error_count: i64::from(right.error_count) - i64::from(left.error_count),
+        duration_ms: left
+            .duration_ms
+            .zip(right.duration_ms)
+            .map(|(left, right)| signed_diff(left, right)),
+        tools_only_left: only_in(left.tools.keys(), right.tools.keys()),
+        tools_only_right: only_in(right.tools.keys(), left.tools.keys()),
+        files_only_left: only_in(left.files_edited.iter(), right.files_edited.iter()),
+        files_only_right: only_in(right.files_edited.iter(), left.files_edited.iter()),
+    }
+}
+
+fn prompt_preview(prompt: &str) -> String {
+    let trimmed = prompt.trim();
+    match trimmed.char_indices().nth(PROMPT_PREVIEW_CHARS) {
+        Some((cut, _)) => format!("{}…", &trimmed[..cut]),
+        None => trimmed.to_string(),
+    }
+}
+
+fn compared_session(session: &LoadedSession, turn_count: usize) -> ComparedSession {
+    let models: BTreeSet<String> = session
+        .messages
+        .iter()
+        .filter_map(|message| message.model.clone())
+        .collect();
+    ComparedSession {
+        provider: session.provider_id.clone(),
+        session_path: session.session_path.clone(),
+        session_id: session
+            .messages
+            .first()
+            .map_or_else(|| session.session_path.clone(), |m| m.session_id.clone()),
+        models: models.into_iter().collect(),
+        turn_count,
+        metrics: collect_metrics(session.provider, &session.messages),
+    }
+}
+
+fn compare_loaded(left: &LoadedSession, right: &LoadedSession) -> SessionPairComparison {
+    let left_turns = split_turns(&left.messages);
+    let right_turns = split_turns(&right.messages);
+    let left_prompts: Vec<String> = left_turns
+        .iter()
+        .map(|turn| normalize_prompt(&turn.prompt))
+        .collect();
+    let right_prompts: Vec<String> = right_turns
+        .iter()
+        .map(|turn| normalize_prompt(&turn.prompt))
+        .collect();
+
+    let turns = align_turns(&left_prompts, &right_prompts)
+        .into_iter()
+        .map(|(left_index, right_index, alignment)| {
+            let left_metrics =
+                left_index.map(|i| collect_metrics(left.provider, left_turns[i].messages));
+            let right_metrics =
+                right_index.map(|j| collect_metrics(right.provider, right_turns[j].messages));
+            let delta = left_metrics
+                .as_ref()
+                .zip(right_metrics.as_ref())
+                .map(|(l, r)| metrics_delta(l, r));
+            let prompt = left_index
+                .map(|i| left_turns[i].prompt.as_str())
+                .filter(|prompt| !prompt.trim().is_empty())
+                .or_else(|| right_index.map(|j| right_turns[j].prompt.as_str()))
+                .unwrap_or_default();
+            ComparedTurn {
+                alignment: alignment.to_string(),
+                left_index,
+                right_index,
+                prompt: prompt_preview(prompt),
+                left: left_metrics,
+                right: right_metrics,
+                delta,
+            }
+        })
+        .collect();
+
+    let left_session = compared_session(left, left_turns.len());
+    let right_session = compared_session(right, right_turns.len());
+    let delta = metrics_delta(&left_session.metrics, &right_session.metrics);
+    SessionPairComparison {
+        left: left_session,
+        right: right_session,
+        delta,
+        turns,
+    }
+}
+
+async fn load_session(provider_id: String, session_path: String) -> Result<LoadedSession, String> {
+    let provider = stats_provider_from_id(&provider_id)?;
+    let messages = load_provider_messages(provider_id.clone(), session_path.clone()).await?;
+    if messages.is_empty() {
+        return Err(format!("No messages found in session: {session_path}"));
+    }
+    Ok(LoadedSession {
+        provider_id,
+        provider,
+        session_path,
+        messages,
+    })
+}
+
+#[tauri::command]
+/// Align two sessions turn by turn and report how their tools, edited
+/// files, tokens, cost, errors and duration differ.
+pub async fn compare_sessions(
+    left_provider: String,
+    left_session_path: String,
+    right_provider: String,
+    right_session_path: String,
+) -> Result<SessionPairComparison, String> {
+    let left = load_session(left_provider, left_session_path).await?;
+    let right = load_session(right_provider, right_session_path).await?;
+    Ok(compare_loaded(&left, &right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(value: Value) -> ClaudeMessage {
+        serde_json::from_value(value).expect("valid message")
+    }
+
+    fn prompt(uuid: &str, timestamp: &str, text: &str) -> ClaudeMessage {
+        message(json!({
+            "uuid": uuid,
+            "sessionId": "s",
+            "timestamp": timestamp,
+            "type": "user",
+            "content": text,
+        }))
+    }
+
+    fn assistant(
+        uuid: &str,
+        timestamp: &str,
+        model: &str,
+        tools: &[(&str, Value)],
+    ) -> ClaudeMessage {
+        let content: Vec<Value> = tools
+            .iter()
+            .enumerate()
+            .map(|(i, (name, input))| {
+                json!({"type": "tool_use", "id": format!("{uuid}-{i}"), "name": name, "input": input})
+            })
+            .collect();
+        message(json!({
+            "uuid": uuid,
+            "sessionId": "s",
+            "timestamp": timestamp,
+            "type": "assistant",
+            "model": model,
+            "content": content,
+            "usage": {"input_tokens": 100, "output_tokens": 50},
+        }))
+    }
+
+    fn tool_result(uuid: &str, timestamp: &str, is_error: bool) -> ClaudeMessage {
+        message(json!({
+            "uuid": uuid,
+            "sessionId": "s",
+            "timestamp": timestamp,
+            "type": "user",
+            "content": [{"type": "tool_result", "tool_use_id": "x", "content": "out", "is_error": is_error}],
+        }))
+    }
+
+    fn loaded(provider_id: &str, messages: Vec<ClaudeMessage>) -> LoadedSession {
+        LoadedSession {
+            provider_id: provider_id.to_string(),
+            provider: stats_provider_from_id(provider_id).expect("known provider"),
+            session_path: format!("{provider_id}://session"),
+            messages,
+        }
+    }
+
+    #[test]
+    fn split_turns_starts_at_prompts_not_tool_results() {
+        let messages = vec![
+            assistant("a0", "2026-01-01T00:00:00Z", "m", &[]),
+            prompt("u1", "2026-01-01T00:00:01Z", "first"),
+            assistant("a1", "2026-01-01T00:00:02Z", "m", &[("Bash", json!({}))]),
+            tool_result("r1", "2026-01-01T00:00:03Z", false),
+            prompt("u2", "2026-01-01T00:00:04Z", "second"),
+        ];
+        let turns = split_turns(&messages);
+        let shape: Vec<(&str, usize)> = turns
+            .iter()
+            .map(|turn| (turn.prompt.as_str(), turn.messages.len()))
+            .collect();
+        assert_eq!(shape, vec![("", 1), ("first", 3), ("second", 1)]);
+    }
+
+    #[test]
+    fn align_turns_matches_prompts_and_pairs_gaps_by_position() {
+        let left: Vec<String> = ["a", "x", "b", "c"].map(String::from).to_vec();
+        let right: Vec<String> = ["a", "y", "z", "b"].map(String::from).to_vec();
+        assert_eq!(
+            align_turns(&left, &right),
+            vec![
+                (Some(0), Some(0), "prompt"),
+                (Some(1), Some(1), "position"),
+                (None, Some(2), "right_only"),
+                (Some(2), Some(3), "prompt"),
+                (Some(3), None, "left_only"),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_turns_never_anchors_on_empty_prompts() {
+        let left = vec![String::new(), "a".to_string()];
+        let right = vec![String::new(), "a".to_string()];
+        assert_eq!(
+            align_turns(&left, &right),
+            vec![(Some(0), Some(0), "position"), (Some(1), Some(1), "prompt")]
+        );
+    }
+
+    #[test]
+    fn compare_loaded_reports_tool_file_token_and_error_differences() {
+        let left = loaded(
+            "claude",
+            vec![
+                prompt("u1", "2026-01-01T00:00:00Z", "Fix the bug"),
+                assistant(
+                    "a1",
+                    "2026-01-01T00:00:10Z",
+                    "claude-sonnet-4-5",
+                    &[
+                        ("Read", json!({"file_path": "src/lib.rs"})),
+                        (
+                            "Edit",
+                            json!({"file_path": "src/lib.rs", "old_string": "a", "new_string": "b"}),
+                        ),
+                    ],
+                ),
+                tool_result("r1", "2026-01-01T00:00:20Z", false),
+            ],
+        );
+        let right = loaded(
+            "codex",
+            vec![
+                prompt("u1", "2026-01-02T00:00:00Z", "  fix the   BUG "),
+                assistant(
+                    "a1",
+                    "2026-01-02T00:00:05Z",
+                    "gpt-5",
+                    &[(
+                        "apply_patch",
+                        json!({"patch": "*** Begin Patch\n*** Update File: src/main.rs\n*** End Patch"}),
+                    )],
+                ),
+                tool_result("r1", "2026-01-02T00:00:06Z", true),
+                assistant("a2", "2026-01-02T00:00:07Z", "gpt-5", &[]),
+                prompt("u2", "2026-01-02T00:01:00Z", "Now add tests"),
+                assistant("a3", "2026-01-02T00:01:30Z", "gpt-5", &[]),
+            ],
+        );
+
+        let result = compare_loaded(&left, &right);
+        assert_eq!(result.left.turn_count, 1);
+        assert_eq!(result.right.turn_count, 2);
+        assert_eq!(result.left.models, vec!["claude-sonnet-4-5"]);
+        assert_eq!(result.right.session_id, "s");
+
+        assert_eq!(result.turns.len(), 2);
+        let first = &result.turns[0];
+        assert_eq!(first.alignment, "prompt");
+        assert_eq!(first.prompt, "Fix the bug");
+        let delta = first.delta.as_ref().expect("paired turn delta");
+        assert_eq!(delta.tools_only_left, vec!["Edit", "Read"]);
+        assert_eq!(delta.tools_only_right, vec!["apply_patch"]);
+        assert_eq!(delta.files_only_left, vec!["src/lib.rs"]);
+        assert_eq!(delta.files_only_right, vec!["src/main.rs"]);
+        assert_eq!(delta.total_tokens, 150);
+        assert_eq!(delta.error_count, 1);
+        assert_eq!(delta.duration_ms, Some(7_000 - 20_000));
+
+        let second = &result.turns[1];
+        assert_eq!(second.alignment, "right_only");
+        assert!(second.left.is_none() && second.delta.is_none());
+        assert_eq!(
+            second.right.as_ref().map(|m| m.duration_ms),
+            Some(Some(30_000))
+        );
+
+        assert_eq!(result.left.metrics.total_tokens, 150);
+        assert_eq!(result.right.metrics.total_tokens, 450);
+        assert_eq!(result.right.metrics.error_count, 1);
+        assert_eq!(result.delta.total_tokens, 300);
+        assert_eq!(result.delta.tool_calls, -1);
+    }
+
+    #[test]
+    fn stats_provider_from_id_rejects_unknown_ids() {
+        assert_eq!(stats_provider_from_id("codex"), Ok(StatsProvider::Codex));
+        assert!(stats_provider_from_id("nope").is_err());
+    }
+}
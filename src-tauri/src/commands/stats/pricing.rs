@@ -0,0 +1,391 @@
+//! Model pricing table used to estimate cost when logs carry no `costUSD`.
+//!
+//! Claude Code often omits `costUSD` and other providers never write it, so
+//! `build_model_stats` fills [`ModelStats::estimated_cost_usd`] for every
+//! provider from a per-model pricing table. The built-in table is
+//! `src/config/modelPricing.json`, which the dashboard imports as well, so
+//! both price from one source. Users can override or extend it with
+//! `~/.claude-history-viewer/pricing.json`, which has the same shape:
+//!
+//! ```json
+//! {
+//!   "models": {
+//!     "claude-sonnet-4-5": {
+//!       "input": 3, "output": 15, "cacheRead": 0.3,
+//!       "cacheWrite5m": 3.75, "cacheWrite1h": 6
+//!     },
+//!     "my-proxy-model": { "input": 1, "output": 2, "cacheRead": 0.1, "reasoning": 4 }
+//!   },
+//!   "tokenUnsupportedModels": ["^my-tts-model(?:-|$)"]
+//! }
+//! ```
+//!
+//! Prices are USD per million tokens. A user entry replaces the built-in
+//! entry with the same key. `reasoning` defaults to the output rate and
+//! `cacheWrite1h` to the 5-minute rate; a missing `cacheWrite5m` means cache
+//! writes are not charged. `contextTiers` (each with `minContextTokens`) and
+//! `serviceTiers` (keyed by `service_tier`, e.g. `fast`) nest full entries.
+//! `tokenUnsupportedModels` patterns are added to the built-in ones and
+//! leave matching models unpriced.
+//!
+//! The override file is re-read whenever its modification time changes, so
+//! edits apply to the next computed summary without a restart.
+//!
+//! [`ModelStats::estimated_cost_usd`]: crate::models::ModelStats::estimated_cost_usd
+
+use crate::models::ModelContextStats;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+const PRICING_FILE_NAME: &str = "pricing.json";
+
+/// Built-in table, shared with the dashboard's `calculations.ts`.
+const BUILTIN_PRICING: &str = include_str!("../../../../src/config/modelPricing.json");
+
+/// Prices for one model (or one context/service tier of it), in USD per
+/// million tokens.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    /// 5-minute (default) cache write rate; `None` when writes are free.
+    #[serde(default)]
+    pub cache_write_5m: Option<f64>,
+    /// 1-hour cache write rate; falls back to the 5-minute rate.
+    #[serde(default)]
+    pub cache_write_1h: Option<f64>,
+    /// Reasoning token rate; falls back to the output rate.
+    #[serde(default)]
+    pub reasoning: Option<f64>,
+    /// Lowest request context size this tier applies to.
+    #[serde(default)]
+    pub min_context_tokens: Option<u64>,
+    #[serde(default)]
+    pub context_tiers: Vec<ModelPricing>,
+    #[serde(default)]
+    pub service_tiers: HashMap<String, ModelPricing>,
+}
+
+/// Shape of `pricing.json` and of the built-in table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PricingFile {
+    #[serde(default)]
+    models: HashMap<String, ModelPricing>,
+    /// Models billed per audio/image/video unit, which token rates cannot
+    /// price. Kept out of prefix matching so e.g.
+    /// `gemini-2.5-flash-preview-tts` is not priced as ordinary Gemini 2.5
+    /// Flash text traffic.
+    #[serde(default)]
+    token_unsupported_models: Vec<String>,
+}
+
+/// Pricing entries, keyed by model name prefix.
+#[derive(Debug, Clone)]
+pub(crate) struct PricingTable {
+    /// Sorted by key length, longest first, so the most specific key wins.
+    entries: Vec<(String, ModelPricing)>,
+    token_unsupported: Vec<Regex>,
+}
+
+/// Final `provider/model` path segment, lowercased, without `models/`.
+fn model_segment(model_name: &str) -> String {
+    let normalized = model_name.trim().to_ascii_lowercase();
+    let normalized = normalized.strip_prefix("models/").unwrap_or(&normalized);
+    normalized
+        .rsplit('/')
+        .next()
+        .unwrap_or(normalized)
+        .to_string()
+}
+
+fn matches_key(model: &str, key: &str) -> bool {
+    model == key
+        || model
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with(['-', '@', ':']))
+}
+
+impl ModelPricing {
+    /// The tier billing a request of `context_tokens`: the context tier with
+    /// the highest threshold not above it, else `self`.
+    fn for_context(&self, context_tokens: u64) -> &Self {
+        self.context_tiers
+            .iter()
+            .filter(|tier| tier.min_context_tokens.unwrap_or(0) <= context_tokens)
+            .max_by_key(|tier| tier.min_context_tokens.unwrap_or(0))
+            .unwrap_or(self)
+    }
+
+    /// Cost in USD of one context bucket priced entirely by this tier.
+    fn cost(&self, usage: &ModelContextStats) -> f64 {
+        // Buckets from older payloads may carry only the combined count.
+        let cache_1h = usage.cache_creation_tokens_1h;
+        let cache_5m = if usage.cache_creation_tokens_5m + cache_1h == 0 {
+            usage.cache_creation_tokens
+        } else {
+            usage.cache_creation_tokens_5m
+        };
+        let write_5m = self.cache_write_5m.unwrap_or(0.0);
+        let write_1h = self.cache_write_1h.or(self.cache_write_5m).unwrap_or(0.0);
+        let reasoning = self.reasoning.unwrap_or(self.output);
+
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.reasoning_tokens as f64 * reasoning
+            + cache_5m as f64 * write_5m
+            + cache_1h as f64 * write_1h
+            + usage.cache_read_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+impl PricingTable {
+    /// Built-in prices with `overrides` replacing same-key entries and
+    /// adding unsupported-model patterns.
+    fn with_overrides(overrides: PricingFile) -> Self {
+        let builtin: PricingFile =
+            serde_json::from_str(BUILTIN_PRICING).expect("valid built-in pricing table");
+        let mut models = builtin.models;
+        models.extend(overrides.models);
+        let mut entries: Vec<(String, ModelPricing)> = models
+            .into_iter()
+            .map(|(key, pricing)| (key.trim().to_ascii_lowercase(), pricing))
+            .collect();
+        entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        let token_unsupported = builtin
+            .token_unsupported_models
+            .iter()
+            .chain(&overrides.token_unsupported_models)
+            .filter_map(|pattern| {
+                Regex::new(pattern)
+                    .map_err(|e| log::warn!("Ignoring invalid model pattern {pattern:?}: {e}"))
+                    .ok()
+            })
+            .collect();
+        Self {
+            entries,
+            token_unsupported,
+        }
+    }
+
+    /// Pricing for `model_name` under `service_tier`, or `None` when the
+    /// model is unknown or not token-priced.
+    pub(crate) fn find(
+        &self,
+        model_name: &str,
+        service_tier: Option<&str>,
+    ) -> Option<&ModelPricing> {
+        let model = model_segment(model_name);
+        if self
+            .token_unsupported
+            .iter()
+            .any(|pattern| pattern.is_match(&model))
+        {
+            return None;
+        }
+        let pricing = self
+            .entries
+            .iter()
+            .find(|(key, _)| matches_key(&model, key))
+            .map(|(_, pricing)| pricing)?;
+
+        let tier = service_tier.map(|tier| match tier.trim().to_ascii_lowercase().as_str() {
+            "priority" => "fast".to_string(),
+            other => other.to_string(),
+        });
+        Some(
+            tier.and_then(|tier| pricing.service_tiers.get(&tier))
+                .unwrap_or(pricing),
+        )
+    }
+
+    /// Estimated cost in USD of a model's usage, priced per context bucket.
+    pub(crate) fn estimate(
+        &self,
+        model_name: &str,
+        service_tier: Option<&str>,
+        context_breakdown: &[ModelContextStats],
+    ) -> Option<f64> {
+        let pricing = self.find(model_name, service_tier)?;
+        Some(
+            context_breakdown
+                .iter()
+                .map(|bucket| pricing.for_context(bucket.min_context_tokens).cost(bucket))
+                .sum(),
+        )
+    }
+}
+
+fn pricing_file_path() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".claude-history-viewer")
+            .join(PRICING_FILE_NAME),
+    )
+}
+
+/// The pricing table in effect: built-ins plus the user's overrides.
+///
+/// The override file is re-read when its modification time changes. An
+/// unreadable or malformed file is logged and ignored (built-ins only).
+/// Tests always get the built-in table.
+pub(crate) fn current_pricing() -> Arc<PricingTable> {
+    type Loaded = Option<(Option<SystemTime>, Arc<PricingTable>)>;
+    static LOADED: OnceLock<Mutex<Loaded>> = OnceLock::new();
+
+    let path = if cfg!(test) {
+        None
+    } else {
+        pricing_file_path()
+    };
+    let mtime = path
+        .as_ref()
+        .and_then(|path| fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok());
+
+    let mut loaded = LOADED
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some((loaded_mtime, table)) = loaded.as_ref() {
+        if *loaded_mtime == mtime {
+            return Arc::clone(table);
+        }
+    }
+
+    let overrides = match (&path, mtime) {
+        (Some(path), Some(_)) => fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<PricingFile>(&content).map_err(|e| e.to_string())
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid {}: {e}", path.to_string_lossy());
+                PricingFile::default()
+            }),
+        _ => PricingFile::default(),
+    };
+    let table = Arc::new(PricingTable::with_overrides(overrides));
+    *loaded = Some((mtime, Arc::clone(&table)));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(min_context_tokens: u64) -> ModelContextStats {
+        ModelContextStats {
+            min_context_tokens,
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn builtin() -> PricingTable {
+        PricingTable::with_overrides(PricingFile::default())
+    }
+
+    #[test]
+    fn test_find_matches_longest_key_on_model_segment() {
+        let table = builtin();
+        let price = |model: &str| table.find(model, None).map(|p| p.input);
+
+        assert_eq!(price("claude-opus-4-5-20251101"), Some(5.0));
+        assert_eq!(price("claude-opus-4-20250514"), Some(15.0));
+        assert_eq!(price("anthropic/claude-sonnet-4-5"), Some(3.0));
+        assert_eq!(price("models/gemini-2.5-flash-lite"), Some(0.1));
+        assert_eq!(price("gpt-4.1-mini-2025-04-14"), Some(0.4));
+        assert_eq!(price("not-gpt-4.1"), None);
+        assert_eq!(price("gemini-2.5-flash-preview-tts"), None);
+    }
+
+    #[test]
+    fn test_builtin_table_loads_every_entry_and_pattern() {
+        let file: PricingFile = serde_json::from_str(BUILTIN_PRICING).unwrap();
+        let table = builtin();
+        assert!(!file.models.is_empty());
+        assert_eq!(table.entries.len(), file.models.len());
+        assert_eq!(
+            table.token_unsupported.len(),
+            file.token_unsupported_models.len()
+        );
+    }
+
+    #[test]
+    fn test_service_and_context_tiers() {
+        let table = builtin();
+        let codex = table.find("gpt-5.3-codex", Some("priority")).unwrap();
+        assert_eq!(codex.input.to_bits(), 3.5_f64.to_bits());
+
+        // 1M input + 1M output per bucket.
+        let cost = |min: u64| table.estimate("gpt-5.4", None, &[bucket(min)]).unwrap();
+        assert!((cost(0) - 17.5).abs() < 1e-9);
+        assert!((cost(272_001) - 27.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_prices_cache_writes_reads_and_reasoning() {
+        let table = builtin();
+        let usage = ModelContextStats {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            reasoning_tokens: 1_000_000,
+            cache_creation_tokens: 3_000_000,
+            cache_creation_tokens_5m: 2_000_000,
+            cache_creation_tokens_1h: 1_000_000,
+            cache_read_tokens: 10_000_000,
+            ..Default::default()
+        };
+        let cost = table.estimate("claude-sonnet-4-5", None, &[usage]).unwrap();
+        // 3 + 15 + 15 (reasoning at output rate) + 2 * 3.75 + 6 + 10 * 0.3
+        assert!((cost - 49.5).abs() < 1e-9, "cost = {cost}");
+    }
+
+    #[test]
+    fn test_overrides_replace_and_extend_builtins() {
+        let file: PricingFile = serde_json::from_value(serde_json::json!({
+            "models": {
+                "claude-sonnet-4-5": {"input": 1, "output": 2, "cacheRead": 0},
+                "My-Proxy-Model": {
+                    "input": 1, "output": 2, "cacheRead": 0, "reasoning": 10,
+                    "serviceTiers": {"flex": {"input": 0.5, "output": 1, "cacheRead": 0}}
+                },
+                "my-tts-model": {"input": 1, "output": 2, "cacheRead": 0}
+            },
+            "tokenUnsupportedModels": ["^my-tts-model(?:-|$)", "("]
+        }))
+        .unwrap();
+        let table = PricingTable::with_overrides(file);
+
+        let sonnet = table.find("claude-sonnet-4-5-20250929", None).unwrap();
+        assert_eq!(sonnet.input.to_bits(), 1.0_f64.to_bits());
+        assert_eq!(sonnet.cache_write_5m, None);
+        assert_eq!(
+            table
+                .find("claude-sonnet-4-20250514", None)
+                .unwrap()
+                .input
+                .to_bits(),
+            3.0_f64.to_bits(),
+            "other built-ins are kept"
+        );
+        let proxy = table.find("my-proxy-model", None).unwrap();
+        assert_eq!(proxy.reasoning, Some(10.0));
+        let flex = table.find("my-proxy-model", Some("flex")).unwrap();
+        assert_eq!(flex.input.to_bits(), 0.5_f64.to_bits());
+        // User patterns add to the built-in ones; invalid ones are skipped.
+        assert_eq!(table.find("my-tts-model", None), None);
+        assert_eq!(table.find("gemini-2.5-flash-preview-tts", None), None);
+    }
+}
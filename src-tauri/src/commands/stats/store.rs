@@ -0,0 +1,408 @@
+//! Persistent on-disk backing store for the per-file stats caches.
+//!
+//! The in-memory caches in the sibling `cache` module are lost on every app
+//! launch and every `--serve` restart, so a cold start used to re-parse the
+//! whole `~/.claude/projects` tree. This module persists each cached
+//! [`FileAggregate`] to a local `SQLite` database
+//! (`~/.claude-history-viewer/cache/stats-cache.db`) keyed by
+//! `(cache, key, stats mode)` and validated by an opaque signature token,
+//! exactly like the in-memory entries: the file's `(size, mtime)` for the
+//! Claude caches (keyed by canonical path), the provider's content identity
+//! for the provider cache (keyed by provider id and session path).
+//!
+//! ## Invalidation
+//!
+//! - A row whose signature differs from the current one is treated as a miss
+//!   and replaced by the next save.
+//! - Rows for files that no longer exist are pruned when the store opens.
+//!   Provider rows are keyed by virtual paths that cannot be checked that
+//!   way, so they are pruned once they have not been re-saved for
+//!   [`PROVIDER_ROW_TTL_SECS`]; an unchanged session then simply rebuilds.
+//! - The aggregate payload is serialized `FileAggregate` JSON, so any change
+//!   to that shape must bump [`SCHEMA_VERSION`]; a version mismatch drops and
+//!   recreates the table instead of attempting a migration. Payloads that
+//!   fail to deserialize are treated as misses.
+//!
+//! Every failure is non-fatal: the caches fall back to in-memory behavior.
+
+use super::cache::FileAggregate;
+use crate::utils::open_cache_db;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bump whenever the table layout or the serialized `FileAggregate` shape
+/// changes; older databases are reset on open.
+const SCHEMA_VERSION: i64 = 3;
+
+/// Cache name whose keys are provider session paths rather than files.
+pub(super) const PROVIDER_CACHE: &str = "provider";
+
+/// Age after which provider rows are pruned on open (90 days).
+const PROVIDER_ROW_TTL_SECS: i64 = 90 * 24 * 60 * 60;
+
+const STORE_FILE_NAME: &str = "stats-cache.db";
+
+const SCHEMA: &str = "
+    DROP TABLE IF EXISTS file_aggregates;
+    CREATE TABLE file_aggregates (
+        cache     TEXT    NOT NULL,
+        path      TEXT    NOT NULL,
+        mode      INTEGER NOT NULL,
+        signature TEXT    NOT NULL,
+        saved_at  INTEGER NOT NULL,
+        payload   BLOB    NOT NULL,
+        PRIMARY KEY (cache, path, mode)
+    );";
+
+/// SQLite-backed persistence for `FileAggregate`s.
+pub(super) struct StatsStore {
+    conn: Mutex<Connection>,
+}
+
+/// `(size, mtime)` identity of a session file at the time it was parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct FileSignature {
+    pub(super) size: u64,
+    pub(super) mtime: Option<SystemTime>,
+}
+
+impl FileSignature {
+    /// Store token: `<size>:<mtime ns since the Unix epoch>`, with `-` when
+    /// the platform reports no mtime (or one before the epoch).
+    pub(super) fn token(self) -> String {
+        match self
+            .mtime
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        {
+            Some(elapsed) => format!("{}:{}", self.size, elapsed.as_nanos()),
+            None => format!("{}:-", self.size),
+        }
+    }
+}
+
+/// Default location: `~/.claude-history-viewer/cache/stats-cache.db`.
+fn default_store_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude-history-viewer")
+            .join("cache")
+            .join(STORE_FILE_NAME),
+    )
+}
+
+/// Process-wide store, opened lazily on first use. `None` when the home
+/// directory is unavailable or the database cannot be opened — callers then
+/// run with the in-memory caches only. Disabled under `cfg(test)` so unit
+/// tests never read or write the developer's real cache.
+pub(super) fn persistent_store() -> Option<&'static StatsStore> {
+    static STORE: OnceLock<Option<StatsStore>> = OnceLock::new();
+    STORE
+        .get_or_init(|| {
+            if cfg!(test) {
+                return None;
+            }
+            let path = default_store_path()?;
+            match StatsStore::open(&path) {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    log::warn!(
+                        "Stats cache store unavailable at {}: {e}",
+                        path.to_string_lossy()
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+impl StatsStore {
+    /// Open (or create) the store at `path`, resetting it on schema mismatch
+    /// and pruning rows for vanished files.
+    pub(super) fn open(path: &Path) -> Result<Self, String> {
+        let conn = open_cache_db(path, SCHEMA_VERSION, SCHEMA)?;
+        if let Err(e) = prune_missing_files(&conn) {
+            log::warn!("Failed to prune stale stats cache rows: {e}");
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Return the persisted aggregate for `key`/`slot` when its stored
+    /// signature matches `signature`.
+    pub(super) fn load(
+        &self,
+        cache: &str,
+        key: &str,
+        slot: usize,
+        signature: &str,
+    ) -> Option<FileAggregate> {
+        let conn = self.lock_conn();
+        let row: Option<(String, Vec<u8>)> = conn
+            .query_row(
+                "SELECT signature, payload FROM file_aggregates
+                 WHERE cache = ?1 AND path = ?2 AND mode = ?3",
+                params![cache, key, slot],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .unwrap_or_else(|e| {
+                log::warn!("Stats cache store read failed: {e}");
+                None
+            });
+        drop(conn);
+
+        let (stored_signature, payload) = row?;
+        if stored_signature != signature {
+            return None;
+        }
+        serde_json::from_slice(&payload).ok()
+    }
+
+    /// Persist `aggregate` for `key`/`slot` under `signature`, dropping rows
+    /// for the same file recorded under any other signature.
+    pub(super) fn save(
+        &self,
+        cache: &str,
+        key: &str,
+        slot: usize,
+        signature: &str,
+        aggregate: &FileAggregate,
+    ) {
+        let payload = match serde_json::to_vec(aggregate) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::warn!("Failed to serialize stats aggregate: {e}");
+                return;
+            }
+        };
+        let saved_at = unix_now_secs();
+
+        let mut conn = self.lock_conn();
+        let result = conn.transaction().and_then(|tx| {
+            tx.execute(
+                "DELETE FROM file_aggregates
+                 WHERE cache = ?1 AND path = ?2 AND signature != ?3",
+                params![cache, key, signature],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO file_aggregates
+                     (cache, path, mode, signature, saved_at, payload)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![cache, key, slot, signature, saved_at, payload],
+            )?;
+            tx.commit()
+        });
+        if let Err(e) = result {
+            log::warn!("Stats cache store write failed: {e}");
+        }
+    }
+
+    /// Drop every row (all modes) persisted for `key` in `cache`.
+    pub(super) fn remove(&self, cache: &str, key: &str) {
+        let conn = self.lock_conn();
+        if let Err(e) = conn.execute(
+            "DELETE FROM file_aggregates WHERE cache = ?1 AND path = ?2",
+            params![cache, key],
+        ) {
+            log::warn!("Stats cache store delete failed: {e}");
+        }
+    }
+
+    /// Lock the connection, recovering from a poisoned lock like the
+    /// in-memory caches do.
+    fn lock_conn(&self) -> MutexGuard<'_, Connection> {
+        match self.conn.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn prune_missing_files(conn: &Connection) -> rusqlite::Result<()> {
+    let paths: Vec<String> = {
+        let mut stmt =
+            conn.prepare("SELECT DISTINCT path FROM file_aggregates WHERE cache != ?1")?;
+        let rows = stmt.query_map(params![PROVIDER_CACHE], |row| row.get(0))?;
+        rows.filter_map(Result::ok).collect()
+    };
+    for path in paths.iter().filter(|path| !Path::new(path).exists()) {
+        conn.execute(
+            "DELETE FROM file_aggregates WHERE cache != ?1 AND path = ?2",
+            params![PROVIDER_CACHE, path],
+        )?;
+    }
+    conn.execute(
+        "DELETE FROM file_aggregates WHERE cache = ?1 AND saved_at < ?2",
+        params![PROVIDER_CACHE, unix_now_secs() - PROVIDER_ROW_TTL_SECS],
+    )?;
+    Ok(())
+}
+
+fn unix_now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|elapsed| i64::try_from(elapsed.as_secs()).ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn signature(size: u64, secs: u64) -> String {
+        FileSignature {
+            size,
+            mtime: Some(UNIX_EPOCH + Duration::from_secs(secs)),
+        }
+        .token()
+    }
+
+    fn key(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_store_round_trips_and_invalidates_on_signature_change() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let session = temp_dir.path().join("session.jsonl");
+        fs::write(&session, "{}\n").expect("session file");
+        let store = StatsStore::open(&temp_dir.path().join("store.db")).expect("open store");
+
+        let aggregate = FileAggregate::default();
+        store.save("global", &key(&session), 0, &signature(3, 100), &aggregate);
+
+        assert!(store
+            .load("global", &key(&session), 0, &signature(3, 100))
+            .is_some());
+        assert!(
+            store
+                .load("global", &key(&session), 1, &signature(3, 100))
+                .is_none(),
+            "other mode slot was never stored"
+        );
+        assert!(
+            store
+                .load("message", &key(&session), 0, &signature(3, 100))
+                .is_none(),
+            "caches are keyed separately"
+        );
+        assert!(
+            store
+                .load("global", &key(&session), 0, &signature(4, 100))
+                .is_none(),
+            "size change invalidates"
+        );
+        assert!(
+            store
+                .load("global", &key(&session), 0, &signature(3, 101))
+                .is_none(),
+            "mtime change invalidates"
+        );
+
+        // A save under a new signature drops the other slot's stale row.
+        store.save("global", &key(&session), 1, &signature(3, 100), &aggregate);
+        store.save("global", &key(&session), 0, &signature(9, 200), &aggregate);
+        assert!(store
+            .load("global", &key(&session), 1, &signature(3, 100))
+            .is_none());
+        assert!(store
+            .load("global", &key(&session), 0, &signature(9, 200))
+            .is_some());
+    }
+
+    #[test]
+    fn test_store_survives_reopen_and_prunes_vanished_files() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let db_path = temp_dir.path().join("store.db");
+        let kept = temp_dir.path().join("kept.jsonl");
+        let removed = temp_dir.path().join("removed.jsonl");
+        fs::write(&kept, "{}\n").expect("kept file");
+        fs::write(&removed, "{}\n").expect("removed file");
+
+        {
+            let store = StatsStore::open(&db_path).expect("open store");
+            let aggregate = FileAggregate::default();
+            store.save("global", &key(&kept), 0, &signature(3, 1), &aggregate);
+            store.save("global", &key(&removed), 0, &signature(3, 1), &aggregate);
+        }
+        fs::remove_file(&removed).expect("remove file");
+
+        let store = StatsStore::open(&db_path).expect("reopen store");
+        assert!(store
+            .load("global", &key(&kept), 0, &signature(3, 1))
+            .is_some());
+        let remaining: i64 = store
+            .lock_conn()
+            .query_row("SELECT COUNT(*) FROM file_aggregates", [], |row| row.get(0))
+            .expect("count rows");
+        assert_eq!(remaining, 1, "row for the vanished file is pruned");
+    }
+
+    #[test]
+    fn test_store_keeps_virtual_provider_rows_until_they_expire() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let db_path = temp_dir.path().join("store.db");
+        let fresh = "zed:zed://thread-1";
+        let stale = "zed:zed://thread-2";
+
+        {
+            let store = StatsStore::open(&db_path).expect("open store");
+            let aggregate = FileAggregate::default();
+            store.save(PROVIDER_CACHE, fresh, 0, "row:a", &aggregate);
+            store.save(PROVIDER_CACHE, stale, 0, "row:b", &aggregate);
+            store
+                .lock_conn()
+                .execute(
+                    "UPDATE file_aggregates SET saved_at = 0 WHERE path = ?1",
+                    params![stale],
+                )
+                .expect("age row");
+        }
+
+        let store = StatsStore::open(&db_path).expect("reopen store");
+        assert!(
+            store.load(PROVIDER_CACHE, fresh, 0, "row:a").is_some(),
+            "virtual session paths are not pruned as missing files"
+        );
+        assert!(store.load(PROVIDER_CACHE, stale, 0, "row:b").is_none());
+    }
+
+    #[test]
+    fn test_store_resets_on_schema_version_mismatch() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let db_path = temp_dir.path().join("store.db");
+        let session = temp_dir.path().join("session.jsonl");
+        fs::write(&session, "{}\n").expect("session file");
+
+        {
+            let store = StatsStore::open(&db_path).expect("open store");
+            store.save(
+                "global",
+                &key(&session),
+                0,
+                &signature(3, 1),
+                &FileAggregate::default(),
+            );
+            store
+                .lock_conn()
+                .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+                .expect("bump version");
+        }
+
+        let store = StatsStore::open(&db_path).expect("reopen store");
+        assert!(
+            store
+                .load("global", &key(&session), 0, &signature(3, 1))
+                .is_none(),
+            "rows from another schema version are discarded"
+        );
+    }
+}
@@ -307,6 +307,10 @@ fn handle_file_event(app_handle: &AppHandle, event: &DebouncedEvent) {
     if let Err(e) = app_handle.emit(&watch_event.event_type, &watch_event) {
         log::error!("Failed to emit file watch event: {e}");
     }
+
+    // Keep the persistent search index (when enabled) current so indexed
+    // searches rarely have to catch up at query time.
+    crate::commands::session::refresh_search_index(&event.path);
 }
 
 /// Extract project path and session path from a `.jsonl` file path
@@ -1,13 +1,18 @@
 //! Headless session export (issue #343).
 //!
-//! Renders a session's messages to a standalone HTML report (or raw JSON) without
-//! launching the GUI. The HTML rendering is a faithful Rust port of the frontend
-//! exporters (`src/services/export/contentExtractor.ts` and `htmlExporter.ts`) so
-//! that `--export … --format html` matches the in-app "Export HTML" output.
+//! Renders a session's messages to a standalone HTML report (or raw JSON,
+//! GitHub-flavored Markdown, or provider-neutral normalized JSON) without
+//! launching the GUI. The HTML rendering is a
+//! faithful Rust port of the frontend exporters
+//! (`src/services/export/contentExtractor.ts` and `htmlExporter.ts`) so that
+//! `--export … --format html` matches the in-app "Export HTML" output.
 //!
-//! Messages are read from the raw JSONL on disk, where each line is one message
-//! object with top-level `type`/`timestamp`/`isSidechain` and the conversation
-//! payload nested under `message.{content,model,usage}`.
+//! Claude messages are read from the raw JSONL on disk, where each line is one
+//! message object with top-level `type`/`timestamp`/`isSidechain` and the
+//! conversation payload nested under `message.{content,model,usage}`. Other
+//! providers' sessions go through the same loaders as `load_provider_messages`
+//! and are rendered from the normalized message shape, which carries
+//! `content`/`model`/`usage` at the top level.
 
 use serde_json::Value;
 use std::fs;
@@ -399,7 +404,7 @@ fn escape_html(text: &str) -> String {
 }
 
 /// Group an integer with thousands separators (approximates `toLocaleString`).
-fn group_thousands(n: i64) -> String {
+pub(crate) fn group_thousands(n: i64) -> String {
     let neg = n < 0;
     let digits = n.unsigned_abs().to_string();
     let bytes = digits.as_bytes();
@@ -461,11 +466,9 @@ fn block_to_html(block: &ExtractedBlock) -> String {
     }
 }
 
-/// Render a full session to a standalone HTML document. `messages` are the raw
-/// JSONL message objects; `session_name` is used as the report title.
-pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
-    let filtered: Vec<&Value> = messages.iter().filter(|m| is_exportable(m)).collect();
-
+/// First-to-last timestamp span of the exported messages, e.g.
+/// `2026-01-01 10:00:00 ~ 10:05:00`; empty when no message has a timestamp.
+fn session_date_range(filtered: &[&Value]) -> String {
     let ts_of = |m: &Value| {
         m.get("timestamp")
             .and_then(|v| v.as_str())
@@ -475,7 +478,7 @@ pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
     let last = filtered.last().and_then(|m| ts_of(m));
     let start = first.as_deref().map(format_timestamp);
     let end = last.as_deref().map(format_timestamp);
-    let date_range = match (&start, &end) {
+    match (&start, &end) {
         (Some((sd, st)), Some((ed, _))) if ed != sd => {
             let (_, et) = end.as_ref().unwrap();
             format!("{sd} {st} ~ {ed} {et}")
@@ -483,7 +486,40 @@ pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
         (Some((sd, st)), Some((_, et))) => format!("{sd} {st} ~ {et}"),
         (Some((sd, st)), None) => format!("{sd} {st}"),
         _ => String::new(),
-    };
+    }
+}
+
+/// `in: 1,200 / out: 7 · $0.0012` for an assistant message, or `None` when the
+/// message carries no token counts. `inner` is the nested `message` payload.
+fn usage_summary(msg: &Value, inner: Option<&Value>) -> Option<String> {
+    let usage = inner
+        .and_then(|m| m.get("usage"))
+        .and_then(|v| v.as_object())?;
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(n) = usage.get("input_tokens").and_then(Value::as_i64) {
+        parts.push(format!("in: {}", group_thousands(n)));
+    }
+    if let Some(n) = usage.get("output_tokens").and_then(Value::as_i64) {
+        parts.push(format!("out: {}", group_thousands(n)));
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    let cost = msg
+        .get("costUSD")
+        .or_else(|| inner.and_then(|m| m.get("costUSD")))
+        .and_then(Value::as_f64)
+        .map(|c| format!(" · ${c:.4}"))
+        .unwrap_or_default();
+    Some(format!("{}{cost}", parts.join(" / ")))
+}
+
+/// Render a full session to a standalone HTML document. `messages` are the raw
+/// JSONL message objects; `session_name` is used as the report title.
+pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
+    let filtered: Vec<&Value> = messages.iter().filter(|m| is_exportable(m)).collect();
+
+    let date_range = session_date_range(&filtered);
 
     let type_is = |m: &Value, t: &str| m.get("type").and_then(|v| v.as_str()) == Some(t);
     let user_count = filtered.iter().filter(|m| type_is(m, "user")).count();
@@ -502,7 +538,8 @@ pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
             .map(|ts| escape_html(&format_timestamp(ts).1))
             .unwrap_or_default();
 
-        let inner = msg.get("message");
+        // Raw Claude lines nest the payload; normalized provider messages don't.
+        let inner = msg.get("message").or(Some(*msg));
         let model_html = if is_user {
             String::new()
         } else {
@@ -525,33 +562,13 @@ pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
             .join("\n");
 
         // Token usage (assistant only)
-        let mut usage_html = String::new();
-        if !is_user {
-            if let Some(usage) = inner
-                .and_then(|m| m.get("usage"))
-                .and_then(|v| v.as_object())
-            {
-                let mut parts: Vec<String> = Vec::new();
-                if let Some(n) = usage.get("input_tokens").and_then(Value::as_i64) {
-                    parts.push(format!("in: {}", group_thousands(n)));
-                }
-                if let Some(n) = usage.get("output_tokens").and_then(Value::as_i64) {
-                    parts.push(format!("out: {}", group_thousands(n)));
-                }
-                if !parts.is_empty() {
-                    let cost = msg
-                        .get("costUSD")
-                        .or_else(|| inner.and_then(|m| m.get("costUSD")))
-                        .and_then(Value::as_f64)
-                        .map(|c| format!(" · ${c:.4}"))
-                        .unwrap_or_default();
-                    usage_html = format!(
-                        "<div class=\"usage\">Tokens: {}{cost}</div>",
-                        parts.join(" / ")
-                    );
-                }
-            }
-        }
+        let usage_html = if is_user {
+            String::new()
+        } else {
+            usage_summary(msg, inner)
+                .map(|u| format!("<div class=\"usage\">Tokens: {u}</div>"))
+                .unwrap_or_default()
+        };
 
         message_blocks.push(format!(
             "<div class=\"message\">\n<span class=\"role {role}\">{role_label}</span>{model_html}\n<span class=\"timestamp\">{time}</span>\n<div class=\"content\">{content_html}</div>\n{usage_html}\n</div>"
@@ -566,6 +583,127 @@ pub fn render_session_html(messages: &[Value], session_name: &str) -> String {
     )
 }
 
+// ---------------------------------------------------------------------------
+// Markdown rendering
+// ---------------------------------------------------------------------------
+
+/// A backtick fence longer than any backtick run in `text`, so the content can
+/// never close its own code block.
+fn code_fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Unified-diff output (hunk headers or a `---`/`+++` pair) gets a `diff` fence
+/// so GitHub colours the added and removed lines.
+fn looks_like_diff(text: &str) -> bool {
+    let has = |prefix: &str| text.lines().any(|l| l.starts_with(prefix));
+    has("@@ ") || has("diff --git ") || (has("--- ") && has("+++ "))
+}
+
+fn fenced(text: &str) -> String {
+    let fence = code_fence(text);
+    let lang = if looks_like_diff(text) { "diff" } else { "" };
+    format!("{fence}{lang}\n{}\n{fence}", text.trim_end_matches('\n'))
+}
+
+/// A collapsed `<details>` section. The blank lines around `body` are required
+/// for GitHub to render Markdown inside the HTML block.
+fn details(summary: &str, body: &str) -> String {
+    format!(
+        "<details>\n<summary>{}</summary>\n\n{body}\n\n</details>",
+        escape_html(summary)
+    )
+}
+
+fn block_to_markdown(block: &ExtractedBlock) -> String {
+    match block.kind {
+        BlockKind::Text => block.text.trim_end().to_string(),
+        // Escaped so a stray `</details>` in the reasoning can't end the section.
+        BlockKind::Thinking => details("Thinking", &escape_html(block.text.trim_end())),
+        BlockKind::Tool => {
+            let first_line = block.text.lines().next().unwrap_or_default();
+            details(
+                &format!("Tool: {}", truncate(first_line, 80)),
+                &fenced(&block.text),
+            )
+        }
+        BlockKind::Result => {
+            let summary = if block.text.starts_with("[Error]") {
+                "Result (error)"
+            } else {
+                "Result"
+            };
+            details(summary, &fenced(&block.text))
+        }
+        BlockKind::Code => fenced(&block.text),
+        BlockKind::Search | BlockKind::Media => format!("_{}_", block.text),
+    }
+}
+
+/// Render a full session to GitHub-flavored Markdown for pasting into PRs, wikis
+/// and issues. Tool calls, results and thinking are collapsed into `<details>`
+/// sections; everything else mirrors [`render_session_html`].
+pub fn render_session_markdown(messages: &[Value], session_name: &str) -> String {
+    let filtered: Vec<&Value> = messages.iter().filter(|m| is_exportable(m)).collect();
+
+    let type_is = |m: &Value, t: &str| m.get("type").and_then(|v| v.as_str()) == Some(t);
+    let user_count = filtered.iter().filter(|m| type_is(m, "user")).count();
+    let assistant_count = filtered.iter().filter(|m| type_is(m, "assistant")).count();
+
+    let title = session_name.replace(['\r', '\n'], " ");
+    let mut meta = vec![format!(
+        "{user_count} user / {assistant_count} assistant messages"
+    )];
+    let date_range = session_date_range(&filtered);
+    if !date_range.is_empty() {
+        meta.insert(0, date_range);
+    }
+    let mut sections: Vec<String> = vec![
+        format!("# Session: {title}"),
+        format!("_{}_", meta.join(" · ")),
+    ];
+
+    for msg in &filtered {
+        let is_user = type_is(msg, "user");
+        let inner = msg.get("message").or(Some(*msg));
+
+        let mut heading = vec![if is_user { "User" } else { "Assistant" }.to_string()];
+        if !is_user {
+            if let Some(model) = inner.and_then(|m| m.get("model")).and_then(|v| v.as_str()) {
+                heading.push(format!("`{model}`"));
+            }
+        }
+        if let Some(ts) = msg.get("timestamp").and_then(|v| v.as_str()) {
+            heading.push(format_timestamp(ts).1);
+        }
+
+        let content = inner
+            .and_then(|m| m.get("content"))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let mut parts: Vec<String> = vec![format!("## {}", heading.join(" · "))];
+        parts.extend(
+            extract_blocks(&content)
+                .iter()
+                .map(block_to_markdown)
+                .filter(|b| !b.is_empty()),
+        );
+        if !is_user {
+            if let Some(usage) = usage_summary(msg, inner) {
+                parts.push(format!("_Tokens: {usage}_"));
+            }
+        }
+
+        sections.push("---".to_string());
+        sections.push(parts.join("\n\n"));
+    }
+
+    let mut out = sections.join("\n\n");
+    out.push('\n');
+    out
+}
+
 // ---------------------------------------------------------------------------
 // JSONL loading
 // ---------------------------------------------------------------------------
@@ -592,9 +730,20 @@ pub fn load_jsonl_messages(path: &Path) -> Result<Vec<Value>, String> {
 // ---------------------------------------------------------------------------
 
 use crate::cli_args::extract_flag_value;
+use crate::commands::multi_provider;
+use crate::models::ClaudeSession;
 use std::path::PathBuf;
 
-const USAGE: &str = "Usage: --export <session-id|/abs/path.jsonl> [--format html|json] [--output <file>]\n\nExport a single session to a standalone HTML report (default) or raw JSON\nwithout launching the GUI. <session-id> is resolved under ~/.claude/projects\n(an id prefix is accepted when unambiguous). Without --output, the result is\nwritten to stdout.";
+mod bulk;
+mod normalized;
+mod redact;
+pub use bulk::run_bulk_export;
+pub(crate) use bulk::{collect_sessions, matches_project, parse_date_bound};
+pub use redact::{load_redaction_config, Redactor};
+
+const USAGE: &str = "Usage: --export <session> [--provider <id>] [--format html|json|markdown|normalized]\n       [--output <file>] [--redact] [--redact-config <file>] [--redact-dry-run]\n\nExport a single session to a standalone HTML report (default), JSON, or\nGitHub-flavored Markdown without launching the GUI. <session> is a Claude\nsession id (an id prefix is accepted when unambiguous) or absolute .jsonl path,\nor a provider-qualified session such as codex://<session-id> or\nopencode://<session-id>. --provider <id> selects the provider for a bare id or\npath instead. Without --output, the result is written to stdout.\n\n--format normalized writes the provider-neutral conversation schema (see\ndocs/NORMALIZED_EXPORT.md), the same for every provider.\n\n--redact scrubs secrets, e-mail addresses, internal hostnames and home paths\nusing the built-in detectors plus the rules in\n~/.claude-history-viewer/redaction.json (or --redact-config <file>).\n--redact-dry-run writes a JSON report of what would be redacted, and where,\ninstead of the export.";
+
+const EXPORT_FORMATS: &[&str] = &["html", "json", "markdown", "normalized"];
 
 fn looks_like_session_id(s: &str) -> bool {
     !s.is_empty()
@@ -602,6 +751,181 @@ fn looks_like_session_id(s: &str) -> bool {
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
 }
 
+/// A session located for export.
+enum ExportSource {
+    /// Raw Claude JSONL transcript.
+    ClaudeFile(PathBuf),
+    /// Session of another provider, loaded through its message loader.
+    Provider {
+        provider: String,
+        session_path: String,
+        title: String,
+    },
+}
+
+impl ExportSource {
+    fn provider(&self) -> &str {
+        match self {
+            ExportSource::ClaudeFile(_) => "claude",
+            ExportSource::Provider { provider, .. } => provider,
+        }
+    }
+
+    /// Source for a session listed by `load_provider_sessions`. Claude sessions
+    /// are read straight from their JSONL file.
+    fn from_session(provider: &str, session: ClaudeSession) -> Self {
+        if provider == "claude" {
+            return ExportSource::ClaudeFile(PathBuf::from(session.file_path));
+        }
+        let title = session
+            .summary
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or(session.actual_session_id);
+        ExportSource::Provider {
+            provider: provider.to_string(),
+            session_path: session.file_path,
+            title,
+        }
+    }
+}
+
+/// Split a `--export` value into `(provider, session)`. `--provider` wins;
+/// otherwise a `<provider>://` prefix selects the provider (the full value is
+/// kept, since several providers use that scheme for their session paths).
+fn split_provider(value: &str, provider_flag: Option<&str>) -> (String, String) {
+    if let Some(provider) = provider_flag {
+        return (provider.trim().to_ascii_lowercase(), value.to_string());
+    }
+    match value.split_once("://") {
+        Some((scheme, _))
+            if !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-') =>
+        {
+            (scheme.to_ascii_lowercase(), value.to_string())
+        }
+        _ => ("claude".to_string(), value.to_string()),
+    }
+}
+
+/// Pick the session `value` refers to: an exact session id, native session id
+/// or session path (with or without the `<provider>://` prefix), else an
+/// unambiguous session id prefix.
+fn match_provider_session(
+    provider: &str,
+    value: &str,
+    sessions: Vec<ClaudeSession>,
+) -> Result<ClaudeSession, String> {
+    let scheme = format!("{provider}://");
+    let strip = |s: &str| s.strip_prefix(&scheme).unwrap_or(s).to_string();
+    let key = strip(value);
+    if key.is_empty() {
+        return Err(format!("Missing session after '{scheme}'"));
+    }
+
+    let mut exact = Vec::new();
+    let mut prefix = Vec::new();
+    for session in sessions {
+        let ids = [
+            session.session_id.as_str(),
+            session.actual_session_id.as_str(),
+            session.file_path.as_str(),
+        ];
+        if ids.iter().any(|id| *id == value || strip(id) == key) {
+            exact.push(session);
+        } else if ids[..2].iter().any(|id| strip(id).starts_with(&key)) {
+            prefix.push(session);
+        }
+    }
+    // The same session can be listed under several projects (e.g. workspaces).
+    let dedup = |mut found: Vec<ClaudeSession>| {
+        found.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        found.dedup_by(|a, b| a.file_path == b.file_path);
+        found
+    };
+    let exact = dedup(exact);
+    let mut matches = if exact.is_empty() {
+        dedup(prefix)
+    } else {
+        exact
+    };
+    match matches.len() {
+        0 => Err(format!("No {provider} session found matching '{value}'")),
+        1 => Ok(matches.remove(0)),
+        n => Err(format!(
+            "'{value}' is ambiguous — {n} {provider} sessions match; use the full session id"
+        )),
+    }
+}
+
+/// Locate a non-Claude session by scanning the provider's projects.
+fn resolve_provider_session(provider: &str, value: &str) -> Result<ClaudeSession, String> {
+    let projects = tauri::async_runtime::block_on(multi_provider::scan_all_projects(
+        None,
+        Some(vec![provider.to_string()]),
+        None,
+        None,
+        None,
+    ))?;
+    if projects.is_empty() {
+        return Err(format!("No {provider} projects found on this machine"));
+    }
+
+    let mut sessions = Vec::new();
+    for project in projects {
+        match tauri::async_runtime::block_on(multi_provider::load_provider_sessions(
+            provider.to_string(),
+            project.path.clone(),
+            Some(false),
+        )) {
+            Ok(found) => sessions.extend(found),
+            Err(e) => log::warn!("Skipping {provider} project {}: {e}", project.path),
+        }
+    }
+    match_provider_session(provider, value, sessions)
+}
+
+/// Resolve a `--export` argument (and optional `--provider`) to its session.
+fn resolve_export_source(value: &str, provider_flag: Option<&str>) -> Result<ExportSource, String> {
+    let (provider, value) = split_provider(value, provider_flag);
+    if provider == "claude" {
+        let value = value.strip_prefix("claude://").unwrap_or(&value);
+        return resolve_session_path(value).map(ExportSource::ClaudeFile);
+    }
+    if !multi_provider::is_known_provider(&provider) {
+        return Err(format!("Unknown provider: {provider}"));
+    }
+    let session = resolve_provider_session(&provider, &value)?;
+    Ok(ExportSource::from_session(&provider, session))
+}
+
+/// Load the messages of `source` together with a display title.
+fn load_export_messages(source: &ExportSource) -> Result<(Vec<Value>, String), String> {
+    match source {
+        ExportSource::ClaudeFile(path) => {
+            let messages = load_jsonl_messages(path)?;
+            let title = session_title(&messages, path);
+            Ok((messages, title))
+        }
+        ExportSource::Provider {
+            provider,
+            session_path,
+            title,
+        } => {
+            let messages = tauri::async_runtime::block_on(multi_provider::load_provider_messages(
+                provider.clone(),
+                session_path.clone(),
+            ))?
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to serialize messages: {e}"))?;
+            Ok((messages, title.clone()))
+        }
+    }
+}
+
 /// Resolve a `--export` argument to a `.jsonl` transcript path. Accepts either an
 /// absolute path to a `.jsonl` file, or a session id / id-prefix searched for
 /// under `~/.claude/projects`.
@@ -686,7 +1010,7 @@ fn resolve_session_path(value: &str) -> Result<PathBuf, String> {
 }
 
 /// Best-effort display title: a `summary` message if present, else the file stem.
-fn session_title(messages: &[Value], path: &Path) -> String {
+pub(crate) fn session_title(messages: &[Value], path: &Path) -> String {
     messages
         .iter()
         .find_map(|m| {
@@ -706,6 +1030,32 @@ fn session_title(messages: &[Value], path: &Path) -> String {
         })
 }
 
+/// Render `messages` of a `provider` session in one of [`EXPORT_FORMATS`].
+fn render_export(
+    format: &str,
+    provider: &str,
+    messages: &[Value],
+    title: &str,
+) -> Result<String, String> {
+    match format {
+        "json" => serde_json::to_string_pretty(messages)
+            .map_err(|e| format!("Failed to serialize JSON: {e}")),
+        "normalized" => normalized::render_normalized(provider, messages, title),
+        "markdown" => Ok(render_session_markdown(messages, title)),
+        _ => Ok(render_session_html(messages, title)),
+    }
+}
+
+/// Atomic write: stage to a temp file in the same directory, then rename, so an
+/// interrupted run can't leave a truncated report.
+fn write_atomic(out_path: &Path, contents: &str) -> Result<(), String> {
+    let tmp_path = out_path.with_extension(format!("tmp.{}", uuid::Uuid::new_v4()));
+    fs::write(&tmp_path, contents.as_bytes()).map_err(|e| e.to_string())?;
+    crate::commands::fs_utils::atomic_rename(&tmp_path, out_path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp_path);
+    })
+}
+
 /// Handle the `--export` CLI flag. Returns the process exit code.
 pub fn run_export(args: &[String]) -> i32 {
     let Some(value) = extract_flag_value(args, "--export") else {
@@ -714,59 +1064,76 @@ pub fn run_export(args: &[String]) -> i32 {
     };
 
     let format = extract_flag_value(args, "--format").unwrap_or_else(|| "html".to_string());
-    if format != "html" && format != "json" {
-        eprintln!("Unsupported --format '{format}' (expected 'html' or 'json')");
+    if !EXPORT_FORMATS.contains(&format.as_str()) {
+        eprintln!(
+            "Unsupported --format '{format}' (expected 'html', 'json', 'markdown' or 'normalized')"
+        );
         return 2;
     }
     let output = extract_flag_value(args, "--output");
+    let provider = extract_flag_value(args, "--provider");
+    let redactor = match redact::redactor_from_args(args) {
+        Ok(redactor) => redactor,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
 
-    let path = match resolve_session_path(&value) {
-        Ok(p) => p,
+    let source = match resolve_export_source(&value, provider.as_deref()) {
+        Ok(source) => source,
         Err(e) => {
             eprintln!("{e}");
             return 1;
         }
     };
 
-    let messages = match load_jsonl_messages(&path) {
-        Ok(m) => m,
+    let (mut messages, mut title) = match load_export_messages(&source) {
+        Ok(loaded) => loaded,
         Err(e) => {
             eprintln!("{e}");
             return 1;
         }
     };
     if messages.is_empty() {
-        eprintln!("No messages found in {}", path.display());
+        eprintln!("No messages found in '{value}'");
         return 1;
     }
 
-    let rendered = match format.as_str() {
-        "json" => match serde_json::to_string_pretty(&messages) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("Failed to serialize JSON: {e}");
-                return 1;
-            }
-        },
-        _ => render_session_html(&messages, &session_title(&messages, &path)),
+    let report = redactor
+        .as_ref()
+        .map(|r| r.redact_session(&mut messages, &mut title));
+    let dry_run = report.is_some() && redact::is_dry_run(args);
+    let rendered = match (&report, dry_run) {
+        (Some(report), true) => {
+            eprintln!("{} value(s) would be redacted", report.total);
+            serde_json::to_string_pretty(report)
+                .map_err(|e| format!("Failed to serialize redaction report: {e}"))
+        }
+        _ => render_export(&format, source.provider(), &messages, &title),
     };
+    let rendered = match rendered {
+        Ok(rendered) => rendered,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if let (Some(report), false) = (&report, dry_run) {
+        eprintln!("Redacted {} value(s)", report.total);
+    }
 
     match output {
         Some(out) => {
-            // Atomic write: stage to a temp file in the same directory, then
-            // rename, so an interrupted run can't leave a truncated report.
-            let out_path = Path::new(&out);
-            let tmp_path = out_path.with_extension(format!("tmp.{}", uuid::Uuid::new_v4()));
-            if let Err(e) = fs::write(&tmp_path, rendered.as_bytes()) {
+            if let Err(e) = write_atomic(Path::new(&out), &rendered) {
                 eprintln!("Failed to write {out}: {e}");
                 return 1;
             }
-            if let Err(e) = crate::commands::fs_utils::atomic_rename(&tmp_path, out_path) {
-                let _ = fs::remove_file(&tmp_path);
-                eprintln!("Failed to write {out}: {e}");
-                return 1;
+            if dry_run {
+                eprintln!("Wrote redaction report to {out}");
+            } else {
+                eprintln!("Exported {} message(s) to {out}", messages.len());
             }
-            eprintln!("Exported {} message(s) to {out}", messages.len());
         }
         None => println!("{rendered}"),
     }
@@ -882,6 +1249,131 @@ mod tests {
         assert!(!html.contains("should be skipped"));
     }
 
+    #[test]
+    fn render_html_reads_normalized_provider_messages() {
+        // Provider messages serialize content/model/usage at the top level.
+        let messages = vec![json!({
+            "type": "assistant",
+            "timestamp": "2026-01-01T10:00:05Z",
+            "model": "gpt-5-codex",
+            "content": [{ "type": "text", "text": "from codex" }],
+            "usage": { "input_tokens": 10, "output_tokens": 2 },
+            "isSidechain": false
+        })];
+        let html = render_session_html(&messages, "codex session");
+        assert!(html.contains("gpt-5-codex"));
+        assert!(html.contains("from codex"));
+        assert!(html.contains("in: 10 / out: 2"));
+    }
+
+    fn session(session_id: &str, actual_session_id: &str, file_path: &str) -> ClaudeSession {
+        ClaudeSession {
+            session_id: session_id.to_string(),
+            actual_session_id: actual_session_id.to_string(),
+            file_path: file_path.to_string(),
+            project_name: "p".to_string(),
+            message_count: 1,
+            first_message_time: String::new(),
+            last_message_time: String::new(),
+            last_modified: String::new(),
+            has_tool_use: false,
+            has_errors: false,
+            summary: None,
+            is_renamed: false,
+            provider: None,
+            storage_type: None,
+            entrypoint: None,
+        }
+    }
+
+    #[test]
+    fn split_provider_prefers_flag_then_scheme() {
+        assert_eq!(
+            split_provider("codex://abc", None),
+            ("codex".to_string(), "codex://abc".to_string())
+        );
+        assert_eq!(
+            split_provider("abc", Some("OpenCode")),
+            ("opencode".to_string(), "abc".to_string())
+        );
+        assert_eq!(
+            split_provider("abc-123", None),
+            ("claude".to_string(), "abc-123".to_string())
+        );
+        assert_eq!(split_provider("/tmp/s.jsonl", None).0, "claude");
+    }
+
+    #[test]
+    fn match_provider_session_by_id_path_and_prefix() {
+        let sessions = || {
+            vec![
+                session("/r/rollout-1.jsonl", "019a-aaaa", "/r/rollout-1.jsonl"),
+                session("/r/rollout-2.jsonl", "019a-bbbb", "/r/rollout-2.jsonl"),
+                session("opencode://ses_x", "ses_x", "opencode://proj/ses_x"),
+            ]
+        };
+        let found =
+            |value: &str| match_provider_session("codex", value, sessions()).map(|s| s.file_path);
+        assert_eq!(found("codex://019a-bbbb").unwrap(), "/r/rollout-2.jsonl");
+        assert_eq!(found("/r/rollout-1.jsonl").unwrap(), "/r/rollout-1.jsonl");
+        assert_eq!(found("019a-a").unwrap(), "/r/rollout-1.jsonl");
+        assert!(found("019a").unwrap_err().contains("ambiguous"));
+        assert!(found("codex://").is_err());
+        assert!(found("zzz").unwrap_err().contains("No codex session"));
+
+        let opencode = match_provider_session("opencode", "opencode://ses_x", sessions());
+        assert_eq!(opencode.unwrap().file_path, "opencode://proj/ses_x");
+    }
+
+    #[test]
+    fn run_export_rejects_unknown_provider() {
+        let args = vec!["app".into(), "--export".into(), "nope://abc".into()];
+        assert_eq!(run_export(&args), 1);
+        let args = vec![
+            "app".into(),
+            "--provider".into(),
+            "nope".into(),
+            "--export".into(),
+            "abc".into(),
+        ];
+        assert_eq!(run_export(&args), 1);
+    }
+
+    #[test]
+    fn render_session_markdown_collapses_tools_and_thinking() {
+        let messages = vec![
+            json!({ "type": "user", "timestamp": "2026-01-01T10:00:00Z", "message": { "role": "user", "content": "Fix **it**" } }),
+            json!({ "type": "assistant", "timestamp": "2026-01-01T10:00:05Z", "message": { "role": "assistant", "model": "claude-x", "content": [
+                { "type": "thinking", "thinking": "close </details> early?" },
+                { "type": "tool_use", "id": "t1", "name": "Bash", "input": { "command": "git diff" } },
+                { "type": "text", "text": "Done." }
+            ], "usage": { "input_tokens": 1200, "output_tokens": 7 } } }),
+            json!({ "type": "user", "message": { "role": "user", "content": [
+                { "type": "tool_result", "tool_use_id": "t1", "content": "@@ -1 +1 @@\n-old\n+new" }
+            ] } }),
+        ];
+        let md = render_session_markdown(&messages, "My\nSession");
+        assert!(md.starts_with("# Session: My Session\n"));
+        assert!(md.contains("2 user / 1 assistant messages"));
+        assert!(md.contains("Fix **it**")); // text passes through as Markdown
+        assert!(md.contains("## Assistant · `claude-x`"));
+        assert!(md.contains(
+            "<details>\n<summary>Thinking</summary>\n\nclose &lt;/details&gt; early?\n\n</details>"
+        ));
+        assert!(md.contains(
+            "<summary>Tool: Bash(command: git diff)</summary>\n\n```\nBash(command: git diff)\n```"
+        ));
+        assert!(md.contains("<summary>Result</summary>\n\n```diff\n@@ -1 +1 @@\n-old\n+new\n```"));
+        assert!(md.contains("_Tokens: in: 1,200 / out: 7_"));
+    }
+
+    #[test]
+    fn code_fence_outgrows_backtick_runs() {
+        assert_eq!(code_fence("plain"), "```");
+        assert_eq!(code_fence("a ``` b"), "````");
+        assert_eq!(fenced("x\n"), "```\nx\n```");
+    }
+
     #[test]
     fn render_markdown_neutralizes_raw_html() {
         // comrak with unsafe_ = false drops raw HTML (the TS exporter escapes it
@@ -944,6 +1436,58 @@ mod tests {
         assert!(parsed.is_array());
     }
 
+    #[test]
+    fn run_export_markdown_to_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let session = dir.path().join("s.jsonl");
+        std::fs::write(
+            &session,
+            "{\"type\":\"user\",\"message\":{\"content\":\"x\"}}\n",
+        )
+        .unwrap();
+        let out = dir.path().join("out.md");
+        let args = vec![
+            "app".into(),
+            "--export".into(),
+            session.to_string_lossy().to_string(),
+            "--format".into(),
+            "markdown".into(),
+            "--output".into(),
+            out.to_string_lossy().to_string(),
+        ];
+        assert_eq!(run_export(&args), 0);
+        let md = std::fs::read_to_string(&out).unwrap();
+        assert!(md.starts_with("# Session: s\n"));
+        assert!(md.contains("## User"));
+    }
+
+    #[test]
+    fn run_export_normalized_to_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let session = dir.path().join("s.jsonl");
+        std::fs::write(
+            &session,
+            "{\"type\":\"user\",\"uuid\":\"u1\",\"sessionId\":\"abc\",\"timestamp\":\"2026-01-01T00:00:00Z\",\"message\":{\"role\":\"user\",\"content\":\"x\"}}\n",
+        )
+        .unwrap();
+        let out = dir.path().join("out.json");
+        let args = vec![
+            "app".into(),
+            "--export".into(),
+            session.to_string_lossy().to_string(),
+            "--format".into(),
+            "normalized".into(),
+            "--output".into(),
+            out.to_string_lossy().to_string(),
+        ];
+        assert_eq!(run_export(&args), 0);
+        let parsed: Value = serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(parsed["schemaVersion"], 1);
+        assert_eq!(parsed["provider"], "claude");
+        assert_eq!(parsed["sessionId"], "abc");
+        assert_eq!(parsed["turns"][0]["content"][0]["text"], "x");
+    }
+
     #[test]
     fn run_export_rejects_unknown_format() {
         let args = vec![
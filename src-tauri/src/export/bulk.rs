@@ -0,0 +1,795 @@
+//! Bulk export (`--export-project` / `--export-all`).
+//!
+//! Writes one file per session into `--output-dir`, plus an index (`index.html`,
+//! `index.md` or `index.json`, following `--format`) that links every session
+//! with its title, timestamps, token totals and models, so a project's agent
+//! history can be published as a browsable static archive.
+
+use super::redact::{is_dry_run, redactor_from_args, Redactor};
+use super::{
+    escape_html, format_timestamp, group_thousands, is_exportable, load_export_messages,
+    render_export, write_atomic, ExportSource, CSS, EXPORT_FORMATS,
+};
+use crate::cli_args::extract_flag_value;
+use crate::commands::multi_provider;
+use crate::models::{ClaudeProject, ClaudeSession, RedactionReport};
+use crate::utils::parse_rfc3339_utc;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+const USAGE: &str = "Usage: --export-project <path|name> --output-dir <dir> [options]\n       --export-all --output-dir <dir> [options]\n\nOptions: [--format html|json|markdown|normalized] [--provider <id>] [--since <date>] [--until <date>]\n         [--redact] [--redact-config <file>] [--redact-dry-run]\n\nExport every matching session to its own file in <dir>, plus an index page\n(index.html, index.md or index.json, following --format) that links them. A\nproject is matched by its directory, its provider storage path, or its name,\nacross all providers unless --provider narrows it. Dates are YYYY-MM-DD (local\ntime, inclusive) or RFC 3339; a session is included when its activity overlaps\nthe range. Redaction works as for --export; a dry run prints one report per\nfile and writes nothing.";
+
+/// Longest session-id stem used in an exported file name.
+const MAX_FILE_STEM: usize = 100;
+
+/// A session picked for export, with the project it was listed under.
+pub(crate) struct BulkSession {
+    pub(crate) provider: String,
+    pub(crate) project: String,
+    pub(crate) session: ClaudeSession,
+}
+
+/// One row of the index.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    file: String,
+    title: String,
+    provider: String,
+    project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ended: Option<String>,
+    message_count: usize,
+    total_tokens: u64,
+    models: Vec<String>,
+}
+
+/// Parse a `--since`/`--until` value. A bare date is a whole local day, so the
+/// `until` bound of `2026-01-31` is the last instant of that day.
+pub(crate) fn parse_date_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Some(ts) = parse_rfc3339_utc(value) {
+        return Ok(ts);
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{value}' (expected YYYY-MM-DD or RFC 3339)"))?;
+    let day = if end_of_day {
+        date.succ_opt()
+    } else {
+        Some(date)
+    }
+    .ok_or_else(|| format!("Date out of range: {value}"))?;
+    let midnight = Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .ok_or_else(|| format!("Date out of range: {value}"))?
+        .with_timezone(&Utc);
+    Ok(if end_of_day {
+        midnight - chrono::Duration::nanoseconds(1)
+    } else {
+        midnight
+    })
+}
+
+pub(crate) fn matches_project(project: &ClaudeProject, query: &str) -> bool {
+    let query = query.trim_end_matches(['/', '\\']);
+    [&project.actual_path, &project.path, &project.name]
+        .iter()
+        .any(|candidate| candidate.trim_end_matches(['/', '\\']) == query)
+}
+
+/// Whether the session's activity overlaps `[since, until]`. Sessions without
+/// any parseable timestamp only pass when no bound is set.
+fn session_in_range(
+    session: &ClaudeSession,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> bool {
+    if since.is_none() && until.is_none() {
+        return true;
+    }
+    let last = parse_rfc3339_utc(&session.last_message_time)
+        .or_else(|| parse_rfc3339_utc(&session.last_modified));
+    let first = parse_rfc3339_utc(&session.first_message_time).or(last);
+    let (Some(first), Some(last)) = (first, last) else {
+        return false;
+    };
+    since.map_or(true, |since| last >= since) && until.map_or(true, |until| first <= until)
+}
+
+/// `<provider>-<session id>.<ext>`, restricted to filename-safe characters and
+/// made unique within the export.
+fn export_file_name(
+    provider: &str,
+    session: &ClaudeSession,
+    ext: &str,
+    used: &mut HashSet<String>,
+) -> String {
+    let id = if session.actual_session_id.is_empty() {
+        &session.session_id
+    } else {
+        &session.actual_session_id
+    };
+    let stem: String = format!("{provider}-{id}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_FILE_STEM)
+        .collect();
+    let stem = stem.trim_start_matches('.').to_string();
+
+    let mut name = format!("{stem}.{ext}");
+    let mut n = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{stem}-{n}.{ext}");
+        n += 1;
+    }
+    name
+}
+
+/// Timestamps, message count, token total and models of the exported messages.
+fn summarize_messages(
+    messages: &[Value],
+) -> (Option<String>, Option<String>, usize, u64, Vec<String>) {
+    let filtered: Vec<&Value> = messages.iter().filter(|m| is_exportable(m)).collect();
+    let timestamp = |m: &&Value| {
+        m.get("timestamp")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let started = filtered.iter().find_map(timestamp);
+    let ended = filtered.iter().rev().find_map(timestamp);
+
+    let mut total_tokens = 0u64;
+    let mut models = BTreeSet::new();
+    for msg in &filtered {
+        let inner = msg.get("message").unwrap_or(*msg);
+        if let Some(usage) = inner.get("usage").and_then(Value::as_object) {
+            total_tokens += [
+                "input_tokens",
+                "output_tokens",
+                "cache_creation_input_tokens",
+                "cache_read_input_tokens",
+            ]
+            .iter()
+            .filter_map(|key| usage.get(*key).and_then(Value::as_u64))
+            .sum::<u64>();
+        }
+        if msg.get("type").and_then(Value::as_str) == Some("assistant") {
+            if let Some(model) = inner.get("model").and_then(Value::as_str) {
+                models.insert(model.to_string());
+            }
+        }
+    }
+    (
+        started,
+        ended,
+        filtered.len(),
+        total_tokens,
+        models.into_iter().collect(),
+    )
+}
+
+fn format_when(ts: Option<&str>) -> String {
+    ts.map(|ts| {
+        let (date, time) = format_timestamp(ts);
+        if date == time {
+            date
+        } else {
+            format!("{date} {time}")
+        }
+    })
+    .unwrap_or_default()
+}
+
+/// Escape a Markdown table cell: pipes would split the cell, brackets would
+/// break the link text, and line breaks would end the row.
+fn md_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(['\r', '\n'], " ")
+}
+
+fn render_index(format: &str, heading: &str, entries: &[IndexEntry]) -> Result<String, String> {
+    match format {
+        "json" | "normalized" => serde_json::to_string_pretty(&serde_json::json!({
+            "title": heading,
+            "sessions": entries,
+        }))
+        .map_err(|e| format!("Failed to serialize index: {e}")),
+        "markdown" => {
+            let mut lines = vec![
+                format!("# {}", md_cell(heading)),
+                String::new(),
+                format!("_{} session(s)_", entries.len()),
+                String::new(),
+                "| Session | Provider | Project | Started | Ended | Messages | Tokens | Models |"
+                    .to_string(),
+                "| --- | --- | --- | --- | --- | ---: | ---: | --- |".to_string(),
+            ];
+            for e in entries {
+                lines.push(format!(
+                    "| [{}]({}) | {} | {} | {} | {} | {} | {} | {} |",
+                    md_cell(&e.title),
+                    e.file,
+                    md_cell(&e.provider),
+                    md_cell(&e.project),
+                    format_when(e.started.as_deref()),
+                    format_when(e.ended.as_deref()),
+                    e.message_count,
+                    group_thousands(i64::try_from(e.total_tokens).unwrap_or(i64::MAX)),
+                    md_cell(&e.models.join(", ")),
+                ));
+            }
+            lines.push(String::new());
+            Ok(lines.join("\n"))
+        }
+        _ => {
+            let rows: Vec<String> = entries
+                .iter()
+                .map(|e| {
+                    format!(
+                        "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                        escape_html(&e.file),
+                        escape_html(&e.title),
+                        escape_html(&e.provider),
+                        escape_html(&e.project),
+                        escape_html(&format_when(e.started.as_deref())),
+                        escape_html(&format_when(e.ended.as_deref())),
+                        e.message_count,
+                        group_thousands(i64::try_from(e.total_tokens).unwrap_or(i64::MAX)),
+                        escape_html(&e.models.join(", ")),
+                    )
+                })
+                .collect();
+            let heading = escape_html(heading);
+            Ok(format!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"UTF-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n<title>{heading}</title>\n<style>{CSS}\nbody {{ max-width: 1200px; }}\ntable {{ width: 100%; border-collapse: collapse; font-size: 0.9rem; }}\nth, td {{ text-align: left; padding: 0.4rem 0.6rem; border-bottom: 1px solid #e5e7eb; vertical-align: top; }}\ntd.num {{ text-align: right; white-space: nowrap; }}</style>\n</head>\n<body>\n<h1>{heading}</h1>\n<div class=\"meta\">\n<span>{count} session(s)</span>\n</div>\n<table>\n<thead><tr><th>Session</th><th>Provider</th><th>Project</th><th>Started</th><th>Ended</th><th>Messages</th><th>Tokens</th><th>Models</th></tr></thead>\n<tbody>\n{rows}\n</tbody>\n</table>\n</body>\n</html>",
+                count = entries.len(),
+                rows = rows.join("\n"),
+            ))
+        }
+    }
+}
+
+/// List the sessions matching a project query, provider and date range,
+/// oldest first.
+pub(crate) fn collect_sessions(
+    project_query: Option<&str>,
+    provider: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<BulkSession>, String> {
+    let projects = tauri::async_runtime::block_on(multi_provider::scan_all_projects(
+        None,
+        provider.map(|p| vec![p.to_string()]),
+        None,
+        None,
+        None,
+    ))?;
+    let projects: Vec<ClaudeProject> = projects
+        .into_iter()
+        .filter(|p| project_query.map_or(true, |q| matches_project(p, q)))
+        .collect();
+    if let (Some(query), true) = (project_query, projects.is_empty()) {
+        return Err(format!("No project found matching '{query}'"));
+    }
+
+    let mut seen = HashSet::new();
+    let mut sessions = Vec::new();
+    for project in projects {
+        let provider = project.provider.as_deref().unwrap_or("claude").to_string();
+        let found = match tauri::async_runtime::block_on(multi_provider::load_provider_sessions(
+            provider.clone(),
+            project.path.clone(),
+            Some(true),
+        )) {
+            Ok(found) => found,
+            Err(e) => {
+                log::warn!("Skipping {provider} project {}: {e}", project.path);
+                continue;
+            }
+        };
+        for session in found {
+            if session_in_range(&session, since, until) && seen.insert(session.file_path.clone()) {
+                sessions.push(BulkSession {
+                    provider: provider.clone(),
+                    project: project.actual_path.clone(),
+                    session,
+                });
+            }
+        }
+    }
+    sessions.sort_by_cached_key(|s| parse_rfc3339_utc(&s.session.first_message_time));
+    Ok(sessions)
+}
+
+/// What [`export_sessions`] did.
+struct BulkOutcome {
+    written: usize,
+    failed: usize,
+    /// Redaction report per exported file name, when redaction was requested.
+    redactions: BTreeMap<String, RedactionReport>,
+}
+
+/// Write each session plus the index into `dir`, redacting first when a
+/// `redactor` is given; the index's heading and project paths are redacted
+/// too. A dry run only fills in the redaction reports and writes nothing.
+fn export_sessions(
+    sessions: Vec<BulkSession>,
+    format: &str,
+    heading: &str,
+    dir: &Path,
+    redactor: Option<&Redactor>,
+    dry_run: bool,
+) -> Result<BulkOutcome, String> {
+    if !dry_run {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let ext = match format {
+        "markdown" => "md",
+        "normalized" => "json",
+        other => other,
+    };
+
+    let index_file = format!("index.{ext}");
+    let mut used = HashSet::from([index_file.clone()]);
+    let mut entries = Vec::with_capacity(sessions.len());
+    let mut redactions = BTreeMap::new();
+    let mut failed = 0;
+    for BulkSession {
+        provider,
+        project,
+        session,
+    } in sessions
+    {
+        let file = export_file_name(&provider, &session, ext, &mut used);
+        let label = session.file_path.clone();
+        let written = load_export_messages(&ExportSource::from_session(&provider, session))
+            .and_then(|(mut messages, mut title)| {
+                if let Some(redactor) = redactor {
+                    let report = redactor.redact_session(&mut messages, &mut title);
+                    redactions.insert(file.clone(), report);
+                }
+                if !dry_run {
+                    let rendered = render_export(format, &provider, &messages, &title)?;
+                    write_atomic(&dir.join(&file), &rendered)
+                        .map_err(|e| format!("Failed to write {file}: {e}"))?;
+                }
+                Ok((messages, title))
+            });
+        match written {
+            Ok((messages, title)) => {
+                let (started, ended, message_count, total_tokens, models) =
+                    summarize_messages(&messages);
+                entries.push(IndexEntry {
+                    file,
+                    title,
+                    provider,
+                    project,
+                    started,
+                    ended,
+                    message_count,
+                    total_tokens,
+                    models,
+                });
+            }
+            Err(e) => {
+                eprintln!("Skipping {label}: {e}");
+                failed += 1;
+            }
+        }
+    }
+
+    let mut heading = heading.to_string();
+    if let Some(redactor) = redactor {
+        let mut report = RedactionReport::default();
+        heading = redactor.redact_text(&heading, "title", &mut report);
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.project =
+                redactor.redact_text(&entry.project, &format!("/{i}/project"), &mut report);
+        }
+        redactions.insert(index_file.clone(), report);
+    }
+
+    if !dry_run {
+        let index = render_index(format, &heading, &entries)?;
+        let index_path = dir.join(&index_file);
+        write_atomic(&index_path, &index)
+            .map_err(|e| format!("Failed to write {}: {e}", index_path.display()))?;
+    }
+    Ok(BulkOutcome {
+        written: entries.len(),
+        failed,
+        redactions,
+    })
+}
+
+/// Handle the `--export-project` / `--export-all` CLI flags. Returns the
+/// process exit code.
+pub fn run_bulk_export(args: &[String]) -> i32 {
+    let project = extract_flag_value(args, "--export-project");
+    let all = args.iter().any(|a| a == "--export-all");
+    // Exactly one of the two modes.
+    if project.is_some() == all {
+        eprintln!("{USAGE}");
+        return 2;
+    }
+    let Some(output_dir) = extract_flag_value(args, "--output-dir") else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    let format = extract_flag_value(args, "--format").unwrap_or_else(|| "html".to_string());
+    if !EXPORT_FORMATS.contains(&format.as_str()) {
+        eprintln!(
+            "Unsupported --format '{format}' (expected 'html', 'json', 'markdown' or 'normalized')"
+        );
+        return 2;
+    }
+    let provider = extract_flag_value(args, "--provider");
+    if let Some(p) = provider.as_deref() {
+        if !multi_provider::is_known_provider(p) {
+            eprintln!("Unknown provider: {p}");
+            return 2;
+        }
+    }
+    let redactor = match redactor_from_args(args) {
+        Ok(redactor) => redactor,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+    let dry_run = redactor.is_some() && is_dry_run(args);
+    let bound = |flag: &str, end_of_day: bool| {
+        extract_flag_value(args, flag)
+            .map(|v| parse_date_bound(&v, end_of_day))
+            .transpose()
+    };
+    let (since, until) = match (bound("--since", false), bound("--until", true)) {
+        (Ok(since), Ok(until)) => (since, until),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    let sessions = match collect_sessions(project.as_deref(), provider.as_deref(), since, until) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if sessions.is_empty() {
+        eprintln!("No sessions matched");
+        return 1;
+    }
+
+    let heading = match &project {
+        Some(project) => format!("Project: {project}"),
+        None => "All sessions".to_string(),
+    };
+    let outcome = match export_sessions(
+        sessions,
+        &format,
+        &heading,
+        Path::new(&output_dir),
+        redactor.as_ref(),
+        dry_run,
+    ) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if dry_run {
+        match serde_json::to_string_pretty(&outcome.redactions) {
+            Ok(report) => println!("{report}"),
+            Err(e) => {
+                eprintln!("Failed to serialize redaction report: {e}");
+                return 1;
+            }
+        }
+    } else {
+        eprintln!("Exported {} session(s) to {output_dir}", outcome.written);
+        if redactor.is_some() {
+            let total: usize = outcome.redactions.values().map(|r| r.total).sum();
+            eprintln!("Redacted {total} value(s)");
+        }
+    }
+    if outcome.failed > 0 {
+        eprintln!("{} session(s) could not be exported", outcome.failed);
+        return 1;
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session(actual_session_id: &str, file_path: &str, first: &str, last: &str) -> ClaudeSession {
+        ClaudeSession {
+            session_id: file_path.to_string(),
+            actual_session_id: actual_session_id.to_string(),
+            file_path: file_path.to_string(),
+            project_name: "p".to_string(),
+            message_count: 1,
+            first_message_time: first.to_string(),
+            last_message_time: last.to_string(),
+            last_modified: String::new(),
+            has_tool_use: false,
+            has_errors: false,
+            summary: None,
+            is_renamed: false,
+            provider: None,
+            storage_type: None,
+            entrypoint: None,
+        }
+    }
+
+    fn project(name: &str, path: &str, actual_path: &str) -> ClaudeProject {
+        ClaudeProject {
+            name: name.to_string(),
+            path: path.to_string(),
+            actual_path: actual_path.to_string(),
+            session_count: 0,
+            message_count: 0,
+            last_modified: String::new(),
+            git_info: None,
+            provider: None,
+            storage_type: None,
+            custom_directory_label: None,
+        }
+    }
+
+    #[test]
+    fn parse_date_bound_accepts_dates_and_rfc3339() {
+        let ts = parse_date_bound("2026-01-02T03:04:05Z", false).unwrap();
+        assert_eq!(ts.to_rfc3339(), "2026-01-02T03:04:05+00:00");
+
+        let start = parse_date_bound("2026-01-02", false).unwrap();
+        let end = parse_date_bound("2026-01-02", true).unwrap();
+        assert_eq!(
+            (end - start).num_seconds(),
+            24 * 60 * 60 - 1,
+            "a date bound spans the whole local day"
+        );
+        assert!(parse_date_bound("yesterday", false).is_err());
+    }
+
+    #[test]
+    fn matches_project_by_directory_storage_path_or_name() {
+        let p = project("app", "/home/u/.claude/projects/-work-app", "/work/app");
+        assert!(matches_project(&p, "/work/app/"));
+        assert!(matches_project(&p, "/home/u/.claude/projects/-work-app"));
+        assert!(matches_project(&p, "app"));
+        assert!(!matches_project(&p, "/work"));
+    }
+
+    #[test]
+    fn session_in_range_checks_overlap() {
+        let s = session("a", "/a", "2026-01-10T10:00:00Z", "2026-01-12T10:00:00Z");
+        let at = |ts: &str| parse_rfc3339_utc(ts);
+        assert!(session_in_range(&s, None, None));
+        assert!(session_in_range(&s, at("2026-01-11T00:00:00Z"), None));
+        assert!(!session_in_range(&s, at("2026-01-13T00:00:00Z"), None));
+        assert!(session_in_range(&s, None, at("2026-01-10T10:00:00Z")));
+        assert!(!session_in_range(&s, None, at("2026-01-09T00:00:00Z")));
+
+        let undated = session("b", "/b", "", "");
+        assert!(!session_in_range(
+            &undated,
+            at("2026-01-01T00:00:00Z"),
+            None
+        ));
+    }
+
+    #[test]
+    fn export_file_name_is_safe_and_unique() {
+        let mut used = HashSet::from(["index.md".to_string()]);
+        let s = session("../a b", "/x", "", "");
+        assert_eq!(
+            export_file_name("codex", &s, "md", &mut used),
+            "codex-.._a_b.md"
+        );
+        assert_eq!(
+            export_file_name("codex", &s, "md", &mut used),
+            "codex-.._a_b-2.md"
+        );
+    }
+
+    #[test]
+    fn summarize_messages_totals_tokens_and_models() {
+        let messages = vec![
+            json!({ "type": "user", "timestamp": "2026-01-01T10:00:00Z", "message": { "content": "hi" } }),
+            json!({ "type": "assistant", "timestamp": "2026-01-01T10:00:05Z", "message": { "model": "m-1", "usage": { "input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 100 } } }),
+            json!({ "type": "assistant", "timestamp": "2026-01-01T10:01:00Z", "model": "m-0", "usage": { "input_tokens": 1 } }),
+            json!({ "type": "summary", "summary": "skipped" }),
+        ];
+        let (started, ended, count, tokens, models) = summarize_messages(&messages);
+        assert_eq!(started.as_deref(), Some("2026-01-01T10:00:00Z"));
+        assert_eq!(ended.as_deref(), Some("2026-01-01T10:01:00Z"));
+        assert_eq!(count, 3);
+        assert_eq!(tokens, 116);
+        assert_eq!(models, vec!["m-0".to_string(), "m-1".to_string()]);
+    }
+
+    #[test]
+    fn export_sessions_writes_files_and_markdown_index() {
+        let src = tempfile::TempDir::new().unwrap();
+        let jsonl = src.path().join("s1.jsonl");
+        std::fs::write(
+            &jsonl,
+            "{\"type\":\"summary\",\"summary\":\"Fix | the [build]\"}\n{\"type\":\"user\",\"timestamp\":\"2026-01-01T10:00:00Z\",\"message\":{\"role\":\"user\",\"content\":\"hi\"}}\n{\"type\":\"assistant\",\"timestamp\":\"2026-01-01T10:00:01Z\",\"message\":{\"role\":\"assistant\",\"model\":\"m-1\",\"content\":[{\"type\":\"text\",\"text\":\"hello\"}],\"usage\":{\"input_tokens\":1200,\"output_tokens\":34}}}\n",
+        )
+        .unwrap();
+        let sessions = vec![
+            BulkSession {
+                provider: "claude".to_string(),
+                project: "/work/app".to_string(),
+                session: session("s1", &jsonl.to_string_lossy(), "", ""),
+            },
+            BulkSession {
+                provider: "claude".to_string(),
+                project: "/work/app".to_string(),
+                session: session("gone", "/nonexistent/gone.jsonl", "", ""),
+            },
+        ];
+
+        let out = tempfile::TempDir::new().unwrap();
+        let dir = out.path().join("archive");
+        let outcome =
+            export_sessions(sessions, "markdown", "Project: app", &dir, None, false).unwrap();
+        assert_eq!((outcome.written, outcome.failed), (1, 1));
+
+        let session_md = std::fs::read_to_string(dir.join("claude-s1.md")).unwrap();
+        assert!(session_md.starts_with("# Session: Fix | the [build]\n"));
+
+        let index = std::fs::read_to_string(dir.join("index.md")).unwrap();
+        assert!(index.starts_with("# Project: app\n"));
+        assert!(index.contains("| [Fix \\| the \\[build\\]](claude-s1.md) | claude | /work/app |"));
+        assert!(index.contains("| 2 | 1,234 | m-1 |"));
+    }
+
+    #[test]
+    fn export_sessions_dry_run_reports_without_writing() {
+        let src = tempfile::TempDir::new().unwrap();
+        let jsonl = src.path().join("s1.jsonl");
+        std::fs::write(
+            &jsonl,
+            "{\"type\":\"user\",\"message\":{\"role\":\"user\",\"content\":\"mail ops@example.com\"}}\n",
+        )
+        .unwrap();
+        let sessions = vec![BulkSession {
+            provider: "claude".to_string(),
+            project: "/work/app".to_string(),
+            session: session("s1", &jsonl.to_string_lossy(), "", ""),
+        }];
+        let redactor = Redactor::new(&crate::models::RedactionConfig::default()).unwrap();
+
+        let dir = src.path().join("out");
+        let outcome =
+            export_sessions(sessions, "html", "All", &dir, Some(&redactor), true).unwrap();
+        assert!(!dir.exists());
+        let report = &outcome.redactions["claude-s1.html"];
+        assert_eq!(report.total, 1);
+        assert_eq!(report.findings[0].location, "/0/message/content");
+    }
+
+    #[test]
+    fn export_sessions_redacts_the_index() {
+        let src = tempfile::TempDir::new().unwrap();
+        let jsonl = src.path().join("s1.jsonl");
+        std::fs::write(
+            &jsonl,
+            "{\"type\":\"user\",\"message\":{\"role\":\"user\",\"content\":\"hi\"}}\n",
+        )
+        .unwrap();
+        let sessions = vec![BulkSession {
+            provider: "claude".to_string(),
+            project: "/Users/jane/app".to_string(),
+            session: session("s1", &jsonl.to_string_lossy(), "", ""),
+        }];
+        let redactor = Redactor::new(&crate::models::RedactionConfig::default()).unwrap();
+
+        let dir = src.path().join("out");
+        let outcome = export_sessions(
+            sessions,
+            "markdown",
+            "Project: /Users/jane/app",
+            &dir,
+            Some(&redactor),
+            false,
+        )
+        .unwrap();
+        let index = std::fs::read_to_string(dir.join("index.md")).unwrap();
+        assert!(index.starts_with("# Project: ~/app\n"));
+        assert!(index.contains("| claude | ~/app |"));
+        assert!(!index.contains("jane"));
+        let report = &outcome.redactions["index.md"];
+        assert_eq!(report.total, 2);
+        assert_eq!(report.findings[1].location, "/0/project");
+    }
+
+    #[test]
+    fn render_index_html_escapes_and_links() {
+        let entries = vec![IndexEntry {
+            file: "codex-a.html".to_string(),
+            title: "<b>x</b>".to_string(),
+            provider: "codex".to_string(),
+            project: "/w".to_string(),
+            started: None,
+            ended: None,
+            message_count: 2,
+            total_tokens: 5000,
+            models: vec![],
+        }];
+        let html = render_index("html", "All sessions", &entries).unwrap();
+        assert!(html.contains("<a href=\"codex-a.html\">&lt;b&gt;x&lt;/b&gt;</a>"));
+        assert!(html.contains("<td class=\"num\">5,000</td>"));
+
+        let json: Value =
+            serde_json::from_str(&render_index("json", "All sessions", &entries).unwrap()).unwrap();
+        assert_eq!(json["sessions"][0]["totalTokens"], 5000);
+    }
+
+    #[test]
+    fn run_bulk_export_validates_arguments() {
+        let argv = |items: &[&str]| items.iter().map(|s| (*s).to_string()).collect::<Vec<_>>();
+        // Neither mode, and both modes at once.
+        assert_eq!(
+            run_bulk_export(&argv(&["app", "--output-dir", "/tmp/x"])),
+            2
+        );
+        assert_eq!(
+            run_bulk_export(&argv(&[
+                "app",
+                "--export-all",
+                "--export-project",
+                "p",
+                "--output-dir",
+                "/tmp/x"
+            ])),
+            2
+        );
+        assert_eq!(run_bulk_export(&argv(&["app", "--export-all"])), 2);
+        assert_eq!(
+            run_bulk_export(&argv(&[
+                "app",
+                "--export-all",
+                "--output-dir",
+                "/tmp/x",
+                "--format",
+                "pdf"
+            ])),
+            2
+        );
+        assert_eq!(
+            run_bulk_export(&argv(&[
+                "app",
+                "--export-all",
+                "--output-dir",
+                "/tmp/x",
+                "--since",
+                "last week"
+            ])),
+            2
+        );
+    }
+}
@@ -0,0 +1,616 @@
+//! Normalized conversation export (`--format normalized`).
+//!
+//! A provider-neutral, versioned JSON shape for one conversation, so analytics
+//! can consume every provider's sessions without knowing how each one stores
+//! its transcript. The schema is published as
+//! `docs/schemas/normalized-conversation.v1.schema.json` and described in
+//! `docs/NORMALIZED_EXPORT.md`; bump [`SCHEMA_VERSION`] for any change that is
+//! not purely additive.
+//!
+//! Conversion starts from [`ClaudeMessage`], the shape every provider loader
+//! already produces. Raw Claude JSONL lines are parsed into it first, through
+//! the same parser the viewer uses.
+
+use crate::commands::session::parse_session_bytes;
+use crate::models::{ClaudeMessage, TokenUsage};
+use crate::utils::parse_rfc3339_utc;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Value of the `schema` field.
+pub(crate) const SCHEMA_NAME: &str = "claude-history-viewer/normalized-conversation";
+
+/// Value of the `schemaVersion` field.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// One exported conversation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NormalizedConversation {
+    pub schema: &'static str,
+    pub schema_version: u32,
+    /// Provider id, e.g. `claude`, `codex`, `gemini`.
+    pub provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub title: String,
+    pub timings: Timings,
+    /// Distinct models that produced a turn, in first-use order.
+    pub models: Vec<String>,
+    /// Sum of every turn's usage.
+    pub usage: NormalizedUsage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    pub turns: Vec<Turn>,
+    /// Provider-specific conversation fields with no neutral equivalent.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_metadata: BTreeMap<String, Value>,
+}
+
+/// First and last timestamps, and the wall-clock span between them.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Timings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+}
+
+/// Token counts. Cache reads and writes are reported separately from
+/// `inputTokens`, as providers report them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_field_names)] // Field names are the documented schema.
+pub(crate) struct NormalizedUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub reasoning_tokens: u64,
+}
+
+impl NormalizedUsage {
+    fn from_token_usage(usage: &TokenUsage) -> Self {
+        let n = |v: Option<u32>| u64::from(v.unwrap_or(0));
+        Self {
+            input_tokens: n(usage.input_tokens),
+            output_tokens: n(usage.output_tokens),
+            cache_read_tokens: n(usage.cache_read_input_tokens),
+            cache_write_tokens: n(usage.cache_creation_input_tokens),
+            reasoning_tokens: n(usage.reasoning_tokens),
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+    }
+}
+
+/// One message of the conversation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Turn {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// `user`, `assistant` or `system`.
+    pub role: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub content: Vec<ContentBlock>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<NormalizedUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Part of a subagent (sidechain) rather than the main thread.
+    pub is_sidechain: bool,
+    /// Provider-specific fields with no neutral equivalent.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_metadata: BTreeMap<String, Value>,
+}
+
+/// One piece of a turn's content.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        text: String,
+        /// The provider withheld the reasoning text.
+        redacted: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        name: String,
+        input: Value,
+        /// Executed by the provider's servers rather than the local agent.
+        server_side: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResult {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tool_call_id: Option<String>,
+        /// Text of the result; structured results keep their JSON form.
+        output: Value,
+        is_error: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Image {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        media_type: Option<String>,
+    },
+    /// Any other block, kept verbatim.
+    #[serde(rename_all = "camelCase")]
+    Other {
+        original_type: String,
+        data: Value,
+    },
+}
+
+/// Convert export messages (raw Claude JSONL lines for `claude`, serialized
+/// [`ClaudeMessage`]s for every other provider) into [`ClaudeMessage`]s.
+/// A provider message that does not deserialize fails the export rather
+/// than silently vanishing from it.
+pub(crate) fn to_claude_messages(
+    provider: &str,
+    messages: &[Value],
+) -> Result<Vec<ClaudeMessage>, String> {
+    if provider == "claude" {
+        let mut jsonl = Vec::new();
+        for message in messages {
+            if serde_json::to_writer(&mut jsonl, message).is_ok() {
+                jsonl.push(b'\n');
+            }
+        }
+        return Ok(parse_session_bytes(&jsonl));
+    }
+    messages
+        .iter()
+        .enumerate()
+        .map(|(index, m)| {
+            serde_json::from_value(m.clone())
+                .map_err(|e| format!("Failed to read {provider} message {index}: {e}"))
+        })
+        .collect()
+}
+
+/// Build the normalized form of a conversation.
+pub(crate) fn normalize_conversation(
+    provider: &str,
+    title: &str,
+    messages: &[ClaudeMessage],
+) -> NormalizedConversation {
+    let turns: Vec<Turn> = messages.iter().filter_map(to_turn).collect();
+
+    let mut usage = NormalizedUsage::default();
+    let mut cost_usd: Option<f64> = None;
+    let mut models: Vec<String> = Vec::new();
+    for turn in &turns {
+        if let Some(turn_usage) = &turn.usage {
+            usage.add(turn_usage);
+        }
+        if let Some(cost) = turn.cost_usd {
+            *cost_usd.get_or_insert(0.0) += cost;
+        }
+        if let Some(model) = &turn.model {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+    }
+
+    NormalizedConversation {
+        schema: SCHEMA_NAME,
+        schema_version: SCHEMA_VERSION,
+        provider: provider.to_string(),
+        session_id: messages
+            .iter()
+            .map(|m| m.session_id.as_str())
+            .find(|id| !id.is_empty() && *id != "unknown-session")
+            .map(str::to_string),
+        title: title.to_string(),
+        timings: timings(&turns),
+        models,
+        usage,
+        cost_usd,
+        turns,
+        provider_metadata: conversation_metadata(messages),
+    }
+}
+
+/// Project name and counts of the entries left out of `turns`, by type.
+fn conversation_metadata(messages: &[ClaudeMessage]) -> BTreeMap<String, Value> {
+    let mut metadata = BTreeMap::new();
+    if let Some(project) = messages
+        .iter()
+        .find_map(|m| m.project_name.as_deref().filter(|p| !p.is_empty()))
+    {
+        metadata.insert(
+            "projectName".to_string(),
+            Value::String(project.to_string()),
+        );
+    }
+    let mut skipped: BTreeMap<String, u64> = BTreeMap::new();
+    for message in messages.iter().filter(|m| conversation_role(m).is_none()) {
+        *skipped.entry(message.message_type.clone()).or_default() += 1;
+    }
+    if !skipped.is_empty() {
+        metadata.insert("skippedEntries".to_string(), json!(skipped));
+    }
+    metadata
+}
+
+fn timings(turns: &[Turn]) -> Timings {
+    let mut stamps: Vec<_> = turns
+        .iter()
+        .filter_map(|t| parse_rfc3339_utc(&t.timestamp).map(|ts| (ts, t.timestamp.as_str())))
+        .collect();
+    stamps.sort_by_key(|(ts, _)| *ts);
+    match (stamps.first(), stamps.last()) {
+        (Some((first, started)), Some((last, ended))) => Timings {
+            started_at: Some((*started).to_string()),
+            ended_at: Some((*ended).to_string()),
+            duration_ms: Some((*last - *first).num_milliseconds()),
+        },
+        _ => Timings::default(),
+    }
+}
+
+/// Convert one message, skipping bookkeeping entries (progress, snapshots,
+/// queue operations) that are not part of the conversation.
+fn to_turn(message: &ClaudeMessage) -> Option<Turn> {
+    let role = conversation_role(message)?;
+
+    let mut provider_metadata = BTreeMap::new();
+    let mut keep = |key: &str, value: Option<Value>| {
+        if let Some(value) = value.filter(|v| !v.is_null()) {
+            provider_metadata.insert(key.to_string(), value);
+        }
+    };
+    keep("subtype", message.subtype.clone().map(Value::String));
+    keep("level", message.level.clone().map(Value::String));
+    keep("messageId", message.message_id.clone().map(Value::String));
+    keep("toolUseResult", message.tool_use_result.clone());
+    keep("compactMetadata", message.compact_metadata.clone());
+    keep(
+        "serviceTier",
+        message
+            .usage
+            .as_ref()
+            .and_then(|u| u.service_tier.clone())
+            .map(Value::String),
+    );
+
+    Some(Turn {
+        id: message.uuid.clone(),
+        parent_id: message.parent_uuid.clone(),
+        role: role.to_string(),
+        timestamp: message.timestamp.clone(),
+        model: message.model.clone().filter(|m| !m.is_empty()),
+        content: message
+            .content
+            .as_ref()
+            .map(content_blocks)
+            .unwrap_or_default(),
+        usage: message
+            .usage
+            .as_ref()
+            .map(NormalizedUsage::from_token_usage),
+        stop_reason: message.stop_reason.clone(),
+        duration_ms: message.duration_ms,
+        cost_usd: message.cost_usd,
+        is_sidechain: message.is_sidechain.unwrap_or(false),
+        provider_metadata,
+    })
+}
+
+/// `user`, `assistant` or `system`; `None` for bookkeeping entries.
+fn conversation_role(message: &ClaudeMessage) -> Option<&str> {
+    match message.role.as_deref() {
+        Some(role @ ("user" | "assistant" | "system")) => Some(role),
+        _ => match message.message_type.as_str() {
+            role @ ("user" | "assistant" | "system") => Some(role),
+            _ => None,
+        },
+    }
+}
+
+/// Map message content (a string or an array of typed blocks) to neutral blocks.
+pub(crate) fn content_blocks(content: &Value) -> Vec<ContentBlock> {
+    match content {
+        Value::String(text) if text.is_empty() => Vec::new(),
+        Value::String(text) => vec![ContentBlock::Text { text: text.clone() }],
+        Value::Array(items) => items.iter().filter_map(content_block).collect(),
+        Value::Null => Vec::new(),
+        other => vec![ContentBlock::Other {
+            original_type: "unknown".to_string(),
+            data: other.clone(),
+        }],
+    }
+}
+
+fn content_block(item: &Value) -> Option<ContentBlock> {
+    let Some(obj) = item.as_object() else {
+        return item.as_str().map(|text| ContentBlock::Text {
+            text: text.to_string(),
+        });
+    };
+    let text = |key: &str| obj.get(key).and_then(Value::as_str).map(str::to_string);
+    let typ = obj.get("type").and_then(Value::as_str).unwrap_or("");
+    let block = match typ {
+        "text" => ContentBlock::Text {
+            text: text("text").unwrap_or_default(),
+        },
+        "thinking" => ContentBlock::Thinking {
+            text: text("thinking").unwrap_or_default(),
+            redacted: false,
+        },
+        "redacted_thinking" => ContentBlock::Thinking {
+            text: String::new(),
+            redacted: true,
+        },
+        "tool_use" | "server_tool_use" => ContentBlock::ToolCall {
+            id: text("id"),
+            name: text("name").unwrap_or_else(|| "unknown".to_string()),
+            input: obj.get("input").cloned().unwrap_or(Value::Null),
+            server_side: typ == "server_tool_use",
+        },
+        "mcp_tool_use" => ContentBlock::ToolCall {
+            id: text("id"),
+            name: mcp_tool_name(obj),
+            input: obj.get("input").cloned().unwrap_or(Value::Null),
+            server_side: false,
+        },
+        "tool_result" | "mcp_tool_result" => ContentBlock::ToolResult {
+            tool_call_id: text("tool_use_id"),
+            output: tool_output(obj.get("content")),
+            is_error: obj
+                .get("is_error")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        },
+        // Server tool results (`web_search_tool_result`, `code_execution_tool_result`, …)
+        t if t.ends_with("_tool_result") => ContentBlock::ToolResult {
+            tool_call_id: text("tool_use_id"),
+            output: obj.get("content").cloned().unwrap_or(Value::Null),
+            is_error: false,
+        },
+        "image" => ContentBlock::Image {
+            media_type: obj
+                .get("source")
+                .and_then(|s| s.get("media_type"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        },
+        _ => ContentBlock::Other {
+            original_type: (if typ.is_empty() { "unknown" } else { typ }).to_string(),
+            data: item.clone(),
+        },
+    };
+    Some(block)
+}
+
+fn mcp_tool_name(obj: &Map<String, Value>) -> String {
+    let field = |key: &str| obj.get(key).and_then(Value::as_str).unwrap_or("");
+    match (field("server_name"), field("tool_name")) {
+        ("", "") => "unknown".to_string(),
+        (server, "") => server.to_string(),
+        ("", tool) => tool.to_string(),
+        (server, tool) => format!("{server}.{tool}"),
+    }
+}
+
+/// Tool results are a string, or a list of blocks that is usually all text;
+/// flatten the all-text case to a single string.
+fn tool_output(content: Option<&Value>) -> Value {
+    match content {
+        Some(Value::Array(items)) => {
+            let texts: Option<Vec<&str>> = items
+                .iter()
+                .map(|i| {
+                    i.get("type")
+                        .and_then(Value::as_str)
+                        .filter(|t| *t == "text")
+                        .and_then(|_| i.get("text").and_then(Value::as_str))
+                })
+                .collect();
+            match texts {
+                Some(texts) => Value::String(texts.join("\n")),
+                None => Value::Array(items.clone()),
+            }
+        }
+        Some(other) => other.clone(),
+        None => Value::Null,
+    }
+}
+
+/// Render `messages` from `provider` as pretty-printed normalized JSON.
+pub(crate) fn render_normalized(
+    provider: &str,
+    messages: &[Value],
+    title: &str,
+) -> Result<String, String> {
+    let conversation =
+        normalize_conversation(provider, title, &to_claude_messages(provider, messages)?);
+    serde_json::to_string_pretty(&conversation)
+        .map_err(|e| format!("Failed to serialize normalized export: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(value: Value) -> ClaudeMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn content_blocks_map_tools_thinking_and_unknown_types() {
+        let blocks = content_blocks(&json!([
+            {"type": "thinking", "thinking": "plan"},
+            {"type": "text", "text": "hi"},
+            {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}},
+            {"type": "tool_result", "tool_use_id": "t1", "is_error": true,
+             "content": [{"type": "text", "text": "a"}, {"type": "text", "text": "b"}]},
+            {"type": "mcp_tool_use", "server_name": "gh", "tool_name": "search"},
+            {"type": "image", "source": {"media_type": "image/png", "data": "xx"}},
+            {"type": "citation_marker", "n": 1}
+        ]));
+        assert_eq!(
+            blocks,
+            vec![
+                ContentBlock::Thinking {
+                    text: "plan".into(),
+                    redacted: false
+                },
+                ContentBlock::Text { text: "hi".into() },
+                ContentBlock::ToolCall {
+                    id: Some("t1".into()),
+                    name: "Bash".into(),
+                    input: json!({"command": "ls"}),
+                    server_side: false
+                },
+                ContentBlock::ToolResult {
+                    tool_call_id: Some("t1".into()),
+                    output: json!("a\nb"),
+                    is_error: true
+                },
+                ContentBlock::ToolCall {
+                    id: None,
+                    name: "gh.search".into(),
+                    input: Value::Null,
+                    server_side: false
+                },
+                ContentBlock::Image {
+                    media_type: Some("image/png".into())
+                },
+                ContentBlock::Other {
+                    original_type: "citation_marker".into(),
+                    data: json!({"type": "citation_marker", "n": 1})
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_sums_usage_and_timings_and_skips_bookkeeping() {
+        let messages = vec![
+            message(json!({
+                "uuid": "u1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:00Z",
+                "type": "user", "content": "hello", "provider": "codex"
+            })),
+            message(json!({
+                "uuid": "p1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:01Z",
+                "type": "progress"
+            })),
+            message(json!({
+                "uuid": "a1", "parentUuid": "u1", "sessionId": "s1",
+                "timestamp": "2026-01-01T00:00:02.500Z", "type": "assistant",
+                "role": "assistant", "model": "gpt-5", "content": [{"type": "text", "text": "hi"}],
+                "usage": {"input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 3},
+                "costUSD": 0.25
+            })),
+        ];
+        let conv = normalize_conversation("codex", "Title", &messages);
+        assert_eq!(conv.schema_version, SCHEMA_VERSION);
+        assert_eq!(conv.session_id.as_deref(), Some("s1"));
+        assert_eq!(conv.turns.len(), 2);
+        assert_eq!(conv.turns[1].parent_id.as_deref(), Some("u1"));
+        assert_eq!(conv.models, vec!["gpt-5".to_string()]);
+        assert_eq!(conv.usage.input_tokens, 10);
+        assert_eq!(conv.usage.cache_read_tokens, 3);
+        assert!((conv.cost_usd.unwrap() - 0.25).abs() < f64::EPSILON);
+        assert_eq!(conv.timings.duration_ms, Some(2500));
+        assert_eq!(
+            conv.provider_metadata.get("skippedEntries"),
+            Some(&json!({"progress": 1}))
+        );
+    }
+
+    #[test]
+    fn unreadable_provider_messages_fail_the_export() {
+        let messages = vec![
+            json!({
+                "uuid": "u1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:00Z",
+                "type": "user", "content": "hello"
+            }),
+            json!({"uuid": 7, "type": "assistant"}),
+        ];
+        let err = render_normalized("codex", &messages, "t").unwrap_err();
+        assert!(err.contains("codex message 1"), "{err}");
+    }
+
+    #[test]
+    fn raw_claude_lines_and_provider_messages_normalize_alike() {
+        let raw = vec![json!({
+            "uuid": "a1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:00Z",
+            "type": "assistant",
+            "message": {"role": "assistant", "model": "claude-opus",
+                        "content": [{"type": "text", "text": "hi"}],
+                        "usage": {"input_tokens": 4, "output_tokens": 2}}
+        })];
+        let provider = vec![json!({
+            "uuid": "a1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:00Z",
+            "type": "assistant", "role": "assistant", "model": "claude-opus",
+            "content": [{"type": "text", "text": "hi"}],
+            "usage": {"input_tokens": 4, "output_tokens": 2}
+        })];
+        let from_raw: Value =
+            serde_json::from_str(&render_normalized("claude", &raw, "t").unwrap()).unwrap();
+        let from_provider: Value =
+            serde_json::from_str(&render_normalized("kimi", &provider, "t").unwrap()).unwrap();
+        assert_eq!(from_raw["turns"], from_provider["turns"]);
+        assert_eq!(from_raw["usage"]["outputTokens"], 2);
+        assert_eq!(from_raw["turns"][0]["content"][0]["type"], "text");
+    }
+
+    #[test]
+    fn published_schema_matches_the_emitted_fields() {
+        let schema: Value = serde_json::from_str(include_str!(
+            "../../../docs/schemas/normalized-conversation.v1.schema.json"
+        ))
+        .unwrap();
+        assert_eq!(schema["properties"]["schema"]["const"], SCHEMA_NAME);
+        assert_eq!(
+            schema["properties"]["schemaVersion"]["const"],
+            json!(SCHEMA_VERSION)
+        );
+
+        let messages = vec![message(json!({
+            "uuid": "a1", "sessionId": "s1", "timestamp": "2026-01-01T00:00:00Z",
+            "type": "assistant", "role": "assistant", "model": "m",
+            "content": [{"type": "text", "text": "hi"}], "costUSD": 0.1, "durationMs": 5,
+            "stop_reason": "end_turn", "subtype": "x", "projectName": "p",
+            "usage": {"input_tokens": 1}
+        }))];
+        let conv = serde_json::to_value(normalize_conversation("claude", "t", &messages)).unwrap();
+        let allowed = |def: &Value, value: &Value| {
+            let props = def["properties"].as_object().unwrap();
+            for key in value.as_object().unwrap().keys() {
+                assert!(props.contains_key(key), "schema is missing '{key}'");
+            }
+        };
+        assert_eq!(conv["providerMetadata"]["projectName"], "p");
+        allowed(&schema, &conv);
+        allowed(&schema["$defs"]["turn"], &conv["turns"][0]);
+        allowed(&schema["$defs"]["usage"], &conv["usage"]);
+        allowed(&schema["$defs"]["timings"], &conv["timings"]);
+    }
+}
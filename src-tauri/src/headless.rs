@@ -0,0 +1,632 @@
+//! Headless reporting modes: `--stats`, `--search` and `--list-sessions`.
+//!
+//! Like `--export`, these run before any window is created, so they work from
+//! scripts and cron jobs on machines without a display. They drive the same
+//! commands the GUI uses (`get_global_stats_summary`,
+//! `get_project_stats_summary`, `search_all_providers`, `scan_all_projects`)
+//! and print a plain-text report, or the command's JSON result with `--json`.
+
+use crate::cli_args::extract_flag_value;
+use crate::commands::{multi_provider, stats};
+use crate::export::{collect_sessions, group_thousands, matches_project, parse_date_bound};
+use crate::models::{
+    models_total_cost_usd, ClaudeMessage, ClaudeProject, ClaudeSession, GlobalStatsSummary,
+    ModelStats, ProjectStatsSummary, TokenDistribution, ToolUsageStats,
+};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+const USAGE: &str = "Usage: --stats [--project <path|name>] [--provider <id>] [--since <date>] [--until <date>] [--json]\n       --search <query> [--provider <id>] [--project <name>] [--since <date>] [--until <date>]\n                [--limit <n>] [--regex] [--json]\n       --list-sessions [--project <path|name>] [--provider <id>] [--since <date>] [--until <date>] [--json]\n\n--stats prints usage totals, models, tools and top projects across all\nproviders, or for one project. --search prints one tab-separated line per hit:\ntime, provider, project, session id, snippet. --list-sessions prints one\ntab-separated line per session: start time, provider, message count, session\nid, project, summary. Dates are YYYY-MM-DD (local time, inclusive) or RFC 3339.\n--json prints the underlying result as JSON instead.";
+
+/// Rows shown per section of the text stats report.
+const TOP_ROWS: usize = 10;
+
+/// Default number of search hits.
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Options shared by every headless mode.
+struct CommonArgs {
+    provider: Option<String>,
+    project: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    json: bool,
+}
+
+impl CommonArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let provider = extract_flag_value(args, "--provider");
+        if let Some(provider) = provider.as_deref() {
+            if !multi_provider::is_known_provider(provider) {
+                return Err(format!("Unknown --provider '{provider}'"));
+            }
+        }
+        let since = extract_flag_value(args, "--since")
+            .map(|v| parse_date_bound(&v, false))
+            .transpose()?;
+        let until = extract_flag_value(args, "--until")
+            .map(|v| parse_date_bound(&v, true))
+            .transpose()?;
+        if let (Some(since), Some(until)) = (since, until) {
+            if since > until {
+                return Err("--since must not be after --until".to_string());
+            }
+        }
+        Ok(Self {
+            provider,
+            project: extract_flag_value(args, "--project"),
+            since,
+            until,
+            json: args.iter().any(|a| a == "--json"),
+        })
+    }
+
+    fn providers(&self) -> Option<Vec<String>> {
+        self.provider.clone().map(|p| vec![p])
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> i32 {
+    match serde_json::to_string_pretty(value) {
+        Ok(text) => {
+            println!("{text}");
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to serialize result: {e}");
+            1
+        }
+    }
+}
+
+/// Local `YYYY-MM-DD HH:MM` for an RFC 3339 timestamp, or the raw value.
+fn local_time(ts: &str) -> String {
+    DateTime::parse_from_rfc3339(ts).map_or_else(
+        |_| ts.to_string(),
+        |dt| {
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        },
+    )
+}
+
+/// Collapse whitespace so a value fits on one tab-separated line.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn tokens(n: u64) -> String {
+    group_thousands(i64::try_from(n).unwrap_or(i64::MAX))
+}
+
+fn token_lines(total: u64, dist: &TokenDistribution, models: &[ModelStats]) -> Vec<String> {
+    let mut lines = vec![format!(
+        "Tokens: {} (input {}, output {}, cache write {}, cache read {}, reasoning {})",
+        tokens(total),
+        tokens(dist.input),
+        tokens(dist.output),
+        tokens(dist.cache_creation),
+        tokens(dist.cache_read),
+        tokens(dist.reasoning),
+    )];
+    if let Some(cost) = models_total_cost_usd(models) {
+        lines.push(format!("Cost: ${cost:.2}"));
+    }
+    lines
+}
+
+fn model_lines(models: &[ModelStats]) -> Vec<String> {
+    if models.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![String::new(), "Models".to_string()];
+    lines.extend(models.iter().take(TOP_ROWS).map(|model| {
+        let cost = model
+            .total_cost_usd()
+            .map(|cost| format!("  ${cost:.2}"))
+            .unwrap_or_default();
+        format!(
+            "  {:<32} {:>16} tokens  {:>8} msgs{cost}",
+            model.model_name,
+            tokens(model.token_count),
+            model.message_count,
+        )
+    }));
+    lines
+}
+
+fn tool_lines(tools: &[ToolUsageStats]) -> Vec<String> {
+    if tools.is_empty() {
+        return Vec::new();
+    }
+    let mut lines = vec![String::new(), "Tools".to_string()];
+    lines.extend(
+        tools
+            .iter()
+            .take(TOP_ROWS)
+            .map(|tool| format!("  {:<32} {:>8}", tool.tool_name, tool.usage_count)),
+    );
+    lines
+}
+
+fn render_global_text(summary: &GlobalStatsSummary) -> String {
+    let mut lines = vec![format!(
+        "Projects: {}  Sessions: {}  Messages: {}",
+        summary.total_projects,
+        summary.total_sessions,
+        group_thousands(i64::from(summary.total_messages)),
+    )];
+    lines.extend(token_lines(
+        summary.total_tokens,
+        &summary.token_distribution,
+        &summary.model_distribution,
+    ));
+    if let (Some(first), Some(last)) = (
+        summary.date_range.first_message.as_deref(),
+        summary.date_range.last_message.as_deref(),
+    ) {
+        lines.push(format!(
+            "Active: {} → {} ({} days)",
+            local_time(first),
+            local_time(last),
+            summary.date_range.days_span,
+        ));
+    }
+    lines.push(format!(
+        "Session time: {} min",
+        summary.total_session_duration_minutes
+    ));
+    lines.extend(model_lines(&summary.model_distribution));
+    lines.extend(tool_lines(&summary.most_used_tools));
+    if summary.provider_distribution.len() > 1 {
+        lines.push(String::new());
+        lines.push("Providers".to_string());
+        lines.extend(summary.provider_distribution.iter().map(|provider| {
+            format!(
+                "  {:<32} {:>16} tokens  {:>8} sessions",
+                provider.provider_id,
+                tokens(provider.tokens),
+                provider.sessions,
+            )
+        }));
+    }
+    if !summary.top_projects.is_empty() {
+        lines.push(String::new());
+        lines.push("Top projects".to_string());
+        lines.extend(summary.top_projects.iter().take(TOP_ROWS).map(|project| {
+            format!(
+                "  {:<32} {:>16} tokens  {:>8} sessions",
+                project.project_name,
+                tokens(project.tokens),
+                project.sessions,
+            )
+        }));
+    }
+    lines.join("\n")
+}
+
+fn render_project_text(project: &ClaudeProject, summary: &ProjectStatsSummary) -> String {
+    let mut lines = vec![
+        format!(
+            "Project: {} ({})",
+            summary.project_name, project.actual_path
+        ),
+        format!(
+            "Sessions: {}  Messages: {}",
+            summary.total_sessions,
+            group_thousands(i64::try_from(summary.total_messages).unwrap_or(i64::MAX)),
+        ),
+    ];
+    lines.extend(token_lines(
+        summary.total_tokens,
+        &summary.token_distribution,
+        &summary.model_distribution,
+    ));
+    lines.push(format!(
+        "Session time: {} min (avg {} min)",
+        summary.total_session_duration, summary.avg_session_duration
+    ));
+    lines.extend(model_lines(&summary.model_distribution));
+    lines.extend(tool_lines(&summary.most_used_tools));
+    lines.join("\n")
+}
+
+/// Find the single project matching `query`.
+fn resolve_project(query: &str, provider: Option<Vec<String>>) -> Result<ClaudeProject, String> {
+    let projects = tauri::async_runtime::block_on(multi_provider::scan_all_projects(
+        None, provider, None, None, None,
+    ))?;
+    let mut matches: Vec<ClaudeProject> = projects
+        .into_iter()
+        .filter(|project| matches_project(project, query))
+        .collect();
+    match matches.len() {
+        0 => Err(format!("No project found matching '{query}'")),
+        1 => Ok(matches.remove(0)),
+        n => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .map(|p| format!("  {} {}", p.provider.as_deref().unwrap_or("claude"), p.path))
+                .collect();
+            Err(format!(
+                "'{query}' matches {n} projects; narrow it with --provider or a storage path:\n{}",
+                candidates.join("\n")
+            ))
+        }
+    }
+}
+
+/// Handle the `--stats` CLI flag. Returns the process exit code.
+pub fn run_stats(args: &[String]) -> i32 {
+    let common = match CommonArgs::parse(args) {
+        Ok(common) => common,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+    let since = common.since.map(|ts| ts.to_rfc3339());
+    let until = common.until.map(|ts| ts.to_rfc3339());
+
+    if let Some(query) = common.project.as_deref() {
+        let project = match resolve_project(query, common.providers()) {
+            Ok(project) => project,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+        let summary = match tauri::async_runtime::block_on(stats::get_project_stats_summary(
+            project.path.clone(),
+            since,
+            until,
+            None,
+        )) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+        if common.json {
+            return print_json(&summary);
+        }
+        println!("{}", render_project_text(&project, &summary));
+        return 0;
+    }
+
+    let claude_path = crate::providers::claude::get_base_path().unwrap_or_else(|| {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".claude")
+            .to_string_lossy()
+            .to_string()
+    });
+    let summary = match tauri::async_runtime::block_on(stats::get_global_stats_summary(
+        claude_path,
+        common.providers(),
+        None,
+        since,
+        until,
+        None,
+    )) {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if common.json {
+        return print_json(&summary);
+    }
+    println!("{}", render_global_text(&summary));
+    0
+}
+
+/// Build the `search_all_providers` filter object for the CLI options.
+fn search_filters(common: &CommonArgs, regex: bool) -> Value {
+    let mut filters = serde_json::Map::new();
+    if regex {
+        filters.insert("matchMode".to_string(), json!("regex"));
+    }
+    if let Some(project) = common.project.as_deref() {
+        filters.insert("projects".to_string(), json!([project]));
+    }
+    if common.since.is_some() || common.until.is_some() {
+        // The filter needs both ends; an open end spans all realistic dates.
+        let since = common
+            .since
+            .map_or_else(|| "1970-01-01T00:00:00Z".to_string(), |ts| ts.to_rfc3339());
+        let until = common
+            .until
+            .map_or_else(|| "9999-12-31T23:59:59Z".to_string(), |ts| ts.to_rfc3339());
+        filters.insert("dateRange".to_string(), json!([since, until]));
+    }
+    Value::Object(filters)
+}
+
+fn search_hit_line(message: &ClaudeMessage) -> String {
+    let snippet = message
+        .search_matches
+        .as_ref()
+        .and_then(|matches| matches.first())
+        .map(|hit| one_line(&hit.snippet))
+        .unwrap_or_default();
+    format!(
+        "{}\t{}\t{}\t{}\t{snippet}",
+        local_time(&message.timestamp),
+        message.provider.as_deref().unwrap_or("claude"),
+        message.project_name.as_deref().unwrap_or(""),
+        message.session_id,
+    )
+}
+
+/// Handle the `--search` CLI flag. Returns the process exit code.
+pub fn run_search(args: &[String]) -> i32 {
+    let Some(query) = extract_flag_value(args, "--search") else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    let common = match CommonArgs::parse(args) {
+        Ok(common) => common,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+    let limit = match extract_flag_value(args, "--limit").map(|v| v.parse::<usize>()) {
+        None => DEFAULT_SEARCH_LIMIT,
+        Some(Ok(limit)) if limit > 0 => limit,
+        Some(_) => {
+            eprintln!("--limit must be a positive number\n\n{USAGE}");
+            return 2;
+        }
+    };
+    let filters = search_filters(&common, args.iter().any(|a| a == "--regex"));
+
+    let hits = match tauri::async_runtime::block_on(multi_provider::search_all_providers(
+        None,
+        query,
+        common.providers(),
+        None,
+        Some(filters),
+        Some(limit),
+        None,
+        None,
+        None,
+    )) {
+        Ok(hits) => hits,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if common.json {
+        return print_json(&hits);
+    }
+    if hits.is_empty() {
+        eprintln!("No matches");
+    }
+    for hit in &hits {
+        println!("{}", search_hit_line(hit));
+    }
+    0
+}
+
+/// One `--list-sessions --json` entry.
+#[derive(Serialize)]
+struct SessionListing<'a> {
+    provider: &'a str,
+    project: &'a str,
+    session: &'a ClaudeSession,
+}
+
+fn session_line(provider: &str, project: &str, session: &ClaudeSession) -> String {
+    let id = if session.actual_session_id.is_empty() {
+        &session.session_id
+    } else {
+        &session.actual_session_id
+    };
+    format!(
+        "{}\t{provider}\t{}\t{id}\t{project}\t{}",
+        local_time(&session.first_message_time),
+        session.message_count,
+        one_line(session.summary.as_deref().unwrap_or("")),
+    )
+}
+
+/// Handle the `--list-sessions` CLI flag. Returns the process exit code.
+pub fn run_list_sessions(args: &[String]) -> i32 {
+    let common = match CommonArgs::parse(args) {
+        Ok(common) => common,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+    let sessions = match collect_sessions(
+        common.project.as_deref(),
+        common.provider.as_deref(),
+        common.since,
+        common.until,
+    ) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if common.json {
+        let listing: Vec<SessionListing<'_>> = sessions
+            .iter()
+            .map(|s| SessionListing {
+                provider: &s.provider,
+                project: &s.project,
+                session: &s.session,
+            })
+            .collect();
+        return print_json(&listing);
+    }
+    for s in &sessions {
+        println!("{}", session_line(&s.provider, &s.project, &s.session));
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DateRange, ProviderUsageStats};
+
+    fn argv(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    fn model(name: &str, token_count: u64, cost_usd: Option<f64>) -> ModelStats {
+        ModelStats {
+            provider_id: None,
+            model_name: name.to_string(),
+            service_tier: None,
+            message_count: 3,
+            token_count,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            reasoning_tokens: 0,
+            cost_usd,
+            reported_cost_tokens: cost_usd.map_or(0, |_| token_count),
+            estimated_cost_usd: None,
+            context_breakdown: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn common_args_validate_provider_and_dates() {
+        let parsed = CommonArgs::parse(&argv(&[
+            "app",
+            "--provider",
+            "codex",
+            "--since",
+            "2026-01-01",
+            "--until",
+            "2026-01-31",
+            "--json",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.providers(), Some(vec!["codex".to_string()]));
+        assert!(parsed.json);
+        assert!(parsed.since.unwrap() < parsed.until.unwrap());
+
+        assert!(CommonArgs::parse(&argv(&["app", "--provider", "nope"])).is_err());
+        assert!(CommonArgs::parse(&argv(&["app", "--since", "yesterday"])).is_err());
+        assert!(CommonArgs::parse(&argv(&[
+            "app",
+            "--since",
+            "2026-02-01",
+            "--until",
+            "2026-01-01"
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn search_filters_map_cli_options() {
+        let common = CommonArgs::parse(&argv(&[
+            "app",
+            "--project",
+            "demo",
+            "--since",
+            "2026-01-01T00:00:00Z",
+        ]))
+        .unwrap();
+        let filters = search_filters(&common, true);
+        assert_eq!(filters["matchMode"], "regex");
+        assert_eq!(filters["projects"], json!(["demo"]));
+        assert_eq!(filters["dateRange"][0], "2026-01-01T00:00:00+00:00");
+        crate::commands::session::validate_search_filters(&filters).unwrap();
+
+        let none = CommonArgs::parse(&argv(&["app"])).unwrap();
+        assert_eq!(search_filters(&none, false), json!({}));
+    }
+
+    #[test]
+    fn render_global_text_lists_models_and_projects() {
+        let summary = GlobalStatsSummary {
+            total_projects: 2,
+            total_sessions: 5,
+            total_messages: 1234,
+            total_tokens: 5000,
+            date_range: DateRange::default(),
+            model_distribution: vec![
+                model("claude-sonnet-4-5", 4000, Some(1.5)),
+                model("gpt-5", 1000, None),
+            ],
+            provider_distribution: vec![
+                ProviderUsageStats {
+                    provider_id: "claude".to_string(),
+                    projects: 1,
+                    sessions: 3,
+                    messages: 1000,
+                    tokens: 4000,
+                },
+                ProviderUsageStats {
+                    provider_id: "codex".to_string(),
+                    projects: 1,
+                    sessions: 2,
+                    messages: 234,
+                    tokens: 1000,
+                },
+            ],
+            ..GlobalStatsSummary::default()
+        };
+        let text = render_global_text(&summary);
+        assert!(text.starts_with("Projects: 2  Sessions: 5  Messages: 1,234"));
+        assert!(text.contains("Tokens: 5,000"));
+        assert!(text.contains("Cost: $1.50"));
+        assert!(text.contains("claude-sonnet-4-5"));
+        assert!(text.contains("\nProviders\n  claude"));
+        assert!(!text.contains("Active:"));
+    }
+
+    #[test]
+    fn session_line_is_tab_separated_on_one_line() {
+        let session = ClaudeSession {
+            session_id: "path-id".to_string(),
+            actual_session_id: "abc".to_string(),
+            file_path: "/tmp/abc.jsonl".to_string(),
+            project_name: "demo".to_string(),
+            message_count: 7,
+            first_message_time: "not a time".to_string(),
+            last_message_time: String::new(),
+            last_modified: String::new(),
+            has_tool_use: false,
+            has_errors: false,
+            summary: Some("Fix\nthe  bug".to_string()),
+            is_renamed: false,
+            provider: None,
+            storage_type: None,
+            entrypoint: None,
+        };
+        assert_eq!(
+            session_line("claude", "/work/demo", &session),
+            "not a time\tclaude\t7\tabc\t/work/demo\tFix the bug"
+        );
+    }
+
+    #[test]
+    fn run_modes_reject_bad_arguments() {
+        assert_eq!(run_search(&argv(&["app", "--search"])), 2);
+        assert_eq!(
+            run_search(&argv(&["app", "--search", "x", "--limit", "0"])),
+            2
+        );
+        assert_eq!(run_stats(&argv(&["app", "--stats", "--since", "soon"])), 2);
+        assert_eq!(
+            run_list_sessions(&argv(&["app", "--list-sessions", "--provider", "nope"])),
+            2
+        );
+    }
+}
@@ -2,6 +2,7 @@ pub mod cli;
 pub mod cli_args;
 pub mod commands;
 pub mod export;
+pub mod headless;
 pub mod models;
 pub mod providers;
 pub mod utils;
@@ -22,6 +23,9 @@ const AUTH_USER_FLAG: &str = "--auth-user";
 #[cfg(feature = "webui-server")]
 const AUTH_PASSWORD_HASH_FLAG: &str = "--auth-password-hash";
 
+#[cfg(feature = "webui-server")]
+const AUTH_USERS_FLAG: &str = "--auth-users";
+
 #[cfg(feature = "webui-server")]
 const SECURE_COOKIES_FLAG: &str = "--secure-cookies";
 
@@ -40,6 +44,12 @@ use crate::commands::{
         get_archive_disk_usage, get_archive_sessions, get_expiring_sessions, list_archives,
         load_archive_session_messages, rename_archive,
     },
+    archive_bundle::{export_archive, import_archive},
+    auto_archive::{
+        get_auto_archive_log, load_auto_archive_config, run_auto_archive_now,
+        save_auto_archive_config,
+    },
+    budget::{get_budget_status, load_budgets, save_budgets},
     claude_settings::{
         get_all_mcp_servers, get_all_settings, get_claude_json_config, get_mcp_servers,
         get_settings_by_scope, read_text_file, save_mcp_servers, save_screenshot, save_settings,
@@ -69,8 +79,8 @@ use crate::commands::{
     },
     settings::{delete_preset, get_preset, load_presets, save_preset},
     stats::{
-        get_global_stats_summary, get_project_stats_summary, get_project_token_stats,
-        get_session_comparison, get_session_token_stats,
+        compare_sessions, get_global_stats_summary, get_project_stats_summary,
+        get_project_token_stats, get_session_comparison, get_session_token_stats,
     },
     unified_presets::{
         delete_unified_preset, get_unified_preset, load_unified_presets, save_unified_preset,
@@ -82,23 +92,48 @@ use crate::commands::{
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Headless session export (issue #343): `--export <id|path> [--format html|json]
-    // [--output <file>]`. Handled before any GUI/webview so it works over SSH/CI
-    // with no display.
+    // Headless session export (issue #343): `--export <id|path|provider://id>
+    // [--provider <id>] [--format html|json|markdown] [--output <file>]`. Handled before
+    // any GUI/webview so it works over SSH/CI with no display. Bulk export
+    // (`--export-project <path|name>` / `--export-all`) writes a directory of
+    // sessions plus an index. `--stats`, `--search <query>` and
+    // `--list-sessions` print reports for scripts and cron jobs.
     {
         let args: Vec<String> = std::env::args().collect();
+        if args
+            .iter()
+            .any(|a| a == "--export-all" || a.starts_with("--export-project"))
+        {
+            std::process::exit(export::run_bulk_export(&args));
+        }
         if args
             .iter()
             .any(|a| a == "--export" || a.starts_with("--export="))
         {
             std::process::exit(export::run_export(&args));
         }
+        if args.iter().any(|a| a == "--stats") {
+            std::process::exit(headless::run_stats(&args));
+        }
+        if args
+            .iter()
+            .any(|a| a == "--search" || a.starts_with("--search="))
+        {
+            std::process::exit(headless::run_search(&args));
+        }
+        if args.iter().any(|a| a == "--list-sessions") {
+            std::process::exit(headless::run_list_sessions(&args));
+        }
     }
 
     // Check for --serve flag (WebUI server mode)
     #[cfg(feature = "webui-server")]
     {
         let args: Vec<String> = std::env::args().collect();
+        // `--create-api-token`, `--list-api-tokens`, `--revoke-api-token`
+        if let Some(code) = server::api_tokens::run_cli(&args) {
+            std::process::exit(code);
+        }
         if args.iter().any(|a| a == "--serve") {
             run_server(&args);
             return;
@@ -176,6 +211,16 @@ fn run_tauri() {
         .plugin(tauri_plugin_os::init());
 
     builder
+        .setup(|app| {
+            let handle = app.handle().clone();
+            crate::commands::budget::spawn_budget_monitor(move |alert| {
+                if let Err(e) = handle.emit(crate::commands::budget::BUDGET_ALERT_EVENT, &alert) {
+                    log::error!("Failed to emit budget alert: {e}");
+                }
+            });
+            crate::commands::auto_archive::spawn_auto_archiver();
+            Ok(())
+        })
         .manage(MetadataState::default())
         .manage(startup_session_hint)
         .manage(Arc::new(Mutex::new(None))
@@ -203,7 +248,12 @@ fn run_tauri() {
             get_project_token_stats,
             get_project_stats_summary,
             get_session_comparison,
+            compare_sessions,
             get_global_stats_summary,
+            // Budget commands
+            load_budgets,
+            save_budgets,
+            get_budget_status,
             send_feedback,
             get_system_info,
             open_github_issues,
@@ -272,6 +322,13 @@ fn run_tauri() {
             get_archive_disk_usage,
             get_expiring_sessions,
             export_session,
+            export_archive,
+            import_archive,
+            // Background archiver commands
+            load_auto_archive_config,
+            save_auto_archive_config,
+            get_auto_archive_log,
+            run_auto_archive_now,
             // WSL commands
             detect_wsl_distros,
             is_wsl_available,
@@ -455,12 +512,20 @@ fn run_server(args: &[String]) {
     let (event_tx, _rx) =
         tokio::sync::broadcast::channel::<crate::commands::watcher::FileWatchEvent>(256);
 
+    let (budget_tx, _budget_rx) = tokio::sync::broadcast::channel::<crate::models::BudgetAlert>(16);
+
     let state = Arc::new(server::state::AppState {
         metadata,
         start_time: std::time::Instant::now(),
         auth: resolved_auth.auth.clone(),
         read_only,
         event_tx,
+        budget_tx,
+        metrics: Arc::default(),
+        project_scopes: Arc::default(),
+        api_tokens: Arc::new(server::api_tokens::ApiTokenStore::new(
+            server::api_tokens::api_tokens_path().ok(),
+        )),
     });
 
     // Print access info — resolve a routable IP when bound to 0.0.0.0
@@ -501,16 +566,25 @@ fn run_server(args: &[String]) {
             }
         }
         AuthStartup::Account {
-            username,
+            users,
             source,
             secure_cookies,
         } => {
-            eprintln!("🔐 Account auth enabled for user: {username}");
+            if let [username] = users.as_slice() {
+                eprintln!("🔐 Account auth enabled for user: {username}");
+            } else {
+                eprintln!(
+                    "🔐 Account auth enabled for {} users: {}",
+                    users.len(),
+                    users.join(", ")
+                );
+            }
             eprintln!(
                 "   Credentials source: {}",
                 match source {
-                    AccountAuthSource::Cli => "CLI flags",
-                    AccountAuthSource::Env => "environment variables",
+                    AccountAuthSource::Cli => "CLI flags".to_string(),
+                    AccountAuthSource::Env => "environment variables".to_string(),
+                    AccountAuthSource::File(path) => path.display().to_string(),
                 }
             );
             if *secure_cookies {
@@ -548,6 +622,15 @@ fn run_server(args: &[String]) {
         // Start background file watcher (sends events to broadcast channel)
         let _watcher_handle = start_server_file_watcher(&state);
 
+        // Background budget checks push threshold alerts to SSE subscribers
+        let budget_tx = state.budget_tx.clone();
+        crate::commands::budget::spawn_budget_monitor(move |alert| {
+            let _ = budget_tx.send(alert);
+        });
+
+        // Opt-in archiving of sessions close to Claude's cleanup cutoff
+        crate::commands::auto_archive::spawn_auto_archiver();
+
         server::start(state, &host, port, dist_dir.as_deref(), &base_path).await;
     });
 }
@@ -581,10 +664,11 @@ enum AuthTokenSource {
 }
 
 #[cfg(feature = "webui-server")]
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum AccountAuthSource {
     Cli,
     Env,
+    File(std::path::PathBuf),
 }
 
 #[cfg(feature = "webui-server")]
@@ -601,7 +685,8 @@ enum AuthStartup {
         source: AuthTokenSource,
     },
     Account {
-        username: String,
+        /// Usernames, labelled with role and project limits for `--auth-users` accounts.
+        users: Vec<String>,
         source: AccountAuthSource,
         secure_cookies: bool,
     },
@@ -642,6 +727,8 @@ fn resolve_account_auth(
     args: &[String],
     secure_cookies: bool,
 ) -> Result<Option<ResolvedAuth>, String> {
+    let users_file = require_non_empty_flag(args, AUTH_USERS_FLAG)?
+        .or_else(|| non_empty_env("CCHV_AUTH_USERS_FILE"));
     let username_from_cli = require_non_empty_flag(args, AUTH_USER_FLAG)?;
     let hash_from_cli = require_non_empty_flag(args, AUTH_PASSWORD_HASH_FLAG)?;
     let username_from_env = non_empty_env("CCHV_AUTH_USERNAME");
@@ -653,6 +740,16 @@ fn resolve_account_auth(
         .unwrap_or_default();
     let password_hash = hash_from_cli.clone().or(hash_from_env).unwrap_or_default();
 
+    if let Some(users_file) = users_file {
+        if !username.is_empty() || !password_hash.is_empty() {
+            return Err(format!(
+                "{AUTH_USERS_FLAG} cannot be combined with {AUTH_USER_FLAG}/{AUTH_PASSWORD_HASH_FLAG}; add that account to the users file instead."
+            ));
+        }
+        return resolve_account_users_file(std::path::PathBuf::from(users_file), secure_cookies)
+            .map(Some);
+    }
+
     if username.is_empty() && password_hash.is_empty() {
         return Ok(None);
     }
@@ -683,13 +780,46 @@ fn resolve_account_auth(
             server::auth::AccountAuth::new(username.clone(), password_hash, secure_cookies),
         )),
         startup: AuthStartup::Account {
-            username,
+            users: vec![username],
             source,
             secure_cookies,
         },
     }))
 }
 
+#[cfg(feature = "webui-server")]
+fn resolve_account_users_file(
+    path: std::path::PathBuf,
+    secure_cookies: bool,
+) -> Result<ResolvedAuth, String> {
+    let users = server::auth::load_account_users(&path)?;
+    let labels = users
+        .iter()
+        .map(|user| {
+            if user.projects.is_unrestricted() {
+                format!("{} ({})", user.username, user.role.as_str())
+            } else {
+                format!(
+                    "{} ({}, limited projects)",
+                    user.username,
+                    user.role.as_str()
+                )
+            }
+        })
+        .collect();
+
+    Ok(ResolvedAuth {
+        auth: server::auth::AuthState::Account(std::sync::Arc::new(
+            server::auth::AccountAuth::with_users(users, secure_cookies),
+        )),
+        startup: AuthStartup::Account {
+            users: labels,
+            source: AccountAuthSource::File(path),
+            secure_cookies,
+        },
+    })
+}
+
 #[cfg(feature = "webui-server")]
 fn require_non_empty_flag(args: &[String], flag: &str) -> Result<Option<String>, String> {
     if let Some(value) = crate::cli_args::extract_flag_value(args, flag) {
@@ -805,7 +935,7 @@ mod auth_startup_tests {
 
     fn account(secure_cookies: bool) -> AuthStartup {
         AuthStartup::Account {
-            username: "admin".to_string(),
+            users: vec!["admin".to_string()],
             source: AccountAuthSource::Cli,
             secure_cookies,
         }
@@ -942,6 +1072,7 @@ fn start_server_file_watcher(
                         // at serve time; no push invalidation needed here.
                         // Ignore send errors (no active subscribers yet)
                         let _ = tx.send(watch_event);
+                        crate::commands::session::refresh_search_index(&event.path);
                     }
                 }
             }
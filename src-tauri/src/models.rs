@@ -3,9 +3,12 @@
 //! This module contains all the data structures used throughout the application.
 
 mod antigravity;
+mod auto_archive;
+mod budget;
 mod edit;
 mod message;
 mod metadata;
+mod redaction;
 mod session;
 mod stats;
 
@@ -14,8 +17,11 @@ mod snapshot_tests;
 
 // Re-export all types for backward compatibility
 pub use antigravity::*;
+pub use auto_archive::*;
+pub use budget::*;
 pub use edit::*;
 pub use message::*;
 pub use metadata::*;
+pub use redaction::*;
 pub use session::*;
 pub use stats::*;
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Contents of `~/.claude-history-viewer/auto-archive.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArchiveConfig {
+    /// Scheduled runs are off until this is set.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Archive sessions within this many days of Claude's `cleanupPeriodDays`.
+    #[serde(default = "default_threshold_days")]
+    pub threshold_days: i64,
+    /// Minimum hours between scheduled runs.
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    #[serde(default = "default_include_subagents")]
+    pub include_subagents: bool,
+    /// Claude project storage directories to watch; empty means every project
+    /// under `~/.claude/projects`.
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub retention: AutoArchiveRetention,
+}
+
+impl Default for AutoArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_days: default_threshold_days(),
+            interval_hours: default_interval_hours(),
+            include_subagents: default_include_subagents(),
+            projects: Vec::new(),
+            retention: AutoArchiveRetention::default(),
+        }
+    }
+}
+
+fn default_threshold_days() -> i64 {
+    7
+}
+
+fn default_interval_hours() -> u32 {
+    6
+}
+
+fn default_include_subagents() -> bool {
+    true
+}
+
+/// Limits applied to archives the archiver created. Archives made by hand, or
+/// renamed since, are never deleted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArchiveRetention {
+    /// Delete automatic archives older than this many days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many automatic archives, dropping the oldest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_archives: Option<u32>,
+    /// Drop the oldest automatic archives while their total size exceeds this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_bytes: Option<u64>,
+}
+
+/// Contents of `~/.claude-history-viewer/auto-archive-log.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArchiveLog {
+    /// Most recent runs, oldest first.
+    #[serde(default)]
+    pub runs: Vec<AutoArchiveRun>,
+    /// Archives the archiver created and retention may delete.
+    #[serde(default)]
+    pub archive_ids: Vec<String>,
+    /// Latest archived copy of each source session, by original file path, so
+    /// an unchanged session is not archived twice. Copies pruned by retention
+    /// stay as tombstones until the source file is gone.
+    #[serde(default)]
+    pub sessions: BTreeMap<String, ArchivedSessionStamp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedSessionStamp {
+    pub archive_id: String,
+    pub size_bytes: u64,
+    /// RFC3339 modification time of the source file when it was archived.
+    pub last_modified: String,
+    /// Whether retention deleted `archive_id`; the unchanged session is then
+    /// not archived again.
+    #[serde(default)]
+    pub pruned: bool,
+}
+
+/// Outcome of one archiver run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArchiveRun {
+    pub started_at: String,
+    pub finished_at: String,
+    /// `schedule` or `manual`.
+    pub trigger: String,
+    pub archived: Vec<AutoArchivedSession>,
+    /// Expiring sessions skipped because an identical copy is already archived
+    /// (or was, until retention pruned it).
+    pub skipped_unchanged: u32,
+    /// Automatic archives deleted by the retention rules.
+    pub pruned_archive_ids: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoArchivedSession {
+    pub file_path: String,
+    pub archive_id: String,
+    pub project_name: String,
+    pub days_remaining: i64,
+    pub size_bytes: u64,
+    pub subagent_count: u32,
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+/// What a budget's usage is aggregated over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetScope {
+    /// All providers and projects.
+    Global,
+    /// One provider; `target` is the provider id (e.g. `claude`, `codex`).
+    Provider,
+    /// One project; `target` is the project path as used by the stats commands.
+    Project,
+}
+
+/// Calendar period a budget resets on, in local time. Weeks start on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+/// Unit a budget limit is expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetMetric {
+    Tokens,
+    /// Source cost when the logs record it, otherwise the pricing-table estimate.
+    Usd,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Budget {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub scope: BudgetScope,
+    /// Provider id or project path; ignored for the global scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub period: BudgetPeriod,
+    pub metric: BudgetMetric,
+    pub limit: f64,
+    /// Fractions of `limit` that raise an alert when reached, e.g. `0.8`.
+    #[serde(default = "default_alert_thresholds")]
+    pub thresholds: Vec<f64>,
+}
+
+fn default_alert_thresholds() -> Vec<f64> {
+    vec![0.8, 1.0]
+}
+
+/// Contents of `~/.claude-history-viewer/budgets.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetConfig {
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+}
+
+/// Current burn of one budget within its active period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub budget: Budget,
+    /// RFC3339 start of the active period.
+    pub period_start: String,
+    /// RFC3339 end (exclusive) of the active period.
+    pub period_end: String,
+    /// Usage so far, in the budget's metric.
+    pub used: f64,
+    /// `used / limit`.
+    pub used_ratio: f64,
+    /// Usage at period end if the current rate continues.
+    pub projected: f64,
+    pub projected_ratio: f64,
+    /// Highest threshold reached so far, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crossed_threshold: Option<f64>,
+    /// Tokens that no pricing covers; non-zero means a USD `used` is a lower bound.
+    #[serde(default)]
+    pub unpriced_tokens: u64,
+}
+
+/// Raised once per budget, period and threshold when usage reaches the
+/// threshold. Emitted as the `budget-alert` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    pub budget_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_name: Option<String>,
+    pub scope: BudgetScope,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub period: BudgetPeriod,
+    pub metric: BudgetMetric,
+    pub threshold: f64,
+    pub used: f64,
+    pub limit: f64,
+    pub projected: f64,
+    pub period_start: String,
+    pub period_end: String,
+}
@@ -130,7 +130,7 @@ pub struct RawLogEntry {
     pub is_meta: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ClaudeMessage {
     pub uuid: String,
     #[serde(rename = "parentUuid")]
@@ -211,6 +211,36 @@ pub struct ClaudeMessage {
     /// Provider identifier (claude, codex, opencode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<String>,
+    /// Where the query matched, attached to search results only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_matches: Option<Vec<SearchMatch>>,
+}
+
+/// One search hit inside a message, for highlighting.
+///
+/// Offsets are in UTF-16 code units (JavaScript string indices) so the
+/// frontend can slice strings with them directly.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "webui-server", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    /// Message field holding the hit: `content`, `toolUseResult` or `toolUse`
+    pub field: String,
+    /// JSON pointer to the matched string within `field` (empty when the
+    /// field is itself the string), e.g. `/0/text`
+    pub path: String,
+    /// Index of the content block holding the hit, when `content` is an
+    /// array of blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_index: Option<usize>,
+    /// Match start within the matched string
+    pub start: usize,
+    /// Match end (exclusive) within the matched string
+    pub end: usize,
+    /// The match with surrounding context, cut from the matched string
+    pub snippet: String,
+    /// Match start within `snippet`; the match spans `end - start` units
+    pub snippet_offset: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -436,6 +466,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let serialized = serde_json::to_string(&message).unwrap();
@@ -481,6 +512,7 @@ mod tests {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         let serialized = serde_json::to_string(&message).unwrap();
@@ -92,7 +92,7 @@ impl UserMetadata {
 
     /// Simple glob pattern matching (supports * and ?)
     /// Returns false for patterns that exceed safety limits
-    fn matches_glob_pattern(text: &str, pattern: &str) -> bool {
+    pub(crate) fn matches_glob_pattern(text: &str, pattern: &str) -> bool {
         // ReDoS protection: reject overly long patterns
         if pattern.len() > Self::MAX_PATTERN_LENGTH {
             return false;
@@ -248,6 +248,10 @@ pub struct UserSettings {
     /// Providers explicitly discovered by the user and allowed to scan on startup
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub discovered_provider_ids: Vec<String>,
+
+    /// Whether Claude searches are served from the persistent full-text index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_index: Option<bool>,
 }
 
 #[cfg(test)]
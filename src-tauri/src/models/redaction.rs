@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+/// A user-defined redaction rule. `replacement` may reference capture groups
+/// (`$1`, `${name}`); it defaults to `[REDACTED:<name>]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// Literal path prefix rewrite, e.g. `/srv/acme` → `<repo>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+/// Contents of `~/.claude-history-viewer/redaction.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionConfig {
+    /// Built-in detectors to skip, by name (e.g. `email`).
+    #[serde(default)]
+    pub disabled_detectors: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<RedactionRule>,
+    #[serde(default)]
+    pub path_prefixes: Vec<PathRewrite>,
+    /// Rewrite absolute home directories (`/Users/<name>`, `/home/<name>`,
+    /// `C:\Users\<name>`) to `~`.
+    #[serde(default = "default_true")]
+    pub rewrite_home: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            disabled_detectors: Vec::new(),
+            rules: Vec::new(),
+            path_prefixes: Vec::new(),
+            rewrite_home: true,
+        }
+    }
+}
+
+/// Replacements made by one rule at one location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionFinding {
+    /// Detector, rule or path-rewrite name.
+    pub rule: String,
+    /// JSON pointer into the exported message array (e.g. `/3/message/content/0/text`),
+    /// or `title` for the session title.
+    pub location: String,
+    pub count: usize,
+}
+
+/// What a redaction pass replaced. The matched values themselves are never
+/// included.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionReport {
+    pub total: usize,
+    pub findings: Vec<RedactionFinding>,
+}
@@ -48,6 +48,7 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         assert_json_snapshot!("user_message", message);
@@ -98,6 +99,7 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         assert_json_snapshot!("assistant_message", message);
@@ -166,6 +168,7 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("forgecode".to_string()),
+            ..Default::default()
         };
 
         assert_json_snapshot!("forgecode_message", message);
@@ -225,6 +228,7 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("pi".to_string()),
+            ..Default::default()
         };
 
         assert_json_snapshot!("pi_message", message);
@@ -285,6 +289,7 @@ mod claude_message_snapshots {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: None,
+            ..Default::default()
         };
 
         assert_json_snapshot!("message_with_tool_use", message);
@@ -484,6 +489,7 @@ mod stats_snapshots {
             summary: None,
             most_used_tools: Vec::new(),
             model_distribution: Vec::new(),
+            total_cost_usd: None,
         };
 
         assert_json_snapshot!("session_token_stats", stats);
@@ -20,6 +20,9 @@ pub struct SessionTokenStats {
     /// Per-model token/cost breakdown used by project and session billing UI.
     #[serde(default)]
     pub model_distribution: Vec<ModelStats>,
+    /// [`models_total_cost_usd`] of `model_distribution`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -74,6 +77,9 @@ pub struct ProjectStatsSummary {
     /// Per-model token/cost breakdown used by project billing UI.
     #[serde(default)]
     pub model_distribution: Vec<ModelStats>,
+    /// [`models_total_cost_usd`] of `model_distribution`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -96,6 +102,77 @@ pub struct SessionComparison {
     pub is_above_average: bool,
 }
 
+/// Totals for one side of a `compare_sessions` result, either a whole
+/// session or a single turn.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ComparisonMetrics {
+    pub message_count: usize,
+    pub total_tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Source cost where the provider reports one plus the pricing table
+    /// estimate for the remaining tokens; `None` when neither is available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    pub tool_calls: u32,
+    /// Tool name → call count.
+    pub tools: std::collections::BTreeMap<String, u32>,
+    pub files_edited: Vec<String>,
+    pub error_count: u32,
+    /// Wall-clock span between the first and last timestamped message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// Right-minus-left differences between two [`ComparisonMetrics`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ComparisonDelta {
+    pub total_tokens: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    pub tool_calls: i64,
+    pub error_count: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    pub tools_only_left: Vec<String>,
+    pub tools_only_right: Vec<String>,
+    pub files_only_left: Vec<String>,
+    pub files_only_right: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparedSession {
+    pub provider: String,
+    pub session_path: String,
+    pub session_id: String,
+    pub models: Vec<String>,
+    pub turn_count: usize,
+    pub metrics: ComparisonMetrics,
+}
+
+/// One aligned row of a turn-by-turn comparison.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparedTurn {
+    /// `"prompt"` when both sides share the same prompt, `"position"` when
+    /// they were paired by order between matches, and `"left_only"` /
+    /// `"right_only"` when the other side has no counterpart.
+    pub alignment: String,
+    pub left_index: Option<usize>,
+    pub right_index: Option<usize>,
+    pub prompt: String,
+    pub left: Option<ComparisonMetrics>,
+    pub right: Option<ComparisonMetrics>,
+    pub delta: Option<ComparisonDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPairComparison {
+    pub left: ComparedSession,
+    pub right: ComparedSession,
+    pub delta: ComparisonDelta,
+    pub turns: Vec<ComparedTurn>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DateRange {
     pub first_message: Option<String>,
@@ -120,12 +197,51 @@ pub struct ModelStats {
     pub reasoning_tokens: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cost_usd: Option<f64>,
+    /// Tokens of the messages whose source cost makes up `cost_usd`.
+    #[serde(default)]
+    pub reported_cost_tokens: u64,
+    /// Cost estimated from token counts and the pricing table, independent
+    /// of `cost_usd`; `None` when the model has no token pricing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
     /// Per-request context buckets. Keeping this at the model row preserves
     /// exact context-tier billing after rows are aggregated by model.
     #[serde(default)]
     pub context_breakdown: Vec<ModelContextStats>,
 }
 
+impl ModelStats {
+    /// Tokens with no source cost, billed from the pricing table instead.
+    pub fn unreported_cost_tokens(&self) -> u64 {
+        self.token_count.saturating_sub(self.reported_cost_tokens)
+    }
+
+    /// Source cost where reported plus the estimate for the remaining
+    /// tokens, prorated by token count; `None` when neither is known.
+    pub fn total_cost_usd(&self) -> Option<f64> {
+        let unreported = self.unreported_cost_tokens();
+        #[allow(clippy::cast_precision_loss)]
+        let estimate = self
+            .estimated_cost_usd
+            .filter(|_| unreported > 0)
+            .map(|estimate| estimate * unreported as f64 / self.token_count as f64);
+        match (self.cost_usd, estimate) {
+            (None, None) => None,
+            (cost, estimate) => Some(cost.unwrap_or(0.0) + estimate.unwrap_or(0.0)),
+        }
+    }
+}
+
+/// Cost of a set of models: reported cost plus estimates for unreported
+/// tokens ([`ModelStats::total_cost_usd`]), summed. `None` when no model has
+/// either.
+pub fn models_total_cost_usd(models: &[ModelStats]) -> Option<f64> {
+    models
+        .iter()
+        .filter_map(ModelStats::total_cost_usd)
+        .reduce(|total, cost| total + cost)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ModelContextStats {
     /// Zero means the default/short context tier; otherwise this is the
@@ -146,6 +262,9 @@ pub struct ModelContextStats {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectRanking {
+    /// Set in `provider_projects`; `top_projects` merges providers by name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
     pub project_name: String,
     pub sessions: u32,
     pub messages: u32,
@@ -181,7 +300,14 @@ pub struct GlobalStatsSummary {
     pub most_used_subagents: Vec<ToolUsageStats>,
     pub provider_distribution: Vec<ProviderUsageStats>,
     pub model_distribution: Vec<ModelStats>,
+    /// [`models_total_cost_usd`] of `model_distribution`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
     pub top_projects: Vec<ProjectRanking>,
+    /// Every project, per provider. Only filled when `top_projects` is not
+    /// limited (the `/metrics` summary).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provider_projects: Vec<ProjectRanking>,
 }
 
 #[cfg(test)]
@@ -206,6 +332,7 @@ mod tests {
             summary: Some("Test session summary".to_string()),
             most_used_tools: Vec::new(),
             model_distribution: Vec::new(),
+            total_cost_usd: Some(1.25),
         };
 
         let serialized = serde_json::to_string(&stats).unwrap();
@@ -213,6 +340,7 @@ mod tests {
 
         assert_eq!(deserialized.total_tokens, 1800);
         assert_eq!(deserialized.message_count, 50);
+        assert_eq!(deserialized.total_cost_usd, Some(1.25));
     }
 
     #[test]
@@ -262,4 +390,44 @@ mod tests {
             .unwrap()
             .contains_key("provider_id"));
     }
+
+    #[test]
+    fn test_model_stats_total_cost_adds_estimate_for_unreported_tokens() {
+        let mut stats: ModelStats = serde_json::from_value(json!({
+            "model_name": "claude-sonnet-4-5",
+            "message_count": 2,
+            "token_count": 1000,
+            "input_tokens": 500,
+            "output_tokens": 500,
+            "cache_creation_tokens": 0,
+            "cache_read_tokens": 0,
+            "context_breakdown": []
+        }))
+        .unwrap();
+        assert_eq!(stats.total_cost_usd(), None);
+
+        stats.estimated_cost_usd = Some(2.0);
+        assert_eq!(stats.total_cost_usd(), Some(2.0));
+
+        stats.cost_usd = Some(0.5);
+        stats.reported_cost_tokens = 250;
+        assert_eq!(stats.unreported_cost_tokens(), 750);
+        assert_eq!(stats.total_cost_usd(), Some(2.0));
+
+        stats.reported_cost_tokens = 1000;
+        assert_eq!(stats.total_cost_usd(), Some(0.5));
+
+        stats.reported_cost_tokens = 250;
+        stats.estimated_cost_usd = None;
+        assert_eq!(stats.total_cost_usd(), Some(0.5));
+
+        let mut unpriced = stats.clone();
+        unpriced.cost_usd = None;
+        assert_eq!(models_total_cost_usd(&[]), None);
+        assert_eq!(models_total_cost_usd(&[unpriced.clone()]), None);
+        assert_eq!(
+            models_total_cost_usd(&[stats.clone(), unpriced, stats]),
+            Some(1.0)
+        );
+    }
 }
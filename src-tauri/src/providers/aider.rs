@@ -187,6 +187,13 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     ))
 }
 
+/// Content identity of one session: the signature of the chat history file
+/// holding it (sessions are `#`-indexed slices of one markdown file).
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let (file_path, _) = parse_session_path(session_path).ok()?;
+    super::content_identity::file_identity(Path::new(&file_path))
+}
+
 /// Search across all Aider sessions
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let query_lower = query.to_lowercase();
@@ -192,9 +192,15 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(q_conversation::parse_history(PROVIDER, &value, key))
 }
 
+/// Content identity of one conversation: the signature of the shared
+/// `data.sqlite3` (rows are opaque key/value blobs without a revision).
+pub fn content_identity(_session_path: &str) -> Option<String> {
+    super::content_identity::sqlite_identity(&get_db_path()?)
+}
+
 /// Search across all Amazon Q conversations.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
     let conn = open_db()?;
@@ -596,6 +596,7 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
             prevented_continuation: None,
             compact_metadata: None,
             microcompact_metadata: None,
+            ..Default::default()
         });
 
         // Assistant turn with real token usage
@@ -643,6 +644,7 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
             prevented_continuation: None,
             compact_metadata: None,
             microcompact_metadata: None,
+            ..Default::default()
         });
     }
 
@@ -744,6 +746,7 @@ pub fn search(query: &str, max_results: usize) -> Result<Vec<ClaudeMessage>, Str
             prevented_continuation: None,
             compact_metadata: None,
             microcompact_metadata: None,
+            ..Default::default()
         });
     }
 
@@ -985,6 +988,7 @@ mod tests {
             prevented_continuation: None,
             compact_metadata: None,
             microcompact_metadata: None,
+            ..Default::default()
         }];
 
         let merged = merge_tool_names_into_messages(
@@ -1169,6 +1173,7 @@ mod tests {
                 prevented_continuation: None,
                 compact_metadata: None,
                 microcompact_metadata: None,
+                ..Default::default()
             });
         }
 
@@ -198,6 +198,17 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(messages)
 }
 
+/// Content identity of one task: the signature of its `ui_messages.json`.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let (base_path, task_id) = parse_session_path(session_path).ok()?;
+    super::content_identity::file_identity(
+        &base_path
+            .join("tasks")
+            .join(task_id)
+            .join("ui_messages.json"),
+    )
+}
+
 /// Search across all Cline tasks
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let query_lower = query.to_lowercase();
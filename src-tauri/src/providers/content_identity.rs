@@ -0,0 +1,224 @@
+//! Cheap content identities for provider sessions, used by caches keyed by
+//! session path (the stats cache) to decide whether a cached result is still
+//! valid without loading the session's messages.
+//!
+//! An identity is an opaque token: equal tokens mean "content unchanged",
+//! anything else means "reload". `None` means the provider cannot produce a
+//! trustworthy identity for this path and the caller must not cache.
+//!
+//! Three shapes cover every provider:
+//! - **file** — `(size, mtime)` of the backing file (Codex rollouts, Gemini,
+//!   Qwen, Pi, Copilot, Aider, …).
+//! - **dir** — file count, total bytes, newest mtime and an order-independent
+//!   hash of every `(relative path, size, mtime)` under a session directory
+//!   (Grok, Kimi, Vibe, Cline tasks, `OpenHands` event folders).
+//! - **db** — signatures of an `SQLite` database *and* its `-wal` file. A
+//!   commit always touches one of the two, so this is the cross-process
+//!   equivalent of `PRAGMA data_version` (which only reports changes relative
+//!   to the connection asking, and every read here uses a fresh one).
+//!
+//! Providers whose tables carry a per-session revision (Zed `updated_at`,
+//! `OpenCode` `time_updated`, `llm`'s append-only `responses`) refine the db
+//! shape to a row-level identity in their own module, so writes to one
+//! session do not invalidate every other session in the same database.
+
+use super::ProviderId;
+use crate::utils::{fnv1a_extend, is_symlink, FNV1A_OFFSET};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// Session directories are shallow (`events/*.json`, `tasks/<id>/*.json`);
+/// the bound only protects against a mis-resolved root.
+const MAX_DIR_DEPTH: usize = 4;
+
+/// Content identity of `session_path` for `provider`, or `None` when the
+/// session cannot be identified without loading it.
+pub fn session_content_identity(provider: &ProviderId, session_path: &str) -> Option<String> {
+    match provider {
+        ProviderId::Aider => super::aider::content_identity(session_path),
+        ProviderId::AmazonQ => super::amazon_q::content_identity(session_path),
+        ProviderId::Cline => super::cline::content_identity(session_path),
+        ProviderId::Crush => super::crush::content_identity(session_path),
+        ProviderId::ForgeCode => super::forgecode::content_identity(session_path),
+        ProviderId::Goose => super::goose::content_identity(session_path),
+        ProviderId::Kiro => super::kiro::content_identity(session_path),
+        ProviderId::Llm => super::llm::content_identity(session_path),
+        ProviderId::OpenCode => super::opencode::content_identity(session_path),
+        ProviderId::OpenHands => super::openhands::content_identity(session_path),
+        ProviderId::Trae => super::trae::content_identity(session_path),
+        ProviderId::Zed => super::zed::content_identity(session_path),
+        // Cursor composers and Antigravity usage records are aggregated by
+        // dedicated stats paths that never go through a per-session cache.
+        ProviderId::Antigravity | ProviderId::Cursor => None,
+        _ => path_identity(Path::new(session_path)),
+    }
+}
+
+/// Identity of a real session file or directory; `None` for virtual paths,
+/// missing paths, and symlinks (which the loaders refuse as well).
+pub fn path_identity(path: &Path) -> Option<String> {
+    if is_symlink(path) {
+        return None;
+    }
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.is_file() {
+        file_identity(path)
+    } else if metadata.is_dir() {
+        dir_identity(path)
+    } else {
+        None
+    }
+}
+
+/// `file:<size>:<mtime ns>` for a regular file.
+pub fn file_identity(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok().filter(fs::Metadata::is_file)?;
+    Some(format!(
+        "file:{}:{}",
+        metadata.len(),
+        mtime_nanos(&metadata)
+    ))
+}
+
+/// `dir:<files>:<bytes>:<newest mtime ns>:<hash>` over every regular file
+/// below `path`. The hash makes renames and same-size rewrites visible even
+/// when the newest mtime does not move.
+pub fn dir_identity(path: &Path) -> Option<String> {
+    if !path.is_dir() {
+        return None;
+    }
+    let mut files = 0u64;
+    let mut bytes = 0u64;
+    let mut newest = 0u128;
+    let mut combined = 0u64;
+    for entry in WalkDir::new(path)
+        .follow_links(false)
+        .max_depth(MAX_DIR_DEPTH)
+        .into_iter()
+        .flatten()
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let mtime = mtime_nanos(&metadata);
+        let relative = entry.path().strip_prefix(path).unwrap_or(entry.path());
+        let relative = relative.to_string_lossy();
+        files += 1;
+        bytes += metadata.len();
+        newest = newest.max(mtime);
+        // Wrapping sum keeps the combination independent of walk order.
+        // The 0xff separator keeps `["ab", "c"]` and `["a", "bc"]` apart.
+        let parts: [&[u8]; 3] = [
+            relative.as_bytes(),
+            &metadata.len().to_le_bytes(),
+            &mtime.to_le_bytes(),
+        ];
+        let hash = parts.iter().fold(FNV1A_OFFSET, |hash, part| {
+            fnv1a_extend(fnv1a_extend(hash, part), &[0xff])
+        });
+        combined = combined.wrapping_add(hash);
+    }
+    Some(format!("dir:{files}:{bytes}:{newest}:{combined:016x}"))
+}
+
+/// `db:<size>:<mtime ns>:<wal size>:<wal mtime ns>` for an `SQLite` file.
+/// A missing `-wal` (rollback-journal mode, or checkpointed and removed) is
+/// encoded as zeros.
+pub fn sqlite_identity(db_path: &Path) -> Option<String> {
+    let metadata = fs::metadata(db_path).ok().filter(fs::Metadata::is_file)?;
+    let mut wal_path = db_path.as_os_str().to_owned();
+    wal_path.push("-wal");
+    let (wal_size, wal_mtime) = fs::metadata(&wal_path)
+        .map(|wal| (wal.len(), mtime_nanos(&wal)))
+        .unwrap_or((0, 0));
+    Some(format!(
+        "db:{}:{}:{wal_size}:{wal_mtime}",
+        metadata.len(),
+        mtime_nanos(&metadata)
+    ))
+}
+
+fn mtime_nanos(metadata: &fs::Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn set_mtime(path: &Path, secs: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn file_identity_tracks_size_and_mtime() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("rollout.jsonl");
+        fs::write(&path, "{}\n").unwrap();
+        set_mtime(&path, 1_000);
+        let first = file_identity(&path).unwrap();
+        assert_eq!(file_identity(&path).as_deref(), Some(first.as_str()));
+
+        set_mtime(&path, 2_000);
+        let touched = file_identity(&path).unwrap();
+        assert_ne!(first, touched);
+
+        fs::write(&path, "{}\n{}\n").unwrap();
+        set_mtime(&path, 2_000);
+        assert_ne!(file_identity(&path).unwrap(), touched);
+        assert_eq!(file_identity(&tmp.path().join("missing")), None);
+    }
+
+    #[test]
+    fn dir_identity_detects_same_size_renames() {
+        let tmp = TempDir::new().unwrap();
+        let events = tmp.path().join("events");
+        fs::create_dir_all(&events).unwrap();
+        fs::write(events.join("0.json"), "{}").unwrap();
+        set_mtime(&events.join("0.json"), 1_000);
+        let first = dir_identity(&events).unwrap();
+
+        fs::rename(events.join("0.json"), events.join("1.json")).unwrap();
+        assert_ne!(dir_identity(&events).unwrap(), first);
+        assert_eq!(path_identity(&events), dir_identity(&events));
+    }
+
+    #[test]
+    fn sqlite_identity_changes_on_wal_commit() {
+        let tmp = TempDir::new().unwrap();
+        let db = tmp.path().join("threads.db");
+        let conn = Connection::open(&db).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.execute_batch("CREATE TABLE t (v INTEGER);").unwrap();
+        let before = sqlite_identity(&db).unwrap();
+
+        conn.execute("INSERT INTO t VALUES (1)", []).unwrap();
+        assert_ne!(sqlite_identity(&db).unwrap(), before);
+    }
+
+    #[test]
+    fn virtual_paths_without_provider_support_are_uncacheable() {
+        assert_eq!(
+            session_content_identity(&ProviderId::Cursor, "cursor://abc"),
+            None
+        );
+        assert_eq!(
+            session_content_identity(&ProviderId::Codex, "/definitely/missing.jsonl"),
+            None
+        );
+    }
+}
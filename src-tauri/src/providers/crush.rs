@@ -166,6 +166,14 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     load_messages_conn(&conn, &session_id)
 }
 
+/// Content identity of one Crush session: the signature of its project's
+/// `crush.db`. Crush rewrites message parts in place while streaming, so no
+/// per-row column is a reliable revision.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let (project_dir, _) = parse_session_path(session_path).ok()?;
+    super::content_identity::sqlite_identity(&db_path_for(&project_dir).ok()?)
+}
+
 fn load_messages_conn(conn: &Connection, session_id: &str) -> Result<Vec<ClaudeMessage>, String> {
     let mut stmt = conn
         .prepare(
@@ -196,7 +204,7 @@ fn load_messages_conn(conn: &Connection, session_id: &str) -> Result<Vec<ClaudeM
 
 /// Search across every discovered Crush DB.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
     let pattern = format!("%{query}%");
@@ -647,7 +647,7 @@ pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let base = get_base_path().ok_or("Cursor not found")?;
     let global_db_path = base.join("globalStorage/state.vscdb");
 
-    if !global_db_path.is_file() || query.is_empty() || limit == 0 {
+    if !global_db_path.is_file() || limit == 0 {
         return Ok(Vec::new());
     }
 
@@ -130,6 +130,15 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(Vec::new())
 }
 
+/// Content identity of one conversation: the signature of `.forge.db`. The
+/// conversation columns vary across `ForgeCode` releases, so no per-row
+/// revision is assumed.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    parse_conversation_path(session_path)?;
+    let base_path = get_base_path()?;
+    super::content_identity::sqlite_identity(&Path::new(&base_path).join(".forge.db"))
+}
+
 /// Search `ForgeCode` messages using the detected database path.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let base_path = get_base_path().ok_or_else(|| "ForgeCode not found".to_string())?;
@@ -216,6 +216,12 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     load_messages_conn(&open_db()?, session_id)
 }
 
+/// Content identity of one Goose session: the signature of the shared
+/// sessions database (see `providers::content_identity`).
+pub fn content_identity(_session_path: &str) -> Option<String> {
+    super::content_identity::sqlite_identity(&get_db_path()?)
+}
+
 fn load_messages_conn(conn: &Connection, session_id: &str) -> Result<Vec<ClaudeMessage>, String> {
     let mut stmt = conn
         .prepare(
@@ -254,7 +260,7 @@ fn load_messages_conn(conn: &Connection, session_id: &str) -> Result<Vec<ClaudeM
 
 /// Search across all Goose messages.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
     search_conn(&open_db()?, query, limit)
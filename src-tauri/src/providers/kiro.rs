@@ -173,9 +173,15 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(q_conversation::parse_history(PROVIDER, &value, conv_id))
 }
 
+/// Content identity of one conversation: the signature of the shared
+/// `data.sqlite3` (see `providers::content_identity`).
+pub fn content_identity(_session_path: &str) -> Option<String> {
+    super::content_identity::sqlite_identity(&get_db_path()?)
+}
+
 /// Search across all Kiro conversations
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
 
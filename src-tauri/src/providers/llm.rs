@@ -209,6 +209,31 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     load_messages_conn(&open_db()?, conv_id)
 }
 
+/// Content identity of one conversation. `llm` only ever inserts into
+/// `responses`, so the row count and newest rowid identify the content.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let conv_id = session_path.strip_prefix(SCHEME).unwrap_or(session_path);
+    let conn = open_db().ok()?;
+    let map_row = |row: &rusqlite::Row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?));
+    let (count, max_rowid) = if conv_id == NO_CONVERSATION {
+        conn.query_row(
+            "SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM responses \
+             WHERE conversation_id IS NULL",
+            [],
+            map_row,
+        )
+    } else {
+        conn.query_row(
+            "SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM responses \
+             WHERE conversation_id = ?1",
+            [conv_id],
+            map_row,
+        )
+    }
+    .ok()?;
+    Some(format!("row:{count}:{max_rowid}"))
+}
+
 fn load_messages_conn(conn: &Connection, conv_id: &str) -> Result<Vec<ClaudeMessage>, String> {
     // The orphan bucket selects rows with a NULL conversation_id; named
     // conversations select by id.
@@ -285,7 +310,7 @@ fn load_messages_conn(conn: &Connection, conv_id: &str) -> Result<Vec<ClaudeMess
 
 /// Search across all `llm` responses (prompts + responses).
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
     search_conn(&open_db()?, query, limit)
@@ -10,6 +10,7 @@ pub mod claude;
 pub mod cline;
 pub mod codebuddy;
 pub mod codex;
+pub mod content_identity;
 pub mod continue_dev;
 pub mod copilot;
 pub mod copilot_cli;
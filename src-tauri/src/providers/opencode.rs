@@ -1,6 +1,6 @@
 use super::ProviderInfo;
 use crate::models::{ClaudeMessage, ClaudeProject, ClaudeSession, TokenUsage};
-use crate::utils::{is_safe_storage_id, search_json_value_case_insensitive};
+use crate::utils::{fnv1a, is_safe_storage_id, search_json_value_case_insensitive};
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, OpenFlags};
 use serde_json::Value;
@@ -95,11 +95,7 @@ fn normalize_directory_for_hash(directory: &str) -> String {
 
 fn stable_directory_hash(directory: &str) -> String {
     // FNV-1a keeps the virtual project id deterministic without adding a dependency.
-    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
-    for byte in normalize_directory_for_hash(directory).as_bytes() {
-        hash ^= u64::from(*byte);
-        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
-    }
+    let hash = fnv1a(normalize_directory_for_hash(directory).as_bytes());
     format!("{hash:016x}")
 }
 
@@ -603,12 +599,94 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("opencode".to_string()),
+            ..Default::default()
         });
     }
 
     Ok(messages)
 }
 
+/// Content identity of one session, following the same source order as
+/// [`load_messages`]. For `opencode.db`, `OpenCode` bumps `time_updated` on
+/// every message and part write, so per-session counts and newest revisions
+/// identify the content. Sessions only present in the legacy JSON storage are
+/// identified by their message directory and each message's part directory.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let path_part = session_path
+        .strip_prefix("opencode://")
+        .unwrap_or(session_path);
+    let (_, session_id) = path_part.split_once('/')?;
+    if !is_safe_storage_id(session_id) {
+        return None;
+    }
+    let base_path = get_base_path()?;
+    db_content_identity(&base_path, session_id)
+        .or_else(|| legacy_content_identity(&base_path, session_id))
+}
+
+/// Row-level identity from `opencode.db`, or `None` when the database is
+/// missing or holds no messages for the session (the loader then falls back
+/// to the JSON storage).
+fn db_content_identity(base_path: &str, session_id: &str) -> Option<String> {
+    let conn = open_db(base_path)?;
+    let (messages, message_rev, parts, part_rev): (i64, i64, i64, i64) = conn
+        .query_row(
+            "SELECT \
+               (SELECT COUNT(*) FROM message WHERE session_id = ?1), \
+               (SELECT COALESCE(MAX(time_updated), 0) FROM message WHERE session_id = ?1), \
+               (SELECT COUNT(*) FROM part p JOIN message m ON p.message_id = m.id \
+                WHERE m.session_id = ?1), \
+               (SELECT COALESCE(MAX(p.time_updated), 0) FROM part p \
+                JOIN message m ON p.message_id = m.id WHERE m.session_id = ?1)",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .ok()?;
+    (messages > 0).then(|| format!("row:{messages}:{message_rev}:{parts}:{part_rev}"))
+}
+
+/// Identity of a session in the legacy `storage/` layout: the message
+/// directory plus the part directory of every message in it. Message files
+/// are named after their message id, which is also the part directory name.
+fn legacy_content_identity(base_path: &str, session_id: &str) -> Option<String> {
+    let storage_path = Path::new(base_path).join("storage");
+    let messages_dir = storage_path.join("message").join(session_id);
+    let messages = super::content_identity::dir_identity(&messages_dir)?;
+
+    let mut message_ids: Vec<String> = fs::read_dir(&messages_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            if entry.file_type().map_or(true, |ft| ft.is_symlink()) {
+                return None;
+            }
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_string)
+        })
+        .collect();
+    message_ids.sort();
+    let mut parts = String::new();
+    for message_id in &message_ids {
+        if !is_safe_storage_id(message_id) {
+            return None;
+        }
+        let parts_dir = storage_path.join("part").join(message_id);
+        if let Some(identity) = super::content_identity::dir_identity(&parts_dir) {
+            parts.push_str(message_id);
+            parts.push('=');
+            parts.push_str(&identity);
+            parts.push(';');
+        }
+    }
+    Some(format!(
+        "legacy:{messages}:{:016x}",
+        fnv1a(parts.as_bytes())
+    ))
+}
+
 /// Search `OpenCode` sessions for a query string
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let base_path = get_base_path().ok_or_else(|| "OpenCode not found".to_string())?;
@@ -1087,6 +1165,7 @@ fn load_messages_with_conn(conn: &Connection, session_id: &str) -> Option<Vec<Cl
             compact_metadata: None,
             microcompact_metadata: None,
             provider: Some("opencode".to_string()),
+            ..Default::default()
         });
     }
 
@@ -2135,4 +2214,35 @@ mod tests {
         assert_eq!(sessions[0].actual_session_id, "ses_global_empty");
         assert_eq!(sessions[0].file_path, "opencode://global/ses_global_empty");
     }
+
+    #[test]
+    fn legacy_session_identity_tracks_message_and_part_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().to_string_lossy().to_string();
+        let storage = tmp.path().join("storage");
+        let messages_dir = storage.join("message").join("ses_legacy");
+        fs::create_dir_all(&messages_dir).unwrap();
+        fs::write(messages_dir.join("msg_1.json"), r#"{"id":"msg_1"}"#).unwrap();
+
+        let first = content_identity_for_test(&base, "ses_legacy");
+        assert!(first.starts_with("legacy:"));
+        assert_eq!(content_identity_for_test(&base, "ses_legacy"), first);
+
+        let parts_dir = storage.join("part").join("msg_1");
+        fs::create_dir_all(&parts_dir).unwrap();
+        fs::write(parts_dir.join("prt_1.json"), r#"{"type":"text"}"#).unwrap();
+        let with_part = content_identity_for_test(&base, "ses_legacy");
+        assert_ne!(with_part, first, "a new part changes the identity");
+
+        fs::write(messages_dir.join("msg_2.json"), r#"{"id":"msg_2"}"#).unwrap();
+        assert_ne!(content_identity_for_test(&base, "ses_legacy"), with_part);
+        assert_eq!(db_content_identity(&base, "ses_legacy"), None);
+        assert_eq!(legacy_content_identity(&base, "ses_missing"), None);
+    }
+
+    fn content_identity_for_test(base: &str, session_id: &str) -> String {
+        db_content_identity(base, session_id)
+            .or_else(|| legacy_content_identity(base, session_id))
+            .unwrap()
+    }
 }
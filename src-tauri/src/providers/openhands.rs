@@ -170,6 +170,12 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(messages)
 }
 
+/// Content identity of one session: the signature of its `events/` folder.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let sid = session_path.strip_prefix(SCHEME).unwrap_or(session_path);
+    super::content_identity::dir_identity(&events_dir_for(sid).ok()?)
+}
+
 /// Search across all `OpenHands` conversations.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let Some(dir) = sessions_dir() else {
@@ -280,6 +280,16 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(session.to_messages())
 }
 
+/// Content identity of one session: the signature of its workspace's
+/// `state.vscdb`, which stores every session of that workspace in one value.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let (hash, _) = parse_session_path(session_path).ok()?;
+    if !valid_hash(&hash) {
+        return None;
+    }
+    super::content_identity::sqlite_identity(&workspace_storage()?.join(hash).join("state.vscdb"))
+}
+
 /// Search across all Trae sessions.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
     let Some(storage) = workspace_storage() else {
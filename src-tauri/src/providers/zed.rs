@@ -265,9 +265,24 @@ pub fn load_messages(session_path: &str) -> Result<Vec<ClaudeMessage>, String> {
     Ok(parse_thread(&json, id, updated_at.as_deref().unwrap_or("")))
 }
 
+/// Content identity of one Zed thread. Zed rewrites the whole thread blob and
+/// bumps `updated_at` on every save, so the row alone identifies its content.
+pub fn content_identity(session_path: &str) -> Option<String> {
+    let id = session_path.strip_prefix(SCHEME).unwrap_or(session_path);
+    let conn = open_db().ok()?;
+    let (updated_at, data_len): (Option<String>, i64) = conn
+        .query_row(
+            "SELECT updated_at, length(data) FROM threads WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok()?;
+    Some(format!("row:{}:{data_len}", updated_at.unwrap_or_default()))
+}
+
 /// Search across all Zed threads.
 pub fn search(query: &str, limit: usize) -> Result<Vec<ClaudeMessage>, String> {
-    if query.is_empty() || limit == 0 {
+    if limit == 0 {
         return Ok(Vec::new());
     }
     let conn = open_db()?;
@@ -0,0 +1,561 @@
+//! Named, scoped API tokens for `WebUI` server mode.
+//!
+//! Tokens live in `~/.claude-history-viewer/api-tokens.json`, one record per
+//! token holding its name, scopes, expiry and revocation time plus an Argon2id
+//! hash of the secret — the secret itself is printed once at creation and
+//! never stored. They are managed from the command line:
+//!
+//! ```text
+//! --create-api-token <name> --scopes read:stats[,read:messages,...] [--expires <date|Nd|never>]
+//! --list-api-tokens [--json]
+//! --revoke-api-token <id|name>
+//! ```
+//!
+//! A running server picks up changes to the file on the next request, so a
+//! revoked token stops working without a restart. Clients send the token as
+//! `Authorization: Bearer cchv_<id>_<secret>`, alongside whichever
+//! `--token`/account auth the server uses for the browser.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::auth::{
+    constant_time_eq, hash_password_argon2id, random_token, verify_argon2id_password, Principal,
+    Role,
+};
+use super::scope::ProjectScope;
+use crate::cli_args::extract_flag_value;
+
+/// Prefix of every API token, so they can't be confused with a `--token` value.
+pub const API_TOKEN_PREFIX: &str = "cchv_";
+
+const CREATE_FLAG: &str = "--create-api-token";
+const LIST_FLAG: &str = "--list-api-tokens";
+const REVOKE_FLAG: &str = "--revoke-api-token";
+
+/// Expiry applied when `--expires` is not given.
+const DEFAULT_EXPIRY_DAYS: i64 = 90;
+
+const USAGE: &str = "Usage: --create-api-token <name> --scopes <scope>[,<scope>...] [--expires <YYYY-MM-DD|<n>d|never>]\n       --list-api-tokens [--json]\n       --revoke-api-token <id|name>\n\nScopes: read:stats (stats, project lists and /metrics), read:messages\n(projects, sessions, messages and search), write:metadata (tags, notes and\nother user metadata), admin (everything). Tokens expire after 90 days unless\n--expires says otherwise. The token is printed once; send it as 'Authorization:\nBearer <token>'.";
+
+/// What an API token may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApiScope {
+    #[serde(rename = "read:stats")]
+    ReadStats,
+    #[serde(rename = "read:messages")]
+    ReadMessages,
+    #[serde(rename = "write:metadata")]
+    WriteMetadata,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl ApiScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ReadStats => "read:stats",
+            Self::ReadMessages => "read:messages",
+            Self::WriteMetadata => "write:metadata",
+            Self::Admin => "admin",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "read:stats" => Ok(Self::ReadStats),
+            "read:messages" => Ok(Self::ReadMessages),
+            "write:metadata" => Ok(Self::WriteMetadata),
+            "admin" => Ok(Self::Admin),
+            other => Err(format!(
+                "Unknown scope '{other}' (expected read:stats, read:messages, write:metadata or admin)"
+            )),
+        }
+    }
+}
+
+/// One token as stored on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenRecord {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    /// Argon2id PHC hash of the secret part of the token.
+    hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiTokenRecord {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.map_or(true, |expires| expires > now)
+    }
+
+    fn status(&self, now: DateTime<Utc>) -> &'static str {
+        if self.revoked_at.is_some() {
+            "revoked"
+        } else if self.is_active(now) {
+            "active"
+        } else {
+            "expired"
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ApiTokenFile {
+    #[serde(default)]
+    tokens: Vec<ApiTokenRecord>,
+}
+
+/// `~/.claude-history-viewer/api-tokens.json`
+pub fn api_tokens_path() -> Result<PathBuf, String> {
+    Ok(crate::commands::metadata::get_metadata_folder()?.join("api-tokens.json"))
+}
+
+fn read_tokens(path: &Path) -> Result<Vec<ApiTokenRecord>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str::<ApiTokenFile>(&content)
+        .map(|file| file.tokens)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn write_tokens(path: &Path, tokens: Vec<ApiTokenRecord>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(&ApiTokenFile { tokens })
+        .map_err(|e| format!("Failed to serialize API tokens: {e}"))?;
+    let tmp_path = path.with_extension(format!("json.{}.tmp", uuid::Uuid::new_v4()));
+    let mut file = fs::File::create(&tmp_path)
+        .map_err(|e| format!("Failed to create temp file '{}': {e}", tmp_path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o600));
+    }
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write temp file '{}': {e}", tmp_path.display()))?;
+    file.sync_all()
+        .map_err(|e| format!("Failed to sync temp file '{}': {e}", tmp_path.display()))?;
+    drop(file);
+    crate::commands::fs_utils::atomic_rename(&tmp_path, path)
+}
+
+/// Mint a token and persist its hash. Returns the record and the full token,
+/// which is not recoverable afterwards.
+pub fn create_token(
+    path: &Path,
+    name: &str,
+    scopes: Vec<ApiScope>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiTokenRecord, String), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Token name must not be empty".to_string());
+    }
+    if scopes.is_empty() {
+        return Err("At least one scope is required".to_string());
+    }
+
+    let now = Utc::now();
+    let mut tokens = read_tokens(path)?;
+    if tokens.iter().any(|t| t.name == name && t.is_active(now)) {
+        return Err(format!("An active token named '{name}' already exists"));
+    }
+
+    let id = uuid::Uuid::new_v4().as_simple().to_string()[..12].to_string();
+    let secret = random_token();
+    let record = ApiTokenRecord {
+        id: id.clone(),
+        name: name.to_string(),
+        scopes,
+        hash: hash_password_argon2id(&secret)?,
+        created_at: now,
+        expires_at,
+        revoked_at: None,
+    };
+    tokens.push(record.clone());
+    write_tokens(path, tokens)?;
+    Ok((record, format!("{API_TOKEN_PREFIX}{id}_{secret}")))
+}
+
+/// Revoke the token with this id, or the active token with this name.
+pub fn revoke_token(path: &Path, id_or_name: &str) -> Result<ApiTokenRecord, String> {
+    let now = Utc::now();
+    let mut tokens = read_tokens(path)?;
+    let index = tokens
+        .iter()
+        .position(|t| t.id == id_or_name)
+        .or_else(|| {
+            tokens
+                .iter()
+                .position(|t| t.name == id_or_name && t.is_active(now))
+        })
+        .ok_or_else(|| format!("No token with id or active name '{id_or_name}'"))?;
+    if tokens[index].revoked_at.is_some() {
+        return Err(format!("Token '{id_or_name}' is already revoked"));
+    }
+    tokens[index].revoked_at = Some(now);
+    let record = tokens[index].clone();
+    write_tokens(path, tokens)?;
+    Ok(record)
+}
+
+/// Split `cchv_<id>_<secret>` into id and secret.
+fn split_token(candidate: &str) -> Option<(&str, &str)> {
+    candidate
+        .strip_prefix(API_TOKEN_PREFIX)?
+        .split_once('_')
+        .filter(|(id, secret)| !id.is_empty() && !secret.is_empty())
+}
+
+/// Server-side view of the token file, reloaded when it changes on disk.
+#[derive(Default)]
+pub struct ApiTokenStore {
+    path: Option<PathBuf>,
+    state: Mutex<StoreState>,
+}
+
+#[derive(Default)]
+struct StoreState {
+    /// Modification time and length of the file when `tokens` was read.
+    file_stamp: Option<(SystemTime, u64)>,
+    tokens: Vec<ApiTokenRecord>,
+    /// Secrets that already passed Argon2 verification, by token id, so each
+    /// token pays the hashing cost once per process rather than per request.
+    verified: HashMap<String, String>,
+}
+
+impl ApiTokenStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            state: Mutex::new(StoreState::default()),
+        }
+    }
+
+    /// Resolve a bearer value to a principal, or `None` when it isn't a valid,
+    /// active API token.
+    pub fn authenticate(&self, candidate: &str) -> Option<Principal> {
+        let (id, secret) = split_token(candidate)?;
+        let record = self.active_record(id)?;
+
+        let cached = self
+            .lock()
+            .verified
+            .get(id)
+            .map(|known| constant_time_eq(known.as_bytes(), secret.as_bytes()));
+        let valid = cached.unwrap_or_else(|| {
+            let valid = verify_argon2id_password(secret, &record.hash);
+            if valid {
+                self.lock()
+                    .verified
+                    .insert(id.to_string(), secret.to_string());
+            }
+            valid
+        });
+        valid.then(|| principal_for(&record))
+    }
+
+    fn active_record(&self, id: &str) -> Option<ApiTokenRecord> {
+        let path = self.path.as_ref()?;
+        let file_stamp = fs::metadata(path)
+            .and_then(|m| Ok((m.modified()?, m.len())))
+            .ok();
+        let mut state = self.lock();
+        if state.file_stamp != file_stamp {
+            state.tokens = read_tokens(path).unwrap_or_else(|e| {
+                log::warn!("API tokens unavailable: {e}");
+                Vec::new()
+            });
+            state.file_stamp = file_stamp;
+            state.verified.clear();
+        }
+        state
+            .tokens
+            .iter()
+            .find(|t| t.id == id && t.is_active(Utc::now()))
+            .cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StoreState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn principal_for(record: &ApiTokenRecord) -> Principal {
+    let role = if record.scopes.contains(&ApiScope::Admin) {
+        Role::Admin
+    } else if record.scopes.contains(&ApiScope::WriteMetadata) {
+        Role::Editor
+    } else {
+        Role::Viewer
+    };
+    Principal {
+        username: format!("token:{}", record.name),
+        role,
+        projects: ProjectScope::all(),
+        api_scopes: Some(record.scopes.clone()),
+    }
+}
+
+// ─── CLI ──────────────────────────────────────────────────────────────────────
+
+/// Handle the token management flags. Returns the exit code when one of them
+/// was given, `None` otherwise.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let has = |flag: &str| {
+        args.iter()
+            .any(|a| a == flag || a.starts_with(&format!("{flag}=")))
+    };
+    if !(has(CREATE_FLAG) || has(LIST_FLAG) || has(REVOKE_FLAG)) {
+        return None;
+    }
+
+    let path = match api_tokens_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{e}");
+            return Some(1);
+        }
+    };
+    let result = if has(CREATE_FLAG) {
+        run_create(args, &path)
+    } else if has(REVOKE_FLAG) {
+        run_revoke(args, &path)
+    } else {
+        run_list(args, &path)
+    };
+    Some(match result {
+        Ok(()) => 0,
+        Err(CliError::Usage(e)) => {
+            eprintln!("{e}\n\n{USAGE}");
+            2
+        }
+        Err(CliError::Failed(e)) => {
+            eprintln!("{e}");
+            1
+        }
+    })
+}
+
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+fn run_create(args: &[String], path: &Path) -> Result<(), CliError> {
+    let name = extract_flag_value(args, CREATE_FLAG)
+        .ok_or_else(|| CliError::Usage(format!("{CREATE_FLAG} needs a name")))?;
+    let scopes = extract_flag_value(args, "--scopes")
+        .ok_or_else(|| CliError::Usage("--scopes is required".to_string()))?;
+    let scopes = parse_scopes(&scopes).map_err(CliError::Usage)?;
+    let expires_at = parse_expiry(extract_flag_value(args, "--expires").as_deref(), Utc::now())
+        .map_err(CliError::Usage)?;
+
+    let (record, token) =
+        create_token(path, &name, scopes, expires_at).map_err(CliError::Failed)?;
+    eprintln!(
+        "Created API token '{}' ({}) with scopes {}, expires {}.",
+        record.name,
+        record.id,
+        scopes_label(&record.scopes),
+        record
+            .expires_at
+            .map_or_else(|| "never".to_string(), |t| t.to_rfc3339())
+    );
+    eprintln!("Store it now; it is not shown again.");
+    println!("{token}");
+    Ok(())
+}
+
+fn run_revoke(args: &[String], path: &Path) -> Result<(), CliError> {
+    let target = extract_flag_value(args, REVOKE_FLAG)
+        .ok_or_else(|| CliError::Usage(format!("{REVOKE_FLAG} needs a token id or name")))?;
+    let record = revoke_token(path, target.trim()).map_err(CliError::Failed)?;
+    eprintln!("Revoked API token '{}' ({}).", record.name, record.id);
+    Ok(())
+}
+
+fn run_list(args: &[String], path: &Path) -> Result<(), CliError> {
+    let tokens = read_tokens(path).map_err(CliError::Failed)?;
+    let now = Utc::now();
+    if args.iter().any(|a| a == "--json") {
+        let rows: Vec<_> = tokens
+            .iter()
+            .map(|t| {
+                serde_json::json!({
+                    "id": t.id,
+                    "name": t.name,
+                    "scopes": t.scopes,
+                    "createdAt": t.created_at,
+                    "expiresAt": t.expires_at,
+                    "revokedAt": t.revoked_at,
+                    "status": t.status(now),
+                })
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&rows)
+            .map_err(|e| CliError::Failed(format!("Failed to serialize API tokens: {e}")))?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    if tokens.is_empty() {
+        eprintln!("No API tokens. Create one with {CREATE_FLAG} <name> --scopes <scopes>.");
+        return Ok(());
+    }
+    for t in &tokens {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            t.id,
+            t.status(now),
+            t.expires_at
+                .map_or_else(|| "never".to_string(), |e| e.format("%Y-%m-%d").to_string()),
+            scopes_label(&t.scopes),
+            t.name
+        );
+    }
+    Ok(())
+}
+
+fn parse_scopes(value: &str) -> Result<Vec<ApiScope>, String> {
+    let mut scopes = Vec::new();
+    for part in value.split(',').filter(|p| !p.trim().is_empty()) {
+        let scope = ApiScope::parse(part)?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.is_empty() {
+        return Err("--scopes must name at least one scope".to_string());
+    }
+    Ok(scopes)
+}
+
+/// `never`, `<n>d` (days from now) or a date/RFC 3339 timestamp.
+fn parse_expiry(value: Option<&str>, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(value) = value.map(str::trim) else {
+        return Ok(Some(now + Duration::days(DEFAULT_EXPIRY_DAYS)));
+    };
+    if value.eq_ignore_ascii_case("never") {
+        return Ok(None);
+    }
+    if let Some(days) = value.strip_suffix('d') {
+        let days: i64 = days
+            .parse()
+            .ok()
+            .filter(|d| *d > 0)
+            .ok_or_else(|| format!("Invalid --expires '{value}'"))?;
+        return Ok(Some(now + Duration::days(days)));
+    }
+    let expires = crate::export::parse_date_bound(value, true)?;
+    if expires <= now {
+        return Err(format!("--expires '{value}' is in the past"));
+    }
+    Ok(Some(expires))
+}
+
+fn scopes_label(scopes: &[ApiScope]) -> String {
+    scopes
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn created_token_authenticates_with_its_scopes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-tokens.json");
+        let (record, token) = create_token(&path, "ci", vec![ApiScope::ReadStats], None).unwrap();
+
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains(token.split_once('_').unwrap().1));
+
+        let store = ApiTokenStore::new(Some(path));
+        let principal = store.authenticate(&token).unwrap();
+        assert_eq!(principal.username, "token:ci");
+        assert_eq!(principal.role, Role::Viewer);
+        assert_eq!(principal.api_scopes, Some(vec![ApiScope::ReadStats]));
+        // Second call takes the verified-secret cache path.
+        assert!(store.authenticate(&token).is_some());
+
+        let wrong = format!("{API_TOKEN_PREFIX}{}_not-the-secret", record.id);
+        assert!(store.authenticate(&wrong).is_none());
+        assert!(store.authenticate("plain-static-token").is_none());
+    }
+
+    #[test]
+    fn revoked_and_expired_tokens_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-tokens.json");
+        let (_, revoked) = create_token(&path, "old", vec![ApiScope::Admin], None).unwrap();
+        let (_, expired) = create_token(
+            &path,
+            "short",
+            vec![ApiScope::ReadMessages],
+            Some(Utc::now() - Duration::days(1)),
+        )
+        .unwrap();
+
+        let store = ApiTokenStore::new(Some(path.clone()));
+        assert!(store.authenticate(&revoked).is_some());
+        revoke_token(&path, "old").unwrap();
+        assert!(store.authenticate(&revoked).is_none());
+        assert!(store.authenticate(&expired).is_none());
+    }
+
+    #[test]
+    fn duplicate_active_names_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-tokens.json");
+        create_token(&path, "dash", vec![ApiScope::ReadStats], None).unwrap();
+        assert!(create_token(&path, "dash", vec![ApiScope::ReadStats], None).is_err());
+        revoke_token(&path, "dash").unwrap();
+        assert!(create_token(&path, "dash", vec![ApiScope::ReadStats], None).is_ok());
+    }
+
+    #[test]
+    fn scope_and_expiry_parsing() {
+        assert_eq!(
+            parse_scopes("read:stats, admin,read:stats").unwrap(),
+            vec![ApiScope::ReadStats, ApiScope::Admin]
+        );
+        assert!(parse_scopes("write:everything").is_err());
+        assert!(parse_scopes(" , ").is_err());
+
+        let now = Utc::now();
+        assert_eq!(parse_expiry(Some("never"), now).unwrap(), None);
+        assert_eq!(
+            parse_expiry(Some("30d"), now).unwrap(),
+            Some(now + Duration::days(30))
+        );
+        assert_eq!(
+            parse_expiry(None, now).unwrap(),
+            Some(now + Duration::days(DEFAULT_EXPIRY_DAYS))
+        );
+        assert!(parse_expiry(Some("0d"), now).is_err());
+        assert!(parse_expiry(Some("2001-01-01"), now).is_err());
+    }
+}
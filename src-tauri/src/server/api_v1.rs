@@ -0,0 +1,1090 @@
+//! Versioned, resource-style read API under `/api/v1`.
+//!
+//! The `/api/*` POST routes mirror Tauri commands one-to-one and return the
+//! internal models as-is. This module is the surface for curl, dashboards and
+//! other tools instead: plain `GET` requests with query parameters, and
+//! response types defined here so the JSON stays stable when internal models
+//! change. Additive changes (new optional fields, new endpoints) stay in `v1`;
+//! anything else needs a `v2`.
+//!
+//! [`openapi_document`] describes every route and is served at
+//! `/api/v1/openapi.json`. Routes and their operations come from one table,
+//! and every schema is derived from the Rust types below (`schemars`), so the
+//! document cannot drift from what the handlers accept and return.
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, MethodRouter};
+use axum::{Extension, Json, Router};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+use super::auth::Principal;
+use super::state::AppState;
+use crate::commands::{multi_provider, session, stats};
+use crate::models::{
+    ClaudeMessage, ClaudeProject, ClaudeSession, GlobalStatsSummary, ModelStats,
+    ProjectStatsSummary, SearchMatch, TokenDistribution, TokenUsage, ToolUsageStats,
+};
+
+/// Version reported in the `OpenAPI` document.
+pub const API_VERSION: &str = "1.0.0";
+
+const DEFAULT_PAGE_LIMIT: usize = 100;
+const MAX_PAGE_LIMIT: usize = 500;
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// Routes relative to `/api/v1`, served behind the API auth middleware.
+pub fn router() -> Router<Arc<AppState>> {
+    endpoints(&mut schema_generator())
+        .into_iter()
+        .fold(Router::new(), |router, endpoint| {
+            router.route(endpoint.path, endpoint.handler)
+        })
+}
+
+/// One `GET` route: its handler and the `OpenAPI` operation describing it.
+struct Endpoint {
+    path: &'static str,
+    handler: MethodRouter<Arc<AppState>>,
+    operation: Value,
+}
+
+fn endpoints(gen: &mut SchemaGenerator) -> Vec<Endpoint> {
+    vec![
+        Endpoint {
+            path: "/projects",
+            handler: get(list_projects),
+            operation: operation::<ProjectsQuery, Page<Project>>(
+                gen,
+                "listProjects",
+                "List projects, most recently modified first",
+                &[],
+            ),
+        },
+        Endpoint {
+            path: "/sessions",
+            handler: get(list_sessions),
+            operation: operation::<SessionsQuery, Page<Session>>(
+                gen,
+                "listSessions",
+                "List a project's sessions, most recent first",
+                &["project"],
+            ),
+        },
+        Endpoint {
+            path: "/messages",
+            handler: get(list_messages),
+            operation: operation::<MessagesQuery, Page<Message>>(
+                gen,
+                "listMessages",
+                "List a session's messages, oldest first",
+                &["session"],
+            ),
+        },
+        Endpoint {
+            path: "/stats",
+            handler: get(get_stats),
+            operation: operation::<StatsQuery, Stats>(
+                gen,
+                "getStats",
+                "Usage totals across all projects, or for one project",
+                &[],
+            ),
+        },
+        Endpoint {
+            path: "/search",
+            handler: get(search),
+            operation: operation::<SearchQuery, SearchResults>(
+                gen,
+                "search",
+                "Search messages across providers, newest first",
+                &["q"],
+            ),
+        },
+    ]
+}
+
+/// `GET /api/v1/openapi.json` — served without auth so API tooling can load it.
+pub async fn openapi_handler() -> Json<Value> {
+    Json(openapi_document())
+}
+
+// ─── Errors ───────────────────────────────────────────────────────────────────
+
+/// `{ "error": "..." }` with a 4xx/5xx status.
+#[derive(Debug)]
+pub struct V1Error(StatusCode, String);
+
+impl V1Error {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.into())
+    }
+
+    fn forbidden(message: impl Into<String>) -> Self {
+        Self(StatusCode::FORBIDDEN, message.into())
+    }
+}
+
+impl From<String> for V1Error {
+    fn from(message: String) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl From<QueryRejection> for V1Error {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::bad_request(rejection.body_text())
+    }
+}
+
+/// Body of every error response.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+impl IntoResponse for V1Error {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+type V1Result<T> = Result<Json<T>, V1Error>;
+
+// ─── Response types ───────────────────────────────────────────────────────────
+
+/// One page of a list, in the list's natural order.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(rename = "{T}Page")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Offset of the next page; `null` on the last page.
+    pub next_offset: Option<usize>,
+}
+
+impl<T> Page<T> {
+    fn slice(all: Vec<T>, offset: usize, limit: usize) -> Self {
+        let total = all.len();
+        let items: Vec<T> = all.into_iter().skip(offset).take(limit).collect();
+        let end = offset.saturating_add(items.len());
+        Self {
+            items,
+            total,
+            offset,
+            limit,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub provider: String,
+    pub name: String,
+    /// Storage path; pass it as `project` to `/sessions` and `/stats`.
+    pub path: String,
+    /// Working directory the sessions ran in.
+    pub actual_path: String,
+    pub session_count: usize,
+    pub message_count: usize,
+    pub last_modified: String,
+}
+
+impl From<ClaudeProject> for Project {
+    fn from(project: ClaudeProject) -> Self {
+        Self {
+            provider: project.provider.unwrap_or_else(|| "claude".to_string()),
+            name: project.name,
+            path: project.path,
+            actual_path: project.actual_path,
+            session_count: project.session_count,
+            message_count: project.message_count,
+            last_modified: project.last_modified,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub provider: String,
+    pub id: String,
+    /// Storage path; pass it as `session` to `/messages`.
+    pub path: String,
+    pub project_name: String,
+    pub summary: Option<String>,
+    pub message_count: usize,
+    pub first_message_at: String,
+    pub last_message_at: String,
+    pub last_modified: String,
+    pub has_tool_use: bool,
+    pub has_errors: bool,
+}
+
+impl Session {
+    fn from_model(session: ClaudeSession, provider: &str) -> Self {
+        let id = if session.actual_session_id.is_empty() {
+            session.session_id
+        } else {
+            session.actual_session_id
+        };
+        Self {
+            provider: session.provider.unwrap_or_else(|| provider.to_string()),
+            id,
+            path: session.file_path,
+            project_name: session.project_name,
+            summary: session.summary,
+            message_count: session.message_count,
+            first_message_at: session.first_message_time,
+            last_message_at: session.last_message_time,
+            last_modified: session.last_modified,
+            has_tool_use: session.has_tool_use,
+            has_errors: session.has_errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_creation_tokens: u32,
+    pub cache_read_tokens: u32,
+}
+
+impl From<&TokenUsage> for Usage {
+    fn from(usage: &TokenUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens.unwrap_or(0),
+            output_tokens: usage.output_tokens.unwrap_or(0),
+            cache_creation_tokens: usage.cache_creation_input_tokens.unwrap_or(0),
+            cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub uuid: String,
+    pub parent_uuid: Option<String>,
+    pub session_id: String,
+    pub provider: String,
+    pub timestamp: String,
+    /// `user`, `assistant`, `system`, `summary`, ...
+    #[serde(rename = "type")]
+    pub message_type: String,
+    pub role: Option<String>,
+    pub model: Option<String>,
+    /// A string or an array of content blocks.
+    pub content: Option<Value>,
+    pub tool_use: Option<Value>,
+    pub tool_result: Option<Value>,
+    pub is_sidechain: bool,
+    pub usage: Option<Usage>,
+    pub cost_usd: Option<f64>,
+}
+
+impl From<ClaudeMessage> for Message {
+    fn from(message: ClaudeMessage) -> Self {
+        Self {
+            usage: message.usage.as_ref().map(Usage::from),
+            uuid: message.uuid,
+            parent_uuid: message.parent_uuid,
+            session_id: message.session_id,
+            provider: message.provider.unwrap_or_else(|| "claude".to_string()),
+            timestamp: message.timestamp,
+            message_type: message.message_type,
+            role: message.role,
+            model: message.model,
+            content: message.content,
+            tool_use: message.tool_use,
+            tool_result: message.tool_use_result,
+            is_sidechain: message.is_sidechain.unwrap_or(false),
+            cost_usd: message.cost_usd,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub project_name: Option<String>,
+    /// Where the query matched; offsets are UTF-16 code units.
+    pub matches: Vec<SearchMatch>,
+    pub message: Message,
+}
+
+impl From<ClaudeMessage> for SearchHit {
+    fn from(mut message: ClaudeMessage) -> Self {
+        Self {
+            project_name: message.project_name.take(),
+            matches: message.search_matches.take().unwrap_or_default(),
+            message: Message::from(message),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchResults {
+    pub items: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTotals {
+    pub total: u64,
+    pub input: u64,
+    pub output: u64,
+    pub cache_creation: u64,
+    pub cache_read: u64,
+    pub reasoning: u64,
+}
+
+impl TokenTotals {
+    fn new(total: u64, distribution: &TokenDistribution) -> Self {
+        Self {
+            total,
+            input: distribution.input,
+            output: distribution.output,
+            cache_creation: distribution.cache_creation,
+            cache_read: distribution.cache_read,
+            reasoning: distribution.reasoning,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub messages: u32,
+    pub tokens: u64,
+    /// Cost reported by the provider, when it records one.
+    pub cost_usd: Option<f64>,
+    /// Cost estimated from the pricing table.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+impl From<&ModelStats> for ModelUsage {
+    fn from(model: &ModelStats) -> Self {
+        Self {
+            provider: model
+                .provider_id
+                .clone()
+                .unwrap_or_else(|| "claude".to_string()),
+            model: model.model_name.clone(),
+            messages: model.message_count,
+            tokens: model.token_count,
+            cost_usd: model.cost_usd,
+            estimated_cost_usd: model.estimated_cost_usd,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub name: String,
+    pub uses: u32,
+    /// Share of calls that did not error, 0–1.
+    pub success_rate: f64,
+}
+
+impl From<&ToolUsageStats> for ToolUsage {
+    fn from(tool: &ToolUsageStats) -> Self {
+        Self {
+            name: tool.tool_name.clone(),
+            uses: tool.usage_count,
+            success_rate: f64::from(tool.success_rate) / 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderUsage {
+    pub provider: String,
+    pub projects: u32,
+    pub sessions: u32,
+    pub messages: u32,
+    pub tokens: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// The project storage path, or `null` for all projects.
+    pub project: Option<String>,
+    pub sessions: u64,
+    pub messages: u64,
+    pub tokens: TokenTotals,
+    pub session_minutes: u64,
+    pub models: Vec<ModelUsage>,
+    pub tools: Vec<ToolUsage>,
+    /// Per-provider totals; empty for a single project.
+    pub providers: Vec<ProviderUsage>,
+}
+
+impl From<&GlobalStatsSummary> for Stats {
+    fn from(summary: &GlobalStatsSummary) -> Self {
+        Self {
+            project: None,
+            sessions: u64::from(summary.total_sessions),
+            messages: u64::from(summary.total_messages),
+            tokens: TokenTotals::new(summary.total_tokens, &summary.token_distribution),
+            session_minutes: summary.total_session_duration_minutes,
+            models: summary.model_distribution.iter().map(Into::into).collect(),
+            tools: summary.most_used_tools.iter().map(Into::into).collect(),
+            providers: summary
+                .provider_distribution
+                .iter()
+                .map(|provider| ProviderUsage {
+                    provider: provider.provider_id.clone(),
+                    projects: provider.projects,
+                    sessions: provider.sessions,
+                    messages: provider.messages,
+                    tokens: provider.tokens,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Stats {
+    fn for_project(project: String, summary: &ProjectStatsSummary) -> Self {
+        Self {
+            project: Some(project),
+            sessions: summary.total_sessions as u64,
+            messages: summary.total_messages as u64,
+            tokens: TokenTotals::new(summary.total_tokens, &summary.token_distribution),
+            session_minutes: u64::from(summary.total_session_duration),
+            models: summary.model_distribution.iter().map(Into::into).collect(),
+            tools: summary.most_used_tools.iter().map(Into::into).collect(),
+            providers: Vec::new(),
+        }
+    }
+}
+
+// ─── Query parameters ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectsQuery {
+    /// Only this provider's projects.
+    pub provider: Option<String>,
+    /// Items to skip (default 0).
+    pub offset: Option<usize>,
+    /// Page size, 1–500 (default 100).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionsQuery {
+    /// Project storage `path` from /projects.
+    pub project: Option<String>,
+    /// Provider of the project (default claude).
+    pub provider: Option<String>,
+    /// Items to skip (default 0).
+    pub offset: Option<usize>,
+    /// Page size, 1–500 (default 100).
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagesQuery {
+    /// Session storage `path` from /sessions.
+    pub session: Option<String>,
+    /// Provider of the session (default claude).
+    pub provider: Option<String>,
+    /// Items to skip (default 0).
+    pub offset: Option<usize>,
+    /// Page size, 1–500 (default 100).
+    pub limit: Option<usize>,
+    /// Drop sub-agent messages.
+    pub exclude_sidechain: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsQuery {
+    /// Project storage `path`; omit for all projects.
+    pub project: Option<String>,
+    /// Only this provider (all projects only).
+    pub provider: Option<String>,
+    /// Start date: YYYY-MM-DD (local) or RFC 3339.
+    pub since: Option<String>,
+    /// End date, inclusive: YYYY-MM-DD (local) or RFC 3339.
+    pub until: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    /// Query text; supports the search query syntax.
+    pub q: Option<String>,
+    /// Only this provider.
+    pub provider: Option<String>,
+    /// Only this project name.
+    pub project: Option<String>,
+    /// Treat q as a regular expression.
+    pub regex: Option<bool>,
+    /// Match case.
+    pub case_sensitive: Option<bool>,
+    /// Maximum hits, 1–1000 (default 50).
+    pub limit: Option<usize>,
+}
+
+fn required(value: Option<String>, name: &str) -> Result<String, V1Error> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .ok_or_else(|| V1Error::bad_request(format!("Missing required parameter '{name}'")))
+}
+
+fn provider_param(provider: Option<String>) -> Result<Option<String>, V1Error> {
+    match provider {
+        Some(provider) if !multi_provider::is_known_provider(&provider) => Err(
+            V1Error::bad_request(format!("Unknown provider '{provider}'")),
+        ),
+        provider => Ok(provider),
+    }
+}
+
+fn page_limit(limit: Option<usize>, default: usize, max: usize) -> Result<usize, V1Error> {
+    match limit {
+        None => Ok(default),
+        Some(limit) if (1..=max).contains(&limit) => Ok(limit),
+        Some(_) => Err(V1Error::bad_request(format!(
+            "'limit' must be between 1 and {max}"
+        ))),
+    }
+}
+
+/// Validate a `since`/`until` value and normalize it to RFC 3339.
+fn date_param(value: Option<String>, end_of_day: bool) -> Result<Option<String>, V1Error> {
+    value
+        .map(|v| {
+            crate::export::parse_date_bound(&v, end_of_day)
+                .map(|ts| ts.to_rfc3339())
+                .map_err(V1Error::bad_request)
+        })
+        .transpose()
+}
+
+/// Account callers limited to some projects may only name projects they can see,
+/// and must name one for endpoints that otherwise span every project.
+async fn check_project(
+    state: &AppState,
+    principal: Option<&Principal>,
+    project: Option<&str>,
+) -> Result<(), V1Error> {
+    let Some(principal) = principal.filter(|p| !p.projects.is_unrestricted()) else {
+        return Ok(());
+    };
+    let Some(project) = project else {
+        return Err(V1Error::forbidden(format!(
+            "Account '{}' is limited to specific projects; pass 'project'",
+            principal.username
+        )));
+    };
+    if state
+        .project_scopes
+        .allows_project_path(principal, project)
+        .await
+    {
+        Ok(())
+    } else {
+        Err(V1Error::forbidden(format!(
+            "Account '{}' does not have access to this project",
+            principal.username
+        )))
+    }
+}
+
+// ─── Handlers ─────────────────────────────────────────────────────────────────
+
+/// `GET /api/v1/projects` — projects of every provider, most recent first.
+async fn list_projects(
+    principal: Option<Extension<Principal>>,
+    query: Result<Query<ProjectsQuery>, QueryRejection>,
+) -> V1Result<Page<Project>> {
+    let Query(q) = query?;
+    let provider = provider_param(q.provider)?;
+    let limit = page_limit(q.limit, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT)?;
+    let mut projects =
+        multi_provider::scan_all_projects(None, provider.map(|p| vec![p]), None, None, None)
+            .await?;
+    if let Some(Extension(principal)) = principal {
+        projects.retain(|project| principal.projects.allows_project(project));
+    }
+    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    let projects: Vec<Project> = projects.into_iter().map(Project::from).collect();
+    Ok(Json(Page::slice(projects, q.offset.unwrap_or(0), limit)))
+}
+
+/// `GET /api/v1/sessions?project=` — a project's sessions, most recent first.
+async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+    query: Result<Query<SessionsQuery>, QueryRejection>,
+) -> V1Result<Page<Session>> {
+    let Query(q) = query?;
+    let project = required(q.project, "project")?;
+    check_project(&state, principal.as_deref(), Some(&project)).await?;
+    let provider = provider_param(q.provider)?.unwrap_or_else(|| "claude".to_string());
+    let limit = page_limit(q.limit, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT)?;
+    let page = multi_provider::load_provider_sessions_page(
+        provider.clone(),
+        project,
+        None,
+        q.offset,
+        Some(limit),
+    )
+    .await?;
+    Ok(Json(Page {
+        items: page
+            .sessions
+            .into_iter()
+            .map(|session| Session::from_model(session, &provider))
+            .collect(),
+        total: page.total,
+        offset: page.offset,
+        limit: page.limit,
+        next_offset: page.has_more.then_some(page.next_offset),
+    }))
+}
+
+/// `GET /api/v1/messages?session=` — a session's messages, oldest first.
+/// Claude sessions are read only as far as the requested page.
+async fn list_messages(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+    query: Result<Query<MessagesQuery>, QueryRejection>,
+) -> V1Result<Page<Message>> {
+    let Query(q) = query?;
+    let session = required(q.session, "session")?;
+    if let Some(Extension(principal)) = &principal {
+        if !state
+            .project_scopes
+            .allows_session_path(principal, &session)
+            .await
+        {
+            return Err(V1Error::forbidden(format!(
+                "Account '{}' does not have access to this session",
+                principal.username
+            )));
+        }
+    }
+    let provider = provider_param(q.provider)?.unwrap_or_else(|| "claude".to_string());
+    let limit = page_limit(q.limit, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT)?;
+    let offset = q.offset.unwrap_or(0);
+    let page = multi_provider::load_provider_messages_range(
+        provider,
+        session,
+        offset,
+        limit,
+        q.exclude_sidechain.unwrap_or(false),
+    )
+    .await?;
+    Ok(Json(Page {
+        items: page.messages.into_iter().map(Message::from).collect(),
+        total: page.total_count,
+        offset,
+        limit,
+        next_offset: page.has_more.then_some(page.next_offset),
+    }))
+}
+
+/// `GET /api/v1/stats` — usage across all projects, or one with `project=`.
+async fn get_stats(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+    query: Result<Query<StatsQuery>, QueryRejection>,
+) -> V1Result<Stats> {
+    let Query(q) = query?;
+    check_project(&state, principal.as_deref(), q.project.as_deref()).await?;
+    let provider = provider_param(q.provider)?;
+    let since = date_param(q.since, false)?;
+    let until = date_param(q.until, true)?;
+    if let Some(project) = q.project {
+        let summary = stats::get_project_stats_summary(project.clone(), since, until, None).await?;
+        return Ok(Json(Stats::for_project(project, &summary)));
+    }
+    let claude_path = crate::providers::claude::get_base_path().unwrap_or_default();
+    let summary = stats::get_global_stats_summary(
+        claude_path,
+        provider.map(|p| vec![p]),
+        None,
+        since,
+        until,
+        None,
+    )
+    .await?;
+    Ok(Json(Stats::from(&summary)))
+}
+
+/// `GET /api/v1/search?q=` — messages matching a query, newest first.
+async fn search(
+    principal: Option<Extension<Principal>>,
+    query: Result<Query<SearchQuery>, QueryRejection>,
+) -> V1Result<SearchResults> {
+    let Query(q) = query?;
+    // The `project` filter matches by name, which isn't unique across projects,
+    // so search stays off for project-limited accounts (like `/api/search_messages`).
+    if let Some(Extension(principal)) = principal {
+        if !principal.projects.is_unrestricted() {
+            return Err(V1Error::forbidden(format!(
+                "Account '{}' is limited to specific projects",
+                principal.username
+            )));
+        }
+    }
+    let text = required(q.q, "q")?;
+    let provider = provider_param(q.provider)?;
+    let limit = page_limit(q.limit, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT)?;
+    let mut filters = serde_json::Map::new();
+    if q.regex.unwrap_or(false) {
+        filters.insert("matchMode".to_string(), json!("regex"));
+    }
+    if q.case_sensitive.unwrap_or(false) {
+        filters.insert("caseSensitive".to_string(), json!(true));
+    }
+    if let Some(project) = q.project {
+        filters.insert("projects".to_string(), json!([project]));
+    }
+    let filters = Value::Object(filters);
+    // Only a malformed query is the caller's fault; any later failure
+    // (reading sessions, the search index) is a server error.
+    session::MatchOptions::from_filters(&filters)
+        .and_then(|options| session::parse_search_query(&text, options))
+        .map_err(V1Error::bad_request)?;
+    let hits = multi_provider::search_all_providers(
+        None,
+        text,
+        provider.map(|p| vec![p]),
+        None,
+        Some(filters),
+        Some(limit),
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(Json(SearchResults {
+        items: hits.into_iter().map(SearchHit::from).collect(),
+    }))
+}
+
+// ─── OpenAPI ──────────────────────────────────────────────────────────────────
+
+/// Generator whose definitions land under `#/components/schemas/`.
+fn schema_generator() -> SchemaGenerator {
+    SchemaSettings::draft2019_09()
+        .with(|settings| {
+            settings.definitions_path = "#/components/schemas/".to_string();
+        })
+        .into_generator()
+}
+
+/// `T`'s schema as a `$ref`, registering it and every type it uses with `gen`.
+fn schema_ref<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    serde_json::to_value(gen.subschema_for::<T>()).expect("schemas serialize to JSON")
+}
+
+/// Query parameters from the fields of `Q`; `required` lists those the
+/// handler rejects when missing (every field is optional to serde).
+fn query_params<Q: JsonSchema>(required: &[&str]) -> Vec<Value> {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|settings| settings.option_add_null_type = false)
+        .into_generator()
+        .into_root_schema_for::<Q>();
+    let schema = serde_json::to_value(schema).expect("schemas serialize to JSON");
+    let Some(properties) = schema["properties"].as_object() else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut property = property.clone();
+            let description = property
+                .as_object_mut()
+                .and_then(|p| p.remove("description"))
+                .unwrap_or(Value::Null);
+            json!({
+                "name": name,
+                "in": "query",
+                "required": required.contains(&name.as_str()),
+                "description": description,
+                "schema": property,
+            })
+        })
+        .collect()
+}
+
+fn operation<Q: JsonSchema, R: JsonSchema>(
+    gen: &mut SchemaGenerator,
+    operation_id: &str,
+    summary: &str,
+    required: &[&str],
+) -> Value {
+    let error = json!({
+        "application/json": { "schema": schema_ref::<ErrorBody>(gen) },
+    });
+    json!({
+        "operationId": operation_id,
+        "summary": summary,
+        "parameters": query_params::<Q>(required),
+        "responses": {
+            "200": {
+                "description": "OK",
+                "content": { "application/json": { "schema": schema_ref::<R>(gen) } },
+            },
+            "400": { "description": "Invalid parameters", "content": error },
+            "401": { "description": "Missing or invalid credentials" },
+            "403": { "description": "Outside the account's projects", "content": error },
+            "500": { "description": "The data could not be read", "content": error },
+        },
+    })
+}
+
+/// `OpenAPI` 3.1 description of the `/api/v1` routes.
+pub fn openapi_document() -> Value {
+    let mut gen = schema_generator();
+    let paths: Map<String, Value> = endpoints(&mut gen)
+        .into_iter()
+        .map(|endpoint| {
+            (
+                format!("/api/v1{}", endpoint.path),
+                json!({ "get": endpoint.operation }),
+            )
+        })
+        .collect();
+    let schemas = serde_json::to_value(gen.take_definitions()).expect("schemas serialize to JSON");
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Claude Code History Viewer API",
+            "version": API_VERSION,
+            "description": "Read-only access to projects, sessions, messages, usage statistics and search.",
+        },
+        "security": [{ "bearerAuth": [] }],
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+            },
+            "schemas": schemas,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::MessageBuilder;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn page_slice_reports_next_offset() {
+        let page = Page::slice((0..5).collect::<Vec<_>>(), 1, 2);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.next_offset, Some(3));
+
+        let last = Page::slice((0..5).collect::<Vec<_>>(), 3, 2);
+        assert_eq!(last.items, vec![3, 4]);
+        assert_eq!(last.next_offset, None);
+
+        let past_end = Page::slice((0..5).collect::<Vec<_>>(), 9, 2);
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.next_offset, None);
+    }
+
+    #[test]
+    fn parameter_validation() {
+        assert!(required(Some("  ".to_string()), "q").is_err());
+        assert_eq!(required(Some("x".to_string()), "q").unwrap(), "x");
+        assert!(provider_param(Some("nope".to_string())).is_err());
+        assert_eq!(
+            provider_param(Some("codex".to_string()))
+                .unwrap()
+                .as_deref(),
+            Some("codex")
+        );
+        assert_eq!(page_limit(None, 100, 500).unwrap(), 100);
+        assert!(page_limit(Some(0), 100, 500).is_err());
+        assert!(page_limit(Some(501), 100, 500).is_err());
+        assert!(date_param(Some("yesterday".to_string()), false).is_err());
+        assert_eq!(
+            date_param(Some("2026-01-01T00:00:00Z".to_string()), false).unwrap(),
+            Some("2026-01-01T00:00:00+00:00".to_string())
+        );
+    }
+
+    /// Serialized responses must validate against the generated schemas:
+    /// the same fields, with every required one present.
+    #[test]
+    fn openapi_schemas_match_serialized_fields() {
+        let doc = openapi_document();
+        let schemas = &doc["components"]["schemas"];
+        let check = |name: &str, value: Value| {
+            let schema = &schemas[name];
+            let declared: BTreeSet<String> = schema["properties"]
+                .as_object()
+                .unwrap_or_else(|| panic!("schema {name} missing"))
+                .keys()
+                .cloned()
+                .collect();
+            let serialized: BTreeSet<String> = value.as_object().unwrap().keys().cloned().collect();
+            assert_eq!(declared, serialized, "schema {name} drifted");
+            for field in schema["required"].as_array().into_iter().flatten() {
+                let field = field.as_str().unwrap();
+                assert!(serialized.contains(field), "{name}.{field} is required");
+            }
+        };
+
+        let built = MessageBuilder::assistant().with_usage(10, 20).build();
+        let message = Message::from(built.clone());
+        check(
+            "Usage",
+            serde_json::to_value(message.usage.as_ref().unwrap()).unwrap(),
+        );
+        check("Message", serde_json::to_value(&message).unwrap());
+        check(
+            "SearchHit",
+            serde_json::to_value(SearchHit::from(built)).unwrap(),
+        );
+        let project = ClaudeProject {
+            name: "demo".to_string(),
+            path: "/home/me/.claude/projects/-work-demo".to_string(),
+            actual_path: "/work/demo".to_string(),
+            session_count: 1,
+            message_count: 2,
+            last_modified: String::new(),
+            git_info: None,
+            provider: None,
+            storage_type: None,
+            custom_directory_label: None,
+        };
+        check(
+            "Project",
+            serde_json::to_value(Project::from(project)).unwrap(),
+        );
+        check(
+            "Stats",
+            serde_json::to_value(Stats::from(&GlobalStatsSummary::default())).unwrap(),
+        );
+        check(
+            "ProjectPage",
+            serde_json::to_value(Page::<Project>::slice(Vec::new(), 0, 1)).unwrap(),
+        );
+        check(
+            "ErrorBody",
+            serde_json::to_value(ErrorBody {
+                error: "bad".to_string(),
+            })
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn openapi_documents_every_route() {
+        let doc = openapi_document();
+        let paths = doc["paths"].as_object().unwrap();
+        let routes = endpoints(&mut schema_generator());
+        assert_eq!(paths.len(), routes.len(), "duplicate route paths");
+
+        for endpoint in &routes {
+            let operation = &paths[&format!("/api/v1{}", endpoint.path)]["get"];
+            let params = operation["parameters"].as_array().unwrap();
+            assert!(!params.is_empty(), "{} has no parameters", endpoint.path);
+            for param in params {
+                assert!(
+                    param["description"].is_string(),
+                    "{} parameter {} is undocumented",
+                    endpoint.path,
+                    param["name"]
+                );
+            }
+        }
+        // Required parameters name real query fields.
+        let required = |path: &str| -> Vec<String> {
+            paths[path]["get"]["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|p| p["required"] == true)
+                .map(|p| p["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(required("/api/v1/sessions"), ["project"]);
+        assert_eq!(required("/api/v1/messages"), ["session"]);
+        assert_eq!(required("/api/v1/search"), ["q"]);
+
+        // Limit docs are plain doc comments; keep them in step with the caps.
+        let limit_doc = |path: &str| -> String {
+            paths[path]["get"]["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|p| p["name"] == "limit")
+                .map(|p| p["description"].as_str().unwrap().to_string())
+                .unwrap()
+        };
+        for path in ["/api/v1/projects", "/api/v1/sessions", "/api/v1/messages"] {
+            let limit = limit_doc(path);
+            assert!(
+                limit.contains(&format!("1–{MAX_PAGE_LIMIT}")),
+                "{path}: {limit}"
+            );
+            assert!(
+                limit.contains(&format!("default {DEFAULT_PAGE_LIMIT}")),
+                "{path}: {limit}"
+            );
+        }
+        let limit = limit_doc("/api/v1/search");
+        assert!(limit.contains(&format!("1–{MAX_SEARCH_LIMIT}")), "{limit}");
+        assert!(
+            limit.contains(&format!("default {DEFAULT_SEARCH_LIMIT}")),
+            "{limit}"
+        );
+
+        // Every `$ref` resolves to a generated schema.
+        let mut pending = vec![&doc];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Object(map) => {
+                    if let Some(target) = map.get("$ref").and_then(Value::as_str) {
+                        let name = target
+                            .strip_prefix("#/components/schemas/")
+                            .unwrap_or_else(|| panic!("unexpected $ref {target}"));
+                        assert!(
+                            doc["components"]["schemas"].get(name).is_some(),
+                            "dangling $ref {target}"
+                        );
+                    }
+                    pending.extend(map.values());
+                }
+                Value::Array(items) => pending.extend(items),
+                _ => {}
+            }
+        }
+    }
+}
@@ -6,10 +6,14 @@ use axum::http::{header, HeaderMap, Method, Request, StatusCode};
 use axum::response::{AppendHeaders, IntoResponse, Response};
 use base64::Engine;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use super::api_tokens::ApiScope;
+use super::scope::ProjectScope;
+
 pub const LEGACY_AUTH_COOKIE_NAME: &str = "cchv_auth";
 pub const SESSION_COOKIE_NAME: &str = "cchv_session";
 pub const CSRF_COOKIE_NAME: &str = "cchv_csrf";
@@ -22,10 +26,10 @@ const MAX_LOGIN_FAILURES: u32 = 5;
 /// Hard cap on concurrently tracked sessions; least-recently-seen are evicted
 /// past this so a long-running server cannot accumulate sessions without bound.
 const MAX_SESSIONS: usize = 256;
-/// Fixed rate-limit buckets. There is exactly one valid account, so failed logins
-/// are tracked under just these two keys — never under attacker-supplied usernames —
-/// which bounds the attempts map and isolates unknown-user spam from the real account.
-const ACCOUNT_BUCKET: &str = "account";
+/// Rate-limit buckets. Failed logins are tracked per configured account plus one
+/// shared bucket for unknown usernames — never under attacker-supplied text — which
+/// bounds the attempts map and isolates unknown-user spam from the real accounts.
+const ACCOUNT_BUCKET_PREFIX: &str = "account:";
 const UNKNOWN_BUCKET: &str = "_unknown";
 
 #[derive(Clone)]
@@ -37,15 +41,92 @@ pub enum AuthState {
 
 #[derive(Clone)]
 pub struct AccountAuth {
-    username: String,
-    password_hash: String,
+    users: Vec<AccountUser>,
     secure_cookies: bool,
     sessions: Arc<Mutex<HashMap<String, SessionRecord>>>,
     attempts: Arc<Mutex<HashMap<String, LoginAttempt>>>,
 }
 
+/// What an account may do. Roles are ordered: each includes the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access (the `--read-only` allowlist).
+    Viewer,
+    /// Viewer plus metadata, presets and other non-destructive writes.
+    Editor,
+    /// Everything, including deleting sessions, restoring files and settings.
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Editor => "editor",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// One login from the `--auth-users` file.
+#[derive(Debug, Clone)]
+pub struct AccountUser {
+    pub username: String,
+    password_hash: String,
+    pub role: Role,
+    pub projects: ProjectScope,
+}
+
+impl AccountUser {
+    pub fn new(
+        username: String,
+        password_hash: String,
+        role: Role,
+        projects: ProjectScope,
+    ) -> Self {
+        Self {
+            username,
+            password_hash,
+            role,
+            projects,
+        }
+    }
+}
+
+/// Identity of an account- or API-token-authenticated request, inserted into
+/// request extensions by the auth middleware. `--token` auth and `--no-auth`
+/// requests carry none.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub username: String,
+    pub role: Role,
+    pub projects: ProjectScope,
+    /// Scopes of a named API token; `None` for account sessions, whose access
+    /// follows `role` instead.
+    pub api_scopes: Option<Vec<ApiScope>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct AccountUsersFile {
+    users: Vec<AccountUserEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct AccountUserEntry {
+    username: String,
+    password_hash: String,
+    role: Role,
+    /// Omitted means every project.
+    #[serde(default)]
+    projects: Option<Vec<String>>,
+}
+
 #[derive(Clone)]
 struct SessionRecord {
+    username: String,
     csrf_token: String,
     created_at: Instant,
     last_seen: Instant,
@@ -81,7 +162,10 @@ pub enum LoginOutcome {
 pub enum AuthenticatedRequest {
     None,
     Token,
-    Account { csrf_token: String },
+    Account {
+        csrf_token: String,
+        principal: Principal,
+    },
 }
 
 impl AuthState {
@@ -137,7 +221,10 @@ impl AuthState {
             }
             Self::Account(account) => account
                 .authenticate_session(request.headers())
-                .map(|csrf_token| AuthenticatedRequest::Account { csrf_token })
+                .map(|(csrf_token, principal)| AuthenticatedRequest::Account {
+                    csrf_token,
+                    principal,
+                })
                 .unwrap_or(AuthenticatedRequest::None),
         }
     }
@@ -152,47 +239,79 @@ impl AuthState {
 }
 
 impl AccountAuth {
+    /// Single admin account that sees every project (`--auth-user` / env vars).
     pub fn new(username: String, password_hash: String, secure_cookies: bool) -> Self {
+        Self::with_users(
+            vec![AccountUser::new(
+                username,
+                password_hash,
+                Role::Admin,
+                ProjectScope::all(),
+            )],
+            secure_cookies,
+        )
+    }
+
+    pub fn with_users(users: Vec<AccountUser>, secure_cookies: bool) -> Self {
         Self {
-            username,
-            password_hash,
+            users,
             secure_cookies,
             sessions: Arc::new(Mutex::new(HashMap::new())),
             attempts: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    pub fn users(&self) -> &[AccountUser] {
+        &self.users
+    }
+
     fn login(&self, payload: &AuthLoginRequest) -> Result<LoginOutcome, AuthFailure> {
         let username = payload.username.as_deref().unwrap_or("").trim();
         let password = payload.password.as_deref().unwrap_or("");
 
-        // Decide the rate-limit bucket from whether the username matched — never from
-        // attacker-supplied text. With a single valid account this caps the attempts
-        // map at two keys (real account vs shared "_unknown"), so a username-rotating
-        // attacker cannot exhaust memory and unknown-user spam cannot lock out the real
-        // account. `user_ok` is a constant-time compare and is computed unconditionally.
-        let user_ok = constant_time_eq(username.as_bytes(), self.username.as_bytes());
-        let attempt_key = if user_ok {
-            ACCOUNT_BUCKET
-        } else {
-            UNKNOWN_BUCKET
-        };
+        // Compare against every configured username in constant time, without
+        // short-circuiting, so the scan doesn't reveal which (if any) matched.
+        let mut matched = None;
+        for (index, user) in self.users.iter().enumerate() {
+            if constant_time_eq(username.as_bytes(), user.username.as_bytes()) && matched.is_none()
+            {
+                matched = Some(index);
+            }
+        }
+
+        // Decide the rate-limit bucket from the matched account — never from
+        // attacker-supplied text. This caps the attempts map at one key per configured
+        // account plus the shared "_unknown", so a username-rotating attacker cannot
+        // exhaust memory and unknown-user spam cannot lock out a real account.
+        let attempt_key = matched.map_or_else(
+            || UNKNOWN_BUCKET.to_string(),
+            |index| format!("{ACCOUNT_BUCKET_PREFIX}{}", self.users[index].username),
+        );
 
-        self.check_rate_limit(attempt_key)?;
+        self.check_rate_limit(&attempt_key)?;
 
-        // Always run Argon2 regardless of `user_ok` so total response time does not
-        // depend on whether the username exists (no enumeration timing oracle).
-        let password_ok = verify_argon2id_password(password, &self.password_hash);
-        if !(user_ok && password_ok) {
-            self.record_failure(attempt_key);
+        // Always run Argon2 — against the first account's hash when the username is
+        // unknown — so total response time does not depend on whether the username
+        // exists (no enumeration timing oracle).
+        let Some(verify_against) = matched.or((!self.users.is_empty()).then_some(0)) else {
             return Err(AuthFailure::InvalidCredentials);
-        }
+        };
+        let password_ok =
+            verify_argon2id_password(password, &self.users[verify_against].password_hash);
+        let Some(user) = matched
+            .filter(|_| password_ok)
+            .map(|index| &self.users[index])
+        else {
+            self.record_failure(&attempt_key);
+            return Err(AuthFailure::InvalidCredentials);
+        };
 
-        self.clear_attempts(attempt_key);
+        self.clear_attempts(&attempt_key);
         let session_id = random_token();
         let csrf_token = random_token();
         let now = Instant::now();
         let record = SessionRecord {
+            username: user.username.clone(),
             csrf_token: csrf_token.clone(),
             created_at: now,
             last_seen: now,
@@ -224,7 +343,7 @@ impl AccountAuth {
         })
     }
 
-    fn authenticate_session(&self, headers: &HeaderMap) -> Option<String> {
+    fn authenticate_session(&self, headers: &HeaderMap) -> Option<(String, Principal)> {
         let session_id = cookie_token(headers, SESSION_COOKIE_NAME)?;
         let now = Instant::now();
         let mut sessions = self.sessions.lock().ok()?;
@@ -237,8 +356,20 @@ impl AccountAuth {
             return None;
         }
 
+        let Some(user) = self.users.iter().find(|u| u.username == record.username) else {
+            sessions.remove(&session_id);
+            return None;
+        };
         record.last_seen = now;
-        Some(record.csrf_token.clone())
+        Some((
+            record.csrf_token.clone(),
+            Principal {
+                username: user.username.clone(),
+                role: user.role,
+                projects: user.projects.clone(),
+                api_scopes: None,
+            },
+        ))
     }
 
     fn remove_session(&self, session_id: &str) {
@@ -462,7 +593,7 @@ fn allow_query_token<B>(request: &Request<B>) -> Option<String> {
     })
 }
 
-fn verify_argon2id_password(password: &str, password_hash: &str) -> bool {
+pub(crate) fn verify_argon2id_password(password: &str, password_hash: &str) -> bool {
     let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
         return false;
     };
@@ -471,6 +602,58 @@ fn verify_argon2id_password(password: &str, password_hash: &str) -> bool {
         .is_ok()
 }
 
+/// Load accounts from an `--auth-users` JSON file:
+///
+/// ```json
+/// { "users": [
+///   { "username": "alice", "passwordHash": "$argon2id$...", "role": "admin" },
+///   { "username": "bob", "passwordHash": "$argon2id$...", "role": "viewer",
+///     "projects": ["/srv/work/team-a/*"] }
+/// ] }
+/// ```
+pub fn load_account_users(path: &Path) -> Result<Vec<AccountUser>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    parse_account_users(&content).map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+fn parse_account_users(content: &str) -> Result<Vec<AccountUser>, String> {
+    let file: AccountUsersFile = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    if file.users.is_empty() {
+        return Err("at least one user is required".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    file.users
+        .into_iter()
+        .map(|entry| {
+            let username = entry.username.trim().to_string();
+            if username.is_empty() {
+                return Err("usernames must not be empty".to_string());
+            }
+            if !seen.insert(username.clone()) {
+                return Err(format!("duplicate username '{username}'"));
+            }
+            if !password_hash_is_valid(&entry.password_hash) {
+                return Err(format!(
+                    "password hash for '{username}' must be a valid Argon2id PHC string"
+                ));
+            }
+            let projects = match entry.projects {
+                Some(patterns) => ProjectScope::from_patterns(patterns)
+                    .map_err(|e| format!("user '{username}': {e}"))?,
+                None => ProjectScope::all(),
+            };
+            Ok(AccountUser::new(
+                username,
+                entry.password_hash,
+                entry.role,
+                projects,
+            ))
+        })
+        .collect()
+}
+
 pub fn password_hash_is_valid(password_hash: &str) -> bool {
     PasswordHash::new(password_hash)
         .map(|hash| hash.algorithm.as_str() == "argon2id")
@@ -493,7 +676,7 @@ fn recommended_argon2id() -> Argon2<'static> {
     Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
 }
 
-fn random_token() -> String {
+pub(crate) fn random_token() -> String {
     let raw = format!(
         "{}{}",
         uuid::Uuid::new_v4().as_simple(),
@@ -623,6 +806,7 @@ mod tests {
                 sessions.insert(
                     format!("pre-{i}"),
                     SessionRecord {
+                        username: "admin".to_string(),
                         csrf_token: "x".to_string(),
                         created_at: now,
                         last_seen: now,
@@ -668,4 +852,102 @@ mod tests {
         };
         assert_eq!(auth.login(&good).unwrap_err(), AuthFailure::RateLimited);
     }
+
+    fn users_file(hash: &str) -> String {
+        serde_json::json!({
+            "users": [
+                { "username": "alice", "passwordHash": hash, "role": "admin" },
+                {
+                    "username": "bob",
+                    "passwordHash": hash,
+                    "role": "viewer",
+                    "projects": ["/work/team-a/*"]
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn users_file_parses_roles_and_project_scopes() {
+        let users = parse_account_users(&users_file(&test_hash())).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].role, Role::Admin);
+        assert!(users[0].projects.is_unrestricted());
+        assert_eq!(users[1].role, Role::Viewer);
+        assert!(!users[1].projects.is_unrestricted());
+    }
+
+    #[test]
+    fn users_file_rejects_duplicates_bad_hashes_and_unknown_roles() {
+        let hash = test_hash();
+        let duplicate = serde_json::json!({ "users": [
+            { "username": "alice", "passwordHash": hash, "role": "admin" },
+            { "username": "alice", "passwordHash": hash, "role": "viewer" }
+        ] });
+        assert!(parse_account_users(&duplicate.to_string()).is_err());
+
+        let bad_hash = serde_json::json!({ "users": [
+            { "username": "alice", "passwordHash": "plaintext", "role": "admin" }
+        ] });
+        assert!(parse_account_users(&bad_hash.to_string()).is_err());
+
+        let bad_role = serde_json::json!({ "users": [
+            { "username": "alice", "passwordHash": hash, "role": "owner" }
+        ] });
+        assert!(parse_account_users(&bad_role.to_string()).is_err());
+        assert!(parse_account_users(r#"{ "users": [] }"#).is_err());
+    }
+
+    #[test]
+    fn session_carries_the_logged_in_users_role() {
+        let users = parse_account_users(&users_file(&test_hash())).unwrap();
+        let auth = AuthState::Account(Arc::new(AccountAuth::with_users(users, false)));
+        let outcome = auth
+            .login(&AuthLoginRequest {
+                token: None,
+                username: Some("bob".to_string()),
+                password: Some("correct horse battery staple".to_string()),
+            })
+            .unwrap();
+        let LoginOutcome::Account { session_id, .. } = outcome else {
+            panic!("expected account login");
+        };
+
+        let request = Request::builder()
+            .uri("/api/scan_projects")
+            .header(
+                header::COOKIE,
+                format!("{SESSION_COOKIE_NAME}={session_id}"),
+            )
+            .body(Body::empty())
+            .unwrap();
+        let AuthenticatedRequest::Account { principal, .. } = auth.authenticate(&request) else {
+            panic!("expected account session");
+        };
+        assert_eq!(principal.username, "bob");
+        assert_eq!(principal.role, Role::Viewer);
+    }
+
+    #[test]
+    fn failed_logins_are_bucketed_per_configured_account() {
+        let users = parse_account_users(&users_file(&test_hash())).unwrap();
+        let auth = AccountAuth::with_users(users, false);
+        for _ in 0..MAX_LOGIN_FAILURES {
+            let _ = auth.login(&bad_login("bob"));
+        }
+        assert_eq!(
+            auth.login(&bad_login("bob")).unwrap_err(),
+            AuthFailure::RateLimited
+        );
+        let good = AuthLoginRequest {
+            token: None,
+            username: Some("alice".to_string()),
+            password: Some("correct horse battery staple".to_string()),
+        };
+        assert!(
+            auth.login(&good).is_ok(),
+            "bob's lockout must not affect alice"
+        );
+    }
 }
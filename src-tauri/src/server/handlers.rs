@@ -4,12 +4,14 @@
 //! and returns the result as JSON. The command function signatures are unchanged.
 
 use axum::extract::State;
-use axum::Json;
+use axum::{Extension, Json};
 use serde::Deserialize;
 use serde_json::Value;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use super::auth::{Principal, Role};
+use super::scope::{Scoped, ScopedParams, ScopedPath};
 use super::state::AppState;
 use crate::commands;
 
@@ -60,6 +62,50 @@ macro_rules! handler_json {
     };
 }
 
+/// Like `handler_json!`, but the body's project/session paths must be visible to
+/// the calling account (see [`Scoped`]).
+macro_rules! handler_scoped {
+    ($name:ident, $params:ty, $body:expr) => {
+        pub async fn $name(Scoped(p): Scoped<$params>) -> Result<Json<Value>, ApiError> {
+            let result = $body(p).await.map_err(ApiError::from)?;
+            Ok(Json(serde_json::to_value(result).map_err(|e| {
+                ApiError(format!("Serialization error: {e}"))
+            })?))
+        }
+    };
+}
+
+/// Implement [`ScopedParams`] from the fields holding project and session paths.
+macro_rules! scoped_params {
+    ($params:ty, projects: [$($project:ident),*], sessions: [$($session:ident),*]) => {
+        impl ScopedParams for $params {
+            fn scoped_paths(&self) -> Vec<ScopedPath<'_>> {
+                let mut paths = Vec::new();
+                $(paths.extend(PathField::get(&self.$project).map(ScopedPath::Project));)*
+                $(paths.extend(PathField::get(&self.$session).map(ScopedPath::Session));)*
+                paths
+            }
+        }
+    };
+}
+
+/// Required (`String`) or optional (`Option<String>`) path parameter.
+trait PathField {
+    fn get(&self) -> Option<&str>;
+}
+
+impl PathField for String {
+    fn get(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl PathField for Option<String> {
+    fn get(&self) -> Option<&str> {
+        self.as_deref()
+    }
+}
+
 // ─── Parameter structs ────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -218,6 +264,15 @@ pub struct SessionComparisonParams {
     pub stats_mode: Option<String>,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareSessionsParams {
+    pub left_provider: String,
+    pub left_session_path: String,
+    pub right_provider: String,
+    pub right_session_path: String,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GlobalStatsParams {
@@ -235,6 +290,28 @@ pub struct GlobalStatsParams {
     pub custom_claude_paths: Option<Vec<commands::multi_provider::CustomClaudePathParam>>,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveBudgetsParams {
+    pub config: crate::models::BudgetConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveAutoArchiveConfigParams {
+    pub config: crate::models::AutoArchiveConfig,
+}
+
+/// Sent by the shared frontend client; the server ignores both roots.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatusParams {
+    #[serde(default)]
+    pub claude_path: Option<String>,
+    #[serde(default)]
+    pub custom_claude_paths: Option<Vec<commands::multi_provider::CustomClaudePathParam>>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsScopeParams {
@@ -419,6 +496,35 @@ pub struct McpScopeParam {
     pub scope: Option<String>,
 }
 
+// ─── Project scope ────────────────────────────────────────────────────────────
+
+scoped_params!(PathParam, projects: [], sessions: [path]);
+scoped_params!(GitLogParams, projects: [actual_path], sessions: []);
+scoped_params!(SessionPathParam, projects: [], sessions: [session_path]);
+scoped_params!(LoadProjectSessionsParams, projects: [project_path], sessions: []);
+scoped_params!(LoadProjectSessionsPageParams, projects: [project_path], sessions: []);
+scoped_params!(PaginatedParams, projects: [], sessions: [session_path]);
+scoped_params!(MessageCountParams, projects: [], sessions: [session_path]);
+scoped_params!(RecentEditsParams, projects: [project_path], sessions: []);
+scoped_params!(SessionTokenStatsParams, projects: [], sessions: [session_path]);
+scoped_params!(ProjectTokenStatsParams, projects: [project_path], sessions: []);
+scoped_params!(ProjectStatsSummaryParams, projects: [project_path], sessions: []);
+scoped_params!(SessionComparisonParams, projects: [project_path], sessions: []);
+scoped_params!(CompareSessionsParams, projects: [], sessions: [left_session_path, right_session_path]);
+scoped_params!(SettingsScopeParams, projects: [project_path], sessions: []);
+scoped_params!(SaveSettingsParams, projects: [project_path], sessions: []);
+scoped_params!(OptionalProjectPath, projects: [project_path], sessions: []);
+scoped_params!(SaveMcpServersParams, projects: [project_path], sessions: []);
+scoped_params!(DeleteSessionParams, projects: [], sessions: [file_path]);
+scoped_params!(RenameSessionParams, projects: [], sessions: [file_path]);
+scoped_params!(RenameOpenCodeParams, projects: [], sessions: [session_path]);
+scoped_params!(ProviderSessionsParams, projects: [project_path], sessions: []);
+scoped_params!(ProviderSessionsPageParams, projects: [project_path], sessions: []);
+scoped_params!(ProviderMessagesParams, projects: [], sessions: [session_path]);
+scoped_params!(ProviderMessagesPaginatedParams, projects: [], sessions: [session_path]);
+scoped_params!(ProviderMessageOffsetParams, projects: [], sessions: [session_path]);
+scoped_params!(UpdateProjectMetadataParams, projects: [project_path], sessions: []);
+
 // ─── Handlers: NO PARAMS ──────────────────────────────────────────────────────
 
 handler_no_params!(
@@ -433,6 +539,19 @@ handler_no_params!(get_system_info, commands::feedback::get_system_info);
 handler_no_params!(detect_providers, commands::multi_provider::detect_providers);
 handler_no_params!(load_presets, commands::settings::load_presets);
 handler_no_params!(load_mcp_presets, commands::mcp_presets::load_mcp_presets);
+handler_no_params!(load_budgets, commands::budget::load_budgets);
+handler_no_params!(
+    load_auto_archive_config,
+    commands::auto_archive::load_auto_archive_config
+);
+handler_no_params!(
+    get_auto_archive_log,
+    commands::auto_archive::get_auto_archive_log
+);
+handler_no_params!(
+    run_auto_archive_now,
+    commands::auto_archive::run_auto_archive_now
+);
 handler_no_params!(
     load_unified_presets,
     commands::unified_presets::load_unified_presets
@@ -444,12 +563,31 @@ handler_no_params!(
 
 pub async fn get_server_config(
     State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
 ) -> Result<Json<Value>, ApiError> {
+    let Some(Extension(principal)) = principal else {
+        return Ok(Json(serde_json::json!({
+            "readOnly": state.read_only,
+        })));
+    };
     Ok(Json(serde_json::json!({
-        "readOnly": state.read_only,
+        "readOnly": state.read_only || principal.role == Role::Viewer,
+        "username": principal.username,
+        "role": principal.role.as_str(),
     })))
 }
 
+/// Drop projects outside the calling account's globs.
+fn visible_projects(
+    principal: Option<Extension<Principal>>,
+    mut projects: Vec<crate::models::ClaudeProject>,
+) -> Vec<crate::models::ClaudeProject> {
+    if let Some(Extension(principal)) = principal {
+        projects.retain(|project| principal.projects.allows_project(project));
+    }
+    projects
+}
+
 /// Note: scope parameter is accepted for API contract compatibility but not used
 /// by the underlying command (it always reads the global MCP config).
 pub async fn get_mcp_servers(Json(_p): Json<McpScopeParam>) -> Result<Json<Value>, ApiError> {
@@ -475,17 +613,22 @@ handler_json!(
     |p: PathParam| async move { commands::project::validate_custom_claude_dir(p.path).await }
 );
 
-handler_json!(
-    scan_projects,
-    ClaudePathParam,
-    |p: ClaudePathParam| async move { commands::project::scan_projects(p.claude_path).await }
-);
+pub async fn scan_projects(
+    principal: Option<Extension<Principal>>,
+    Json(p): Json<ClaudePathParam>,
+) -> Result<Json<Value>, ApiError> {
+    let projects = commands::project::scan_projects(p.claude_path).await?;
+    Ok(Json(
+        serde_json::to_value(visible_projects(principal, projects))
+            .map_err(|e| ApiError(format!("Serialization error: {e}")))?,
+    ))
+}
 
-handler_json!(get_git_log, GitLogParams, |p: GitLogParams| async move {
+handler_scoped!(get_git_log, GitLogParams, |p: GitLogParams| async move {
     commands::project::get_git_log(p.actual_path, p.limit).await
 });
 
-handler_json!(
+handler_scoped!(
     load_project_sessions,
     LoadProjectSessionsParams,
     |p: LoadProjectSessionsParams| async move {
@@ -493,7 +636,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     load_project_sessions_page,
     LoadProjectSessionsPageParams,
     |p: LoadProjectSessionsPageParams| async move {
@@ -507,13 +650,13 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     load_session_messages,
     SessionPathParam,
     |p: SessionPathParam| async move { commands::session::load_session_messages(p.session_path).await }
 );
 
-handler_json!(
+handler_scoped!(
     load_session_messages_paginated,
     PaginatedParams,
     |p: PaginatedParams| async move {
@@ -527,7 +670,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_session_message_count,
     MessageCountParams,
     |p: MessageCountParams| async move {
@@ -535,7 +678,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_session_subagents,
     SessionPathParam,
     |p: SessionPathParam| async move {
@@ -552,7 +695,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_recent_edits,
     RecentEditsParams,
     |p: RecentEditsParams| async move {
@@ -619,7 +762,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     delete_session,
     DeleteSessionParams,
     |p: DeleteSessionParams| async move {
@@ -632,7 +775,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     rename_session_native,
     RenameSessionParams,
     |p: RenameSessionParams| async move {
@@ -643,7 +786,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     reset_session_native_name,
     PathParam,
     |p: PathParam| async move {
@@ -654,7 +797,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     rename_opencode_session_title,
     RenameOpenCodeParams,
     |p: RenameOpenCodeParams| async move {
@@ -721,7 +864,7 @@ pub async fn open_github_issues() -> Result<Json<Value>, ApiError> {
 
 // ─── Handlers: COMPLEX PARAMS ─────────────────────────────────────────────────
 
-handler_json!(
+handler_scoped!(
     get_session_token_stats,
     SessionTokenStatsParams,
     |p: SessionTokenStatsParams| async move {
@@ -735,7 +878,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_project_token_stats,
     ProjectTokenStatsParams,
     |p: ProjectTokenStatsParams| async move {
@@ -751,7 +894,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_project_stats_summary,
     ProjectStatsSummaryParams,
     |p: ProjectStatsSummaryParams| async move {
@@ -765,7 +908,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_session_comparison,
     SessionComparisonParams,
     |p: SessionComparisonParams| async move {
@@ -780,6 +923,20 @@ handler_json!(
     }
 );
 
+handler_scoped!(
+    compare_sessions,
+    CompareSessionsParams,
+    |p: CompareSessionsParams| async move {
+        commands::stats::compare_sessions(
+            p.left_provider,
+            p.left_session_path,
+            p.right_provider,
+            p.right_session_path,
+        )
+        .await
+    }
+);
+
 handler_json!(
     get_global_stats_summary,
     GlobalStatsParams,
@@ -797,6 +954,42 @@ handler_json!(
 );
 
 handler_json!(
+    save_budgets,
+    SaveBudgetsParams,
+    |p: SaveBudgetsParams| async move { commands::budget::save_budgets(p.config).await }
+);
+
+handler_json!(
+    save_auto_archive_config,
+    SaveAutoArchiveConfigParams,
+    |p: SaveAutoArchiveConfigParams| async move {
+        commands::auto_archive::save_auto_archive_config(p.config).await
+    }
+);
+
+/// Budget status; newly reached thresholds go out on the SSE stream.
+///
+/// Measured against the server's configured Claude roots, like the
+/// background monitor. The request's roots are ignored, so no client can
+/// repoint what the monitor watches or which history the alerts describe.
+pub async fn get_budget_status(
+    State(state): State<Arc<AppState>>,
+    Json(_): Json<BudgetStatusParams>,
+) -> Result<Json<Value>, ApiError> {
+    let (claude_path, custom_claude_paths) = commands::budget::configured_roots()
+        .ok_or_else(|| ApiError("Could not find home directory".to_string()))?;
+    let (statuses, alerts) =
+        commands::budget::evaluate_budgets(claude_path, custom_claude_paths).await?;
+    for alert in alerts {
+        // Ignore send errors (no SSE subscribers)
+        let _ = state.budget_tx.send(alert);
+    }
+    Ok(Json(serde_json::to_value(statuses).map_err(|e| {
+        ApiError(format!("Serialization error: {e}"))
+    })?))
+}
+
+handler_scoped!(
     get_settings_by_scope,
     SettingsScopeParams,
     |p: SettingsScopeParams| async move {
@@ -804,7 +997,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     save_settings,
     SaveSettingsParams,
     |p: SaveSettingsParams| async move {
@@ -812,7 +1005,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_all_settings,
     OptionalProjectPath,
     |p: OptionalProjectPath| async move {
@@ -820,7 +1013,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_all_mcp_servers,
     OptionalProjectPath,
     |p: OptionalProjectPath| async move {
@@ -828,7 +1021,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     save_mcp_servers,
     SaveMcpServersParams,
     |p: SaveMcpServersParams| async move {
@@ -836,7 +1029,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_claude_json_config,
     OptionalProjectPath,
     |p: OptionalProjectPath| async move {
@@ -846,22 +1039,25 @@ handler_json!(
 
 // ─── Handlers: MULTI-PROVIDER ─────────────────────────────────────────────────
 
-handler_json!(
-    scan_all_projects,
-    ScanAllProjectsParams,
-    |p: ScanAllProjectsParams| async move {
-        commands::multi_provider::scan_all_projects(
-            p.claude_path,
-            p.active_providers,
-            p.custom_claude_paths,
-            p.wsl_enabled,
-            p.wsl_excluded_distros,
-        )
-        .await
-    }
-);
+pub async fn scan_all_projects(
+    principal: Option<Extension<Principal>>,
+    Json(p): Json<ScanAllProjectsParams>,
+) -> Result<Json<Value>, ApiError> {
+    let projects = commands::multi_provider::scan_all_projects(
+        p.claude_path,
+        p.active_providers,
+        p.custom_claude_paths,
+        p.wsl_enabled,
+        p.wsl_excluded_distros,
+    )
+    .await?;
+    Ok(Json(
+        serde_json::to_value(visible_projects(principal, projects))
+            .map_err(|e| ApiError(format!("Serialization error: {e}")))?,
+    ))
+}
 
-handler_json!(
+handler_scoped!(
     load_provider_sessions,
     ProviderSessionsParams,
     |p: ProviderSessionsParams| async move {
@@ -874,7 +1070,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     load_provider_sessions_page,
     ProviderSessionsPageParams,
     |p: ProviderSessionsPageParams| async move {
@@ -889,7 +1085,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     load_provider_messages,
     ProviderMessagesParams,
     |p: ProviderMessagesParams| async move {
@@ -897,7 +1093,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     load_provider_messages_paginated,
     ProviderMessagesPaginatedParams,
     |p: ProviderMessagesPaginatedParams| async move {
@@ -912,7 +1108,7 @@ handler_json!(
     }
 );
 
-handler_json!(
+handler_scoped!(
     get_provider_message_offset,
     ProviderMessageOffsetParams,
     |p: ProviderMessageOffsetParams| async move {
@@ -1048,7 +1244,7 @@ pub async fn update_session_metadata(
 
 pub async fn update_project_metadata(
     State(state): State<Arc<AppState>>,
-    Json(p): Json<UpdateProjectMetadataParams>,
+    Scoped(p): Scoped<UpdateProjectMetadataParams>,
 ) -> Result<Json<Value>, ApiError> {
     commands::metadata::validate_project_metadata_key(&p.project_path).map_err(ApiError::from)?;
 
@@ -1192,7 +1388,19 @@ fn default_true() -> bool {
     true
 }
 
-handler_json!(
+impl ScopedParams for CreateArchiveParams {
+    fn scoped_paths(&self) -> Vec<ScopedPath<'_>> {
+        std::iter::once(ScopedPath::Project(self.source_project_path.as_str()))
+            .chain(
+                self.session_file_paths
+                    .iter()
+                    .map(|path| ScopedPath::Session(path.as_str())),
+            )
+            .collect()
+    }
+}
+
+handler_scoped!(
     create_archive,
     CreateArchiveParams,
     |p: CreateArchiveParams| async move {
@@ -1257,6 +1465,39 @@ handler_json!(
     }
 );
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportArchiveParams {
+    pub archive_id: String,
+    pub output_path: String,
+}
+
+handler_json!(
+    export_archive,
+    ExportArchiveParams,
+    |p: ExportArchiveParams| async move {
+        // WebUI endpoint must stay within safe export directories.
+        commands::claude_settings::is_safe_path(&PathBuf::from(&p.output_path))?;
+        commands::archive_bundle::export_archive(p.archive_id, p.output_path).await
+    }
+);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportArchiveParams {
+    pub bundle_path: String,
+}
+
+handler_json!(
+    import_archive,
+    ImportArchiveParams,
+    |p: ImportArchiveParams| async move {
+        // WebUI: enforce directory allowlist (Tauri desktop relies on OS dialog)
+        commands::claude_settings::is_safe_path(&PathBuf::from(&p.bundle_path))?;
+        commands::archive_bundle::import_archive(p.bundle_path).await
+    }
+);
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExpiringSessionsParams {
@@ -1265,7 +1506,9 @@ pub struct ExpiringSessionsParams {
     pub threshold_days: Option<i64>,
 }
 
-handler_json!(
+scoped_params!(ExpiringSessionsParams, projects: [project_path], sessions: []);
+
+handler_scoped!(
     get_expiring_sessions,
     ExpiringSessionsParams,
     |p: ExpiringSessionsParams| async move {
@@ -1279,12 +1522,16 @@ handler_json!(
 pub struct ExportSessionParams {
     pub session_file_path: String,
     pub format: String,
+    pub redact: Option<bool>,
+    pub dry_run: Option<bool>,
 }
 
-handler_json!(
+scoped_params!(ExportSessionParams, projects: [], sessions: [session_file_path]);
+
+handler_scoped!(
     export_session,
     ExportSessionParams,
     |p: ExportSessionParams| async move {
-        commands::archive::export_session(p.session_file_path, p.format).await
+        commands::archive::export_session(p.session_file_path, p.format, p.redact, p.dry_run).await
     }
 );
@@ -0,0 +1,630 @@
+//! Prometheus metrics for `WebUI` server mode, served at `GET /metrics`.
+//!
+//! Usage metrics come from the same `GlobalStatsSummary` the dashboard shows,
+//! recomputed on every scrape (the stats caches keep that cheap once warm):
+//! tokens, sessions and messages per provider, per provider and project (every
+//! project, not only the dashboard's top ten) and per model and service tier,
+//! estimated and reported cost per model and service tier, and tool usage. Server health metrics cover request counts and latency per route
+//! plus hit/miss counters for the stats and search caches.
+//!
+//! The body uses the Prometheus text exposition format (0.0.4), which
+//! Prometheus, Grafana Agent and other `OpenMetrics` scrapers all accept.
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write as _};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use super::auth::Principal;
+use super::state::AppState;
+use crate::commands::session::search_cache_counters;
+use crate::commands::stats::{global_stats_summary, stats_cache_counters};
+use crate::models::GlobalStatsSummary;
+
+/// Content type of the text exposition format.
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds (seconds) of the request latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label for requests no route matched (SPA and static assets).
+const FALLBACK_ROUTE: &str = "fallback";
+
+#[derive(Default)]
+struct RouteStats {
+    /// Non-cumulative counts per `LATENCY_BUCKETS` entry; slower requests
+    /// only land in `count` (the `+Inf` bucket).
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum_seconds: f64,
+    statuses: BTreeMap<u16, u64>,
+}
+
+/// Request counters shared by the tracking middleware and the `/metrics`
+/// handler, keyed by (method, route template).
+#[derive(Default)]
+pub struct ServerMetrics {
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+}
+
+impl ServerMetrics {
+    pub fn record(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let mut routes = self.lock_routes();
+        let stats = routes
+            .entry((method.to_string(), route.to_string()))
+            .or_default();
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            stats.buckets[idx] += 1;
+        }
+        stats.count += 1;
+        stats.sum_seconds += seconds;
+        *stats.statuses.entry(status).or_insert(0) += 1;
+    }
+
+    /// Requests recorded for one method, route and status.
+    #[cfg(test)]
+    pub(crate) fn request_count(&self, method: &str, route: &str, status: u16) -> u64 {
+        self.lock_routes()
+            .get(&(method.to_string(), route.to_string()))
+            .and_then(|stats| stats.statuses.get(&status).copied())
+            .unwrap_or(0)
+    }
+
+    /// Lock the route map, recovering from a poisoned lock — metrics must
+    /// never take the server down.
+    fn lock_routes(&self) -> MutexGuard<'_, BTreeMap<(String, String), RouteStats>> {
+        match self.routes.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn render(&self, out: &mut MetricsWriter) {
+        let routes = self.lock_routes();
+
+        out.family(
+            "cchv_http_requests_total",
+            "counter",
+            "HTTP requests handled, by method, route and status code.",
+        );
+        for ((method, route), stats) in routes.iter() {
+            for (status, count) in &stats.statuses {
+                let status = status.to_string();
+                out.sample(
+                    "cchv_http_requests_total",
+                    &[
+                        ("method", method.as_str()),
+                        ("route", route.as_str()),
+                        ("status", status.as_str()),
+                    ],
+                    *count,
+                );
+            }
+        }
+
+        out.family(
+            "cchv_http_request_duration_seconds",
+            "histogram",
+            "Time until the response head was ready, by method and route.",
+        );
+        for ((method, route), stats) in routes.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                cumulative += count;
+                let le = bound.to_string();
+                out.sample(
+                    "cchv_http_request_duration_seconds_bucket",
+                    &[
+                        ("method", method.as_str()),
+                        ("route", route.as_str()),
+                        ("le", le.as_str()),
+                    ],
+                    cumulative,
+                );
+            }
+            out.sample(
+                "cchv_http_request_duration_seconds_bucket",
+                &[
+                    ("method", method.as_str()),
+                    ("route", route.as_str()),
+                    ("le", "+Inf"),
+                ],
+                stats.count,
+            );
+            out.sample(
+                "cchv_http_request_duration_seconds_sum",
+                &[("method", method.as_str()), ("route", route.as_str())],
+                stats.sum_seconds,
+            );
+            out.sample(
+                "cchv_http_request_duration_seconds_count",
+                &[("method", method.as_str()), ("route", route.as_str())],
+                stats.count,
+            );
+        }
+    }
+}
+
+/// Record every request's status and latency, labelled by its route
+/// template (`/api/load_session_messages`, not the raw URI) so label
+/// cardinality stays bounded.
+pub async fn track_request_metrics(
+    State(metrics): State<Arc<ServerMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(FALLBACK_ROUTE, MatchedPath::as_str)
+        .to_string();
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.record(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// `GET /metrics` — usage, cache and HTTP metrics in the text exposition
+/// format.
+pub async fn metrics_handler(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+) -> Response {
+    // Usage gauges cover every project, so project-limited accounts can't scrape.
+    if principal.is_some_and(|Extension(p)| !p.projects.is_unrestricted()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let claude_path = crate::providers::claude::get_base_path().unwrap_or_default();
+    let started = Instant::now();
+    // Every project, not just the dashboard's top ten.
+    let summary = global_stats_summary(claude_path, None, None, None, None, None, None);
+    let stats_seconds = started.elapsed().as_secs_f64();
+    if let Err(e) = &summary {
+        log::warn!("/metrics: global stats unavailable: {e}");
+    }
+
+    let mut out = MetricsWriter::default();
+    out.family(
+        "cchv_uptime_seconds",
+        "gauge",
+        "Seconds since the server started.",
+    );
+    out.sample(
+        "cchv_uptime_seconds",
+        &[],
+        state.start_time.elapsed().as_secs_f64(),
+    );
+    render_stats(&mut out, summary.as_ref().ok(), stats_seconds);
+    render_cache_counters(&mut out, stats_cache_counters(), search_cache_counters());
+    state.metrics.render(&mut out);
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        out.finish(),
+    )
+        .into_response()
+}
+
+fn render_stats(out: &mut MetricsWriter, summary: Option<&GlobalStatsSummary>, seconds: f64) {
+    out.family(
+        "cchv_stats_up",
+        "gauge",
+        "Whether the usage statistics below could be computed for this scrape.",
+    );
+    out.sample("cchv_stats_up", &[], u64::from(summary.is_some()));
+    out.family(
+        "cchv_stats_duration_seconds",
+        "gauge",
+        "Time taken to compute the usage statistics for this scrape.",
+    );
+    out.sample("cchv_stats_duration_seconds", &[], seconds);
+    let Some(summary) = summary else {
+        return;
+    };
+
+    out.family(
+        "cchv_projects",
+        "gauge",
+        "Projects with sessions, by provider.",
+    );
+    for provider in &summary.provider_distribution {
+        out.sample(
+            "cchv_projects",
+            &[("provider", provider.provider_id.as_str())],
+            provider.projects,
+        );
+    }
+    out.family("cchv_sessions", "gauge", "Sessions, by provider.");
+    for provider in &summary.provider_distribution {
+        out.sample(
+            "cchv_sessions",
+            &[("provider", provider.provider_id.as_str())],
+            provider.sessions,
+        );
+    }
+    out.family("cchv_messages", "gauge", "Messages, by provider.");
+    for provider in &summary.provider_distribution {
+        out.sample(
+            "cchv_messages",
+            &[("provider", provider.provider_id.as_str())],
+            provider.messages,
+        );
+    }
+    out.family("cchv_tokens", "gauge", "Tokens used, by provider.");
+    for provider in &summary.provider_distribution {
+        out.sample(
+            "cchv_tokens",
+            &[("provider", provider.provider_id.as_str())],
+            provider.tokens,
+        );
+    }
+
+    out.family(
+        "cchv_project_sessions",
+        "gauge",
+        "Sessions, by provider and project.",
+    );
+    for project in &summary.provider_projects {
+        out.sample(
+            "cchv_project_sessions",
+            &[
+                (
+                    "provider",
+                    project.provider_id.as_deref().unwrap_or("claude"),
+                ),
+                ("project", project.project_name.as_str()),
+            ],
+            project.sessions,
+        );
+    }
+    out.family(
+        "cchv_project_messages",
+        "gauge",
+        "Messages, by provider and project.",
+    );
+    for project in &summary.provider_projects {
+        out.sample(
+            "cchv_project_messages",
+            &[
+                (
+                    "provider",
+                    project.provider_id.as_deref().unwrap_or("claude"),
+                ),
+                ("project", project.project_name.as_str()),
+            ],
+            project.messages,
+        );
+    }
+    out.family(
+        "cchv_project_tokens",
+        "gauge",
+        "Tokens, by provider and project.",
+    );
+    for project in &summary.provider_projects {
+        out.sample(
+            "cchv_project_tokens",
+            &[
+                (
+                    "provider",
+                    project.provider_id.as_deref().unwrap_or("claude"),
+                ),
+                ("project", project.project_name.as_str()),
+            ],
+            project.tokens,
+        );
+    }
+
+    out.family(
+        "cchv_model_messages",
+        "gauge",
+        "Assistant messages, by provider, model and service tier (empty when none).",
+    );
+    for model in &summary.model_distribution {
+        let provider = model.provider_id.as_deref().unwrap_or("claude");
+        let tier = model.service_tier.as_deref().unwrap_or_default();
+        out.sample(
+            "cchv_model_messages",
+            &[
+                ("provider", provider),
+                ("model", model.model_name.as_str()),
+                ("service_tier", tier),
+            ],
+            model.message_count,
+        );
+    }
+    out.family(
+        "cchv_model_tokens",
+        "gauge",
+        "Tokens, by provider, model, service tier and token type.",
+    );
+    for model in &summary.model_distribution {
+        let provider = model.provider_id.as_deref().unwrap_or("claude");
+        let tier = model.service_tier.as_deref().unwrap_or_default();
+        for (kind, count) in [
+            ("input", model.input_tokens),
+            ("output", model.output_tokens),
+            ("cache_creation", model.cache_creation_tokens),
+            ("cache_read", model.cache_read_tokens),
+            ("reasoning", model.reasoning_tokens),
+        ] {
+            out.sample(
+                "cchv_model_tokens",
+                &[
+                    ("provider", provider),
+                    ("model", model.model_name.as_str()),
+                    ("service_tier", tier),
+                    ("type", kind),
+                ],
+                count,
+            );
+        }
+    }
+    out.family(
+        "cchv_model_cost_usd",
+        "gauge",
+        "Cost in USD, by provider, model and service tier: as reported by the provider, or estimated from the pricing table.",
+    );
+    for model in &summary.model_distribution {
+        let provider = model.provider_id.as_deref().unwrap_or("claude");
+        let tier = model.service_tier.as_deref().unwrap_or_default();
+        for (source, cost) in [
+            ("reported", model.cost_usd),
+            ("estimated", model.estimated_cost_usd),
+        ] {
+            if let Some(cost) = cost {
+                out.sample(
+                    "cchv_model_cost_usd",
+                    &[
+                        ("provider", provider),
+                        ("model", model.model_name.as_str()),
+                        ("service_tier", tier),
+                        ("source", source),
+                    ],
+                    cost,
+                );
+            }
+        }
+    }
+
+    out.family("cchv_tool_uses", "gauge", "Tool calls, by tool.");
+    for tool in &summary.most_used_tools {
+        out.sample(
+            "cchv_tool_uses",
+            &[("tool", tool.tool_name.as_str())],
+            tool.usage_count,
+        );
+    }
+    out.family(
+        "cchv_tool_success_ratio",
+        "gauge",
+        "Share of tool calls that did not error, by tool.",
+    );
+    for tool in &summary.most_used_tools {
+        out.sample(
+            "cchv_tool_success_ratio",
+            &[("tool", tool.tool_name.as_str())],
+            f64::from(tool.success_rate) / 100.0,
+        );
+    }
+}
+
+fn render_cache_counters(out: &mut MetricsWriter, stats: (u64, u64), search: (u64, u64)) {
+    out.family(
+        "cchv_cache_requests_total",
+        "counter",
+        "Cache lookups, by cache and result.",
+    );
+    for (cache, (hits, misses)) in [("stats", stats), ("search", search)] {
+        out.sample(
+            "cchv_cache_requests_total",
+            &[("cache", cache), ("result", "hit")],
+            hits,
+        );
+        out.sample(
+            "cchv_cache_requests_total",
+            &[("cache", cache), ("result", "miss")],
+            misses,
+        );
+    }
+}
+
+/// Builder for the text exposition format.
+#[derive(Default)]
+struct MetricsWriter {
+    text: String,
+}
+
+impl MetricsWriter {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            self.text.push('{');
+            for (idx, (key, label)) in labels.iter().enumerate() {
+                if idx > 0 {
+                    self.text.push(',');
+                }
+                let _ = write!(self.text, "{key}=\"{}\"", escape_label(label));
+            }
+            self.text.push('}');
+        }
+        self.text.push(' ');
+        let _ = write!(self.text, "{value}");
+        self.text.push('\n');
+    }
+
+    fn finish(self) -> String {
+        self.text
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ModelStats, ProjectRanking, ProviderUsageStats, ToolUsageStats};
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = ServerMetrics::default();
+        metrics.record("POST", "/api/scan_projects", 200, 0.003);
+        metrics.record("POST", "/api/scan_projects", 200, 0.2);
+        metrics.record("POST", "/api/scan_projects", 500, 30.0);
+
+        let mut out = MetricsWriter::default();
+        metrics.render(&mut out);
+        let text = out.finish();
+
+        let labels = r#"method="POST",route="/api/scan_projects""#;
+        assert!(text.contains(&format!(
+            "cchv_http_request_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "cchv_http_request_duration_seconds_bucket{{{labels},le=\"0.25\"}} 2\n"
+        )));
+        assert!(text.contains(&format!(
+            "cchv_http_request_duration_seconds_bucket{{{labels},le=\"10\"}} 2\n"
+        )));
+        assert!(text.contains(&format!(
+            "cchv_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 3\n"
+        )));
+        assert!(text.contains(&format!(
+            "cchv_http_request_duration_seconds_count{{{labels}}} 3\n"
+        )));
+        assert!(text.contains(&format!(
+            "cchv_http_requests_total{{{labels},status=\"500\"}} 1\n"
+        )));
+    }
+
+    #[tokio::test]
+    async fn middleware_labels_requests_by_route_template() {
+        use axum::body::Body;
+        use axum::routing::get;
+        use axum::Router;
+        use tower::ServiceExt;
+
+        let metrics = Arc::new(ServerMetrics::default());
+        let app = Router::new()
+            .route("/items/{id}", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::clone(&metrics),
+                track_request_metrics,
+            ));
+        for uri in ["/items/1", "/items/2", "/missing"] {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let mut out = MetricsWriter::default();
+        metrics.render(&mut out);
+        let text = out.finish();
+        assert!(text.contains(
+            "cchv_http_requests_total{method=\"GET\",route=\"/items/{id}\",status=\"200\"} 2\n"
+        ));
+        assert!(!text.contains("/items/1"));
+    }
+
+    #[test]
+    fn stats_render_per_provider_project_model_and_tool() {
+        let summary = GlobalStatsSummary {
+            provider_distribution: vec![ProviderUsageStats {
+                provider_id: "codex".to_string(),
+                projects: 2,
+                sessions: 3,
+                messages: 40,
+                tokens: 5000,
+            }],
+            provider_projects: vec![ProjectRanking {
+                provider_id: Some("codex".to_string()),
+                project_name: "say \"hi\"".to_string(),
+                sessions: 1,
+                messages: 10,
+                tokens: 700,
+            }],
+            model_distribution: vec![ModelStats {
+                provider_id: Some("codex".to_string()),
+                model_name: "gpt-5".to_string(),
+                service_tier: Some("priority".to_string()),
+                message_count: 9,
+                token_count: 5000,
+                input_tokens: 4000,
+                output_tokens: 1000,
+                cache_creation_tokens: 0,
+                cache_read_tokens: 0,
+                reasoning_tokens: 0,
+                cost_usd: None,
+                reported_cost_tokens: 0,
+                estimated_cost_usd: Some(0.25),
+                context_breakdown: Vec::new(),
+            }],
+            most_used_tools: vec![ToolUsageStats {
+                tool_name: "Bash".to_string(),
+                usage_count: 12,
+                success_rate: 75.0,
+                avg_execution_time: None,
+            }],
+            ..GlobalStatsSummary::default()
+        };
+        let mut out = MetricsWriter::default();
+        render_stats(&mut out, Some(&summary), 0.5);
+        let text = out.finish();
+
+        assert!(text.contains("cchv_stats_up 1\n"));
+        assert!(text.contains("cchv_sessions{provider=\"codex\"} 3\n"));
+        assert!(text
+            .contains("cchv_project_tokens{provider=\"codex\",project=\"say \\\"hi\\\"\"} 700\n"));
+        assert!(text.contains(
+            "cchv_model_tokens{provider=\"codex\",model=\"gpt-5\",service_tier=\"priority\",type=\"input\"} 4000\n"
+        ));
+        assert!(text.contains(
+            "cchv_model_cost_usd{provider=\"codex\",model=\"gpt-5\",service_tier=\"priority\",source=\"estimated\"} 0.25\n"
+        ));
+        assert!(!text.contains("source=\"reported\""));
+        assert!(text.contains("cchv_tool_success_ratio{tool=\"Bash\"} 0.75\n"));
+        assert!(text.contains("# TYPE cchv_tokens gauge\n"));
+    }
+
+    #[test]
+    fn stats_failure_reports_down_without_usage_metrics() {
+        let mut out = MetricsWriter::default();
+        render_stats(&mut out, None, 0.1);
+        let text = out.finish();
+        assert!(text.contains("cchv_stats_up 0\n"));
+        assert!(!text.contains("cchv_tokens"));
+    }
+
+    #[test]
+    fn cache_counters_render_hits_and_misses() {
+        let mut out = MetricsWriter::default();
+        render_cache_counters(&mut out, (5, 2), (0, 7));
+        let text = out.finish();
+        assert!(text.contains("cchv_cache_requests_total{cache=\"stats\",result=\"hit\"} 5\n"));
+        assert!(text.contains("cchv_cache_requests_total{cache=\"search\",result=\"miss\"} 7\n"));
+    }
+}
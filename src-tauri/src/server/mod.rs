@@ -11,8 +11,12 @@
 //! - **External**: `--dist <path>` serves assets from the filesystem.
 //!   Useful during development or when overriding the built-in frontend.
 
+pub mod api_tokens;
+pub mod api_v1;
 pub mod auth;
 pub mod handlers;
+pub mod metrics;
+pub mod scope;
 pub mod state;
 
 use axum::body::Body;
@@ -22,7 +26,7 @@ use axum::middleware::Next;
 use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{get, post};
-use axum::{middleware, Json, Router};
+use axum::{middleware, Extension, Json, Router};
 use rust_embed::Embed;
 use std::convert::Infallible;
 use std::net::SocketAddr;
@@ -32,12 +36,15 @@ use tokio_stream::{Stream, StreamExt};
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
+use self::api_tokens::{ApiScope, API_TOKEN_PREFIX};
 use self::auth::{
     auth_error_response, clear_auth_cookies_response, csrf_valid, login_response, AuthLoginRequest,
-    AuthState, AuthenticatedRequest,
+    AuthState, AuthenticatedRequest, Principal, Role,
 };
 use self::handlers as h;
 use self::state::AppState;
+use crate::commands::watcher::FileWatchEvent;
+use crate::models::BudgetAlert;
 /// `/api/*` POST routes that are safe to serve while the server is in read-only
 /// mode (the read/load/scan/search/export surface).
 ///
@@ -48,6 +55,7 @@ use self::state::AppState;
 /// This list must stay in sync with the POST routes registered on `protected_api`
 /// in `build_router`; the `read_only_*` tests below pin the current classification.
 const READ_ONLY_ALLOWED_API_PATHS: &[&str] = &[
+    "/compare_sessions",
     "/detect_claude_config_dir",
     "/detect_providers",
     "/export_session",
@@ -56,6 +64,8 @@ const READ_ONLY_ALLOWED_API_PATHS: &[&str] = &[
     "/get_archive_base_path",
     "/get_archive_disk_usage",
     "/get_archive_sessions",
+    "/get_auto_archive_log",
+    "/get_budget_status",
     "/get_claude_folder_path",
     "/get_claude_json_config",
     "/get_expiring_sessions",
@@ -81,6 +91,8 @@ const READ_ONLY_ALLOWED_API_PATHS: &[&str] = &[
     "/is_project_hidden",
     "/list_archives",
     "/load_archive_session_messages",
+    "/load_auto_archive_config",
+    "/load_budgets",
     "/load_mcp_presets",
     "/load_presets",
     "/load_project_sessions",
@@ -116,13 +128,18 @@ const READ_ONLY_MUTATING_API_PATHS: &[&str] = &[
     "/delete_preset",
     "/delete_session",
     "/delete_unified_preset",
+    "/export_archive",
+    "/import_archive",
     "/rename_archive",
     "/rename_opencode_session_title",
     "/rename_session_native",
     "/reset_session_native_name",
     "/restore_file",
+    "/run_auto_archive_now",
+    "/save_auto_archive_config",
     "/save_mcp_preset",
     "/save_mcp_servers",
+    "/save_budgets",
     "/save_preset",
     "/save_screenshot",
     "/save_settings",
@@ -137,6 +154,109 @@ const READ_ONLY_MUTATING_API_PATHS: &[&str] = &[
     "/write_text_file",
 ];
 
+/// Mutating `/api/*` POST routes an `editor` account may call on top of the
+/// read-only allowlist: metadata, presets, renames and archiving. Like read-only
+/// mode this is deny-by-default — every other mutation (deleting sessions,
+/// restoring files, writing settings, ...) needs the `admin` role.
+const EDITOR_ALLOWED_API_PATHS: &[&str] = &[
+    "/create_archive",
+    "/delete_mcp_preset",
+    "/delete_preset",
+    "/delete_unified_preset",
+    "/import_archive",
+    "/rename_archive",
+    "/rename_opencode_session_title",
+    "/rename_session_native",
+    "/reset_session_native_name",
+    "/save_mcp_preset",
+    "/save_preset",
+    "/save_unified_preset",
+    "/save_user_metadata",
+    "/send_feedback",
+    "/start_file_watcher",
+    "/stop_file_watcher",
+    "/update_project_metadata",
+    "/update_session_metadata",
+];
+
+/// `/api/*` routes that read or write data across every project at once. Accounts
+/// limited to some projects get `403` here; per-project routes are checked in the
+/// handlers instead (see [`scope::Scoped`]).
+const CROSS_PROJECT_API_PATHS: &[&str] = &[
+    "/delete_archive",
+    "/export_archive",
+    "/get_archive_disk_usage",
+    "/get_archive_sessions",
+    "/get_auto_archive_log",
+    "/get_budget_status",
+    "/get_claude_json_config",
+    "/get_global_stats_summary",
+    "/import_archive",
+    "/list_archives",
+    "/load_archive_session_messages",
+    "/load_auto_archive_config",
+    "/load_budgets",
+    "/read_text_file",
+    "/rename_archive",
+    "/run_auto_archive_now",
+    "/save_auto_archive_config",
+    "/save_user_metadata",
+    "/search_all_providers",
+    "/search_messages",
+];
+
+/// `/api/*` POST routes an API token with the `read:stats` scope may call:
+/// aggregate statistics and the project lists needed to address them. The token
+/// also gets `GET /api/v1/stats`, `GET /api/v1/projects` and `/metrics`.
+const STATS_API_PATHS: &[&str] = &[
+    "/detect_providers",
+    "/get_global_stats_summary",
+    "/get_project_stats_summary",
+    "/get_project_token_stats",
+    "/get_server_config",
+    "/get_session_token_stats",
+    "/scan_all_projects",
+    "/scan_projects",
+];
+
+/// `/api/*` POST routes an API token with the `read:messages` scope may call:
+/// project and session lists, messages and search. Settings, MCP config, file
+/// reads and exports stay behind `admin`. The token also gets every
+/// `GET /api/v1/*` route and `/events`.
+const MESSAGE_API_PATHS: &[&str] = &[
+    "/compare_sessions",
+    "/detect_providers",
+    "/get_provider_message_offset",
+    "/get_server_config",
+    "/get_session_comparison",
+    "/get_session_display_name",
+    "/get_session_message_count",
+    "/get_session_subagents",
+    "/load_project_sessions",
+    "/load_project_sessions_page",
+    "/load_provider_messages",
+    "/load_provider_messages_paginated",
+    "/load_provider_sessions",
+    "/load_provider_sessions_page",
+    "/load_session_messages",
+    "/load_session_messages_paginated",
+    "/scan_all_projects",
+    "/scan_projects",
+    "/search_all_providers",
+    "/search_messages",
+];
+
+/// `/api/*` POST routes an API token with the `write:metadata` scope may call on
+/// top of its other scopes: user metadata only, never sessions or settings.
+const METADATA_API_PATHS: &[&str] = &[
+    "/get_server_config",
+    "/load_user_metadata",
+    "/save_user_metadata",
+    "/update_project_metadata",
+    "/update_session_metadata",
+    "/update_user_settings",
+];
+
 /// Frontend assets embedded at compile time from the `dist/` directory.
 ///
 /// When building with `cargo build --features webui-server`, the contents of
@@ -241,10 +361,15 @@ pub fn build_router(
             post(h::get_project_stats_summary),
         )
         .route("/get_session_comparison", post(h::get_session_comparison))
+        .route("/compare_sessions", post(h::compare_sessions))
         .route(
             "/get_global_stats_summary",
             post(h::get_global_stats_summary),
         )
+        // Budget commands
+        .route("/load_budgets", post(h::load_budgets))
+        .route("/save_budgets", post(h::save_budgets))
+        .route("/get_budget_status", post(h::get_budget_status))
         // Feedback commands
         .route("/send_feedback", post(h::send_feedback))
         .route("/get_system_info", post(h::get_system_info))
@@ -327,8 +452,23 @@ pub fn build_router(
         )
         .route("/get_archive_disk_usage", post(h::get_archive_disk_usage))
         .route("/get_expiring_sessions", post(h::get_expiring_sessions))
+        .route(
+            "/load_auto_archive_config",
+            post(h::load_auto_archive_config),
+        )
+        .route(
+            "/save_auto_archive_config",
+            post(h::save_auto_archive_config),
+        )
+        .route("/get_auto_archive_log", post(h::get_auto_archive_log))
+        .route("/run_auto_archive_now", post(h::run_auto_archive_now))
         .route("/export_session", post(h::export_session))
+        .route("/export_archive", post(h::export_archive))
+        .route("/import_archive", post(h::import_archive))
+        // Versioned GET surface for scripts and dashboards
+        .nest("/v1", api_v1::router())
         // Auth middleware — checks Bearer header or ?token= query param
+        .route_layer(middleware::from_fn(role_middleware))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             read_only_middleware,
@@ -341,12 +481,30 @@ pub fn build_router(
     let api = Router::new()
         .route("/auth/login", post(auth_login_handler))
         .route("/auth/logout", post(auth_logout_handler))
+        .route("/v1/openapi.json", get(api_v1::openapi_handler))
         .merge(protected_api);
 
+    // Prometheus scrape endpoint — same auth as the API (scrapers send the
+    // token as a Bearer header).
+    let metrics_route = Router::new()
+        .route("/metrics", get(metrics::metrics_handler))
+        .route_layer(middleware::from_fn(role_middleware))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    let server_metrics = Arc::clone(&state.metrics);
     let mut app = Router::new()
         .route("/health", get(health_handler))
         .nest("/api", api)
+        .merge(metrics_route)
         .with_state(state)
+        // Per-route request counts and latency for `/metrics`.
+        .layer(middleware::from_fn_with_state(
+            server_metrics,
+            metrics::track_request_metrics,
+        ))
         // Apply security headers to all responses (API + static assets).
         .layer(middleware::from_fn(security_headers_middleware))
         .layer(cors)
@@ -563,6 +721,79 @@ fn is_read_only_allowed_path(path: &str) -> bool {
     READ_ONLY_ALLOWED_API_PATHS.contains(&api_path)
 }
 
+/// Per-account route checks for `--auth-users` accounts: viewers get the read-only
+/// allowlist, editors add [`EDITOR_ALLOWED_API_PATHS`], admins get everything, and
+/// project-limited accounts can't reach [`CROSS_PROJECT_API_PATHS`].
+async fn role_middleware(request: Request, next: Next) -> Response {
+    if let Some(principal) = request.extensions().get::<Principal>() {
+        if let Some(error) = role_denial(principal, request.method(), request.uri().path()) {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+fn role_denial(principal: &Principal, method: &Method, path: &str) -> Option<String> {
+    let api_path = path.strip_prefix("/api").unwrap_or(path);
+    if let Some(scopes) = &principal.api_scopes {
+        return (!scopes
+            .iter()
+            .any(|scope| api_scope_allows(*scope, method, api_path)))
+        .then(|| {
+            format!(
+                "API token '{}' does not have a scope for {api_path}",
+                principal.username
+            )
+        });
+    }
+    if !principal.projects.is_unrestricted() && CROSS_PROJECT_API_PATHS.contains(&api_path) {
+        return Some(format!(
+            "Account '{}' is limited to specific projects",
+            principal.username
+        ));
+    }
+    if method != Method::POST {
+        return None;
+    }
+
+    let allowed = match principal.role {
+        Role::Viewer => is_read_only_allowed_path(path),
+        Role::Editor => {
+            is_read_only_allowed_path(path) || EDITOR_ALLOWED_API_PATHS.contains(&api_path)
+        }
+        Role::Admin => true,
+    };
+    (!allowed).then(|| {
+        format!(
+            "Role '{}' is not allowed to call {api_path}",
+            principal.role.as_str()
+        )
+    })
+}
+
+/// Whether one API token scope covers a request. `api_path` has `/api` stripped.
+fn api_scope_allows(scope: ApiScope, method: &Method, api_path: &str) -> bool {
+    let get = method == Method::GET;
+    let post = method == Method::POST;
+    match scope {
+        ApiScope::Admin => true,
+        ApiScope::ReadStats => {
+            (post && STATS_API_PATHS.contains(&api_path))
+                || (get && matches!(api_path, "/v1/stats" | "/v1/projects" | "/metrics"))
+        }
+        ApiScope::ReadMessages => {
+            (post && MESSAGE_API_PATHS.contains(&api_path))
+                || (get && (api_path.starts_with("/v1/") || api_path == "/events"))
+        }
+        ApiScope::WriteMetadata => post && METADATA_API_PATHS.contains(&api_path),
+    }
+}
+
 /// Axum middleware that validates a Bearer token on every `/api/*` request.
 ///
 /// Accepts the token from either:
@@ -571,19 +802,35 @@ fn is_read_only_allowed_path(path: &str) -> bool {
 ///   - `cchv_session=<random-session-id>` `HttpOnly` cookie (account mode)
 ///   - `?token=<token>` query parameter for SSE only (legacy token mode)
 ///
-/// When auth is disabled (`--no-auth`), all requests pass through.
+/// A `Bearer cchv_...` header is first checked against the named API tokens
+/// (see [`api_tokens`]), whatever the server's auth mode.
+///
+/// When auth is disabled (`--no-auth`), all requests pass through. Account sessions
+/// and API tokens add their [`Principal`] to the request extensions for role,
+/// scope and project checks.
 async fn auth_middleware(
     State(state): State<Arc<AppState>>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<impl IntoResponse, StatusCode> {
+    if let Some(principal) = bearer_api_token(request.headers())
+        .and_then(|candidate| state.api_tokens.authenticate(candidate))
+    {
+        request.extensions_mut().insert(principal);
+        return Ok(next.run(request).await);
+    }
+
     match state.auth.authenticate(&request) {
         AuthenticatedRequest::None if matches!(state.auth, AuthState::Disabled) => {
             Ok(next.run(request).await)
         }
         AuthenticatedRequest::Token => Ok(next.run(request).await),
-        AuthenticatedRequest::Account { csrf_token } => {
+        AuthenticatedRequest::Account {
+            csrf_token,
+            principal,
+        } => {
             if csrf_valid(&request, &csrf_token) {
+                request.extensions_mut().insert(principal);
                 Ok(next.run(request).await)
             } else {
                 Err(StatusCode::FORBIDDEN)
@@ -593,31 +840,91 @@ async fn auth_middleware(
     }
 }
 
+/// The `Authorization: Bearer` value when it looks like a named API token.
+fn bearer_api_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| token.starts_with(API_TOKEN_PREFIX))
+}
+
 // ---------------------------------------------------------------------------
 // SSE endpoint
 // ---------------------------------------------------------------------------
 
-/// Server-Sent Events endpoint streaming real-time file change notifications.
+/// Server-Sent Events endpoint streaming real-time file change notifications
+/// and budget alerts.
 ///
 /// Clients connect via `EventSource` at `GET /api/events?token=<token>`.
 /// Each event has:
-///   - `event:` field = `session-file-changed` (matching Tauri event names)
-///   - `data:` field  = JSON-encoded `FileWatchEvent`
+///   - `event:` field = `session-file-changed` or `budget-alert` (matching Tauri event names)
+///   - `data:` field  = JSON-encoded `FileWatchEvent` or `BudgetAlert`
+///
+/// Accounts limited to some projects only get file changes for sessions they
+/// can see, and no budget alerts (those are cross-project totals).
 async fn sse_handler(
     State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = state.event_tx.subscribe();
-
-    let stream = BroadcastStream::new(rx).filter_map(|result| {
-        result.ok().and_then(|file_event| {
-            let data = serde_json::to_string(&file_event).ok()?;
-            Some(Ok::<_, Infallible>(
-                Event::default().event(file_event.event_type).data(data),
-            ))
-        })
+    let restricted = principal
+        .map(|Extension(principal)| principal)
+        .filter(|principal| !principal.projects.is_unrestricted());
+
+    let file_events = visible_file_events(&state, restricted.clone()).map(|file_event| {
+        let data = serde_json::to_string(&file_event).unwrap_or_default();
+        Ok::<_, Infallible>(Event::default().event(file_event.event_type).data(data))
+    });
+    let budget_alerts = visible_budget_alerts(&state, restricted.as_ref()).filter_map(|alert| {
+        let data = serde_json::to_string(&alert).ok()?;
+        Some(Ok::<_, Infallible>(
+            Event::default()
+                .event(crate::commands::budget::BUDGET_ALERT_EVENT)
+                .data(data),
+        ))
     });
 
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    Sse::new(file_events.merge(budget_alerts)).keep_alive(KeepAlive::default())
+}
+
+/// File watcher events, limited to `restricted`'s visible sessions when set.
+fn visible_file_events(
+    state: &Arc<AppState>,
+    restricted: Option<Principal>,
+) -> impl Stream<Item = FileWatchEvent> {
+    let state = Arc::clone(state);
+    BroadcastStream::new(state.event_tx.subscribe())
+        .then(move |result| {
+            let state = Arc::clone(&state);
+            let restricted = restricted.clone();
+            async move {
+                let file_event = result.ok()?;
+                match &restricted {
+                    Some(principal)
+                        if !state
+                            .project_scopes
+                            .allows_session_path(principal, &file_event.session_path)
+                            .await =>
+                    {
+                        None
+                    }
+                    _ => Some(file_event),
+                }
+            }
+        })
+        .filter_map(|file_event| file_event)
+}
+
+/// Budget alerts, or none for a project-limited `restricted` account.
+fn visible_budget_alerts(
+    state: &AppState,
+    restricted: Option<&Principal>,
+) -> impl Stream<Item = BudgetAlert> {
+    let allowed = restricted.is_none();
+    BroadcastStream::new(state.budget_tx.subscribe())
+        .filter_map(move |result| result.ok().filter(|_| allowed))
 }
 
 // ---------------------------------------------------------------------------
@@ -766,16 +1073,17 @@ mod tests {
     use super::*;
     use crate::commands::metadata::MetadataState;
     use crate::server::auth::{
-        hash_password_argon2id, AccountAuth, CSRF_COOKIE_NAME, LEGACY_AUTH_COOKIE_NAME,
-        SESSION_COOKIE_NAME,
+        hash_password_argon2id, AccountAuth, AccountUser, CSRF_COOKIE_NAME,
+        LEGACY_AUTH_COOKIE_NAME, SESSION_COOKIE_NAME,
     };
+    use crate::server::scope::ProjectScope;
     use axum::body::to_bytes;
     use axum::body::Body;
     use tower::ServiceExt;
 
     fn test_state(auth_token: Option<&str>) -> Arc<AppState> {
         let (event_tx, _rx) =
-            tokio::sync::broadcast::channel::<crate::commands::watcher::FileWatchEvent>(1);
+            tokio::sync::broadcast::channel::<crate::commands::watcher::FileWatchEvent>(8);
         Arc::new(AppState {
             metadata: Arc::new(MetadataState::default()),
             start_time: std::time::Instant::now(),
@@ -787,6 +1095,10 @@ mod tests {
                 .unwrap_or(AuthState::Disabled),
             read_only: false,
             event_tx,
+            budget_tx: tokio::sync::broadcast::channel(8).0,
+            metrics: Arc::default(),
+            project_scopes: Arc::default(),
+            api_tokens: Arc::default(),
         })
     }
 
@@ -804,6 +1116,10 @@ mod tests {
             ))),
             read_only: false,
             event_tx,
+            budget_tx: tokio::sync::broadcast::channel(1).0,
+            metrics: Arc::default(),
+            project_scopes: Arc::default(),
+            api_tokens: Arc::default(),
         })
     }
 
@@ -816,6 +1132,10 @@ mod tests {
             auth: AuthState::Disabled,
             read_only: true,
             event_tx,
+            budget_tx: tokio::sync::broadcast::channel(1).0,
+            metrics: Arc::default(),
+            project_scopes: Arc::default(),
+            api_tokens: Arc::default(),
         })
     }
 
@@ -920,6 +1240,70 @@ mod tests {
         assert!(cookie.contains("Max-Age=604800"));
     }
 
+    #[tokio::test]
+    async fn test_metrics_requires_auth_and_is_tracked() {
+        let state = test_state(Some("secret-token"));
+        let app = build_router(Arc::clone(&state), "127.0.0.1", 3727, None, "/");
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(state.metrics.request_count("GET", "/metrics", 401), 1);
+    }
+
+    #[tokio::test]
+    async fn test_api_v1_openapi_is_public_and_resources_need_auth() {
+        let app = build_router(
+            test_state(Some("secret-token")),
+            "127.0.0.1",
+            3727,
+            None,
+            "/",
+        );
+        let get_status = |uri: &'static str, token: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::builder().method(Method::GET).uri(uri);
+                if let Some(token) = token {
+                    request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+                }
+                app.oneshot(request.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        assert_eq!(
+            get_status("/api/v1/openapi.json", None).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            get_status("/api/v1/projects", None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get_status("/api/v1/sessions", Some("secret-token")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            get_status("/api/v1/search?q=x&limit=abc", Some("secret-token")).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            get_status("/api/v1/search?q=%28&regex=true", Some("secret-token")).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
     #[tokio::test]
     async fn test_auth_cookie_allows_protected_api() {
         let app = build_router(
@@ -1037,6 +1421,329 @@ mod tests {
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
     }
 
+    fn test_team_state() -> Arc<AppState> {
+        let (event_tx, _rx) =
+            tokio::sync::broadcast::channel::<crate::commands::watcher::FileWatchEvent>(1);
+        let password_hash = hash_password_argon2id("secret-password").unwrap();
+        let users = ["viewer", "editor", "admin"]
+            .into_iter()
+            .zip([Role::Viewer, Role::Editor, Role::Admin])
+            .map(|(name, role)| {
+                AccountUser::new(
+                    name.to_string(),
+                    password_hash.clone(),
+                    role,
+                    ProjectScope::all(),
+                )
+            })
+            .collect();
+        Arc::new(AppState {
+            metadata: Arc::new(MetadataState::default()),
+            start_time: std::time::Instant::now(),
+            auth: AuthState::Account(Arc::new(AccountAuth::with_users(users, false))),
+            read_only: false,
+            event_tx,
+            budget_tx: tokio::sync::broadcast::channel(1).0,
+            metrics: Arc::default(),
+            project_scopes: Arc::default(),
+            api_tokens: Arc::default(),
+        })
+    }
+
+    /// Log in as `username` and return `(cookie header, csrf token)`.
+    async fn login_as(app: &Router, username: &str) -> (String, String) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/auth/login")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(format!(
+                        r#"{{"username":"{username}","password":"secret-password"}}"#
+                    )))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let cookies = cookie_header_from_response(&response);
+        let csrf = cookies
+            .split("; ")
+            .find_map(|c| c.strip_prefix(&format!("{CSRF_COOKIE_NAME}=")))
+            .unwrap()
+            .to_string();
+        (cookies, csrf)
+    }
+
+    #[tokio::test]
+    async fn test_account_roles_gate_admin_routes() {
+        let app = build_router(test_team_state(), "127.0.0.1", 3727, None, "/");
+        for (username, expected) in [
+            ("viewer", StatusCode::FORBIDDEN),
+            ("editor", StatusCode::FORBIDDEN),
+            ("admin", StatusCode::INTERNAL_SERVER_ERROR),
+        ] {
+            let (cookies, csrf) = login_as(&app, username).await;
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/api/delete_session")
+                        .header(header::COOKIE, cookies)
+                        .header("x-csrf-token", csrf)
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(r#"{"filePath":"/nonexistent/session.jsonl"}"#))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            // Admin passes the role check and fails later on the bogus path.
+            assert_eq!(response.status(), expected, "{username}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_config_reports_account_role() {
+        let app = build_router(test_team_state(), "127.0.0.1", 3727, None, "/");
+        let (cookies, csrf) = login_as(&app, "viewer").await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/get_server_config")
+                    .header(header::COOKIE, cookies)
+                    .header("x-csrf-token", csrf)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["role"], "viewer");
+        assert_eq!(json["readOnly"], true);
+    }
+
+    fn principal(role: Role, projects: ProjectScope) -> Principal {
+        Principal {
+            username: "someone".to_string(),
+            role,
+            projects,
+            api_scopes: None,
+        }
+    }
+
+    #[test]
+    fn test_role_denial_by_role_and_project_scope() {
+        let viewer = principal(Role::Viewer, ProjectScope::all());
+        let editor = principal(Role::Editor, ProjectScope::all());
+        let admin = principal(Role::Admin, ProjectScope::all());
+        for path in [
+            "/api/delete_session",
+            "/api/restore_file",
+            "/api/save_settings",
+        ] {
+            assert!(role_denial(&viewer, &Method::POST, path).is_some());
+            assert!(role_denial(&editor, &Method::POST, path).is_some());
+            assert!(role_denial(&admin, &Method::POST, path).is_none());
+        }
+        assert!(role_denial(&viewer, &Method::POST, "/api/update_session_metadata").is_some());
+        assert!(role_denial(&editor, &Method::POST, "/api/update_session_metadata").is_none());
+        assert!(role_denial(&viewer, &Method::POST, "/api/load_session_messages").is_none());
+
+        let limited = principal(
+            Role::Admin,
+            ProjectScope::from_patterns(vec!["/work/team-a/*".to_string()]).unwrap(),
+        );
+        assert!(role_denial(&limited, &Method::POST, "/api/search_messages").is_some());
+        assert!(role_denial(&limited, &Method::POST, "/api/get_global_stats_summary").is_some());
+        assert!(role_denial(&limited, &Method::POST, "/api/scan_all_projects").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_events_are_limited_to_visible_projects() {
+        use crate::models::{BudgetMetric, BudgetPeriod, BudgetScope};
+        use std::time::Duration;
+
+        let state = test_state(None);
+        let limited = principal(
+            Role::Viewer,
+            ProjectScope::from_patterns(vec!["/work/a".to_string()]).unwrap(),
+        );
+        state
+            .project_scopes
+            .seed(&limited.username, &["/store/-work-a"]);
+        let mut events = Box::pin(visible_file_events(&state, Some(limited.clone())));
+        let mut limited_alerts = Box::pin(visible_budget_alerts(&state, Some(&limited)));
+        let mut alerts = Box::pin(visible_budget_alerts(&state, None));
+
+        for project in ["/store/-work-b", "/store/-work-a"] {
+            state
+                .event_tx
+                .send(FileWatchEvent {
+                    project_path: project.to_string(),
+                    session_path: format!("{project}/s1.jsonl"),
+                    event_type: "session-file-changed".to_string(),
+                })
+                .unwrap();
+        }
+        let event = events.next().await.unwrap();
+        assert_eq!(event.session_path, "/store/-work-a/s1.jsonl");
+
+        state
+            .budget_tx
+            .send(BudgetAlert {
+                budget_id: "daily".to_string(),
+                budget_name: None,
+                scope: BudgetScope::Global,
+                target: None,
+                period: BudgetPeriod::Day,
+                metric: BudgetMetric::Usd,
+                threshold: 0.8,
+                used: 8.0,
+                limit: 10.0,
+                projected: 12.0,
+                period_start: String::new(),
+                period_end: String::new(),
+            })
+            .unwrap();
+        assert_eq!(alerts.next().await.unwrap().budget_id, "daily");
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), limited_alerts.next())
+                .await
+                .is_err(),
+            "project-limited accounts get no budget alerts"
+        );
+    }
+
+    #[test]
+    fn test_role_denial_by_api_token_scope() {
+        let token = |scopes: Vec<ApiScope>| Principal {
+            username: "token:ci".to_string(),
+            role: Role::Admin,
+            projects: ProjectScope::all(),
+            api_scopes: Some(scopes),
+        };
+        let stats = token(vec![ApiScope::ReadStats]);
+        assert!(role_denial(&stats, &Method::POST, "/api/get_global_stats_summary").is_none());
+        assert!(role_denial(&stats, &Method::GET, "/v1/stats").is_none());
+        assert!(role_denial(&stats, &Method::GET, "/metrics").is_none());
+        assert!(role_denial(&stats, &Method::POST, "/api/load_session_messages").is_some());
+        assert!(role_denial(&stats, &Method::GET, "/v1/messages").is_some());
+
+        let messages = token(vec![ApiScope::ReadMessages]);
+        assert!(role_denial(&messages, &Method::POST, "/api/load_session_messages").is_none());
+        assert!(role_denial(&messages, &Method::GET, "/v1/messages").is_none());
+        assert!(role_denial(&messages, &Method::POST, "/api/search_all_providers").is_none());
+        assert!(role_denial(&messages, &Method::POST, "/api/update_session_metadata").is_some());
+        for path in [
+            "/api/read_text_file",
+            "/api/export_session",
+            "/api/get_all_settings",
+            "/api/get_settings_by_scope",
+            "/api/get_claude_json_config",
+            "/api/get_mcp_servers",
+            "/api/get_all_mcp_servers",
+        ] {
+            assert!(
+                role_denial(&messages, &Method::POST, path).is_some(),
+                "read:messages must not reach {path}"
+            );
+        }
+
+        let writer = token(vec![ApiScope::ReadStats, ApiScope::WriteMetadata]);
+        assert!(role_denial(&writer, &Method::POST, "/api/update_session_metadata").is_none());
+        assert!(role_denial(&writer, &Method::POST, "/api/get_project_token_stats").is_none());
+        assert!(role_denial(&writer, &Method::POST, "/api/delete_session").is_some());
+
+        let admin = token(vec![ApiScope::Admin]);
+        assert!(role_denial(&admin, &Method::POST, "/api/delete_session").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_api_token_scopes_gate_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api-tokens.json");
+        let (_, token) =
+            api_tokens::create_token(&path, "dashboard", vec![ApiScope::ReadStats], None).unwrap();
+        let (event_tx, _rx) =
+            tokio::sync::broadcast::channel::<crate::commands::watcher::FileWatchEvent>(1);
+        let state = Arc::new(AppState {
+            metadata: Arc::new(MetadataState::default()),
+            start_time: std::time::Instant::now(),
+            auth: AuthState::Token {
+                token: "static-token".to_string(),
+                secure_cookies: false,
+            },
+            read_only: false,
+            event_tx,
+            budget_tx: tokio::sync::broadcast::channel(1).0,
+            metrics: Arc::default(),
+            project_scopes: Arc::default(),
+            api_tokens: Arc::new(api_tokens::ApiTokenStore::new(Some(path.clone()))),
+        });
+        let app = build_router(state, "127.0.0.1", 3727, None, "/");
+        let send = |method: Method, uri: &str, bearer: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, format!("Bearer {bearer}"))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from("{}"))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let metrics = send(Method::GET, "/metrics", &token).await.unwrap();
+        assert_eq!(metrics.status(), StatusCode::OK);
+        let messages = send(Method::POST, "/api/load_session_messages", &token)
+            .await
+            .unwrap();
+        assert_eq!(messages.status(), StatusCode::FORBIDDEN);
+
+        let (_, reader) =
+            api_tokens::create_token(&path, "reader", vec![ApiScope::ReadMessages], None).unwrap();
+        for uri in [
+            "/api/read_text_file",
+            "/api/get_all_settings",
+            "/api/get_mcp_servers",
+        ] {
+            let response = send(Method::POST, uri, &reader).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{uri}");
+        }
+
+        api_tokens::revoke_token(&path, "dashboard").unwrap();
+        let revoked = send(Method::GET, "/metrics", &token).await.unwrap();
+        assert_eq!(revoked.status(), StatusCode::UNAUTHORIZED);
+        // The static `--token` keeps working alongside named tokens.
+        let legacy = send(Method::GET, "/metrics", "static-token").await.unwrap();
+        assert_eq!(legacy.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_role_lists_name_registered_routes() {
+        let mutating: std::collections::HashSet<&str> =
+            READ_ONLY_MUTATING_API_PATHS.iter().copied().collect();
+        for path in EDITOR_ALLOWED_API_PATHS {
+            assert!(mutating.contains(path), "{path} is not a mutating route");
+        }
+        for path in CROSS_PROJECT_API_PATHS
+            .iter()
+            .chain(STATS_API_PATHS)
+            .chain(MESSAGE_API_PATHS)
+            .chain(METADATA_API_PATHS)
+        {
+            assert!(
+                mutating.contains(path) || READ_ONLY_ALLOWED_API_PATHS.contains(path),
+                "{path} is not a registered route"
+            );
+        }
+    }
+
     #[test]
     fn test_read_only_blocks_mutating_api_paths() {
         for path in [
@@ -1061,6 +1768,7 @@ mod tests {
             "/api/search_messages",
             "/api/get_all_settings",
             "/api/export_session",
+            "/api/compare_sessions",
         ] {
             assert!(
                 is_read_only_allowed_path(path),
@@ -0,0 +1,366 @@
+//! Per-account project visibility for the `WebUI` server.
+//!
+//! Accounts loaded from an `--auth-users` file can be limited to a set of
+//! project globs. Listings are filtered with [`ProjectScope::allows_project`];
+//! requests that name a project or session path go through the [`Scoped`]
+//! extractor, which resolves the path against the projects the account can see.
+
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::auth::Principal;
+use super::state::AppState;
+use crate::commands::multi_provider;
+use crate::models::{ClaudeProject, UserMetadata};
+
+/// How long a resolved project/session index is reused before rescanning.
+const INDEX_TTL: Duration = Duration::from_secs(30);
+
+/// Project globs an account may see. `*` matches any run of characters
+/// (including `/`), `?` matches one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectScope {
+    patterns: Vec<String>,
+}
+
+impl ProjectScope {
+    /// Scope that sees every project.
+    pub fn all() -> Self {
+        Self {
+            patterns: vec!["*".to_string()],
+        }
+    }
+
+    /// Build a scope from config globs, expanding a leading `~/` to the home directory.
+    pub fn from_patterns(patterns: Vec<String>) -> Result<Self, String> {
+        let home = dirs::home_dir();
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let pattern = pattern.trim().to_string();
+                if pattern.is_empty() {
+                    return Err("project globs must not be empty".to_string());
+                }
+                match (pattern.strip_prefix("~/"), &home) {
+                    (Some(rest), Some(home)) => Ok(home.join(rest).to_string_lossy().into_owned()),
+                    _ => Ok(pattern),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.patterns.iter().any(|pattern| pattern == "*")
+    }
+
+    /// Whether a glob matches the project's working directory, storage path or name.
+    pub fn allows_project(&self, project: &ClaudeProject) -> bool {
+        self.is_unrestricted()
+            || [&project.actual_path, &project.path, &project.name]
+                .iter()
+                .any(|candidate| self.matches(candidate))
+    }
+
+    fn matches(&self, candidate: &str) -> bool {
+        let candidate = candidate.trim_end_matches(['/', '\\']);
+        self.patterns
+            .iter()
+            .any(|pattern| UserMetadata::matches_glob_pattern(candidate, pattern))
+    }
+}
+
+/// A project or session reference carried by a request body.
+pub enum ScopedPath<'a> {
+    /// A project storage path (`ClaudeProject::path`) or working directory.
+    Project(&'a str),
+    /// A session file path or provider session URI.
+    Session(&'a str),
+}
+
+/// Request parameters that name projects or sessions the caller must be allowed to see.
+pub trait ScopedParams {
+    fn scoped_paths(&self) -> Vec<ScopedPath<'_>>;
+}
+
+/// JSON body extractor that rejects paths outside the caller's project scope
+/// with `403`. Token auth, `--no-auth` and unrestricted accounts pass through.
+pub struct Scoped<T>(pub T);
+
+impl<T> FromRequest<Arc<AppState>> for Scoped<T>
+where
+    T: DeserializeOwned + ScopedParams + Send,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let principal = req.extensions().get::<Principal>().cloned();
+        let Json(params) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        if let Some(principal) = principal {
+            state
+                .project_scopes
+                .authorize(&principal, &params.scoped_paths())
+                .await
+                .map_err(IntoResponse::into_response)?;
+        }
+        Ok(Self(params))
+    }
+}
+
+/// `403 { "error": "..." }` for out-of-scope project or session access.
+pub struct ScopeDenied(String);
+
+impl IntoResponse for ScopeDenied {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": self.0 })),
+        )
+            .into_response()
+    }
+}
+
+/// Cached view of which projects and sessions each restricted account can see.
+#[derive(Default)]
+pub struct ProjectScopeIndex {
+    entries: Mutex<HashMap<String, Arc<VisibleProjects>>>,
+}
+
+struct VisibleProjects {
+    built_at: Instant,
+    /// Storage paths and working directories of visible projects.
+    projects: HashSet<String>,
+    /// Storage directories whose files all belong to one visible project.
+    session_roots: Vec<String>,
+    /// Session paths of visible projects that don't live under a storage directory.
+    sessions: HashSet<String>,
+}
+
+impl ProjectScopeIndex {
+    /// Check every path against the principal's scope.
+    pub async fn authorize(
+        &self,
+        principal: &Principal,
+        paths: &[ScopedPath<'_>],
+    ) -> Result<(), ScopeDenied> {
+        if paths.is_empty() || principal.projects.is_unrestricted() {
+            return Ok(());
+        }
+        let visible = self.visible(principal).await;
+        for path in paths {
+            let allowed = match path {
+                ScopedPath::Project(project) => visible.allows_project_path(project),
+                ScopedPath::Session(session) => visible.allows_session_path(session),
+            };
+            if !allowed {
+                return Err(ScopeDenied(format!(
+                    "Account '{}' does not have access to this project",
+                    principal.username
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the principal can see `project_path`, for handlers outside the extractor.
+    pub async fn allows_project_path(&self, principal: &Principal, project_path: &str) -> bool {
+        principal.projects.is_unrestricted()
+            || self
+                .visible(principal)
+                .await
+                .allows_project_path(project_path)
+    }
+
+    /// Whether the principal can see `session_path`, for handlers outside the extractor.
+    pub async fn allows_session_path(&self, principal: &Principal, session_path: &str) -> bool {
+        principal.projects.is_unrestricted()
+            || self
+                .visible(principal)
+                .await
+                .allows_session_path(session_path)
+    }
+
+    /// Pre-resolve an account's visible Claude storage directories, skipping the scan.
+    #[cfg(test)]
+    pub(crate) fn seed(&self, username: &str, session_roots: &[&str]) {
+        let roots: Vec<String> = session_roots.iter().map(|r| (*r).to_string()).collect();
+        let entry = VisibleProjects {
+            built_at: Instant::now(),
+            projects: roots.iter().cloned().collect(),
+            session_roots: roots,
+            sessions: HashSet::new(),
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(username.to_string(), Arc::new(entry));
+    }
+
+    async fn visible(&self, principal: &Principal) -> Arc<VisibleProjects> {
+        let cached = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&principal.username)
+            .filter(|entry| entry.built_at.elapsed() < INDEX_TTL)
+            .cloned();
+        if let Some(entry) = cached {
+            return entry;
+        }
+
+        let entry = Arc::new(VisibleProjects::build(&principal.projects).await);
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(principal.username.clone(), Arc::clone(&entry));
+        entry
+    }
+}
+
+impl VisibleProjects {
+    async fn build(scope: &ProjectScope) -> Self {
+        let projects = multi_provider::scan_all_projects(None, None, None, None, None)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Project scope scan failed: {e}");
+                Vec::new()
+            });
+
+        let mut visible = Self {
+            built_at: Instant::now(),
+            projects: HashSet::new(),
+            session_roots: Vec::new(),
+            sessions: HashSet::new(),
+        };
+        for project in projects.into_iter().filter(|p| scope.allows_project(p)) {
+            visible
+                .projects
+                .insert(trim_path(&project.path).to_string());
+            visible
+                .projects
+                .insert(trim_path(&project.actual_path).to_string());
+
+            let provider = project
+                .provider
+                .clone()
+                .unwrap_or_else(|| "claude".to_string());
+            if provider == "claude" {
+                visible
+                    .session_roots
+                    .push(trim_path(&project.path).to_string());
+                continue;
+            }
+            match multi_provider::load_provider_sessions(provider, project.path.clone(), None).await
+            {
+                Ok(sessions) => visible
+                    .sessions
+                    .extend(sessions.into_iter().map(|s| s.file_path)),
+                Err(e) => log::warn!(
+                    "Project scope session scan failed for {}: {e}",
+                    project.path
+                ),
+            }
+        }
+        visible
+    }
+
+    fn allows_project_path(&self, project_path: &str) -> bool {
+        self.projects.contains(trim_path(project_path))
+    }
+
+    fn allows_session_path(&self, session_path: &str) -> bool {
+        if self.sessions.contains(session_path) {
+            return true;
+        }
+        // Claude session files (and their subagent transcripts) live under the
+        // project's storage directory. Reject `..` so a prefix can't be escaped.
+        if session_path.split(['/', '\\']).any(|part| part == "..") {
+            return false;
+        }
+        self.session_roots.iter().any(|root| {
+            session_path
+                .strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.starts_with(['/', '\\']))
+        })
+    }
+}
+
+fn trim_path(path: &str) -> &str {
+    path.trim_end_matches(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(actual_path: &str, path: &str) -> ClaudeProject {
+        ClaudeProject {
+            name: actual_path
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            path: path.to_string(),
+            actual_path: actual_path.to_string(),
+            session_count: 0,
+            message_count: 0,
+            last_modified: String::new(),
+            git_info: None,
+            provider: Some("claude".to_string()),
+            storage_type: None,
+            custom_directory_label: None,
+        }
+    }
+
+    fn visible(roots: &[&str]) -> VisibleProjects {
+        VisibleProjects {
+            built_at: Instant::now(),
+            projects: roots.iter().map(|r| (*r).to_string()).collect(),
+            session_roots: roots.iter().map(|r| (*r).to_string()).collect(),
+            sessions: HashSet::from(["codex-session.jsonl".to_string()]),
+        }
+    }
+
+    #[test]
+    fn scope_matches_actual_path_storage_path_or_name() {
+        let scope = ProjectScope::from_patterns(vec!["/work/team-a/*".to_string()]).unwrap();
+        assert!(scope.allows_project(&project("/work/team-a/api", "/s/-work-team-a-api")));
+        assert!(!scope.allows_project(&project("/work/team-b/api", "/s/-work-team-b-api")));
+
+        let by_name = ProjectScope::from_patterns(vec!["api".to_string()]).unwrap();
+        assert!(by_name.allows_project(&project("/work/team-b/api", "/s/x")));
+    }
+
+    #[test]
+    fn star_is_unrestricted_and_empty_globs_are_rejected() {
+        assert!(ProjectScope::all().is_unrestricted());
+        assert!(!ProjectScope::from_patterns(vec!["/work/*".to_string()])
+            .unwrap()
+            .is_unrestricted());
+        assert!(ProjectScope::from_patterns(vec!["  ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn session_paths_resolve_under_visible_storage_roots() {
+        let visible = visible(&["/home/u/.claude/projects/-work-a"]);
+        assert!(visible.allows_session_path("/home/u/.claude/projects/-work-a/s1.jsonl"));
+        assert!(visible
+            .allows_session_path("/home/u/.claude/projects/-work-a/s1/subagents/agent-1.jsonl"));
+        assert!(visible.allows_session_path("codex-session.jsonl"));
+        // Sibling directory sharing the prefix, and `..` escapes, stay hidden.
+        assert!(!visible.allows_session_path("/home/u/.claude/projects/-work-ab/s1.jsonl"));
+        assert!(
+            !visible.allows_session_path("/home/u/.claude/projects/-work-a/../-work-b/s1.jsonl")
+        );
+        assert!(visible.allows_project_path("/home/u/.claude/projects/-work-a/"));
+        assert!(!visible.allows_project_path("/home/u/.claude/projects/-work-b"));
+    }
+}
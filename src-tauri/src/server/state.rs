@@ -5,7 +5,11 @@
 
 use crate::commands::metadata::MetadataState;
 use crate::commands::watcher::FileWatchEvent;
+use crate::models::BudgetAlert;
+use crate::server::api_tokens::ApiTokenStore;
 use crate::server::auth::AuthState;
+use crate::server::metrics::ServerMetrics;
+use crate::server::scope::ProjectScopeIndex;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::broadcast;
@@ -23,4 +27,12 @@ pub struct AppState {
     pub read_only: bool,
     /// Broadcast channel for file-change events (SSE consumers subscribe here).
     pub event_tx: broadcast::Sender<FileWatchEvent>,
+    /// Broadcast channel for budget threshold alerts, merged into the SSE stream.
+    pub budget_tx: broadcast::Sender<BudgetAlert>,
+    /// Request counters and latencies reported at `/metrics`.
+    pub metrics: Arc<ServerMetrics>,
+    /// Projects and sessions visible to each project-limited account.
+    pub project_scopes: Arc<ProjectScopeIndex>,
+    /// Named API tokens from `api-tokens.json`, reloaded when the file changes.
+    pub api_tokens: Arc<ApiTokenStore>,
 }
@@ -4,6 +4,7 @@ use memchr::memchr_iter;
 use serde_json::Value;
 use std::fs;
 use std::path::{Component, Path};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Estimated average bytes per JSONL line (used for capacity pre-allocation)
 /// Based on typical Claude message sizes (800-1200 bytes average)
@@ -460,6 +461,56 @@ pub fn is_symlink(path: &std::path::Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Initial state of [`fnv1a_extend`].
+pub(crate) const FNV1A_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Feed `bytes` into a 64-bit FNV-1a hash. Streaming: hashing `a` and then
+/// `b` equals hashing `a ++ b`. Stable across Rust versions and platforms, so
+/// the result may be persisted; not collision resistant.
+pub(crate) fn fnv1a_extend(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// 64-bit FNV-1a of `bytes`.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(FNV1A_OFFSET, bytes)
+}
+
+/// Open (or create) a rebuildable `SQLite` cache database at `path`.
+///
+/// WAL + NORMAL sync: the caches are rebuilt from the session files, so
+/// durability of the last few writes is not worth an fsync per write. When
+/// the database's `user_version` differs from `schema_version`, `schema` runs
+/// (it must drop and recreate every table) and the version is stamped; the
+/// caches reset instead of migrating.
+pub(crate) fn open_cache_db(
+    path: &Path,
+    schema_version: i64,
+    schema: &str,
+) -> Result<rusqlite::Connection, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create cache folder: {e}"))?;
+    }
+    let conn =
+        rusqlite::Connection::open(path).map_err(|e| format!("Failed to open database: {e}"))?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+        .map_err(|e| format!("Failed to configure database: {e}"))?;
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema version: {e}"))?;
+    if version != schema_version {
+        conn.execute_batch(&format!(
+            "{schema}\nPRAGMA user_version = {schema_version};"
+        ))
+        .map_err(|e| format!("Failed to initialize schema: {e}"))?;
+    }
+    Ok(conn)
+}
+
 /// Convert milliseconds timestamp to ISO 8601 string
 pub fn ms_to_iso(ms: u64) -> String {
     chrono::DateTime::from_timestamp_millis(i64::try_from(ms).unwrap_or(0))
@@ -558,6 +609,7 @@ pub fn build_provider_message(
         compact_metadata: None,
         microcompact_metadata: None,
         provider: Some(provider.to_string()),
+        ..Default::default()
     }
 }
 
@@ -663,6 +715,39 @@ fn collect_workflow_agent_files(workflows_dir: &Path, files: &mut Vec<std::path:
     }
 }
 
+/// Lifetime hit/miss counters for a process-global cache, reported by the
+/// `WebUI` server's `/metrics` endpoint.
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounters {
+    pub const fn new() -> Self {
+        Self {
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_hits(&self, count: u64) {
+        self.hits.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn record_misses(&self, count: u64) {
+        self.misses.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// `(hits, misses)` recorded so far.
+    pub fn snapshot(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,6 +4,8 @@
  * Helper functions for analytics calculations and formatting.
  */
 
+import modelPricingTable from "@/config/modelPricing.json";
+
 /**
  * Calculate growth rate between two values
  */
@@ -53,168 +55,56 @@ export interface ModelPriceOptions {
   reasoningTokens?: number;
 }
 
-type ModelPricingTierOptions = Omit<
-  ModelPricing,
-  "input" | "output" | "cacheRead" | "cacheWrite"
-> & { cacheWrite?: number | null };
-
-const tier = (
-  input: number,
-  output: number,
-  cacheRead: number,
-  options: ModelPricingTierOptions = {},
-): ModelPricing => ({ input, output, cacheRead, cacheWrite: null, ...options });
-
-const MODEL_PRICING: Record<string, ModelPricing> = {
-  // Anthropic. Current standard prices; cacheWrite is the 5-minute rate.
-  "claude-fable-5": tier(10, 50, 1, { cacheWrite: 12.5, cacheWriteOneHour: 20 }),
-  "claude-mythos-5": tier(10, 50, 1, { cacheWrite: 12.5, cacheWriteOneHour: 20 }),
-  "claude-mythos-preview": tier(10, 50, 1, { cacheWrite: 12.5, cacheWriteOneHour: 20 }),
-  "claude-opus-5": tier(5, 25, 0.5, { cacheWrite: 6.25, cacheWriteOneHour: 10 }),
-  "claude-opus-4-8": tier(5, 25, 0.5, { cacheWrite: 6.25, cacheWriteOneHour: 10 }),
-  "claude-opus-4-7": tier(5, 25, 0.5, { cacheWrite: 6.25, cacheWriteOneHour: 10 }),
-  "claude-opus-4-6": tier(5, 25, 0.5, { cacheWrite: 6.25, cacheWriteOneHour: 10 }),
-  "claude-opus-4-5": tier(5, 25, 0.5, { cacheWrite: 6.25, cacheWriteOneHour: 10 }),
-  "claude-opus-4-1": tier(15, 75, 1.5, { cacheWrite: 18.75, cacheWriteOneHour: 30 }),
-  "claude-opus-4": tier(15, 75, 1.5, { cacheWrite: 18.75, cacheWriteOneHour: 30 }),
-  "claude-sonnet-5": tier(2, 10, 0.2, { cacheWrite: 2.5, cacheWriteOneHour: 4 }),
-  "claude-sonnet-4-6": tier(3, 15, 0.3, { cacheWrite: 3.75, cacheWriteOneHour: 6 }),
-  "claude-sonnet-4-5": tier(3, 15, 0.3, { cacheWrite: 3.75, cacheWriteOneHour: 6 }),
-  "claude-sonnet-4": tier(3, 15, 0.3, { cacheWrite: 3.75, cacheWriteOneHour: 6 }),
-  "claude-haiku-4-5": tier(1, 5, 0.1, { cacheWrite: 1.25, cacheWriteOneHour: 2 }),
-  "claude-3-5-sonnet": tier(3, 15, 0.3, { cacheWrite: 3.75, cacheWriteOneHour: 6 }),
-  "claude-3-5-haiku": tier(0.8, 4, 0.08, { cacheWrite: 1, cacheWriteOneHour: 1.6 }),
-  "claude-haiku-3": tier(0.25, 1.25, 0.03, { cacheWrite: 0.3, cacheWriteOneHour: 0.5 }),
-  "claude-3-haiku": tier(0.25, 1.25, 0.03, { cacheWrite: 0.3, cacheWriteOneHour: 0.5 }),
-
-  // MiniMax. M3 has a second price tier above 512K context.
-  "minimax-m3": tier(0.6, 2.4, 0.12, {
-    cacheWrite: null,
-    contextTiers: [tier(1.2, 4.8, 0.24, { minContextTokens: 512_001, cacheWrite: null })],
-  }),
-  "minimax-m2.7": tier(0.3, 1.2, 0.06, { cacheWrite: 0.375 }),
-  "minimax-m2.5": tier(0.3, 1.2, 0.06, { cacheWrite: 0.375 }),
-  "minimax-m2.1": tier(0.3, 1.2, 0.06, { cacheWrite: 0.375 }),
-
-  // OpenAI. Cached input is a separate input rate. Cache writes are only
-  // charged for the GPT-5.6 family; older GPT-5 generations do not publish a
-  // separate cache-write charge. Long-context GPT-5.4/5.5/5.6 rates apply to
-  // the whole request after the 272K prompt threshold is crossed.
-  "gpt-5.6": tier(5, 30, 0.5, {
-    cacheWrite: 6.25,
-    contextTiers: [tier(10, 45, 1, { minContextTokens: 272_001, cacheWrite: 12.5 })],
-  }),
-  "gpt-5.6-sol": tier(5, 30, 0.5, {
-    cacheWrite: 6.25,
-    contextTiers: [tier(10, 45, 1, { minContextTokens: 272_001, cacheWrite: 12.5 })],
-  }),
-  "gpt-5.6-terra": tier(2, 12, 0.2, {
-    cacheWrite: 2.5,
-    contextTiers: [tier(4, 18, 0.4, { minContextTokens: 272_001, cacheWrite: 5 })],
-  }),
-  "gpt-5.6-luna": tier(0.2, 1.2, 0.02, {
-    cacheWrite: 0.25,
-    contextTiers: [tier(0.4, 1.8, 0.04, { minContextTokens: 272_001, cacheWrite: 0.5 })],
-  }),
-  "gpt-5.6-cyber": tier(12.5, 75, 1.25, { cacheWrite: 15.625 }),
-  "daybreak-blue-latest": tier(5, 30, 0.5, { cacheWrite: 6.25 }),
-  "daybreak-red-latest": tier(12.5, 75, 1.25, { cacheWrite: 15.625 }),
-  "gpt-5.5": tier(5, 30, 0.5, {
-    contextTiers: [tier(10, 45, 1, { minContextTokens: 272_001 })],
-  }),
-  "gpt-5.4": tier(2.5, 15, 0.25, {
-    contextTiers: [tier(5, 22.5, 0.5, { minContextTokens: 272_001 })],
-  }),
-  "gpt-5.4-mini": tier(0.75, 4.5, 0.075),
-  "gpt-5.4-nano": tier(0.2, 1.25, 0.02),
-  "gpt-5.2": tier(1.75, 14, 0.175),
-  "chat-latest": tier(5, 30, 0.5),
-  "gpt-5.3-chat-latest": tier(1.75, 14, 0.175),
-  "gpt-5.3-codex-fast": tier(3.5, 28, 0.35),
-  "gpt-5.3-codex": tier(1.75, 14, 0.175, {
-    serviceTiers: {
-      fast: tier(3.5, 28, 0.35),
-    },
-  }),
-  "gpt-5.2-codex": tier(1.75, 14, 0.175),
-  "gpt-5.1": tier(1.25, 10, 0.125),
-  "gpt-5.1-chat-latest": tier(1.25, 10, 0.125),
-  "gpt-5.1-codex": tier(1.25, 10, 0.125),
-  "gpt-5.1-codex-max": tier(1.25, 10, 0.125),
-  "gpt-5": tier(1.25, 10, 0.125),
-  "gpt-5-chat-latest": tier(1.25, 10, 0.125),
-  "gpt-5-codex": tier(1.25, 10, 0.125),
-  "gpt-5-mini": tier(0.25, 2, 0.025),
-  "gpt-5-nano": tier(0.05, 0.4, 0.005),
-  "gpt-4.1-mini": tier(0.4, 1.6, 0.1, { cacheWrite: null }),
-  "gpt-4.1-nano": tier(0.1, 0.4, 0.025, { cacheWrite: null }),
-  "gpt-4.1": tier(2, 8, 0.5, { cacheWrite: null }),
-  "gpt-4o-mini": tier(0.15, 0.6, 0.075, { cacheWrite: null }),
-  "gpt-4o": tier(2.5, 10, 1.25, { cacheWrite: null }),
-  "gpt-4": tier(30, 60, 0, { cacheWrite: null }),
-  "o4-mini": tier(1.1, 4.4, 0.275, { cacheWrite: null }),
-  "codex-mini": tier(1.5, 6, 0.375, { cacheWrite: null }),
+/**
+ * Entry shape of the shared pricing table, `src/config/modelPricing.json`.
+ * The backend embeds the same file and accepts the same shape in the user's
+ * `~/.claude-history-viewer/pricing.json`.
+ *
+ * Notes on the built-in prices:
+ * - Anthropic entries are current standard prices with 5-minute and 1-hour
+ *   cache write rates.
+ * - OpenAI bills cached input as a separate input rate; cache writes are only
+ *   charged for the GPT-5.6 family. Long-context GPT-5.4/5.5/5.6 rates apply
+ *   to the whole request once the 272K prompt threshold is crossed.
+ * - Gemini output rates include thinking tokens where the API does so; the
+ *   promotional paid-tier rates are valid through 2026-12-31.
+ * - MiniMax M3 and xAI long-context tiers start above 512K and 200K prompt
+ *   tokens respectively.
+ */
+interface PricingTableEntry {
+  input: number;
+  output: number;
+  cacheRead: number;
+  cacheWrite5m?: number;
+  cacheWrite1h?: number;
+  minContextTokens?: number;
+  contextTiers?: PricingTableEntry[];
+  serviceTiers?: Record<string, PricingTableEntry>;
+}
 
-  // Google. Gemini output rates include thinking tokens where the API does so.
-  // Current promotional paid-tier rates are valid through 2026-12-31.
-  "gemini-3.7-flash": tier(0.75, 3.75, 0.075),
-  "gemini-3.6-flash": tier(0.75, 3.75, 0.075),
-  "gemini-3.5-flash": tier(1.5, 9, 0.15),
-  "gemini-3.5-flash-lite": tier(0.3, 2.5, 0.03),
-  "gemini-3.1-pro-preview": tier(2, 12, 0.2, {
-    contextTiers: [tier(4, 18, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "gemini-3-flash-preview": tier(0.5, 3, 0.05),
-  "gemini-3.1-flash-lite": tier(0.25, 1.5, 0.025),
-  "gemini-2.5-pro": tier(1.25, 10, 0.125, {
-    contextTiers: [tier(2.5, 15, 0.25, { minContextTokens: 200_001 })],
-  }),
-  "gemini-2.5-flash": tier(0.3, 2.5, 0.03),
-  "gemini-2.5-flash-lite": tier(0.1, 0.4, 0.01),
+const toModelPricing = (entry: PricingTableEntry): ModelPricing => {
+  const pricing: ModelPricing = {
+    input: entry.input,
+    output: entry.output,
+    cacheRead: entry.cacheRead,
+    cacheWrite: entry.cacheWrite5m ?? null,
+  };
+  if (entry.cacheWrite1h != null) pricing.cacheWriteOneHour = entry.cacheWrite1h;
+  if (entry.minContextTokens != null) pricing.minContextTokens = entry.minContextTokens;
+  if (entry.contextTiers) pricing.contextTiers = entry.contextTiers.map(toModelPricing);
+  if (entry.serviceTiers) {
+    pricing.serviceTiers = Object.fromEntries(
+      Object.entries(entry.serviceTiers).map(([name, tier]) => [name, toModelPricing(tier)]),
+    );
+  }
+  return pricing;
+};
 
-  // xAI. Long-context rates apply from 200K prompt tokens.
-  "grok-4.6": tier(2, 6, 0.5, {
-    contextTiers: [tier(4, 12, 1, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.5": tier(2, 6, 0.3, {
-    contextTiers: [tier(4, 12, 0.6, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.5-build": tier(2, 6, 0.3, {
-    contextTiers: [tier(4, 12, 0.6, { minContextTokens: 200_001 })],
-  }),
-  "grok-build-latest": tier(2, 6, 0.3, {
-    contextTiers: [tier(4, 12, 0.6, { minContextTokens: 200_001 })],
-  }),
-  "grok-build-0.1": tier(1, 2, 0.2, {
-    contextTiers: [tier(2, 4, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-code-fast-1-0825": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-code-fast-1": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-code-fast": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.3": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.20-multi-agent-0309": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.20-0309-reasoning": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-4.20-0309-non-reasoning": tier(1.25, 2.5, 0.2, {
-    contextTiers: [tier(2.5, 5, 0.4, { minContextTokens: 200_001 })],
-  }),
-  "grok-4": tier(3, 15, 0.75),
-  "grok-build": tier(2, 6, 0.3, {
-    contextTiers: [tier(4, 12, 0.6, { minContextTokens: 200_001 })],
-  }),
+const PRICING_TABLE_MODELS: Record<string, PricingTableEntry> = modelPricingTable.models;
 
-};
+const MODEL_PRICING: Record<string, ModelPricing> = Object.fromEntries(
+  Object.entries(PRICING_TABLE_MODELS).map(([key, entry]) => [key, toModelPricing(entry)]),
+);
 
 const SORTED_MODEL_PRICING_ENTRIES = Object.entries(MODEL_PRICING).sort(
   (a, b) => b[0].length - a[0].length,
@@ -224,17 +114,9 @@ const SORTED_MODEL_PRICING_ENTRIES = Object.entries(MODEL_PRICING).sort(
 // represented by the token-only fields in this dashboard. Keep them out of
 // prefix matching so e.g. `gemini-2.5-flash-preview-tts` is not accidentally
 // priced as ordinary Gemini 2.5 Flash text traffic.
-const TOKEN_UNSUPPORTED_MODEL_PATTERNS = [
-  /^(?:gemini-2\.5-(?:flash|pro)-preview-tts)(?:-|$)/,
-  /^gemini-2\.5-flash-(?:native-audio|image)(?:-|$)/,
-  /^gemini-.*-(?:tts|native-audio|audio|live|image|image-generation|video-generation)(?:-|$)/,
-  /^gemini-omni-flash(?:-|$)/,
-  /^gemini-(?:3\.5-live-translate|3\.1-flash-live)(?:-|$)/,
-  /^gpt-(?:4o(?:-mini)?|4\.1(?:-mini)?)-(?:audio|realtime|tts|transcribe|search-preview)(?:-|$)/,
-  /^gpt-(?:realtime|image)(?:-|$)/,
-  /^sora(?:-|$)/,
-  /^grok-imagine(?:-|$)/,
-];
+const TOKEN_UNSUPPORTED_MODEL_PATTERNS = modelPricingTable.tokenUnsupportedModels.map(
+  (pattern) => new RegExp(pattern),
+);
 
 const SOURCE_COST_ONLY_PROVIDER_IDS = new Set([
   "antigravity",
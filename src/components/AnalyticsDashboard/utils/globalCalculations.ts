@@ -34,6 +34,8 @@ export interface ModelUsageLike {
   cache_read_tokens: number;
   reasoning_tokens?: number;
   cost_usd?: number | null;
+  /** Backend estimate from the (user-overridable) pricing table. */
+  estimated_cost_usd?: number | null;
   context_breakdown?: ModelContextStats[];
 }
 
@@ -135,13 +137,17 @@ export const calculateModelMetrics = (
     serviceTier?: string;
     reasoningTokens?: number;
     sourceCostUSD?: number | null;
+    estimatedCostUSD?: number | null;
     contextTokens?: number;
     contextBreakdown?: ModelContextStats[];
   } = {},
 ): ModelDisplayMetrics => {
   const hasSourceCost = options.sourceCostUSD !== undefined && options.sourceCostUSD !== null;
+  const backendEstimate = options.estimatedCostUSD ?? null;
   const price = hasSourceCost
     ? options.sourceCostUSD ?? null
+    : backendEstimate !== null
+      ? backendEstimate
     : options.contextBreakdown
       ? calculateContextBreakdownPrice(modelName, {
           provider_id: options.providerId,
@@ -203,9 +209,13 @@ export const calculateGlobalCostSummary = (
 
   for (const model of models) {
     const hasSourceCost = model.cost_usd !== undefined && model.cost_usd !== null;
+    const hasBackendEstimate =
+      model.estimated_cost_usd !== undefined && model.estimated_cost_usd !== null;
     const price = hasSourceCost
       ? model.cost_usd ?? 0
-      : calculateContextBreakdownPrice(model.model_name, model);
+      : hasBackendEstimate
+        ? model.estimated_cost_usd ?? 0
+        : calculateContextBreakdownPrice(model.model_name, model);
 
     if (price == null) {
       unpricedTokens += model.token_count;
@@ -222,7 +232,11 @@ export const calculateGlobalCostSummary = (
       pricedModels += 1;
     }
 
-    if (hasSourceCost || hasExplicitModelPricing(model.model_name, model.provider_id)) {
+    if (
+      hasSourceCost ||
+      hasBackendEstimate ||
+      hasExplicitModelPricing(model.model_name, model.provider_id)
+    ) {
       coveredTokens += model.token_count;
     }
   }
@@ -211,6 +211,7 @@ export const GlobalStatsView: React.FC<GlobalStatsViewProps> = ({
                     providerId: model.provider_id,
                     serviceTier: model.service_tier,
                     sourceCostUSD: model.cost_usd,
+                    estimatedCostUSD: model.estimated_cost_usd,
                     reasoningTokens: model.reasoning_tokens,
                     contextBreakdown: model.context_breakdown,
                   },
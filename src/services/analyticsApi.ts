@@ -11,6 +11,7 @@ import type {
   PaginatedTokenStats,
   ProjectStatsSummary,
   SessionComparison,
+  SessionPairComparison,
   PaginatedRecentEdits,
   GlobalStatsSummary,
   ProviderId,
@@ -193,6 +194,24 @@ export async function fetchSessionComparison(
   });
 }
 
+/**
+ * Align two sessions (possibly from different providers) turn by turn
+ */
+export async function fetchSessionPairComparison(
+  left: { provider: ProviderId; sessionPath: string },
+  right: { provider: ProviderId; sessionPath: string }
+): Promise<SessionPairComparison> {
+  const key = `sessionPairComparison:${left.provider}:${left.sessionPath}:${right.provider}:${right.sessionPath}`;
+  return dedupeInFlight(key, () =>
+    api<SessionPairComparison>("compare_sessions", {
+      leftProvider: left.provider,
+      leftSessionPath: left.sessionPath,
+      rightProvider: right.provider,
+      rightSessionPath: right.sessionPath,
+    })
+  );
+}
+
 // ============================================================================
 // Recent Edits API
 // ============================================================================
@@ -13,7 +13,11 @@ import type {
   ArchiveDiskUsage,
   ExpiringSession,
   ExportResult,
+  ArchiveExportSummary,
   ClaudeMessage,
+  AutoArchiveConfig,
+  AutoArchiveLog,
+  AutoArchiveRun,
 } from '@/types';
 
 export const archiveApi = {
@@ -63,6 +67,28 @@ export const archiveApi = {
       thresholdDays: thresholdDays ?? 7,
     }),
 
-  exportSession: (sessionFilePath: string, format: 'json') =>
-    api<ExportResult>('export_session', { sessionFilePath, format }),
+  exportSession: (
+    sessionFilePath: string,
+    format: 'json' | 'markdown',
+    redact?: boolean,
+    dryRun?: boolean,
+  ) => api<ExportResult>('export_session', { sessionFilePath, format, redact, dryRun }),
+
+  loadAutoArchiveConfig: () => api<AutoArchiveConfig>('load_auto_archive_config'),
+
+  saveAutoArchiveConfig: (config: AutoArchiveConfig) =>
+    api<void>('save_auto_archive_config', { config }),
+
+  getAutoArchiveLog: () => api<AutoArchiveLog>('get_auto_archive_log'),
+
+  /** Runs once with the saved settings, even when scheduling is disabled. */
+  runAutoArchiveNow: () => api<AutoArchiveRun>('run_auto_archive_now'),
+
+  /** Writes the archive to a single `.chvarchive` bundle at `outputPath`. */
+  exportArchive: (archiveId: string, outputPath: string) =>
+    api<ArchiveExportSummary>('export_archive', { archiveId, outputPath }),
+
+  /** Verifies a bundle's checksums and adds it as a new archive. */
+  importArchive: (bundlePath: string) =>
+    api<ArchiveEntry>('import_archive', { bundlePath }),
 };
@@ -0,0 +1,25 @@
+/**
+ * Budget API Service
+ *
+ * Thin wrapper around the api() adapter for budget commands.
+ * Works in both Tauri desktop mode and WebUI server mode.
+ */
+
+import { api } from './api';
+import type { BudgetConfig, BudgetStatus } from '@/types';
+
+export const budgetApi = {
+  loadBudgets: () => api<BudgetConfig>('load_budgets'),
+
+  saveBudgets: (config: BudgetConfig) => api<void>('save_budgets', { config }),
+
+  /** Also raises `budget-alert` events for newly reached thresholds. */
+  getBudgetStatus: (
+    claudePath: string,
+    customClaudePaths?: { path: string; label?: string | null }[],
+  ) =>
+    api<BudgetStatus[]>('get_budget_status', {
+      claudePath,
+      customClaudePaths: customClaudePaths ?? null,
+    }),
+};
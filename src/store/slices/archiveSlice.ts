@@ -59,7 +59,7 @@ export interface ArchiveSliceActions {
   loadArchiveSessions: (id: string) => Promise<void>;
   loadDiskUsage: () => Promise<void>;
   loadExpiringSessions: (projectPath: string, thresholdDays?: number) => Promise<void>;
-  exportSession: (path: string, format: 'json') => Promise<string>;
+  exportSession: (path: string, format: 'json' | 'markdown') => Promise<string>;
   setArchiveActiveTab: (tab: ArchiveViewTab) => void;
   clearArchiveError: () => void;
   resetArchive: () => void;
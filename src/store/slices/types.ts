@@ -405,7 +405,7 @@ export interface AppStoreActions {
   loadArchiveSessions: (id: string) => Promise<void>;
   loadDiskUsage: () => Promise<void>;
   loadExpiringSessions: (projectPath: string, thresholdDays?: number) => Promise<void>;
-  exportSession: (path: string, format: 'json') => Promise<string>;
+  exportSession: (path: string, format: 'json' | 'markdown') => Promise<string>;
   setArchiveActiveTab: (tab: import('../../types').ArchiveViewTab) => void;
   clearArchiveError: () => void;
   resetArchive: () => void;
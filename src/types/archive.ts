@@ -33,6 +33,8 @@ export interface ArchiveSessionInfo {
   subagentCount: number;
   subagentSizeBytes: number;
   subagents: SubagentFileInfo[];
+  /** Tags, notes, etc. carried in by an imported bundle */
+  userMetadata?: import('./metadata.types').SessionMetadata;
 }
 
 export interface SubagentFileInfo {
@@ -42,7 +44,10 @@ export interface SubagentFileInfo {
 }
 
 export interface ArchiveDiskUsage {
+  /** Bytes on disk; content shared between archives counts once */
   totalBytes: number;
+  /** Uncompressed size of all archived sessions */
+  logicalBytes: number;
   archiveCount: number;
   sessionCount: number;
   perArchive: ArchiveDiskEntry[];
@@ -62,10 +67,91 @@ export interface ExpiringSession {
   subagentCount: number;
 }
 
+export interface RedactionFinding {
+  /** Detector, rule or path-rewrite name */
+  rule: string;
+  /** JSON pointer into the exported messages, or `title` */
+  location: string;
+  count: number;
+}
+
+export interface RedactionReport {
+  total: number;
+  findings: RedactionFinding[];
+}
+
 export interface ExportResult {
+  /** Empty for a redaction dry run */
   content: string;
   format: string;
   sessionId: string;
+  /** Present when the export was redacted or dry-run */
+  redaction?: RedactionReport;
 }
 
 export type ArchiveViewTab = 'overview' | 'browse';
+
+// Archive bundles — maps to src-tauri/src/commands/archive_bundle.rs
+
+/** Extension of single-file archive bundles (zstd-compressed tar). */
+export const ARCHIVE_BUNDLE_EXTENSION = 'chvarchive';
+
+export interface ArchiveExportSummary {
+  path: string;
+  sessionCount: number;
+  subagentCount: number;
+  bundleBytes: number;
+}
+
+// Background archiver — maps to src-tauri/src/models/auto_archive.rs
+
+/** Limits applied only to archives the background archiver created. */
+export interface AutoArchiveRetention {
+  maxAgeDays?: number;
+  maxArchives?: number;
+  maxTotalBytes?: number;
+}
+
+export interface AutoArchiveConfig {
+  enabled: boolean;
+  /** Archive sessions within this many days of Claude's `cleanupPeriodDays`. */
+  thresholdDays: number;
+  intervalHours: number;
+  includeSubagents: boolean;
+  /** Claude project storage directories; empty means every project. */
+  projects: string[];
+  retention: AutoArchiveRetention;
+}
+
+export interface AutoArchivedSession {
+  filePath: string;
+  archiveId: string;
+  projectName: string;
+  daysRemaining: number;
+  sizeBytes: number;
+  subagentCount: number;
+}
+
+export interface AutoArchiveRun {
+  startedAt: string;
+  finishedAt: string;
+  trigger: 'schedule' | 'manual';
+  archived: AutoArchivedSession[];
+  skippedUnchanged: number;
+  prunedArchiveIds: string[];
+  errors: string[];
+}
+
+export interface ArchivedSessionStamp {
+  archiveId: string;
+  sizeBytes: number;
+  lastModified: string;
+  /** Retention deleted the archive; the unchanged session isn't archived again. */
+  pruned: boolean;
+}
+
+export interface AutoArchiveLog {
+  runs: AutoArchiveRun[];
+  archiveIds: string[];
+  sessions: Record<string, ArchivedSessionStamp>;
+}
@@ -0,0 +1,55 @@
+/**
+ * Budget Types
+ * Maps to Rust structs in src-tauri/src/models/budget.rs
+ */
+
+export type BudgetScope = "global" | "provider" | "project";
+export type BudgetPeriod = "day" | "week" | "month";
+export type BudgetMetric = "tokens" | "usd";
+
+export interface Budget {
+  id: string;
+  name?: string;
+  scope: BudgetScope;
+  /** Provider id or project path; omitted for the global scope. */
+  target?: string;
+  period: BudgetPeriod;
+  metric: BudgetMetric;
+  limit: number;
+  /** Fractions of `limit` that raise an alert, e.g. 0.8. Defaults to [0.8, 1]. */
+  thresholds?: number[];
+}
+
+export interface BudgetConfig {
+  budgets: Budget[];
+}
+
+export interface BudgetStatus {
+  budget: Budget;
+  periodStart: string;
+  periodEnd: string;
+  used: number;
+  usedRatio: number;
+  /** Usage at period end if the current rate continues. */
+  projected: number;
+  projectedRatio: number;
+  crossedThreshold?: number;
+  /** Tokens without any pricing; a USD `used` is a lower bound when non-zero. */
+  unpricedTokens: number;
+}
+
+/** Payload of the `budget-alert` Tauri event / SSE event. */
+export interface BudgetAlert {
+  budgetId: string;
+  budgetName?: string;
+  scope: BudgetScope;
+  target?: string;
+  period: BudgetPeriod;
+  metric: BudgetMetric;
+  threshold: number;
+  used: number;
+  limit: number;
+  projected: number;
+  periodStart: string;
+  periodEnd: string;
+}
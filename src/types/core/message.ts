@@ -172,6 +172,24 @@ export interface BaseClaudeMessage {
   provider?: import("./session").ProviderId;
   isSidechain?: boolean;
   content?: string | ContentItem[] | Record<string, unknown>;
+  /** Where the query matched (search results only) */
+  search_matches?: SearchMatch[];
+}
+
+/** One search hit inside a message; offsets are JavaScript string indices */
+export interface SearchMatch {
+  /** Message field holding the hit */
+  field: "content" | "toolUseResult" | "toolUse";
+  /** JSON pointer to the matched string within `field`, e.g. "/0/text" */
+  path: string;
+  /** Content block holding the hit, when `content` is a block array */
+  blockIndex?: number;
+  start: number;
+  end: number;
+  /** The match with surrounding context (`snippetContext` chars per side) */
+  snippet: string;
+  /** Match start within `snippet` */
+  snippetOffset: number;
 }
 
 /** Represents input from the human user */
@@ -89,6 +89,8 @@ export interface UserSettings {
   wsl?: WslSettings;
   /** Providers explicitly discovered by the user and allowed to scan on startup */
   discoveredProviderIds?: ProviderId[];
+  /** Serve Claude searches from the persistent full-text index */
+  searchIndex?: boolean;
 }
 
 // ============================================================================
@@ -115,4 +115,13 @@ export interface SearchFilters {
   hasToolCalls?: boolean;
   hasErrors?: boolean;
   hasFileChanges?: boolean;
+  /** Treat the query as literal text (default) or a regular expression */
+  matchMode?: "literal" | "regex";
+  caseSensitive?: boolean;
+  /** Characters of context around each match in result snippets (default 40) */
+  snippetContext?: number;
+  /** Restrict matching to these parts of a message (default: all) */
+  fields?: Array<"userText" | "assistantText" | "thinking" | "toolInput" | "toolResult">;
+  /** Result order: newest first (default) or best match first */
+  sort?: "newest" | "relevance";
 }
@@ -28,6 +28,7 @@ export type {
   ClaudeMessage,
   ClaudeAssistantMessage,
   ClaudeUserMessage,
+  SearchMatch,
   ClaudeSystemMessage,
   ClaudeSummaryMessage,
   ClaudeFileHistoryMessage,
@@ -217,6 +218,12 @@ export type {
   ProjectRanking,
   ProviderUsageStats,
   SessionComparison,
+  ComparisonMetrics,
+  ComparisonDelta,
+  ComparedSession,
+  TurnAlignment,
+  ComparedTurn,
+  SessionPairComparison,
   GlobalStatsSummary,
 } from "./stats.types";
 
@@ -273,5 +280,26 @@ export type {
   ArchiveDiskEntry,
   ExpiringSession,
   ExportResult,
+  RedactionFinding,
+  RedactionReport,
   ArchiveViewTab,
+  ArchiveExportSummary,
+  AutoArchiveConfig,
+  AutoArchiveRetention,
+  AutoArchiveLog,
+  AutoArchiveRun,
+  AutoArchivedSession,
+  ArchivedSessionStamp,
 } from "./archive";
+export { ARCHIVE_BUNDLE_EXTENSION } from "./archive";
+
+// Budget Types
+export type {
+  Budget,
+  BudgetAlert,
+  BudgetConfig,
+  BudgetMetric,
+  BudgetPeriod,
+  BudgetScope,
+  BudgetStatus,
+} from "./budget";
@@ -26,6 +26,8 @@ export interface SessionTokenStats {
   summary?: string;
   most_used_tools: ToolUsageStats[];
   model_distribution?: ModelStats[];
+  /** Reported cost plus estimates for unreported tokens, summed over `model_distribution`. */
+  total_cost_usd?: number | null;
 }
 
 /**
@@ -100,6 +102,10 @@ export interface ModelStats {
   cache_read_tokens: number;
   reasoning_tokens: number;
   cost_usd?: number | null;
+  /** Tokens of the messages whose source cost makes up `cost_usd`. */
+  reported_cost_tokens?: number;
+  /** Estimated from token counts via the pricing table (`~/.claude-history-viewer/pricing.json` overrides). */
+  estimated_cost_usd?: number | null;
   /** Per-request context-tier buckets used to avoid pricing an aggregate as one request. */
   context_breakdown?: ModelContextStats[];
 }
@@ -135,6 +141,8 @@ export interface ProjectStatsSummary {
   daily_stats: DailyStats[];
   activity_heatmap: ActivityHeatmap[];
   model_distribution?: ModelStats[];
+  /** Reported cost plus estimates for unreported tokens, summed over `model_distribution`. */
+  total_cost_usd?: number | null;
   token_distribution: {
     input: number;
     output: number;
@@ -145,6 +153,8 @@ export interface ProjectStatsSummary {
 }
 
 export interface ProjectRanking {
+  /** Set in `provider_projects`; `top_projects` merges providers by name. */
+  provider_id?: string;
   project_name: string;
   sessions: number;
   messages: number;
@@ -172,6 +182,67 @@ export interface SessionComparison {
   is_above_average: boolean;
 }
 
+// ============================================================================
+// Session Pair Comparison (compare_sessions)
+// ============================================================================
+
+/** Totals for one side of a session pair comparison (whole session or one turn). */
+export interface ComparisonMetrics {
+  message_count: number;
+  total_tokens: number;
+  input_tokens: number;
+  output_tokens: number;
+  /** Source cost when reported, otherwise the pricing-table estimate. */
+  cost_usd?: number;
+  tool_calls: number;
+  /** Tool name → call count. */
+  tools: Record<string, number>;
+  files_edited: string[];
+  error_count: number;
+  duration_ms?: number;
+}
+
+/** Right-minus-left differences between two sides. */
+export interface ComparisonDelta {
+  total_tokens: number;
+  cost_usd?: number;
+  tool_calls: number;
+  error_count: number;
+  duration_ms?: number;
+  tools_only_left: string[];
+  tools_only_right: string[];
+  files_only_left: string[];
+  files_only_right: string[];
+}
+
+export interface ComparedSession {
+  provider: string;
+  session_path: string;
+  session_id: string;
+  models: string[];
+  turn_count: number;
+  metrics: ComparisonMetrics;
+}
+
+export type TurnAlignment = "prompt" | "position" | "left_only" | "right_only";
+
+export interface ComparedTurn {
+  alignment: TurnAlignment;
+  left_index: number | null;
+  right_index: number | null;
+  prompt: string;
+  left: ComparisonMetrics | null;
+  right: ComparisonMetrics | null;
+  delta: ComparisonDelta | null;
+}
+
+export interface SessionPairComparison {
+  left: ComparedSession;
+  right: ComparedSession;
+  delta: ComparisonDelta;
+  turns: ComparedTurn[];
+}
+
 // ============================================================================
 // Global Stats Summary
 // ============================================================================
@@ -199,5 +270,9 @@ export interface GlobalStatsSummary {
   most_used_subagents: ToolUsageStats[];
   provider_distribution: ProviderUsageStats[];
   model_distribution?: ModelStats[];
+  /** Reported cost plus estimates for unreported tokens, summed over `model_distribution`. */
+  total_cost_usd?: number | null;
   top_projects: ProjectRanking[];
+  /** Every project, per provider; only filled for unlimited summaries. */
+  provider_projects?: ProjectRanking[];
 }